@@ -0,0 +1,39 @@
+# Kazeterm shell integration for bash.
+#
+# Marks prompts, command input, output and exit status with OSC 133 so
+# Kazeterm knows where every command starts and ends. Source it at the end of
+# ~/.bashrc, after PS1 and PROMPT_COMMAND are set:
+#
+#   [[ "$TERM_PROGRAM" == kazeterm ]] && source /path/to/kazeterm.bash
+
+[[ $- == *i* ]] || return 0
+[[ -z "$__kazeterm_integration" ]] || return 0
+__kazeterm_integration=1
+
+__kazeterm_command_running=
+__kazeterm_armed=
+
+__kazeterm_prompt_command() {
+  local status=$?
+  __kazeterm_armed=
+  if [[ -n "$__kazeterm_command_running" ]]; then
+    printf '\e]133;D;%s\a' "$status"
+    __kazeterm_command_running=
+  fi
+  printf '\e]133;A\a'
+  return "$status"
+}
+
+# PS0 is expanded after a command line is read and before it runs, but it
+# cannot set variables. The DEBUG trap records that a command is running; it is
+# armed only after PROMPT_COMMAND has finished so its own commands don't count.
+__kazeterm_preexec() {
+  [[ -n "$__kazeterm_armed" && "$BASH_COMMAND" != __kazeterm_prompt_command ]] || return 0
+  __kazeterm_armed=
+  __kazeterm_command_running=1
+}
+
+trap '__kazeterm_preexec' DEBUG
+PROMPT_COMMAND="__kazeterm_prompt_command${PROMPT_COMMAND:+;$PROMPT_COMMAND};__kazeterm_armed=1"
+PS0='\e]133;C\a'"$PS0"
+PS1="$PS1"'\[\e]133;B\a\]'
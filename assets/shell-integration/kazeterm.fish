@@ -0,0 +1,30 @@
+# Kazeterm shell integration for fish.
+#
+# Marks prompts, command input, output and exit status with OSC 133 so
+# Kazeterm knows where every command starts and ends. Source it from
+# ~/.config/fish/config.fish:
+#
+#   test "$TERM_PROGRAM" = kazeterm; and source /path/to/kazeterm.fish
+
+status is-interactive; or exit 0
+set -q __kazeterm_integration; and exit 0
+set -g __kazeterm_integration 1
+
+function __kazeterm_prompt_start --on-event fish_prompt
+    printf '\e]133;A\a'
+end
+
+function __kazeterm_preexec --on-event fish_preexec
+    printf '\e]133;C\a'
+end
+
+function __kazeterm_postexec --on-event fish_postexec
+    printf '\e]133;D;%s\a' $status
+end
+
+# Wrap the prompt so input starts right after it.
+functions -c fish_prompt __kazeterm_original_prompt
+function fish_prompt
+    __kazeterm_original_prompt
+    printf '\e]133;B\a'
+end
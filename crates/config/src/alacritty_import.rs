@@ -304,12 +304,14 @@ fn build_config_patch(alacritty: &AlacrittyConfig) -> AlacrittyConfigPatch {
       shell: program.clone(),
       args: vec![],
       working_directory: None,
+      persist_scrollback: None,
     },
     AlacrittyShell::Detailed(detailed) => Profile {
       name: "Alacritty".to_string(),
       shell: detailed.program.clone().unwrap_or_else(|| String::from("")),
       args: detailed.args.clone(),
       working_directory: None,
+      persist_scrollback: None,
     },
   });
 
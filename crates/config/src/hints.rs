@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+/// What happens to a match once its hint label has been typed.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HintAction {
+  /// Copy the matched text to the clipboard.
+  #[default]
+  Copy,
+  /// Paste the matched text into the terminal.
+  Paste,
+  /// Open the matched text as a URL or path.
+  Open,
+}
+
+/// A pattern labelled while hint mode is active.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct HintRule {
+  /// Short name used in errors when the regex is invalid.
+  pub name: String,
+  /// Regular expression matched against each visible logical line.
+  pub regex: String,
+  /// Action performed on the selected match.
+  #[serde(default)]
+  pub action: HintAction,
+}
+
+impl HintRule {
+  fn new(name: &str, regex: &str, action: HintAction) -> Self {
+    Self {
+      name: name.to_string(),
+      regex: regex.to_string(),
+      action,
+    }
+  }
+}
+
+/// Hex words of at least seven characters containing an `a-f` letter, so plain
+/// numbers such as timestamps, ports and PIDs are not labelled. The `regex`
+/// crate has no lookahead, so the first seven characters spell out where the
+/// first letter can sit.
+const GIT_SHA_PATTERN: &str = concat!(
+  r"\b(?:",
+  r"(?:[a-f][0-9a-f]{6}|[0-9][a-f][0-9a-f]{5}|[0-9]{2}[a-f][0-9a-f]{4}|[0-9]{3}[a-f][0-9a-f]{3}",
+  r"|[0-9]{4}[a-f][0-9a-f]{2}|[0-9]{5}[a-f][0-9a-f]|[0-9]{6}[a-f])[0-9a-f]{0,33}",
+  r"|[0-9]{7,39}[a-f][0-9a-f]{0,32}",
+  r")\b",
+);
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HintsConfig {
+  /// Characters used to build hint labels. Needs at least two distinct characters.
+  pub alphabet: String,
+  /// Patterns labelled in hint mode. Earlier rules win when matches overlap.
+  pub rules: Vec<HintRule>,
+}
+
+impl Default for HintsConfig {
+  fn default() -> Self {
+    Self {
+      alphabet: "jfkdls;ahgurieowpq".to_string(),
+      rules: vec![
+        HintRule::new(
+          "url",
+          r#"(https?://|ftp://|file://|git://|ssh://|mailto:)[^\s<>"'`]+[^\s<>"'`.,:;)]"#,
+          HintAction::Open,
+        ),
+        HintRule::new(
+          "uuid",
+          r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+          HintAction::Copy,
+        ),
+        HintRule::new(
+          "file_line",
+          r"(?:[\w.~-]+/)*[\w.-]+\.\w+:\d+(?::\d+)?",
+          HintAction::Copy,
+        ),
+        HintRule::new(
+          "ipv4",
+          r"\b(?:\d{1,3}\.){3}\d{1,3}(?::\d{1,5})?\b",
+          HintAction::Copy,
+        ),
+        HintRule::new("git_sha", GIT_SHA_PATTERN, HintAction::Copy),
+      ],
+    }
+  }
+}
+
+impl HintsConfig {
+  /// Distinct label characters in configured order.
+  pub fn label_alphabet(&self) -> Vec<char> {
+    let mut alphabet = Vec::new();
+    for c in self.alphabet.chars() {
+      if !c.is_whitespace() && !alphabet.contains(&c) {
+        alphabet.push(c);
+      }
+    }
+    alphabet
+  }
+
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    if self.label_alphabet().len() < 2 {
+      return Err(format!(
+        "hints.alphabet '{}' must contain at least two distinct characters.",
+        self.alphabet
+      ));
+    }
+    for rule in &self.rules {
+      if let Err(error) = regex::Regex::new(&rule.regex) {
+        return Err(format!(
+          "hints.rules.{}: invalid regex '{}': {error}",
+          rule.name, rule.regex
+        ));
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{HintAction, HintRule, HintsConfig};
+
+  #[test]
+  fn rule_regexes_must_compile() {
+    let mut config = HintsConfig::default();
+    assert!(config.validate().is_ok());
+
+    config
+      .rules
+      .push(HintRule::new("broken", "path(", HintAction::Copy));
+    assert!(
+      config
+        .validate()
+        .unwrap_err()
+        .contains("hints.rules.broken")
+    );
+  }
+}
@@ -1,4 +1,8 @@
-use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+use serde::{
+  Deserialize, Deserializer, Serialize, Serializer,
+  de::{DeserializeOwned, Error as _},
+  ser::SerializeMap as _,
+};
 use std::collections::{BTreeMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -71,15 +75,42 @@ impl KeybindingList {
   }
 
   pub fn matches(&self, control: bool, shift: bool, alt: bool, platform: bool, key: &str) -> bool {
-    self
-      .iter()
-      .any(|binding| ParsedKeybinding::parse(binding).matches(control, shift, alt, platform, key))
+    self.matches_sequence(&[ParsedKeybinding::from_event(
+      control, shift, alt, platform, key,
+    )])
+  }
+
+  /// Whether `keystrokes` complete one of these bindings.
+  pub fn matches_sequence(&self, keystrokes: &[ParsedKeybinding]) -> bool {
+    self.iter().any(|binding| {
+      let sequence = ParsedKeybinding::parse_sequence(binding);
+      sequence.len() == keystrokes.len() && sequence_starts_with(&sequence, keystrokes)
+    })
+  }
+
+  /// Whether `keystrokes` are the leading keys of a longer binding in this list.
+  pub fn continues_sequence(&self, keystrokes: &[ParsedKeybinding]) -> bool {
+    self.iter().any(|binding| {
+      let sequence = ParsedKeybinding::parse_sequence(binding);
+      sequence.len() > keystrokes.len() && sequence_starts_with(&sequence, keystrokes)
+    })
+  }
+
+  /// Bindings in the space-separated form GPUI keymaps expect, e.g. `"ctrl-a c"`.
+  pub fn keymap_bindings(&self) -> impl Iterator<Item = String> + '_ {
+    self.iter().map(|binding| {
+      binding
+        .split_whitespace()
+        .map(strip_sequence_separator)
+        .collect::<Vec<_>>()
+        .join(" ")
+    })
   }
 
   pub fn display_text(&self) -> String {
     let display = self
       .iter()
-      .map(|binding| ParsedKeybinding::parse(binding).display_text())
+      .map(|binding| ParsedKeybinding::display_sequence(&ParsedKeybinding::parse_sequence(binding)))
       .collect::<Vec<_>>()
       .join(" / ");
 
@@ -148,6 +179,13 @@ pub(crate) enum KeybindingAction {
   SelectTab8,
   SelectLastTab,
   ToggleSearch,
+  ToggleSearchPanel,
+  HintMode,
+  ScrollToPreviousPrompt,
+  ScrollToNextPrompt,
+  SelectCommandOutput,
+  CopyLastCommandOutput,
+  CopyMode,
   SplitHorizontal,
   SplitVertical,
   ClosePane,
@@ -177,7 +215,7 @@ pub(crate) enum KeybindingAction {
 }
 
 impl KeybindingAction {
-  const ALL: [Self; 43] = [
+  const ALL: [Self; 50] = [
     Self::Copy,
     Self::Paste,
     Self::ZoomIn,
@@ -195,6 +233,13 @@ impl KeybindingAction {
     Self::SelectTab8,
     Self::SelectLastTab,
     Self::ToggleSearch,
+    Self::ToggleSearchPanel,
+    Self::HintMode,
+    Self::ScrollToPreviousPrompt,
+    Self::ScrollToNextPrompt,
+    Self::SelectCommandOutput,
+    Self::CopyLastCommandOutput,
+    Self::CopyMode,
     Self::SplitHorizontal,
     Self::SplitVertical,
     Self::ClosePane,
@@ -242,6 +287,13 @@ impl KeybindingAction {
       "select_tab_8" => Some(Self::SelectTab8),
       "select_tab_9" | "select_last_tab" => Some(Self::SelectLastTab),
       "toggle_search" => Some(Self::ToggleSearch),
+      "toggle_search_panel" => Some(Self::ToggleSearchPanel),
+      "hint_mode" => Some(Self::HintMode),
+      "scroll_to_previous_prompt" => Some(Self::ScrollToPreviousPrompt),
+      "scroll_to_next_prompt" => Some(Self::ScrollToNextPrompt),
+      "select_command_output" => Some(Self::SelectCommandOutput),
+      "copy_last_command_output" => Some(Self::CopyLastCommandOutput),
+      "copy_mode" => Some(Self::CopyMode),
       "split_horizontal" => Some(Self::SplitHorizontal),
       "split_vertical" => Some(Self::SplitVertical),
       "close_pane" => Some(Self::ClosePane),
@@ -291,6 +343,13 @@ impl KeybindingAction {
       Self::SelectTab8 => "select_tab_8",
       Self::SelectLastTab => "select_last_tab",
       Self::ToggleSearch => "toggle_search",
+      Self::ToggleSearchPanel => "toggle_search_panel",
+      Self::HintMode => "hint_mode",
+      Self::ScrollToPreviousPrompt => "scroll_to_previous_prompt",
+      Self::ScrollToNextPrompt => "scroll_to_next_prompt",
+      Self::SelectCommandOutput => "select_command_output",
+      Self::CopyLastCommandOutput => "copy_last_command_output",
+      Self::CopyMode => "copy_mode",
       Self::SplitHorizontal => "split_horizontal",
       Self::SplitVertical => "split_vertical",
       Self::ClosePane => "close_pane",
@@ -334,7 +393,37 @@ pub(crate) fn rewrite_keybinding_table_to_key_first(
     .collect();
 }
 
+/// Entries of a keybinding table that carry their own `keys` instead of mapping
+/// a key to an action.
+const KEYED_BINDING_ENTRIES: [&str; 2] = ["send_text", "search_preset"];
+
 pub(crate) fn merge_keybinding_tables(
+  target: &mut toml::map::Map<String, toml::Value>,
+  mut overlay: toml::map::Map<String, toml::Value>,
+) {
+  // Keyed entries from both tables are concatenated so imports add to them.
+  let mut keyed = Vec::new();
+  for name in KEYED_BINDING_ENTRIES {
+    let entries = [target.remove(name), overlay.remove(name)]
+      .into_iter()
+      .flatten()
+      .flat_map(|value| match value {
+        toml::Value::Array(entries) => entries,
+        entry => vec![entry],
+      })
+      .collect::<Vec<_>>();
+    if !entries.is_empty() {
+      keyed.push((name, toml::Value::Array(entries)));
+    }
+  }
+
+  merge_binding_overrides(target, overlay);
+  for (name, entries) in keyed {
+    target.insert(name.to_string(), entries);
+  }
+}
+
+fn merge_binding_overrides(
   target: &mut toml::map::Map<String, toml::Value>,
   overlay: toml::map::Map<String, toml::Value>,
 ) {
@@ -361,6 +450,49 @@ pub(crate) fn merge_keybinding_tables(
     .collect();
 }
 
+/// Text written to the active terminal when a key (or key sequence) is pressed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SendTextBinding {
+  /// Keystroke or key sequence that sends the text
+  pub keys: KeybindingList,
+  /// Text or escape sequence written to the PTY as-is
+  pub text: String,
+}
+
+/// Runs a named `[search.presets]` entry in the search bar when a key (or key
+/// sequence) is pressed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SearchPresetBinding {
+  /// Keystroke or key sequence that runs the preset
+  pub keys: KeybindingList,
+  /// Name of the preset under `[search.presets]`
+  pub preset: String,
+}
+
+/// Parse a table, or an array of tables, of bindings that carry their own `keys`.
+fn keyed_bindings_from_value<T: DeserializeOwned>(
+  name: &str,
+  value: toml::Value,
+  keys: impl Fn(&T) -> &KeybindingList,
+) -> Result<Vec<T>, String> {
+  let entries = match value {
+    toml::Value::Array(entries) => entries,
+    entry @ toml::Value::Table(_) => vec![entry],
+    _ => return Err(format!("{name} must be a table or an array of tables")),
+  };
+
+  entries
+    .into_iter()
+    .map(|entry| {
+      let binding: T = entry.try_into().map_err(|error| error.to_string())?;
+      if keys(&binding).first().is_none() {
+        return Err(format!("{name} entries need at least one key"));
+      }
+      Ok(binding)
+    })
+    .collect()
+}
+
 /// Configuration for custom keyboard shortcuts.
 ///
 /// TOML uses key-first entries such as `"ctrl-shift-c" = "copy"`.
@@ -372,6 +504,10 @@ pub(crate) fn merge_keybinding_tables(
 /// (or `noop = "some-key"` in the legacy format) to disable a specific default key.
 /// Legacy action-first entries are still accepted when loading configs and add
 /// bindings instead of replacing defaults.
+///
+/// `send_text = { keys = "ctrl-backspace", text = "\u0017" }` (or an array of such
+/// tables) writes text straight to the shell instead of running an action, and
+/// `search_preset = { keys = "ctrl-alt-e", preset = "errors" }` runs a saved search.
 #[derive(Debug, Clone)]
 pub struct KeybindingConfig {
   /// Copy selection to clipboard
@@ -408,6 +544,20 @@ pub struct KeybindingConfig {
   pub select_last_tab: KeybindingList,
   /// Toggle search bar
   pub toggle_search: KeybindingList,
+  /// Toggle the panel listing search results across all panes or tabs
+  pub toggle_search_panel: KeybindingList,
+  /// Label visible matches of the configured hint patterns
+  pub hint_mode: KeybindingList,
+  /// Scroll to the previous shell prompt (requires OSC 133 shell integration)
+  pub scroll_to_previous_prompt: KeybindingList,
+  /// Scroll to the next shell prompt (requires OSC 133 shell integration)
+  pub scroll_to_next_prompt: KeybindingList,
+  /// Select the output of the command at the top of the viewport, or of the last command
+  pub select_command_output: KeybindingList,
+  /// Copy the output of the last finished command
+  pub copy_last_command_output: KeybindingList,
+  /// Toggle keyboard-driven copy mode over the scrollback
+  pub copy_mode: KeybindingList,
   /// Split pane horizontally
   pub split_horizontal: KeybindingList,
   /// Split pane vertically
@@ -460,6 +610,10 @@ pub struct KeybindingConfig {
   pub quit: KeybindingList,
   /// Keys explicitly disabled by the user
   pub noop: KeybindingList,
+  /// Keys that write fixed text to the terminal
+  pub send_text: Vec<SendTextBinding>,
+  /// Keys that run a named search preset
+  pub search_preset: Vec<SearchPresetBinding>,
 }
 
 impl KeybindingConfig {
@@ -485,7 +639,8 @@ impl KeybindingConfig {
     KeybindingAction::FocusPaneDown,
   ];
 
-  const MAIN_WINDOW_SHORTCUTS_CONTINUED: [KeybindingAction; 8] = [
+  const MAIN_WINDOW_SHORTCUTS_CONTINUED: [KeybindingAction; 9] = [
+    KeybindingAction::ToggleSearchPanel,
     KeybindingAction::FocusPaneLeft,
     KeybindingAction::FocusPaneRight,
     KeybindingAction::SwapSplitPanes,
@@ -496,6 +651,18 @@ impl KeybindingConfig {
     KeybindingAction::Quit,
   ];
 
+  const NEW_TAB_PROFILE_SHORTCUTS: [KeybindingAction; 9] = [
+    KeybindingAction::NewTabProfile1,
+    KeybindingAction::NewTabProfile2,
+    KeybindingAction::NewTabProfile3,
+    KeybindingAction::NewTabProfile4,
+    KeybindingAction::NewTabProfile5,
+    KeybindingAction::NewTabProfile6,
+    KeybindingAction::NewTabProfile7,
+    KeybindingAction::NewTabProfile8,
+    KeybindingAction::NewTabProfile9,
+  ];
+
   pub fn matches_main_window_shortcut(
     &self,
     control: bool,
@@ -507,22 +674,125 @@ impl KeybindingConfig {
     Self::MAIN_WINDOW_SHORTCUTS
       .into_iter()
       .chain(Self::MAIN_WINDOW_SHORTCUTS_CONTINUED)
-      .chain([
-        KeybindingAction::NewTabProfile1,
-        KeybindingAction::NewTabProfile2,
-        KeybindingAction::NewTabProfile3,
-        KeybindingAction::NewTabProfile4,
-        KeybindingAction::NewTabProfile5,
-        KeybindingAction::NewTabProfile6,
-        KeybindingAction::NewTabProfile7,
-        KeybindingAction::NewTabProfile8,
-        KeybindingAction::NewTabProfile9,
-      ])
+      .chain(Self::NEW_TAB_PROFILE_SHORTCUTS)
       .any(|action| {
         self
           .binding(action)
           .matches(control, shift, alt, platform, key)
       })
+      || self
+        .send_text
+        .iter()
+        .any(|binding| binding.keys.matches(control, shift, alt, platform, key))
+      || self
+        .search_preset
+        .iter()
+        .any(|binding| binding.keys.matches(control, shift, alt, platform, key))
+  }
+
+  /// Whether `keystrokes` are the leading keys of a longer main window shortcut.
+  ///
+  /// Sequences bound to terminal actions are left to the GPUI keymap, which tracks
+  /// its own pending keystrokes.
+  pub fn continues_main_window_sequence(&self, keystrokes: &[ParsedKeybinding]) -> bool {
+    Self::MAIN_WINDOW_SHORTCUTS
+      .into_iter()
+      .chain(Self::MAIN_WINDOW_SHORTCUTS_CONTINUED)
+      .chain(Self::NEW_TAB_PROFILE_SHORTCUTS)
+      .any(|action| self.binding(action).continues_sequence(keystrokes))
+      || self
+        .send_text
+        .iter()
+        .any(|binding| binding.keys.continues_sequence(keystrokes))
+      || self
+        .search_preset
+        .iter()
+        .any(|binding| binding.keys.continues_sequence(keystrokes))
+  }
+
+  /// Remove bindings that can never complete because a shorter binding for a
+  /// different action is one of their prefixes, describing each one removed.
+  pub fn remove_shadowed_sequences(&mut self) -> Vec<String> {
+    let conflicts = self.sequence_conflicts();
+    let shadowed = conflicts
+      .iter()
+      .map(|(_, binding)| binding.as_str())
+      .collect::<HashSet<_>>();
+
+    for action in KeybindingAction::ALL {
+      self
+        .binding_mut(action)
+        .0
+        .retain(|binding| !shadowed.contains(binding.as_str()));
+    }
+    for keys in self
+      .send_text
+      .iter_mut()
+      .map(|send_text| &mut send_text.keys)
+      .chain(
+        self
+          .search_preset
+          .iter_mut()
+          .map(|search_preset| &mut search_preset.keys),
+      )
+    {
+      keys
+        .0
+        .retain(|binding| !shadowed.contains(binding.as_str()));
+    }
+
+    conflicts.into_iter().map(|(message, _)| message).collect()
+  }
+
+  /// Describe bindings that can never complete, paired with the shadowed binding.
+  ///
+  /// A longer binding that runs the same action as its prefix is not a conflict:
+  /// the prefix already does what the sequence would.
+  fn sequence_conflicts(&self) -> Vec<(String, String)> {
+    let bindings = self
+      .to_binding_map()
+      .into_iter()
+      .filter(|(_, action)| *action != KeybindingAction::Noop)
+      .map(|(binding, action)| (binding, action.as_str(), ""))
+      .chain(self.send_text.iter().flat_map(|send_text| {
+        send_text
+          .keys
+          .iter()
+          .map(|binding| (binding.to_string(), "send_text", send_text.text.as_str()))
+      }))
+      .chain(self.search_preset.iter().flat_map(|search_preset| {
+        search_preset.keys.iter().map(|binding| {
+          (
+            binding.to_string(),
+            "search_preset",
+            search_preset.preset.as_str(),
+          )
+        })
+      }))
+      .map(|(binding, action, argument)| {
+        let sequence = ParsedKeybinding::parse_sequence(&binding);
+        (binding, sequence, (action, argument))
+      })
+      .collect::<Vec<_>>();
+
+    let mut conflicts = Vec::new();
+    for (prefix, prefix_sequence, prefix_action) in &bindings {
+      for (binding, sequence, action) in &bindings {
+        if action != prefix_action
+          && sequence.len() > prefix_sequence.len()
+          && sequence[..prefix_sequence.len()] == prefix_sequence[..]
+        {
+          conflicts.push((
+            format!(
+              "'{prefix}' ({}) is a prefix of '{binding}' ({})",
+              prefix_action.0, action.0
+            ),
+            binding.clone(),
+          ));
+        }
+      }
+    }
+    conflicts
   }
 
   fn empty() -> Self {
@@ -594,6 +864,13 @@ impl KeybindingConfig {
       KeybindingAction::SelectTab8 => &self.select_tab_8,
       KeybindingAction::SelectLastTab => &self.select_last_tab,
       KeybindingAction::ToggleSearch => &self.toggle_search,
+      KeybindingAction::ToggleSearchPanel => &self.toggle_search_panel,
+      KeybindingAction::HintMode => &self.hint_mode,
+      KeybindingAction::ScrollToPreviousPrompt => &self.scroll_to_previous_prompt,
+      KeybindingAction::ScrollToNextPrompt => &self.scroll_to_next_prompt,
+      KeybindingAction::SelectCommandOutput => &self.select_command_output,
+      KeybindingAction::CopyLastCommandOutput => &self.copy_last_command_output,
+      KeybindingAction::CopyMode => &self.copy_mode,
       KeybindingAction::SplitHorizontal => &self.split_horizontal,
       KeybindingAction::SplitVertical => &self.split_vertical,
       KeybindingAction::ClosePane => &self.close_pane,
@@ -642,6 +919,13 @@ impl KeybindingConfig {
       KeybindingAction::SelectTab8 => &mut self.select_tab_8,
       KeybindingAction::SelectLastTab => &mut self.select_last_tab,
       KeybindingAction::ToggleSearch => &mut self.toggle_search,
+      KeybindingAction::ToggleSearchPanel => &mut self.toggle_search_panel,
+      KeybindingAction::HintMode => &mut self.hint_mode,
+      KeybindingAction::ScrollToPreviousPrompt => &mut self.scroll_to_previous_prompt,
+      KeybindingAction::ScrollToNextPrompt => &mut self.scroll_to_next_prompt,
+      KeybindingAction::SelectCommandOutput => &mut self.select_command_output,
+      KeybindingAction::CopyLastCommandOutput => &mut self.copy_last_command_output,
+      KeybindingAction::CopyMode => &mut self.copy_mode,
       KeybindingAction::SplitHorizontal => &mut self.split_horizontal,
       KeybindingAction::SplitVertical => &mut self.split_vertical,
       KeybindingAction::ClosePane => &mut self.close_pane,
@@ -672,16 +956,54 @@ impl KeybindingConfig {
   }
 
   fn from_toml_value(value: toml::Value) -> Result<Self, String> {
-    let toml::Value::Table(table) = value else {
+    let toml::Value::Table(mut table) = value else {
       return Err("keybindings must be a table".to_string());
     };
 
+    let send_text = match table.remove("send_text") {
+      Some(value) => keyed_bindings_from_value("send_text", value, |binding: &SendTextBinding| {
+        &binding.keys
+      })
+      .map_err(|error| format!("keybindings.send_text: {error}"))?,
+      None => Vec::new(),
+    };
+    let search_preset = match table.remove("search_preset") {
+      Some(value) => {
+        keyed_bindings_from_value("search_preset", value, |binding: &SearchPresetBinding| {
+          &binding.keys
+        })
+        .map_err(|error| format!("keybindings.search_preset: {error}"))?
+      }
+      None => Vec::new(),
+    };
+
     let mut bindings = Self::default_binding_map();
     for (binding, action) in Self::binding_overrides_from_table(&table)? {
       bindings.insert(binding, action);
     }
 
-    Ok(Self::from_binding_map(bindings))
+    let mut config = Self::from_binding_map(bindings);
+    config.send_text = send_text;
+    config.search_preset = search_preset;
+    Ok(config)
+  }
+
+  /// Text of the `send_text` binding completed by `keystrokes`, if any.
+  pub fn send_text_for(&self, keystrokes: &[ParsedKeybinding]) -> Option<&str> {
+    self
+      .send_text
+      .iter()
+      .find(|binding| binding.keys.matches_sequence(keystrokes))
+      .map(|binding| binding.text.as_str())
+  }
+
+  /// Name of the search preset whose binding is completed by `keystrokes`, if any.
+  pub fn search_preset_for(&self, keystrokes: &[ParsedKeybinding]) -> Option<&str> {
+    self
+      .search_preset
+      .iter()
+      .find(|binding| binding.keys.matches_sequence(keystrokes))
+      .map(|binding| binding.preset.as_str())
   }
 
   fn to_binding_map(&self) -> BTreeMap<String, KeybindingAction> {
@@ -718,7 +1040,22 @@ impl Serialize for KeybindingConfig {
   where
     S: Serializer,
   {
-    self.to_key_first_map().serialize(serializer)
+    let bindings = self.to_key_first_map();
+    let send_text = !self.send_text.is_empty();
+    let search_preset = !self.search_preset.is_empty();
+    let mut map = serializer.serialize_map(Some(
+      bindings.len() + usize::from(send_text) + usize::from(search_preset),
+    ))?;
+    for (binding, action) in &bindings {
+      map.serialize_entry(binding, action)?;
+    }
+    if send_text {
+      map.serialize_entry("send_text", &self.send_text)?;
+    }
+    if search_preset {
+      map.serialize_entry("search_preset", &self.search_preset)?;
+    }
+    map.end()
   }
 }
 
@@ -753,6 +1090,13 @@ impl Default for KeybindingConfig {
         select_tab_8: KeybindingList::new("cmd-8"),
         select_last_tab: KeybindingList::new("cmd-9"),
         toggle_search: KeybindingList::new("cmd-f"),
+        toggle_search_panel: KeybindingList::new("cmd-shift-f"),
+        hint_mode: KeybindingList::new("cmd-shift-h"),
+        scroll_to_previous_prompt: KeybindingList::new("cmd-up"),
+        scroll_to_next_prompt: KeybindingList::new("cmd-down"),
+        select_command_output: KeybindingList::new("cmd-shift-o"),
+        copy_last_command_output: KeybindingList::new("cmd-shift-g"),
+        copy_mode: KeybindingList::new("ctrl-shift-space"),
         split_horizontal: KeybindingList::new("alt-shift-minus"),
         split_vertical: KeybindingList::new("alt-shift-equal"),
         close_pane: KeybindingList::new("cmd-w"),
@@ -779,6 +1123,8 @@ impl Default for KeybindingConfig {
         new_window: KeybindingList::new("cmd-n"),
         quit: KeybindingList::new("cmd-q"),
         noop: KeybindingList::default(),
+        send_text: Vec::new(),
+        search_preset: Vec::new(),
       }
     } else {
       Self {
@@ -799,6 +1145,13 @@ impl Default for KeybindingConfig {
         select_tab_8: KeybindingList::new("ctrl-alt-8"),
         select_last_tab: KeybindingList::new("ctrl-alt-9"),
         toggle_search: KeybindingList::new("ctrl-shift-f"),
+        toggle_search_panel: KeybindingList::new("ctrl-alt-shift-f"),
+        hint_mode: KeybindingList::new("ctrl-shift-h"),
+        scroll_to_previous_prompt: KeybindingList::new("ctrl-shift-up"),
+        scroll_to_next_prompt: KeybindingList::new("ctrl-shift-down"),
+        select_command_output: KeybindingList::new("ctrl-shift-o"),
+        copy_last_command_output: KeybindingList::new("ctrl-shift-g"),
+        copy_mode: KeybindingList::new("ctrl-shift-space"),
         split_horizontal: KeybindingList::new("alt-shift-minus"),
         split_vertical: KeybindingList::new("alt-shift-equal"),
         close_pane: KeybindingList::new("ctrl-shift-w"),
@@ -825,6 +1178,8 @@ impl Default for KeybindingConfig {
         new_window: KeybindingList::new("ctrl-shift-n"),
         quit: KeybindingList::new("alt-f4"),
         noop: KeybindingList::default(),
+        send_text: Vec::new(),
+        search_preset: Vec::new(),
       }
     }
   }
@@ -887,6 +1242,27 @@ impl ParsedKeybinding {
     }
   }
 
+  /// Parse a key sequence such as `"ctrl-a c"` into its keystrokes.
+  ///
+  /// Keystrokes are separated by whitespace; a trailing comma on a keystroke is
+  /// accepted so tmux-style `"ctrl-a, c"` reads the same.
+  pub fn parse_sequence(s: &str) -> Vec<Self> {
+    s.split_whitespace()
+      .map(|stroke| Self::parse(strip_sequence_separator(stroke)))
+      .collect()
+  }
+
+  /// Describe a pressed key event in the same form as a parsed binding.
+  pub fn from_event(control: bool, shift: bool, alt: bool, platform: bool, key: &str) -> Self {
+    Self {
+      control,
+      shift,
+      alt,
+      platform,
+      key: key.to_string(),
+    }
+  }
+
   /// Check if this parsed keybinding matches the given key event parameters.
   ///
   /// On Windows, GPUI converts Shift+digit into the shifted symbol (e.g. `!` for
@@ -938,6 +1314,38 @@ impl ParsedKeybinding {
     parts.push(display_key(&self.key));
     parts.join("+")
   }
+
+  /// Format a key sequence for display, e.g. `"ctrl-a c"` → "Ctrl+A, C"
+  pub fn display_sequence(keystrokes: &[Self]) -> String {
+    keystrokes
+      .iter()
+      .map(Self::display_text)
+      .collect::<Vec<_>>()
+      .join(", ")
+  }
+}
+
+/// Whether the event `keystrokes` match the leading keystrokes of `sequence`.
+fn sequence_starts_with(sequence: &[ParsedKeybinding], keystrokes: &[ParsedKeybinding]) -> bool {
+  !keystrokes.is_empty()
+    && sequence.len() >= keystrokes.len()
+    && sequence.iter().zip(keystrokes).all(|(binding, keystroke)| {
+      binding.matches(
+        keystroke.control,
+        keystroke.shift,
+        keystroke.alt,
+        keystroke.platform,
+        &keystroke.key,
+      )
+    })
+}
+
+/// Drop the tmux-style `,` between keystrokes without eating a bound comma key.
+fn strip_sequence_separator(stroke: &str) -> &str {
+  match stroke.strip_suffix(',') {
+    Some(rest) if !rest.is_empty() && !rest.ends_with('-') => rest,
+    _ => stroke,
+  }
 }
 
 fn platform_modifier_label() -> &'static str {
@@ -1522,11 +1930,229 @@ noop = "{}"
 
     if cfg!(target_os = "macos") {
       assert!(!config.matches_main_window_shortcut(false, false, false, true, "c"));
+      assert!(!config.matches_main_window_shortcut(false, true, false, true, "h"));
     } else {
       assert!(!config.matches_main_window_shortcut(true, true, false, false, "c"));
+      assert!(!config.matches_main_window_shortcut(true, true, false, false, "h"));
     }
   }
 
+  #[test]
+  fn parse_sequence_accepts_spaces_and_tmux_commas() {
+    let expected = vec![
+      ParsedKeybinding::parse("ctrl-a"),
+      ParsedKeybinding::parse("c"),
+    ];
+    assert_eq!(ParsedKeybinding::parse_sequence("ctrl-a c"), expected);
+    assert_eq!(ParsedKeybinding::parse_sequence("ctrl-a, c"), expected);
+    assert_eq!(
+      ParsedKeybinding::parse_sequence("ctrl-, ,"),
+      vec![
+        ParsedKeybinding::parse("ctrl-,"),
+        ParsedKeybinding::parse(","),
+      ]
+    );
+  }
+
+  #[test]
+  fn keybinding_list_matches_sequences_only_when_complete() {
+    let bindings = KeybindingList::new("ctrl-a, c");
+    let prefix = [ParsedKeybinding::from_event(true, false, false, false, "a")];
+    let complete = [
+      ParsedKeybinding::from_event(true, false, false, false, "a"),
+      ParsedKeybinding::from_event(false, false, false, false, "c"),
+    ];
+
+    assert!(!bindings.matches(true, false, false, false, "a"));
+    assert!(bindings.continues_sequence(&prefix));
+    assert!(!bindings.continues_sequence(&complete));
+    assert!(bindings.matches_sequence(&complete));
+    assert_eq!(bindings.display_text(), "Ctrl+A, C");
+    assert_eq!(
+      bindings.keymap_bindings().collect::<Vec<_>>(),
+      vec!["ctrl-a c".to_string()]
+    );
+  }
+
+  #[test]
+  fn keybinding_config_tracks_pending_main_window_sequences() {
+    let config: KeybindingConfig = toml::from_str(
+      r#"
+"ctrl-a c" = "new_tab"
+"ctrl-b x" = "copy"
+"#,
+    )
+    .unwrap();
+
+    assert!(
+      config.continues_main_window_sequence(&[ParsedKeybinding::from_event(
+        true, false, false, false, "a",
+      )])
+    );
+    assert!(
+      !config.continues_main_window_sequence(&[ParsedKeybinding::from_event(
+        true, false, false, false, "b",
+      )])
+    );
+    assert!(config.sequence_conflicts().is_empty());
+  }
+
+  #[test]
+  fn keybinding_config_removes_shadowed_sequences() {
+    let mut config: KeybindingConfig = toml::from_str(
+      r#"
+"ctrl-a" = "copy"
+"ctrl-a, c" = "new_tab"
+"ctrl-b" = "noop"
+"ctrl-b c" = "paste"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      config.remove_shadowed_sequences(),
+      vec!["'ctrl-a' (copy) is a prefix of 'ctrl-a, c' (new_tab)".to_string()]
+    );
+    assert!(config.copy.iter().any(|binding| binding == "ctrl-a"));
+    assert!(!config.new_tab.iter().any(|binding| binding == "ctrl-a, c"));
+    assert!(config.paste.iter().any(|binding| binding == "ctrl-b c"));
+    assert!(config.remove_shadowed_sequences().is_empty());
+  }
+
+  #[test]
+  fn keybinding_config_keeps_sequences_with_the_prefix_action() {
+    let mut config: KeybindingConfig = toml::from_str(
+      r#"
+copy = ["ctrl-a", "ctrl-a c"]
+send_text = [
+  { keys = ["ctrl-b", "ctrl-b x"], text = "ls\r" },
+  { keys = "ctrl-b y", text = "pwd\r" },
+]
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      config.remove_shadowed_sequences(),
+      vec!["'ctrl-b' (send_text) is a prefix of 'ctrl-b y' (send_text)".to_string()]
+    );
+    assert!(config.copy.iter().any(|binding| binding == "ctrl-a c"));
+    assert!(
+      config.send_text[0]
+        .keys
+        .iter()
+        .any(|binding| binding == "ctrl-b x")
+    );
+    assert!(config.send_text[1].keys.first().is_none());
+  }
+
+  #[test]
+  fn merge_keybinding_tables_concatenates_keyed_entries() {
+    let mut target: toml::map::Map<String, toml::Value> = toml::from_str(
+      r#"
+"ctrl-alt-c" = "copy"
+send_text = { keys = "ctrl-backspace", text = "\u0017" }
+"#,
+    )
+    .unwrap();
+    let overlay: toml::map::Map<String, toml::Value> = toml::from_str(
+      r#"
+copy = "ctrl-insert"
+send_text = [{ keys = "alt-s", text = "git status\r" }]
+search_preset = { keys = "ctrl-alt-e", preset = "errors" }
+"#,
+    )
+    .unwrap();
+
+    merge_keybinding_tables(&mut target, overlay);
+    let config: KeybindingConfig = toml::Value::Table(target).try_into().unwrap();
+
+    assert!(config.copy.iter().any(|binding| binding == "ctrl-alt-c"));
+    assert!(config.copy.iter().any(|binding| binding == "ctrl-insert"));
+    assert_eq!(
+      config
+        .send_text
+        .iter()
+        .map(|binding| binding.text.as_str())
+        .collect::<Vec<_>>(),
+      vec!["\u{17}", "git status\r"]
+    );
+    assert_eq!(config.search_preset.len(), 1);
+  }
+
+  #[test]
+  fn keybinding_config_deserialize_send_text_bindings() {
+    let config: KeybindingConfig = toml::from_str(
+      r#"
+"ctrl-shift-c" = "copy"
+send_text = [
+  { keys = "ctrl-backspace", text = "\u0017" },
+  { keys = ["ctrl-g s", "alt-s"], text = "git status\r" },
+]
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.send_text.len(), 2);
+    assert_eq!(
+      config.send_text_for(&[ParsedKeybinding::from_event(
+        true,
+        false,
+        false,
+        false,
+        "backspace",
+      )]),
+      Some("\u{17}")
+    );
+    assert_eq!(
+      config.send_text_for(&ParsedKeybinding::parse_sequence("ctrl-g s")),
+      Some("git status\r")
+    );
+    assert!(config.matches_main_window_shortcut(false, false, true, false, "s"));
+    assert!(
+      config.continues_main_window_sequence(&[ParsedKeybinding::from_event(
+        true, false, false, false, "g",
+      )])
+    );
+  }
+
+  #[test]
+  fn keybinding_config_send_text_roundtrip() {
+    let config: KeybindingConfig =
+      toml::from_str(r#"send_text = { keys = "ctrl-backspace", text = "\u0017" }"#).unwrap();
+
+    let serialized = toml::to_string(&config).unwrap();
+    let reparsed: KeybindingConfig = toml::from_str(&serialized).unwrap();
+
+    assert_eq!(reparsed.send_text, config.send_text);
+    assert_eq!(reparsed.copy, config.copy);
+  }
+
+  #[test]
+  fn keybinding_config_rejects_send_text_without_keys() {
+    let error = toml::from_str::<KeybindingConfig>(r#"send_text = { keys = [], text = "x" }"#)
+      .unwrap_err()
+      .to_string();
+
+    assert!(error.contains("send_text entries need at least one key"));
+  }
+
+  #[test]
+  fn keybinding_config_deserialize_search_preset_bindings() {
+    let config: KeybindingConfig =
+      toml::from_str(r#"search_preset = { keys = "ctrl-alt-e", preset = "errors" }"#).unwrap();
+
+    assert_eq!(
+      config.search_preset_for(&ParsedKeybinding::parse_sequence("ctrl-alt-e")),
+      Some("errors")
+    );
+    assert!(config.matches_main_window_shortcut(true, false, true, false, "e"));
+
+    let serialized = toml::to_string(&config).unwrap();
+    let reparsed: KeybindingConfig = toml::from_str(&serialized).unwrap();
+    assert_eq!(reparsed.search_preset, config.search_preset);
+  }
+
   #[test]
   fn keybinding_list_displays_multiple_bindings() {
     let bindings = KeybindingList::from_vec(vec!["ctrl-shift-c".into(), "ctrl-insert".into()]);
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+/// Direction a layout pane is split in.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutSplit {
+  /// Panes side by side, the first on the left.
+  Vertical,
+  /// Panes stacked, the first on top.
+  Horizontal,
+}
+
+/// A pane of a layout: either a terminal, or a split of exactly two panes.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct PaneLayout {
+  /// Program to run instead of the tab's shell.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub command: Option<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub args: Vec<String>,
+  /// Working directory, inherited by the panes of a split.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cwd: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub split: Option<LayoutSplit>,
+  /// Share of the split taken by the first pane, between 0.1 and 0.9.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ratio: Option<f32>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub panes: Vec<PaneLayout>,
+}
+
+/// A tab of a layout. Its pane fields describe the tab's root pane.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct TabLayout {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub title: Option<String>,
+  /// Profile whose shell runs in panes without a `command`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub profile: Option<String>,
+  #[serde(flatten)]
+  pub root: PaneLayout,
+}
+
+/// A named set of tabs, opened from the new-tab menu or at startup.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Layout {
+  #[serde(default)]
+  pub tabs: Vec<TabLayout>,
+}
+
+impl Layout {
+  pub(crate) fn validate(&self, name: &str) -> Result<(), String> {
+    if self.tabs.is_empty() {
+      return Err(format!("layouts.{name} has no tabs."));
+    }
+    for (ix, tab) in self.tabs.iter().enumerate() {
+      tab.root.validate(&format!("layouts.{name}.tabs[{ix}]"))?;
+    }
+    Ok(())
+  }
+}
+
+impl PaneLayout {
+  fn validate(&self, path: &str) -> Result<(), String> {
+    if !self.args.is_empty() && self.command.is_none() {
+      return Err(format!("{path} sets args without a command."));
+    }
+    if self.split.is_none() {
+      if !self.panes.is_empty() {
+        return Err(format!("{path} lists panes without a split."));
+      }
+      if self.ratio.is_some() {
+        return Err(format!("{path} sets a ratio without a split."));
+      }
+      return Ok(());
+    }
+    if self.command.is_some() {
+      return Err(format!("{path} sets both a split and a command."));
+    }
+    if self.panes.len() != 2 {
+      return Err(format!(
+        "{path} splits into {} panes; a split needs exactly two.",
+        self.panes.len()
+      ));
+    }
+    if let Some(ratio) = self.ratio
+      && !(0.1..=0.9).contains(&ratio)
+    {
+      return Err(format!(
+        "{path} has ratio {ratio}; use a value between 0.1 and 0.9."
+      ));
+    }
+    for (ix, pane) in self.panes.iter().enumerate() {
+      pane.validate(&format!("{path}.panes[{ix}]"))?;
+    }
+    Ok(())
+  }
+
+  /// The terminal that stays in place when this pane is split: the first
+  /// pane all the way down, with the working directory it inherits.
+  pub fn first_terminal<'a>(&'a self, cwd: Option<&'a str>) -> (&'a PaneLayout, Option<&'a str>) {
+    let cwd = self.cwd.as_deref().or(cwd);
+    match (self.split, self.panes.first()) {
+      (Some(_), Some(first)) => first.first_terminal(cwd),
+      _ => (self, cwd),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use super::{Layout, LayoutSplit};
+
+  fn parse(toml: &str) -> BTreeMap<String, Layout> {
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+      layouts: BTreeMap<String, Layout>,
+    }
+    toml::from_str::<Wrapper>(toml).unwrap().layouts
+  }
+
+  #[test]
+  fn parses_nested_splits() {
+    let layouts = parse(
+      r#"
+[[layouts.dev.tabs]]
+title = "build"
+cwd = "/src"
+split = "vertical"
+ratio = 0.6
+panes = [
+  { command = "cargo", args = ["watch"] },
+  { split = "horizontal", panes = [{ command = "htop" }, {}] },
+]
+"#,
+    );
+    let tab = &layouts["dev"].tabs[0];
+    assert_eq!(tab.title.as_deref(), Some("build"));
+    assert_eq!(tab.root.split, Some(LayoutSplit::Vertical));
+    assert_eq!(tab.root.ratio, Some(0.6));
+    assert!(layouts["dev"].validate("dev").is_ok());
+
+    let (first, cwd) = tab.root.first_terminal(None);
+    assert_eq!(first.command.as_deref(), Some("cargo"));
+    assert_eq!(cwd, Some("/src"));
+    let (first, _) = tab.root.panes[1].first_terminal(None);
+    assert_eq!(first.command.as_deref(), Some("htop"));
+  }
+
+  #[test]
+  fn rejects_malformed_splits() {
+    for (toml, message) in [
+      ("[layouts.dev]\ntabs = []", "has no tabs"),
+      (
+        "[[layouts.dev.tabs]]\nsplit = \"vertical\"\npanes = [{}]",
+        "needs exactly two",
+      ),
+      (
+        "[[layouts.dev.tabs]]\nsplit = \"vertical\"\nratio = 0.95\npanes = [{}, {}]",
+        "between 0.1 and 0.9",
+      ),
+      ("[[layouts.dev.tabs]]\npanes = [{}, {}]", "without a split"),
+      ("[[layouts.dev.tabs]]\nargs = [\"-x\"]", "without a command"),
+    ] {
+      let error = parse(toml)["dev"].validate("dev").unwrap_err();
+      assert!(error.contains(message), "{error}");
+    }
+  }
+}
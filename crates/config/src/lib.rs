@@ -1,6 +1,6 @@
 use gpui::Rgba;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 const GENERATED_CONFIG_HEADER: &str = "# Kazeterm Configuration\n# Generated automatically\n\n";
@@ -42,8 +42,19 @@ pub use theme::{
 pub mod migration;
 pub use migration::CURRENT_CONFIG_VERSION;
 
+mod hints;
+pub use hints::{HintAction, HintRule, HintsConfig};
+
 mod keybinding;
-pub use keybinding::{KeybindingConfig, KeybindingList, ParsedKeybinding};
+pub use keybinding::{
+  KeybindingConfig, KeybindingList, ParsedKeybinding, SearchPresetBinding, SendTextBinding,
+};
+
+mod search;
+pub use search::{SearchConfig, SearchPreset};
+
+mod layouts;
+pub use layouts::{Layout, LayoutSplit, PaneLayout, TabLayout};
 
 pub mod alacritty_import;
 
@@ -149,6 +160,14 @@ pub struct WindowConfig {
   /// Show a key debug overlay in the bottom-right corner of the main window.
   /// The overlay lists actions that match the currently held modifiers.
   pub key_debug_mode: bool,
+  /// Seconds to wait after a layout change before autosaving it for crash
+  /// recovery, and between the periodic autosaves that pick up titles,
+  /// working directories and new output. Values above 3600 are treated as
+  /// 3600. Set to 0 to disable autosave.
+  pub autosave_interval_secs: u64,
+  /// Name of a `[layouts.<name>]` entry that new windows open with instead of
+  /// a single tab, unless a previous workspace is restored.
+  pub startup_layout: Option<String>,
 }
 
 impl Default for WindowConfig {
@@ -159,10 +178,20 @@ impl Default for WindowConfig {
       start_maximized: false,
       restore_workspace: true,
       key_debug_mode: false,
+      autosave_interval_secs: 10,
+      startup_layout: None,
     }
   }
 }
 
+impl WindowConfig {
+  /// Get the autosave delay clamped to [1, 3600] seconds, or `None` when disabled
+  pub fn get_autosave_interval(&self) -> Option<std::time::Duration> {
+    (self.autosave_interval_secs > 0)
+      .then(|| std::time::Duration::from_secs(self.autosave_interval_secs.min(3600)))
+  }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct TabConfig {
@@ -369,6 +398,14 @@ pub struct TerminalConfig {
   /// Additional environment variables to set for the terminal shell.
   #[serde(default)]
   pub env: HashMap<String, String>,
+  /// Save each pane's scrollback with the workspace and replay it when the
+  /// workspace is restored. Profiles can override this with `persist_scrollback`.
+  pub persist_scrollback: bool,
+  /// Maximum number of scrollback lines saved per pane.
+  pub persist_scrollback_lines: u32,
+  /// Maximum size in bytes of the uncompressed scrollback saved per pane.
+  /// The oldest lines are dropped first.
+  pub persist_scrollback_max_bytes: u64,
 }
 
 impl Default for TerminalConfig {
@@ -386,6 +423,9 @@ impl Default for TerminalConfig {
       working_directory: None,
       default_profile: None,
       env: HashMap::new(),
+      persist_scrollback: false,
+      persist_scrollback_lines: 2_000,
+      persist_scrollback_max_bytes: 1024 * 1024,
     }
   }
 }
@@ -395,6 +435,16 @@ impl TerminalConfig {
   pub fn get_scrollback_lines(&self) -> usize {
     (self.scrollback_lines as usize).min(100_000)
   }
+
+  /// Get persisted scrollback lines, never more than the scrollback itself holds
+  pub fn get_persist_scrollback_lines(&self) -> usize {
+    (self.persist_scrollback_lines as usize).min(self.get_scrollback_lines())
+  }
+
+  /// Get the persisted scrollback size limit clamped to [0, 16 MiB]
+  pub fn get_persist_scrollback_max_bytes(&self) -> usize {
+    self.persist_scrollback_max_bytes.min(16 * 1024 * 1024) as usize
+  }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -498,6 +548,12 @@ pub struct Config {
   pub cursor: CursorConfig,
   pub notification: NotificationConfig,
   pub auto_update: AutoUpdateConfig,
+  /// Keyboard-driven hint mode over visible terminal text
+  pub hints: HintsConfig,
+  /// Search history and named search presets
+  pub search: SearchConfig,
+  /// Named tab layouts, e.g. `[layouts.dev]` with `[[layouts.dev.tabs]]` entries
+  pub layouts: BTreeMap<String, Layout>,
   #[serde(default)]
   pub profiles: Vec<Profile>,
   /// Custom keyboard shortcuts
@@ -521,6 +577,9 @@ impl Default for Config {
       cursor: CursorConfig::default(),
       notification: NotificationConfig::default(),
       auto_update: AutoUpdateConfig::default(),
+      hints: HintsConfig::default(),
+      search: SearchConfig::default(),
+      layouts: BTreeMap::new(),
       profiles: profiles::default_profiles(),
       keybindings: KeybindingConfig::default(),
       container_profiles: profiles::detect_container_profiles(),
@@ -571,6 +630,21 @@ impl Config {
           Self::current_platform_name()
         ))
       })?;
+    self.hints.validate().map_err(std::io::Error::other)?;
+    self.search.validate().map_err(std::io::Error::other)?;
+    for (name, layout) in &self.layouts {
+      layout.validate(name).map_err(std::io::Error::other)?;
+    }
+    if let Some(name) = &self.window.startup_layout
+      && !self.layouts.contains_key(name)
+    {
+      return Err(
+        std::io::Error::other(format!(
+          "window.startup_layout refers to unknown layout '{name}'."
+        ))
+        .into(),
+      );
+    }
     Ok(())
   }
 
@@ -670,6 +744,17 @@ impl Config {
 
     let mut config: Config = merged.try_into()?;
     config.container_profiles = profiles::detect_container_profiles();
+    for conflict in config.keybindings.remove_shadowed_sequences() {
+      tracing::warn!(
+        "Ignoring keybinding that can never complete: {conflict}. Rebind the shorter key or map it to \"noop\"."
+      );
+    }
+    for preset in config
+      .search
+      .remove_unknown_preset_bindings(&mut config.keybindings)
+    {
+      tracing::warn!("Ignoring keybindings.search_preset for unknown preset '{preset}'.");
+    }
     config.validate()?;
 
     if migrated {
@@ -1272,6 +1357,67 @@ kernel = "does-not-exist"
     std::fs::remove_dir_all(dir).unwrap();
   }
 
+  #[test]
+  fn load_from_path_drops_bindings_to_unknown_search_presets() {
+    let dir = test_dir("unknown-search-preset");
+    let base_path = dir.join("kazeterm.toml");
+
+    std::fs::write(
+      &base_path,
+      format!(
+        r#"version = "{}"
+
+[keybindings]
+search_preset = [
+  {{ keys = "ctrl-alt-e", preset = "errors" }},
+  {{ keys = "ctrl-alt-w", preset = "warnings" }},
+]
+"#,
+        CURRENT_CONFIG_VERSION,
+      ),
+    )
+    .unwrap();
+
+    let config = Config::load_from_path(&base_path).unwrap();
+
+    assert_eq!(
+      config
+        .keybindings
+        .search_preset
+        .iter()
+        .map(|binding| binding.preset.as_str())
+        .collect::<Vec<_>>(),
+      vec!["errors"]
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn load_from_path_rejects_invalid_search_preset_regex() {
+    let dir = test_dir("invalid-search-preset");
+    let base_path = dir.join("kazeterm.toml");
+
+    std::fs::write(
+      &base_path,
+      format!(
+        r#"version = "{}"
+
+[search.presets]
+broken = {{ pattern = "error(", regex = true }}
+"#,
+        CURRENT_CONFIG_VERSION,
+      ),
+    )
+    .unwrap();
+
+    let error = Config::load_from_path(&base_path).unwrap_err().to_string();
+
+    assert!(error.contains("search.presets.broken"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+
   #[cfg(target_os = "macos")]
   #[test]
   fn load_from_path_rejects_platform_unsupported_terminal_kernel() {
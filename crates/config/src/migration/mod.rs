@@ -4,7 +4,7 @@ mod steps;
 use steps::*;
 
 /// Current config version in YYYYMMDD.Rev format.
-pub const CURRENT_CONFIG_VERSION: &str = "20260512.1";
+pub const CURRENT_CONFIG_VERSION: &str = "20261016.9";
 
 /// A migration that transforms raw TOML config from one version to the next.
 struct Migration {
@@ -173,6 +173,51 @@ fn migrations() -> &'static [Migration] {
       to_version: "20260512.1",
       migrate: migrate_v20260422_1_to_20260512_1,
     },
+    Migration {
+      from_version: "20260512.1",
+      to_version: "20261016.1",
+      migrate: migrate_v20260512_1_to_20261016_1,
+    },
+    Migration {
+      from_version: "20261016.1",
+      to_version: "20261016.2",
+      migrate: migrate_v20261016_1_to_20261016_2,
+    },
+    Migration {
+      from_version: "20261016.2",
+      to_version: "20261016.3",
+      migrate: migrate_v20261016_2_to_20261016_3,
+    },
+    Migration {
+      from_version: "20261016.3",
+      to_version: "20261016.4",
+      migrate: migrate_v20261016_3_to_20261016_4,
+    },
+    Migration {
+      from_version: "20261016.4",
+      to_version: "20261016.5",
+      migrate: migrate_v20261016_4_to_20261016_5,
+    },
+    Migration {
+      from_version: "20261016.5",
+      to_version: "20261016.6",
+      migrate: migrate_v20261016_5_to_20261016_6,
+    },
+    Migration {
+      from_version: "20261016.6",
+      to_version: "20261016.7",
+      migrate: migrate_v20261016_6_to_20261016_7,
+    },
+    Migration {
+      from_version: "20261016.7",
+      to_version: "20261016.8",
+      migrate: migrate_v20261016_7_to_20261016_8,
+    },
+    Migration {
+      from_version: "20261016.8",
+      to_version: "20261016.9",
+      migrate: migrate_v20261016_8_to_20261016_9,
+    },
   ]
 }
 
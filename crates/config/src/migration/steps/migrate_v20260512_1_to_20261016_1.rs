@@ -0,0 +1,30 @@
+use toml::Value;
+
+/// Add hint mode configuration and its default shortcut.
+pub(crate) fn migrate_v20260512_1_to_20261016_1(value: &mut Value) {
+  if let Value::Table(table) = value {
+    if let Some(Value::Table(keybindings)) = table.get_mut("keybindings") {
+      let defaults = crate::KeybindingConfig::default();
+      if let Some(binding) = defaults.hint_mode.first()
+        && !keybindings.contains_key(binding)
+      {
+        keybindings.insert(binding.to_string(), Value::String("hint_mode".to_string()));
+      }
+    }
+
+    let hints = table
+      .entry("hints".to_string())
+      .or_insert_with(|| Value::Table(Default::default()));
+
+    if let Value::Table(hints) = hints {
+      hints
+        .entry("alphabet".to_string())
+        .or_insert_with(|| Value::String(crate::HintsConfig::default().alphabet));
+    }
+
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.1".to_string()),
+    );
+  }
+}
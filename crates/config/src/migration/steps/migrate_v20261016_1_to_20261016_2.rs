@@ -0,0 +1,11 @@
+use toml::Value;
+
+/// Add `send_text` keybindings (an empty list by default, so nothing to insert).
+pub(crate) fn migrate_v20261016_1_to_20261016_2(value: &mut Value) {
+  if let Value::Table(table) = value {
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.2".to_string()),
+    );
+  }
+}
@@ -0,0 +1,33 @@
+use toml::Value;
+
+/// Add default shortcuts for prompt navigation and command output selection.
+pub(crate) fn migrate_v20261016_2_to_20261016_3(value: &mut Value) {
+  if let Value::Table(table) = value {
+    if let Some(Value::Table(keybindings)) = table.get_mut("keybindings") {
+      let defaults = crate::KeybindingConfig::default();
+      for (list, action) in [
+        (
+          &defaults.scroll_to_previous_prompt,
+          "scroll_to_previous_prompt",
+        ),
+        (&defaults.scroll_to_next_prompt, "scroll_to_next_prompt"),
+        (&defaults.select_command_output, "select_command_output"),
+        (
+          &defaults.copy_last_command_output,
+          "copy_last_command_output",
+        ),
+      ] {
+        if let Some(binding) = list.first()
+          && !keybindings.contains_key(binding)
+        {
+          keybindings.insert(binding.to_string(), Value::String(action.to_string()));
+        }
+      }
+    }
+
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.3".to_string()),
+    );
+  }
+}
@@ -0,0 +1,20 @@
+use toml::Value;
+
+/// Add the default copy mode shortcut.
+pub(crate) fn migrate_v20261016_3_to_20261016_4(value: &mut Value) {
+  if let Value::Table(table) = value {
+    if let Some(Value::Table(keybindings)) = table.get_mut("keybindings") {
+      let defaults = crate::KeybindingConfig::default();
+      if let Some(binding) = defaults.copy_mode.first()
+        && !keybindings.contains_key(binding)
+      {
+        keybindings.insert(binding.to_string(), Value::String("copy_mode".to_string()));
+      }
+    }
+
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.4".to_string()),
+    );
+  }
+}
@@ -0,0 +1,23 @@
+use toml::Value;
+
+/// Add the default search panel shortcut.
+pub(crate) fn migrate_v20261016_4_to_20261016_5(value: &mut Value) {
+  if let Value::Table(table) = value {
+    if let Some(Value::Table(keybindings)) = table.get_mut("keybindings") {
+      let defaults = crate::KeybindingConfig::default();
+      if let Some(binding) = defaults.toggle_search_panel.first()
+        && !keybindings.contains_key(binding)
+      {
+        keybindings.insert(
+          binding.to_string(),
+          Value::String("toggle_search_panel".to_string()),
+        );
+      }
+    }
+
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.5".to_string()),
+    );
+  }
+}
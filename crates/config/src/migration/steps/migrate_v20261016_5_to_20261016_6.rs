@@ -0,0 +1,27 @@
+use toml::Value;
+
+/// Add the search history size and the default search presets.
+pub(crate) fn migrate_v20261016_5_to_20261016_6(value: &mut Value) {
+  if let Value::Table(table) = value {
+    let search = table
+      .entry("search".to_string())
+      .or_insert_with(|| Value::Table(Default::default()));
+
+    if let Value::Table(search) = search {
+      let defaults = crate::SearchConfig::default();
+      search
+        .entry("history_size".to_string())
+        .or_insert_with(|| Value::Integer(defaults.history_size as i64));
+      if !search.contains_key("presets")
+        && let Ok(presets) = Value::try_from(&defaults.presets)
+      {
+        search.insert("presets".to_string(), presets);
+      }
+    }
+
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.6".to_string()),
+    );
+  }
+}
@@ -0,0 +1,27 @@
+use toml::Value;
+
+/// Add the scrollback persistence settings, off by default.
+pub(crate) fn migrate_v20261016_6_to_20261016_7(value: &mut Value) {
+  if let Value::Table(table) = value {
+    let terminal = table
+      .entry("terminal")
+      .or_insert_with(|| Value::Table(toml::map::Map::new()));
+    if let Value::Table(terminal_table) = terminal {
+      let defaults = crate::TerminalConfig::default();
+      terminal_table
+        .entry("persist_scrollback")
+        .or_insert(Value::Boolean(defaults.persist_scrollback));
+      terminal_table
+        .entry("persist_scrollback_lines")
+        .or_insert(Value::Integer(defaults.persist_scrollback_lines as i64));
+      terminal_table
+        .entry("persist_scrollback_max_bytes")
+        .or_insert(Value::Integer(defaults.persist_scrollback_max_bytes as i64));
+    }
+
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.7".to_string()),
+    );
+  }
+}
@@ -0,0 +1,20 @@
+use toml::Value;
+
+/// Add the crash-recovery autosave interval.
+pub(crate) fn migrate_v20261016_7_to_20261016_8(value: &mut Value) {
+  if let Value::Table(table) = value {
+    let window = table
+      .entry("window")
+      .or_insert_with(|| Value::Table(toml::map::Map::new()));
+    if let Value::Table(window_table) = window
+      && !window_table.contains_key("autosave_interval_secs")
+    {
+      window_table.insert("autosave_interval_secs".to_string(), Value::Integer(10));
+    }
+
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.8".to_string()),
+    );
+  }
+}
@@ -0,0 +1,15 @@
+use toml::Value;
+
+/// Add the table of named layouts.
+pub(crate) fn migrate_v20261016_8_to_20261016_9(value: &mut Value) {
+  if let Value::Table(table) = value {
+    table
+      .entry("layouts".to_string())
+      .or_insert_with(|| Value::Table(Default::default()));
+
+    table.insert(
+      "version".to_string(),
+      Value::String("20261016.9".to_string()),
+    );
+  }
+}
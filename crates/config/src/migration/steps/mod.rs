@@ -87,3 +87,30 @@ pub(super) use migrate_v20260421_1_to_20260422_1::migrate_v20260421_1_to_2026042
 
 mod migrate_v20260422_1_to_20260512_1;
 pub(super) use migrate_v20260422_1_to_20260512_1::migrate_v20260422_1_to_20260512_1;
+
+mod migrate_v20260512_1_to_20261016_1;
+pub(super) use migrate_v20260512_1_to_20261016_1::migrate_v20260512_1_to_20261016_1;
+
+mod migrate_v20261016_1_to_20261016_2;
+pub(super) use migrate_v20261016_1_to_20261016_2::migrate_v20261016_1_to_20261016_2;
+
+mod migrate_v20261016_2_to_20261016_3;
+pub(super) use migrate_v20261016_2_to_20261016_3::migrate_v20261016_2_to_20261016_3;
+
+mod migrate_v20261016_3_to_20261016_4;
+pub(super) use migrate_v20261016_3_to_20261016_4::migrate_v20261016_3_to_20261016_4;
+
+mod migrate_v20261016_4_to_20261016_5;
+pub(super) use migrate_v20261016_4_to_20261016_5::migrate_v20261016_4_to_20261016_5;
+
+mod migrate_v20261016_5_to_20261016_6;
+pub(super) use migrate_v20261016_5_to_20261016_6::migrate_v20261016_5_to_20261016_6;
+
+mod migrate_v20261016_6_to_20261016_7;
+pub(super) use migrate_v20261016_6_to_20261016_7::migrate_v20261016_6_to_20261016_7;
+
+mod migrate_v20261016_7_to_20261016_8;
+pub(super) use migrate_v20261016_7_to_20261016_8::migrate_v20261016_7_to_20261016_8;
+
+mod migrate_v20261016_8_to_20261016_9;
+pub(super) use migrate_v20261016_8_to_20261016_9::migrate_v20261016_8_to_20261016_9;
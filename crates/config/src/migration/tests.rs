@@ -893,3 +893,351 @@ scrollback_lines = 10000
     CURRENT_CONFIG_VERSION
   );
 }
+
+#[test]
+fn migrate_20260512_1_adds_hints_config_and_keybinding() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20260512.1"
+
+[keybindings]
+"ctrl-shift-c" = "copy"
+"#,
+  )
+  .unwrap();
+
+  let migrated = apply_migrations(&mut config);
+  assert!(migrated);
+
+  let default_keybindings = crate::KeybindingConfig::default();
+  assert_eq!(
+    get_nested(
+      &config,
+      "keybindings",
+      default_keybindings.hint_mode.first().unwrap()
+    )
+    .unwrap()
+    .as_str()
+    .unwrap(),
+    "hint_mode"
+  );
+  assert_eq!(
+    get_nested(&config, "hints", "alphabet")
+      .unwrap()
+      .as_str()
+      .unwrap(),
+    crate::HintsConfig::default().alphabet
+  );
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
+
+#[test]
+fn migrate_20261016_1_keeps_keybindings_for_send_text() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.1"
+
+[keybindings]
+"ctrl-shift-c" = "copy"
+"#,
+  )
+  .unwrap();
+
+  let migrated = apply_migrations(&mut config);
+  assert!(migrated);
+
+  assert_eq!(
+    get_nested(&config, "keybindings", "ctrl-shift-c")
+      .unwrap()
+      .as_str()
+      .unwrap(),
+    "copy"
+  );
+  assert!(get_nested(&config, "keybindings", "send_text").is_none());
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
+
+#[test]
+fn migrate_20261016_2_adds_prompt_navigation_keybindings() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.2"
+
+[keybindings]
+"ctrl-shift-c" = "copy"
+"#,
+  )
+  .unwrap();
+
+  let migrated = apply_migrations(&mut config);
+  assert!(migrated);
+
+  let default_keybindings = crate::KeybindingConfig::default();
+  for (list, action) in [
+    (
+      &default_keybindings.scroll_to_previous_prompt,
+      "scroll_to_previous_prompt",
+    ),
+    (
+      &default_keybindings.scroll_to_next_prompt,
+      "scroll_to_next_prompt",
+    ),
+    (
+      &default_keybindings.select_command_output,
+      "select_command_output",
+    ),
+    (
+      &default_keybindings.copy_last_command_output,
+      "copy_last_command_output",
+    ),
+  ] {
+    assert_eq!(
+      get_nested(&config, "keybindings", list.first().unwrap())
+        .unwrap()
+        .as_str()
+        .unwrap(),
+      action
+    );
+  }
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
+
+#[test]
+fn migrate_20261016_3_adds_copy_mode_keybinding() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.3"
+
+[keybindings]
+"ctrl-shift-c" = "copy"
+"#,
+  )
+  .unwrap();
+
+  let migrated = apply_migrations(&mut config);
+  assert!(migrated);
+
+  let default_keybindings = crate::KeybindingConfig::default();
+  assert_eq!(
+    get_nested(
+      &config,
+      "keybindings",
+      default_keybindings.copy_mode.first().unwrap()
+    )
+    .unwrap()
+    .as_str()
+    .unwrap(),
+    "copy_mode"
+  );
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
+
+#[test]
+fn migrate_20261016_4_adds_search_panel_keybinding() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.4"
+
+[keybindings]
+"ctrl-shift-c" = "copy"
+"#,
+  )
+  .unwrap();
+
+  let migrated = apply_migrations(&mut config);
+  assert!(migrated);
+
+  let default_keybindings = crate::KeybindingConfig::default();
+  assert_eq!(
+    get_nested(
+      &config,
+      "keybindings",
+      default_keybindings.toggle_search_panel.first().unwrap()
+    )
+    .unwrap()
+    .as_str()
+    .unwrap(),
+    "toggle_search_panel"
+  );
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
+
+#[test]
+fn migrate_20261016_5_adds_search_defaults() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.5"
+
+[keybindings]
+"ctrl-shift-c" = "copy"
+"#,
+  )
+  .unwrap();
+
+  let migrated = apply_migrations(&mut config);
+  assert!(migrated);
+
+  assert_eq!(
+    get_nested(&config, "search", "history_size")
+      .unwrap()
+      .as_integer()
+      .unwrap(),
+    crate::SearchConfig::default().history_size as i64
+  );
+  let presets = get_nested(&config, "search", "presets").unwrap();
+  assert_eq!(
+    presets["errors"]["pattern"].as_str().unwrap(),
+    "(?i)error|panic"
+  );
+  assert!(presets["errors"]["regex"].as_bool().unwrap());
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
+
+#[test]
+fn migrate_20261016_5_keeps_user_search_presets() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.5"
+
+[search.presets.todo]
+pattern = "TODO"
+"#,
+  )
+  .unwrap();
+
+  apply_migrations(&mut config);
+
+  let presets = get_nested(&config, "search", "presets").unwrap();
+  assert!(presets.get("errors").is_none());
+  assert_eq!(presets["todo"]["pattern"].as_str().unwrap(), "TODO");
+}
+
+#[test]
+fn migrate_20261016_6_adds_scrollback_persistence_defaults() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.6"
+
+[terminal]
+persist_scrollback_lines = 500
+"#,
+  )
+  .unwrap();
+
+  let migrated = apply_migrations(&mut config);
+  assert!(migrated);
+
+  assert!(
+    !get_nested(&config, "terminal", "persist_scrollback")
+      .unwrap()
+      .as_bool()
+      .unwrap()
+  );
+  assert_eq!(
+    get_nested(&config, "terminal", "persist_scrollback_lines")
+      .unwrap()
+      .as_integer()
+      .unwrap(),
+    500
+  );
+  assert_eq!(
+    get_nested(&config, "terminal", "persist_scrollback_max_bytes")
+      .unwrap()
+      .as_integer()
+      .unwrap(),
+    crate::TerminalConfig::default().persist_scrollback_max_bytes as i64
+  );
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
+
+#[test]
+fn migrate_20261016_7_adds_autosave_interval() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.7"
+
+[window]
+restore_workspace = false
+"#,
+  )
+  .unwrap();
+
+  let migrated = apply_migrations(&mut config);
+  assert!(migrated);
+
+  assert_eq!(
+    get_nested(&config, "window", "autosave_interval_secs")
+      .unwrap()
+      .as_integer()
+      .unwrap(),
+    10
+  );
+  assert!(
+    !get_nested(&config, "window", "restore_workspace")
+      .unwrap()
+      .as_bool()
+      .unwrap()
+  );
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
+
+#[test]
+fn migrate_20261016_8_adds_layouts_table() {
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.8"
+"#,
+  )
+  .unwrap();
+
+  assert!(apply_migrations(&mut config));
+  assert!(
+    config
+      .get("layouts")
+      .unwrap()
+      .as_table()
+      .unwrap()
+      .is_empty()
+  );
+
+  let mut config: Value = toml::from_str(
+    r#"
+version = "20261016.8"
+
+[[layouts.dev.tabs]]
+command = "htop"
+"#,
+  )
+  .unwrap();
+
+  assert!(apply_migrations(&mut config));
+  assert!(config.get("layouts").unwrap().get("dev").is_some());
+  assert_eq!(
+    config.get("version").unwrap().as_str().unwrap(),
+    CURRENT_CONFIG_VERSION
+  );
+}
@@ -10,6 +10,9 @@ pub struct Profile {
   #[serde(default)]
   pub args: Vec<String>,
   pub working_directory: Option<String>,
+  /// Overrides `terminal.persist_scrollback` for tabs opened from this profile.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub persist_scrollback: Option<bool>,
 }
 
 pub(super) fn default_profiles() -> Vec<Profile> {
@@ -22,6 +25,7 @@ pub(super) fn default_profiles() -> Vec<Profile> {
       shell: shell::fallback_shell(),
       args: vec![],
       working_directory: None,
+      persist_scrollback: None,
     }];
   }
 
@@ -32,6 +36,7 @@ pub(super) fn default_profiles() -> Vec<Profile> {
       shell: s.command,
       args: vec![],
       working_directory: None,
+      persist_scrollback: None,
     })
     .collect()
 }
@@ -58,6 +63,7 @@ pub(super) fn detect_container_profiles() -> Vec<Profile> {
         shell,
         args,
         working_directory: None,
+        persist_scrollback: None,
       }
     })
     .collect()
@@ -103,6 +109,16 @@ impl Config {
     self.get_profile(profile_name).map(|p| p.shell.clone())
   }
 
+  /// Whether tabs opened from `profile_name`, or from the default profile when
+  /// `None`, keep their scrollback across restarts. Falls back to
+  /// `terminal.persist_scrollback`.
+  pub fn persist_scrollback_for(&self, profile_name: Option<&str>) -> bool {
+    profile_name
+      .map_or_else(|| self.get_default_profile(), |name| self.get_profile(name))
+      .and_then(|p| p.persist_scrollback)
+      .unwrap_or(self.terminal.persist_scrollback)
+  }
+
   pub fn get_local_profile_names(&self) -> Vec<String> {
     self.profiles.iter().map(|p| p.name.clone()).collect()
   }
@@ -156,8 +172,8 @@ impl Config {
 mod tests {
   use crate::{
     AppearanceConfig, AutoUpdateConfig, CURRENT_CONFIG_VERSION, ColorsConfig, Config, CursorConfig,
-    FontConfig, KeybindingConfig, NotificationConfig, PaneConfig, Profile, TabConfig,
-    TerminalConfig, ThemeMode, WindowConfig,
+    FontConfig, HintsConfig, KeybindingConfig, NotificationConfig, PaneConfig, Profile,
+    SearchConfig, TabConfig, TerminalConfig, ThemeMode, WindowConfig,
   };
 
   #[test]
@@ -168,12 +184,14 @@ mod tests {
         shell: "sh".to_string(),
         args: vec![],
         working_directory: None,
+        persist_scrollback: None,
       },
       Profile {
         name: "two".to_string(),
         shell: "bash".to_string(),
         args: vec![],
         working_directory: Some("/tmp".to_string()),
+        persist_scrollback: Some(false),
       },
     ];
 
@@ -206,16 +224,22 @@ mod tests {
         start_maximized: false,
         restore_workspace: true,
         key_debug_mode: false,
+        autosave_interval_secs: 10,
+        startup_layout: None,
       },
       tab: TabConfig::default(),
       pane: PaneConfig::default(),
       terminal: TerminalConfig {
         default_profile: Some("two".into()),
+        persist_scrollback: true,
         ..TerminalConfig::default()
       },
       cursor: CursorConfig::default(),
       notification: NotificationConfig::default(),
       auto_update: AutoUpdateConfig::default(),
+      hints: HintsConfig::default(),
+      search: SearchConfig::default(),
+      layouts: Default::default(),
       profiles: profiles.clone(),
       keybindings: KeybindingConfig::default(),
       container_profiles: vec![],
@@ -235,6 +259,12 @@ mod tests {
     );
     assert!(config.get_shell_for_profile("missing").is_none());
 
+    // persist_scrollback_for lets profiles opt out of the global setting
+    assert!(config.persist_scrollback_for(Some("one")));
+    assert!(!config.persist_scrollback_for(Some("two")));
+    assert!(!config.persist_scrollback_for(None));
+    assert!(config.persist_scrollback_for(Some("missing")));
+
     // get_profile_names preserves order
     let names = config.get_local_profile_names();
     assert_eq!(names, vec!["one", "two"]);
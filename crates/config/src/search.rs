@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::KeybindingConfig;
+
+/// A named search, run from the search bar or a `search_preset` keybinding.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SearchPreset {
+  /// Text, or regular expression when `regex` is set, to search for.
+  pub pattern: String,
+  #[serde(default)]
+  pub match_case: bool,
+  #[serde(default)]
+  pub match_whole: bool,
+  #[serde(default, alias = "use_regex")]
+  pub regex: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SearchConfig {
+  /// Queries kept in the search history file. `0` disables the history.
+  pub history_size: usize,
+  /// Named searches, e.g. `errors = { pattern = "(?i)error|panic", regex = true }`.
+  pub presets: BTreeMap<String, SearchPreset>,
+}
+
+impl Default for SearchConfig {
+  fn default() -> Self {
+    Self {
+      history_size: 100,
+      presets: BTreeMap::from([(
+        "errors".to_string(),
+        SearchPreset {
+          pattern: "(?i)error|panic".to_string(),
+          match_case: false,
+          match_whole: false,
+          regex: true,
+        },
+      )]),
+    }
+  }
+}
+
+impl SearchConfig {
+  /// Drop `search_preset` bindings naming a preset that does not exist and
+  /// return the names they referred to.
+  pub fn remove_unknown_preset_bindings(&self, keybindings: &mut KeybindingConfig) -> Vec<String> {
+    let mut unknown = Vec::new();
+    keybindings.search_preset.retain(|binding| {
+      let known = self.presets.contains_key(&binding.preset);
+      if !known {
+        unknown.push(binding.preset.clone());
+      }
+      known
+    });
+    unknown
+  }
+
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    for (name, preset) in &self.presets {
+      if preset.regex
+        && let Err(error) = regex::Regex::new(&preset.pattern)
+      {
+        return Err(format!(
+          "search.presets.{name}: invalid regex '{}': {error}",
+          preset.pattern
+        ));
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{SearchConfig, SearchPreset};
+  use crate::{KeybindingConfig, KeybindingList, SearchPresetBinding};
+
+  #[test]
+  fn bindings_to_unknown_presets_are_removed() {
+    let mut keybindings = KeybindingConfig::default();
+    for preset in ["errors", "warnings"] {
+      keybindings.search_preset.push(SearchPresetBinding {
+        keys: KeybindingList::new(format!("ctrl-alt-{}", &preset[..1])),
+        preset: preset.to_string(),
+      });
+    }
+
+    let unknown = SearchConfig::default().remove_unknown_preset_bindings(&mut keybindings);
+
+    assert_eq!(unknown, vec!["warnings"]);
+    assert_eq!(keybindings.search_preset.len(), 1);
+    assert_eq!(keybindings.search_preset[0].preset, "errors");
+  }
+
+  #[test]
+  fn regex_presets_must_compile() {
+    let mut config = SearchConfig::default();
+    assert!(config.validate().is_ok());
+
+    config.presets.insert(
+      "broken".to_string(),
+      SearchPreset {
+        pattern: "error(".to_string(),
+        match_case: false,
+        match_whole: false,
+        regex: true,
+      },
+    );
+    assert!(
+      config
+        .validate()
+        .unwrap_err()
+        .contains("search.presets.broken")
+    );
+
+    config.presets.get_mut("broken").unwrap().regex = false;
+    assert!(config.validate().is_ok());
+  }
+}
@@ -1,8 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Part of a pane's grid read by [`AppEvent::GetText`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextRange {
+  /// The visible screen.
+  #[default]
+  Screen,
+  /// History above the screen.
+  Scrollback,
+  /// Scrollback followed by the screen.
+  All,
+}
+
 /// Application events that can be triggered from any thread.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppEvent {
   /// Create a new terminal tab with the default profile.
-  NewTerminalWithDefaultProfile,
+  NewTerminalWithDefaultProfile { working_directory: Option<String> },
 
   /// Create a new terminal tab with a specific profile.
   NewTerminalWithProfile {
@@ -93,13 +108,35 @@ pub enum AppEvent {
 
   /// Request a snapshot of the UI tree (response is logged/sent back).
   SnapshotUITree,
+
+  /// Save every open window as a named session.
+  SaveSession { name: String },
+
+  /// Open a named session in new windows.
+  OpenSession { name: String },
+
+  /// Delete a named session.
+  DeleteSession { name: String },
+
+  /// List the names of the saved sessions.
+  ListSessions,
+
+  /// Read the text of a pane. Unset ids fall back to the active window, tab
+  /// and pane; `ansi` keeps colors and attributes as SGR sequences.
+  GetText {
+    window_id: Option<String>,
+    tab_id: Option<String>,
+    pane_id: Option<String>,
+    range: TextRange,
+    ansi: bool,
+  },
 }
 
 impl AppEvent {
   /// Returns a string discriminant used as the key for subscriber lookup.
   pub fn discriminant(&self) -> &'static str {
     match self {
-      AppEvent::NewTerminalWithDefaultProfile => "NewTerminalWithDefaultProfile",
+      AppEvent::NewTerminalWithDefaultProfile { .. } => "NewTerminalWithDefaultProfile",
       AppEvent::NewTerminalWithProfile { .. } => "NewTerminalWithProfile",
       AppEvent::CloseActiveTab => "CloseActiveTab",
       AppEvent::CloseTab { .. } => "CloseTab",
@@ -129,6 +166,11 @@ impl AppEvent {
       AppEvent::Custom { .. } => "Custom",
       AppEvent::DispatchUIAction { .. } => "DispatchUIAction",
       AppEvent::SnapshotUITree => "SnapshotUITree",
+      AppEvent::SaveSession { .. } => "SaveSession",
+      AppEvent::OpenSession { .. } => "OpenSession",
+      AppEvent::DeleteSession { .. } => "DeleteSession",
+      AppEvent::ListSessions => "ListSessions",
+      AppEvent::GetText { .. } => "GetText",
     }
   }
 }
@@ -0,0 +1,101 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+use anyhow::Context as _;
+
+use crate::{EventResponse, JsonEvent};
+
+/// Id used for the single request sent by [`send_request`].
+const REQUEST_ID: u64 = 1;
+
+/// Send one event to a running Kazeterm over its event socket and wait for the response.
+///
+/// Used by `kazeterm msg`. The connection is closed once the response arrives.
+pub fn send_request(socket_path: &Path, event: &JsonEvent) -> anyhow::Result<EventResponse> {
+  #[cfg(unix)]
+  let stream = std::os::unix::net::UnixStream::connect(socket_path);
+  #[cfg(windows)]
+  let stream = uds_windows::UnixStream::connect(socket_path);
+
+  let stream = stream.with_context(|| {
+    format!(
+      "Failed to connect to Kazeterm at {}. Is it running with --event-source socket?",
+      socket_path.display()
+    )
+  })?;
+  let reader = stream.try_clone().context("Failed to clone event socket")?;
+  exchange(reader, stream, event)
+}
+
+fn exchange<R, W>(reader: R, mut writer: W, event: &JsonEvent) -> anyhow::Result<EventResponse>
+where
+  R: Read,
+  W: Write,
+{
+  let mut request = serde_json::to_value(event).context("Failed to encode event")?;
+  if let Some(request) = request.as_object_mut() {
+    request.insert("id".to_string(), REQUEST_ID.into());
+  }
+  writeln!(writer, "{}", request).context("Failed to send event")?;
+  writer.flush().context("Failed to send event")?;
+
+  for line in BufReader::new(reader).lines() {
+    let line = line.context("Failed to read response")?;
+    // Anything that is not our response (e.g. a notification) is skipped.
+    if let Ok(response) = serde_json::from_str::<EventResponse>(&line)
+      && response.id == REQUEST_ID
+    {
+      return Ok(response);
+    }
+  }
+
+  anyhow::bail!("Kazeterm closed the connection without responding")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use std::io::{BufRead, BufReader, Write};
+  use std::os::unix::net::UnixStream;
+
+  use super::exchange;
+  use crate::JsonEvent;
+
+  #[test]
+  fn exchange_sends_event_with_id_and_reads_response() {
+    let (client, server) = UnixStream::pair().unwrap();
+    let handle = std::thread::spawn(move || {
+      let mut reader = BufReader::new(server.try_clone().unwrap());
+      let mut request = String::new();
+      reader.read_line(&mut request).unwrap();
+      let mut server = server;
+      writeln!(
+        server,
+        r#"{{"notification":"Bell","window_id":"win-1","tab_id":"tab-1","pane_id":"pane-0"}}"#
+      )
+      .unwrap();
+      writeln!(server, r#"{{"id":1,"ok":false,"error":"no terminal"}}"#).unwrap();
+      request
+    });
+
+    let event = JsonEvent::SendTextToTerminal {
+      text: "ls\n".to_string(),
+    };
+    let response = exchange(client.try_clone().unwrap(), client, &event).unwrap();
+    assert!(!response.ok);
+    assert_eq!(response.error.as_deref(), Some("no terminal"));
+
+    let request: serde_json::Value = serde_json::from_str(&handle.join().unwrap()).unwrap();
+    assert_eq!(
+      request,
+      serde_json::json!({"event": "SendTextToTerminal", "text": "ls\n", "id": 1})
+    );
+  }
+
+  #[test]
+  fn exchange_fails_when_connection_closes() {
+    let (client, server) = UnixStream::pair().unwrap();
+    drop(server);
+    let error = exchange(client.try_clone().unwrap(), client, &JsonEvent::NextTab);
+    assert!(error.is_err());
+  }
+}
@@ -2,9 +2,10 @@ use std::collections::HashMap;
 
 use gpui::{Context, Window};
 
-use crate::AppEvent;
+use crate::{AppEvent, EventResult};
 
-type EventHandler<T> = Box<dyn Fn(&mut T, AppEvent, &mut Window, &mut Context<T>) + Send + 'static>;
+type EventHandler<T> =
+  Box<dyn Fn(&mut T, AppEvent, &mut Window, &mut Context<T>) -> EventResult + Send + 'static>;
 
 /// Centralized event bus that dispatches [`AppEvent`]s to registered subscribers.
 ///
@@ -35,6 +36,19 @@ impl<T> EventBus<T> {
   pub fn subscribe<F>(&mut self, discriminant: &'static str, handler: F)
   where
     F: Fn(&mut T, AppEvent, &mut Window, &mut Context<T>) + Send + 'static,
+  {
+    self.subscribe_with_response(discriminant, move |target, event, window, cx| {
+      handler(target, event, window, cx);
+      Ok(serde_json::Value::Null)
+    });
+  }
+
+  /// Register a handler whose result is sent back to the client that issued the event.
+  ///
+  /// Handlers that only perform side effects should use [`EventBus::subscribe`].
+  pub fn subscribe_with_response<F>(&mut self, discriminant: &'static str, handler: F)
+  where
+    F: Fn(&mut T, AppEvent, &mut Window, &mut Context<T>) -> EventResult + Send + 'static,
   {
     self
       .handlers
@@ -56,7 +70,9 @@ impl<T> EventBus<T> {
     let discriminant = event.discriminant();
     if let Some(handlers) = self.handlers.get(discriminant) {
       for handler in handlers {
-        handler(target, event.clone(), window, cx);
+        if let Err(error) = handler(target, event.clone(), window, cx) {
+          tracing::error!("Failed to handle {}: {}", discriminant, error);
+        }
       }
       handlers.len()
     } else {
@@ -65,6 +81,32 @@ impl<T> EventBus<T> {
     }
   }
 
+  /// Dispatch an event and collect a single result for the client that sent it.
+  ///
+  /// Dispatch stops at the first handler error; otherwise the last non-null
+  /// result is returned.
+  pub fn dispatch_with_response(
+    &self,
+    target: &mut T,
+    event: AppEvent,
+    window: &mut Window,
+    cx: &mut Context<T>,
+  ) -> EventResult {
+    let discriminant = event.discriminant();
+    let Some(handlers) = self.handlers.get(discriminant) else {
+      return Err(format!("No handler registered for event: {}", discriminant));
+    };
+
+    let mut response = serde_json::Value::Null;
+    for handler in handlers {
+      let value = handler(target, event.clone(), window, cx)?;
+      if !value.is_null() {
+        response = value;
+      }
+    }
+    Ok(response)
+  }
+
   /// Returns the number of handlers registered for a discriminant.
   pub fn handler_count(&self, discriminant: &str) -> usize {
     self.handlers.get(discriminant).map_or(0, Vec::len)
@@ -1,20 +1,33 @@
+use std::io::{BufRead, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use smol::channel::Sender;
+use smol::channel::{Sender, bounded};
 
-use crate::{AppEvent, JsonEvent};
+use crate::notification::{subscribe, unsubscribe};
+use crate::protocol::{ClientRequest, EventResponse, QueuedEvent, Responder, parse_request};
 
-fn parse_and_send_line(sender: &Sender<AppEvent>, line: &str, source: &str) -> bool {
-  match serde_json::from_str::<JsonEvent>(line) {
-    Ok(json_event) => {
-      let event: AppEvent = json_event.into();
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Lines queued for a client's writer thread before further output is refused.
+///
+/// A client that stops reading fills its queue, after which `try_send` fails and
+/// it is dropped from the notification subscribers instead of buffering forever.
+pub(crate) const CLIENT_OUTPUT_CAPACITY: usize = 256;
+
+fn parse_and_send_line(sender: &Sender<QueuedEvent>, line: &str, source: &str) -> bool {
+  match parse_request(line) {
+    Ok(ClientRequest::Event { event, .. }) => {
       tracing::debug!("Received event from {}: {:?}", source, event);
-      if sender.send_blocking(event).is_err() {
+      if sender.send_blocking(QueuedEvent::new(event)).is_err() {
         tracing::error!("Event channel closed, stopping {} reader", source);
         return false;
       }
     }
-    Err(error) => {
+    Ok(_) => {
+      tracing::warn!("Subscriptions are only supported on the event socket");
+    }
+    Err((_, error)) => {
       tracing::warn!(
         "Failed to parse event from {}: {} - line: {}",
         source,
@@ -28,10 +41,8 @@ fn parse_and_send_line(sender: &Sender<AppEvent>, line: &str, source: &str) -> b
 }
 
 /// Start reading events from stdin in a background thread.
-pub(crate) fn start_stdio_reader(sender: Sender<AppEvent>) {
+pub(crate) fn start_stdio_reader(sender: Sender<QueuedEvent>) {
   std::thread::spawn(move || {
-    use std::io::BufRead;
-
     tracing::info!("Starting stdin event reader");
 
     let stdin = std::io::stdin();
@@ -60,8 +71,102 @@ pub(crate) fn start_stdio_reader(sender: Sender<AppEvent>) {
   });
 }
 
-/// Start reading events from a Unix domain socket in a background thread.
-pub(crate) fn start_socket_reader(sender: Sender<AppEvent>, path: PathBuf) {
+/// Handle one request line from a socket client.
+///
+/// Events are queued for the main thread; subscription requests are answered
+/// directly. Returns `false` once the event channel is closed.
+fn handle_client_line(
+  sender: &Sender<QueuedEvent>,
+  client_id: u64,
+  output: &Sender<String>,
+  line: &str,
+) -> bool {
+  let respond = |id: Option<u64>, result| {
+    if let Some(id) = id {
+      let _ = output.try_send(EventResponse::new(id, result).to_line());
+    }
+  };
+
+  match parse_request(line) {
+    Ok(ClientRequest::Event { id, event }) => {
+      tracing::debug!("Received event from socket: {:?}", event);
+      let queued = QueuedEvent {
+        event,
+        responder: id.map(|id| Responder::new(id, output.clone())),
+      };
+      if sender.send_blocking(queued).is_err() {
+        tracing::error!("Event channel closed, stopping socket reader");
+        return false;
+      }
+    }
+    Ok(ClientRequest::Subscribe { id, notifications }) => {
+      respond(id, subscribe(client_id, &notifications, output.clone()));
+    }
+    Ok(ClientRequest::Unsubscribe { id }) => {
+      unsubscribe(client_id);
+      respond(id, Ok(serde_json::Value::Null));
+    }
+    Err((id, error)) => {
+      tracing::warn!(
+        "Failed to parse event from socket: {} - line: {}",
+        error,
+        line
+      );
+      respond(id, Err(error));
+    }
+  }
+
+  true
+}
+
+/// Serve a single socket client until it disconnects.
+///
+/// Requests are read line by line from `reader`. Responses and notifications
+/// are written as JSON lines to `writer` from a dedicated thread so that slow
+/// clients never block the main thread.
+fn serve_client<R, W>(sender: Sender<QueuedEvent>, reader: R, mut writer: W)
+where
+  R: BufRead,
+  W: Write + Send + 'static,
+{
+  let client_id = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+  let (output, lines) = bounded::<String>(CLIENT_OUTPUT_CAPACITY);
+
+  std::thread::spawn(move || {
+    while let Ok(line) = lines.recv_blocking() {
+      if writeln!(writer, "{}", line)
+        .and_then(|()| writer.flush())
+        .is_err()
+      {
+        break;
+      }
+    }
+  });
+
+  for line in reader.lines() {
+    match line {
+      Ok(line) => {
+        let line = line.trim();
+        if line.is_empty() {
+          continue;
+        }
+
+        if !handle_client_line(&sender, client_id, &output, line) {
+          break;
+        }
+      }
+      Err(error) => {
+        tracing::debug!("Client disconnected: {}", error);
+        break;
+      }
+    }
+  }
+
+  unsubscribe(client_id);
+}
+
+/// Start serving the event socket in a background thread.
+pub(crate) fn start_socket_reader(sender: Sender<QueuedEvent>, path: PathBuf) {
   std::thread::spawn(move || {
     #[cfg(unix)]
     {
@@ -77,8 +182,8 @@ pub(crate) fn start_socket_reader(sender: Sender<AppEvent>, path: PathBuf) {
 
 /// Unix domain socket reader (Unix platforms).
 #[cfg(unix)]
-fn start_unix_socket_reader_unix(sender: Sender<AppEvent>, path: PathBuf) {
-  use std::io::{BufRead, BufReader};
+fn start_unix_socket_reader_unix(sender: Sender<QueuedEvent>, path: PathBuf) {
+  use std::io::BufReader;
   use std::os::unix::net::UnixListener;
 
   tracing::info!("Starting Unix socket event reader at: {:?}", path);
@@ -96,30 +201,10 @@ fn start_unix_socket_reader_unix(sender: Sender<AppEvent>, path: PathBuf) {
   tracing::info!("Listening for events on Unix socket: {:?}", path);
 
   for stream in listener.incoming() {
-    match stream {
-      Ok(stream) => {
+    match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+      Ok((writer, stream)) => {
         let sender = sender.clone();
-        std::thread::spawn(move || {
-          let reader = BufReader::new(stream);
-          for line in reader.lines() {
-            match line {
-              Ok(line) => {
-                let line = line.trim();
-                if line.is_empty() {
-                  continue;
-                }
-
-                if !parse_and_send_line(&sender, line, "socket") {
-                  break;
-                }
-              }
-              Err(error) => {
-                tracing::debug!("Client disconnected: {}", error);
-                break;
-              }
-            }
-          }
-        });
+        std::thread::spawn(move || serve_client(sender, BufReader::new(stream), writer));
       }
       Err(error) => {
         tracing::error!("Failed to accept connection: {}", error);
@@ -133,8 +218,8 @@ fn start_unix_socket_reader_unix(sender: Sender<AppEvent>, path: PathBuf) {
 /// Windows has supported Unix domain sockets since Windows 10 version 1803.
 /// We use the `uds_windows` crate to provide UnixListener/UnixStream on Windows.
 #[cfg(windows)]
-fn start_unix_socket_reader_windows(sender: Sender<AppEvent>, path: PathBuf) {
-  use std::io::{BufRead, BufReader};
+fn start_unix_socket_reader_windows(sender: Sender<QueuedEvent>, path: PathBuf) {
+  use std::io::BufReader;
 
   use uds_windows::UnixListener;
 
@@ -153,30 +238,10 @@ fn start_unix_socket_reader_windows(sender: Sender<AppEvent>, path: PathBuf) {
   tracing::info!("Listening for events on Unix socket: {:?}", path);
 
   for stream in listener.incoming() {
-    match stream {
-      Ok(stream) => {
+    match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+      Ok((writer, stream)) => {
         let sender = sender.clone();
-        std::thread::spawn(move || {
-          let reader = BufReader::new(stream);
-          for line in reader.lines() {
-            match line {
-              Ok(line) => {
-                let line = line.trim();
-                if line.is_empty() {
-                  continue;
-                }
-
-                if !parse_and_send_line(&sender, line, "socket") {
-                  break;
-                }
-              }
-              Err(error) => {
-                tracing::debug!("Client disconnected: {}", error);
-                break;
-              }
-            }
-          }
-        });
+        std::thread::spawn(move || serve_client(sender, BufReader::new(stream), writer));
       }
       Err(error) => {
         tracing::error!("Failed to accept connection: {}", error);
@@ -184,3 +249,66 @@ fn start_unix_socket_reader_windows(sender: Sender<AppEvent>, path: PathBuf) {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use smol::channel::unbounded;
+
+  use super::handle_client_line;
+  use crate::AppEvent;
+
+  #[test]
+  fn socket_requests_queue_events_with_responders() {
+    let (sender, queue) = unbounded();
+    let (output, lines) = unbounded();
+
+    assert!(handle_client_line(
+      &sender,
+      1,
+      &output,
+      r#"{"id": 3, "event": "SnapshotUITree"}"#
+    ));
+    let queued = queue.try_recv().unwrap();
+    assert_eq!(queued.event, AppEvent::SnapshotUITree);
+    queued
+      .responder
+      .unwrap()
+      .respond(Ok(serde_json::json!({"windows": []})));
+    assert_eq!(
+      lines.try_recv().unwrap(),
+      r#"{"id":3,"ok":true,"result":{"windows":[]}}"#
+    );
+
+    assert!(handle_client_line(
+      &sender,
+      1,
+      &output,
+      r#"{"event": "NextTab"}"#
+    ));
+    assert!(queue.try_recv().unwrap().responder.is_none());
+  }
+
+  #[test]
+  fn socket_parse_errors_are_reported_to_client() {
+    let (sender, queue) = unbounded();
+    let (output, lines) = unbounded();
+
+    assert!(handle_client_line(
+      &sender,
+      2,
+      &output,
+      r#"{"id": 5, "event": "Bogus"}"#
+    ));
+    assert!(queue.try_recv().is_err());
+    let line = lines.try_recv().unwrap();
+    assert!(line.starts_with(r#"{"id":5,"ok":false,"error":"#));
+
+    assert!(handle_client_line(
+      &sender,
+      2,
+      &output,
+      r#"{"event": "Bogus"}"#
+    ));
+    assert!(lines.try_recv().is_err());
+  }
+}
@@ -1,8 +1,8 @@
 use std::path::PathBuf;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::AppEvent;
+use crate::{AppEvent, TextRange};
 
 /// Configuration for the external event source.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -17,10 +17,13 @@ pub enum EventSourceConfig {
 }
 
 /// JSON representation of an event for external input.
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(tag = "event")]
 pub enum JsonEvent {
-  NewTerminalWithDefaultProfile,
+  NewTerminalWithDefaultProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    working_directory: Option<String>,
+  },
   NewTerminalWithProfile {
     profile_name: String,
     working_directory: Option<String>,
@@ -67,12 +70,41 @@ pub enum JsonEvent {
   },
   /// Request a snapshot of the current UI tree as JSON.
   SnapshotUITree,
+  /// Save every open window as a named session.
+  SaveSession {
+    name: String,
+  },
+  /// Open a named session in new windows.
+  OpenSession {
+    name: String,
+  },
+  /// Delete a named session.
+  DeleteSession {
+    name: String,
+  },
+  /// List the names of the saved sessions.
+  ListSessions,
+  /// Read the text of a pane, addressed by ids from the UI tree snapshot.
+  GetText {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    window_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tab_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pane_id: Option<String>,
+    #[serde(default)]
+    range: TextRange,
+    #[serde(default)]
+    ansi: bool,
+  },
 }
 
 impl From<JsonEvent> for AppEvent {
   fn from(json: JsonEvent) -> Self {
     match json {
-      JsonEvent::NewTerminalWithDefaultProfile => AppEvent::NewTerminalWithDefaultProfile,
+      JsonEvent::NewTerminalWithDefaultProfile { working_directory } => {
+        AppEvent::NewTerminalWithDefaultProfile { working_directory }
+      }
       JsonEvent::NewTerminalWithProfile {
         profile_name,
         working_directory,
@@ -108,6 +140,23 @@ impl From<JsonEvent> for AppEvent {
       JsonEvent::Custom { name, data } => AppEvent::Custom { name, data },
       JsonEvent::DispatchUIAction { action_json } => AppEvent::DispatchUIAction { action_json },
       JsonEvent::SnapshotUITree => AppEvent::SnapshotUITree,
+      JsonEvent::SaveSession { name } => AppEvent::SaveSession { name },
+      JsonEvent::OpenSession { name } => AppEvent::OpenSession { name },
+      JsonEvent::DeleteSession { name } => AppEvent::DeleteSession { name },
+      JsonEvent::ListSessions => AppEvent::ListSessions,
+      JsonEvent::GetText {
+        window_id,
+        tab_id,
+        pane_id,
+        range,
+        ansi,
+      } => AppEvent::GetText {
+        window_id,
+        tab_id,
+        pane_id,
+        range,
+        ansi,
+      },
     }
   }
 }
@@ -24,11 +24,38 @@
 //!
 //! ```json
 //! {"event": "NewTerminalWithDefaultProfile"}
+//! {"event": "NewTerminalWithDefaultProfile", "working_directory": "/tmp"}
 //! {"event": "NewTerminalWithProfile", "profile_name": "bash", "working_directory": "/home"}
 //! {"event": "NewWindow"}
 //! {"event": "SendTextToTerminal", "text": "echo hello\n"}
 //! {"event": "ToggleFullscreen"}
 //! {"event": "SwitchToTab", "position": 0}
+//! {"event": "GetText", "tab_id": "tab-2", "pane_id": "pane-1", "range": "scrollback", "ansi": true}
+//! {"event": "SaveSession", "name": "oncall"}
+//! {"event": "OpenSession", "name": "oncall"}
+//! ```
+//!
+//! # Socket Protocol
+//!
+//! On the event socket each connection is bidirectional. A request that
+//! carries an `id` gets exactly one [`EventResponse`] line back; requests
+//! without an `id` are fire-and-forget as on stdin:
+//!
+//! ```json
+//! {"id": 1, "event": "SnapshotUITree"}
+//! {"id": 1, "ok": true, "result": {"windows": [{"id": "win-1", "tabs": []}]}}
+//! {"id": 2, "event": "DispatchUIAction", "action_json": "{\"action\": \"close_tab\", \"window_id\": \"win-1\", \"tab_id\": \"tab-9\"}"}
+//! {"id": 2, "ok": false, "error": "Failed to dispatch UIAction: Tab 'tab-9' not found"}
+//! ```
+//!
+//! Clients can also subscribe to a stream of [`Notification`]s, optionally
+//! limited to a set of names:
+//!
+//! ```json
+//! {"id": 3, "event": "Subscribe", "notifications": ["TabCreated", "Bell"]}
+//! {"id": 3, "ok": true, "result": {"subscribed": ["TabCreated", "Bell"]}}
+//! {"notification": "Bell", "window_id": "win-1", "tab_id": "tab-2", "pane_id": "pane-0"}
+//! {"event": "Unsubscribe"}
 //! ```
 //!
 //! # Programmatic Usage
@@ -36,27 +63,48 @@
 //! ```rust,ignore
 //! use kazeterm_event_system::{AppEvent, send_event};
 //!
-//! send_event(AppEvent::NewTerminalWithDefaultProfile);
+//! send_event(AppEvent::NewTerminalWithDefaultProfile {
+//!   working_directory: None,
+//! });
 //! ```
 
 mod app_event;
+mod client;
 mod event_bus;
 mod event_sources;
 mod json_event;
+mod notification;
+mod protocol;
 
-pub use app_event::AppEvent;
+pub use app_event::{AppEvent, TextRange};
+pub use client::send_request;
 pub use event_bus::EventBus;
 pub use json_event::{EventSourceConfig, JsonEvent};
+pub use notification::{Notification, publish_notification};
+pub use protocol::{EventResponse, EventResult};
 
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 use gpui::{AnyWindowHandle, App, AppContext, AsyncApp, WeakEntity};
 use smol::channel::{Receiver, Sender, unbounded};
 
 use crate::event_sources::{start_socket_reader, start_stdio_reader};
+use crate::protocol::QueuedEvent;
 
 /// Global event sender that can be accessed from any thread.
-static EVENT_SENDER: OnceLock<Sender<AppEvent>> = OnceLock::new();
+static EVENT_SENDER: OnceLock<Sender<QueuedEvent>> = OnceLock::new();
+
+/// Absolute path of the event socket, when the socket source is in use.
+static SOCKET_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Path of the event socket this instance listens on, if any.
+///
+/// Shells started by the application get it as `$KAZETERM_SOCKET` so scripts
+/// can reach the running instance with `kazeterm msg`.
+pub fn socket_path() -> Option<&'static Path> {
+  SOCKET_PATH.get().map(PathBuf::as_path)
+}
 
 /// Send an event to the application from any thread.
 ///
@@ -69,7 +117,7 @@ static EVENT_SENDER: OnceLock<Sender<AppEvent>> = OnceLock::new();
 /// event system is not initialized or the channel is closed.
 pub fn send_event(event: AppEvent) -> bool {
   if let Some(sender) = EVENT_SENDER.get() {
-    sender.send_blocking(event).is_ok()
+    sender.send_blocking(QueuedEvent::new(event)).is_ok()
   } else {
     tracing::warn!("Event system not initialized, event dropped: {:?}", event);
     false
@@ -82,7 +130,7 @@ pub fn send_event(event: AppEvent) -> bool {
 /// blocking is not allowed.
 pub fn try_send_event(event: AppEvent) -> bool {
   if let Some(sender) = EVENT_SENDER.get() {
-    sender.try_send(event).is_ok()
+    sender.try_send(QueuedEvent::new(event)).is_ok()
   } else {
     tracing::warn!("Event system not initialized, event dropped: {:?}", event);
     false
@@ -101,7 +149,7 @@ pub fn start_event_system<T: 'static>(
   event_bus: EventBus<T>,
   cx: &mut App,
 ) {
-  let (sender, receiver) = unbounded::<AppEvent>();
+  let (sender, receiver) = unbounded::<QueuedEvent>();
 
   if EVENT_SENDER.set(sender.clone()).is_err() {
     tracing::warn!("Event system already initialized");
@@ -118,6 +166,7 @@ pub fn start_event_system<T: 'static>(
       start_stdio_reader(sender);
     }
     EventSourceConfig::Socket { path } => {
+      let _ = SOCKET_PATH.set(std::path::absolute(&path).unwrap_or_else(|_| path.clone()));
       start_socket_reader(sender, path);
     }
   }
@@ -131,16 +180,29 @@ pub fn start_event_system<T: 'static>(
 async fn run_event_loop<T: 'static>(
   target: WeakEntity<T>,
   window_handle: AnyWindowHandle,
-  receiver: Receiver<AppEvent>,
+  receiver: Receiver<QueuedEvent>,
   event_bus: EventBus<T>,
   cx: &mut AsyncApp,
 ) {
   loop {
     match receiver.recv().await {
-      Ok(event) => {
-        if let Err(error) = dispatch_event(&target, window_handle, event, &event_bus, cx).await {
-          tracing::error!("Failed to dispatch event: {}", error);
-          break;
+      Ok(QueuedEvent { event, responder }) => {
+        match dispatch_event(&target, window_handle, event, &event_bus, cx).await {
+          Ok(result) => match responder {
+            Some(responder) => responder.respond(result),
+            None => {
+              if let Err(error) = result {
+                tracing::warn!("{}", error);
+              }
+            }
+          },
+          Err(error) => {
+            tracing::error!("Failed to dispatch event: {}", error);
+            if let Some(responder) = responder {
+              responder.respond(Err(error.to_string()));
+            }
+            break;
+          }
         }
       }
       Err(error) => {
@@ -157,27 +219,29 @@ async fn dispatch_event<T: 'static>(
   event: AppEvent,
   event_bus: &EventBus<T>,
   cx: &mut AsyncApp,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<EventResult> {
   let target = target
     .upgrade()
     .ok_or_else(|| anyhow::anyhow!("Event target has been dropped"))?;
 
-  cx.update_window(window_handle, |_root_view, window, cx| {
+  let result = cx.update_window(window_handle, |_root_view, window, cx| {
     target.update(cx, |this, cx| {
-      event_bus.dispatch(this, event, window, cx);
-    });
+      event_bus.dispatch_with_response(this, event, window, cx)
+    })
   })?;
 
-  Ok(())
+  Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
-  use super::{AppEvent, JsonEvent};
+  use super::{AppEvent, JsonEvent, TextRange};
 
   #[test]
   fn event_debug_format() {
-    let event = AppEvent::NewTerminalWithDefaultProfile;
+    let event = AppEvent::NewTerminalWithDefaultProfile {
+      working_directory: None,
+    };
     assert!(format!("{:?}", event).contains("NewTerminalWithDefaultProfile"));
 
     let event = AppEvent::NewTerminalWithProfile {
@@ -191,7 +255,21 @@ mod tests {
   fn json_event_parsing() {
     let json = r#"{"event": "NewTerminalWithDefaultProfile"}"#;
     let event: JsonEvent = serde_json::from_str(json).unwrap();
-    assert!(matches!(event, JsonEvent::NewTerminalWithDefaultProfile));
+    assert!(matches!(
+      event,
+      JsonEvent::NewTerminalWithDefaultProfile {
+        working_directory: None
+      }
+    ));
+
+    let json = r#"{"event": "NewTerminalWithDefaultProfile", "working_directory": "/tmp"}"#;
+    let event: JsonEvent = serde_json::from_str(json).unwrap();
+    assert!(matches!(
+      event,
+      JsonEvent::NewTerminalWithDefaultProfile {
+        working_directory: Some(dir)
+      } if dir == "/tmp"
+    ));
 
     let json = r#"{"event": "NewTerminalWithProfile", "profile_name": "bash", "working_directory": "/home"}"#;
     let event: JsonEvent = serde_json::from_str(json).unwrap();
@@ -215,6 +293,28 @@ mod tests {
     let event: JsonEvent = serde_json::from_str(json).unwrap();
     assert!(matches!(event, JsonEvent::FocusPaneRight));
 
+    let json = r#"{"event": "GetText", "pane_id": "pane-3", "range": "all"}"#;
+    let event: JsonEvent = serde_json::from_str(json).unwrap();
+    assert_eq!(
+      event,
+      JsonEvent::GetText {
+        window_id: None,
+        tab_id: None,
+        pane_id: Some("pane-3".to_string()),
+        range: TextRange::All,
+        ansi: false,
+      }
+    );
+
+    let json = r#"{"event": "OpenSession", "name": "oncall"}"#;
+    let event: JsonEvent = serde_json::from_str(json).unwrap();
+    assert_eq!(
+      AppEvent::from(event),
+      AppEvent::OpenSession {
+        name: "oncall".to_string()
+      }
+    );
+
     let json = r#"{"event": "SendTextToTerminal", "text": "echo hello\n"}"#;
     let event: JsonEvent = serde_json::from_str(json).unwrap();
     assert!(matches!(
@@ -225,9 +325,16 @@ mod tests {
 
   #[test]
   fn json_event_to_app_event() {
-    let json_event = JsonEvent::NewTerminalWithDefaultProfile;
+    let json_event = JsonEvent::NewTerminalWithDefaultProfile {
+      working_directory: None,
+    };
     let app_event: AppEvent = json_event.into();
-    assert!(matches!(app_event, AppEvent::NewTerminalWithDefaultProfile));
+    assert!(matches!(
+      app_event,
+      AppEvent::NewTerminalWithDefaultProfile {
+        working_directory: None
+      }
+    ));
 
     let json_event = JsonEvent::SwitchToTab { position: 3 };
     let app_event: AppEvent = json_event.into();
@@ -241,7 +348,10 @@ mod tests {
   #[test]
   fn event_discriminant() {
     assert_eq!(
-      AppEvent::NewTerminalWithDefaultProfile.discriminant(),
+      AppEvent::NewTerminalWithDefaultProfile {
+        working_directory: None,
+      }
+      .discriminant(),
       "NewTerminalWithDefaultProfile"
     );
     assert_eq!(
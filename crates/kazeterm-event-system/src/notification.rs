@@ -0,0 +1,223 @@
+use std::sync::{Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use smol::channel::Sender;
+
+use crate::EventResult;
+
+/// Notifications streamed to subscribed socket clients.
+///
+/// ```json
+/// {"notification": "TabCreated", "window_id": "win-1", "tab_id": "tab-3"}
+/// {"notification": "CwdChanged", "window_id": "win-1", "tab_id": "tab-3", "pane_id": "pane-0", "working_directory": "/tmp"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "notification")]
+pub enum Notification {
+  TabCreated {
+    window_id: String,
+    tab_id: String,
+  },
+  TabClosed {
+    window_id: String,
+    tab_id: String,
+  },
+  TitleChanged {
+    window_id: String,
+    tab_id: String,
+    title: String,
+  },
+  PromptReturned {
+    window_id: String,
+    tab_id: String,
+    pane_id: String,
+  },
+  Bell {
+    window_id: String,
+    tab_id: String,
+    pane_id: String,
+  },
+  CwdChanged {
+    window_id: String,
+    tab_id: String,
+    pane_id: String,
+    working_directory: String,
+  },
+}
+
+impl Notification {
+  /// Names accepted by `{"event": "Subscribe", "notifications": [...]}`.
+  pub const NAMES: [&'static str; 6] = [
+    "TabCreated",
+    "TabClosed",
+    "TitleChanged",
+    "PromptReturned",
+    "Bell",
+    "CwdChanged",
+  ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Notification::TabCreated { .. } => "TabCreated",
+      Notification::TabClosed { .. } => "TabClosed",
+      Notification::TitleChanged { .. } => "TitleChanged",
+      Notification::PromptReturned { .. } => "PromptReturned",
+      Notification::Bell { .. } => "Bell",
+      Notification::CwdChanged { .. } => "CwdChanged",
+    }
+  }
+}
+
+struct Subscriber {
+  client_id: u64,
+  names: Vec<&'static str>,
+  output: Sender<String>,
+}
+
+static SUBSCRIBERS: Mutex<Vec<Subscriber>> = Mutex::new(Vec::new());
+
+/// Start (or replace) the notification stream for a client connection.
+pub(crate) fn subscribe(client_id: u64, names: &[String], output: Sender<String>) -> EventResult {
+  if let Some(unknown) = names
+    .iter()
+    .find(|name| !Notification::NAMES.contains(&name.as_str()))
+  {
+    return Err(format!(
+      "Unknown notification '{unknown}'. Known notifications: {}",
+      Notification::NAMES.join(", ")
+    ));
+  }
+
+  let names = Notification::NAMES
+    .into_iter()
+    .filter(|name| names.is_empty() || names.iter().any(|requested| requested == name))
+    .collect::<Vec<_>>();
+  let result = serde_json::json!({ "subscribed": names });
+
+  let mut subscribers = SUBSCRIBERS.lock().unwrap_or_else(PoisonError::into_inner);
+  subscribers.retain(|subscriber| subscriber.client_id != client_id);
+  subscribers.push(Subscriber {
+    client_id,
+    names,
+    output,
+  });
+  Ok(result)
+}
+
+pub(crate) fn unsubscribe(client_id: u64) {
+  SUBSCRIBERS
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner)
+    .retain(|subscriber| subscriber.client_id != client_id);
+}
+
+/// Send a notification to every client subscribed to it.
+///
+/// Clients whose connection has gone away, or who have stopped reading and let
+/// their output queue fill up, are dropped from the subscriber list.
+pub fn publish_notification(notification: Notification) {
+  let mut subscribers = SUBSCRIBERS.lock().unwrap_or_else(PoisonError::into_inner);
+  let name = notification.name();
+  if !subscribers
+    .iter()
+    .any(|subscriber| subscriber.names.contains(&name))
+  {
+    return;
+  }
+
+  let line = match serde_json::to_string(&notification) {
+    Ok(line) => line,
+    Err(error) => {
+      tracing::error!("Failed to encode {} notification: {}", name, error);
+      return;
+    }
+  };
+  subscribers.retain(|subscriber| {
+    !subscriber.names.contains(&name) || subscriber.output.try_send(line.clone()).is_ok()
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::PoisonError;
+
+  use smol::channel::{bounded, unbounded};
+
+  use super::{Notification, SUBSCRIBERS, publish_notification, subscribe, unsubscribe};
+  use crate::event_sources::CLIENT_OUTPUT_CAPACITY;
+
+  #[test]
+  fn notification_json_is_tagged() {
+    let notification = Notification::Bell {
+      window_id: "win-1".into(),
+      tab_id: "tab-2".into(),
+      pane_id: "pane-0".into(),
+    };
+    assert_eq!(
+      serde_json::to_string(&notification).unwrap(),
+      r#"{"notification":"Bell","window_id":"win-1","tab_id":"tab-2","pane_id":"pane-0"}"#
+    );
+    assert_eq!(notification.name(), "Bell");
+  }
+
+  #[test]
+  fn subscribers_receive_only_requested_notifications() {
+    let (output, received) = unbounded();
+    let result = subscribe(u64::MAX, &["TabClosed".to_string()], output).unwrap();
+    assert_eq!(result, serde_json::json!({ "subscribed": ["TabClosed"] }));
+
+    publish_notification(Notification::TabCreated {
+      window_id: "win-1".into(),
+      tab_id: "tab-7".into(),
+    });
+    publish_notification(Notification::TabClosed {
+      window_id: "win-1".into(),
+      tab_id: "tab-7".into(),
+    });
+    unsubscribe(u64::MAX);
+    publish_notification(Notification::TabClosed {
+      window_id: "win-1".into(),
+      tab_id: "tab-8".into(),
+    });
+
+    assert_eq!(
+      received.try_recv().unwrap(),
+      r#"{"notification":"TabClosed","window_id":"win-1","tab_id":"tab-7"}"#
+    );
+    assert!(received.try_recv().is_err());
+  }
+
+  #[test]
+  fn subscribe_rejects_unknown_notifications() {
+    let (output, _received) = unbounded();
+    let error = subscribe(u64::MAX - 1, &["Resize".to_string()], output).unwrap_err();
+    assert!(error.contains("Unknown notification 'Resize'"));
+  }
+
+  #[test]
+  fn subscribers_that_stop_reading_are_dropped() {
+    let client_id = u64::MAX - 2;
+    let (output, _received) = bounded(CLIENT_OUTPUT_CAPACITY);
+    subscribe(client_id, &["Bell".to_string()], output).unwrap();
+    let subscribed = || {
+      SUBSCRIBERS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .any(|subscriber| subscriber.client_id == client_id)
+    };
+
+    let bell = || Notification::Bell {
+      window_id: "win-1".into(),
+      tab_id: "tab-2".into(),
+      pane_id: "pane-0".into(),
+    };
+    for _ in 0..CLIENT_OUTPUT_CAPACITY {
+      publish_notification(bell());
+    }
+    assert!(subscribed());
+
+    publish_notification(bell());
+    assert!(!subscribed());
+  }
+}
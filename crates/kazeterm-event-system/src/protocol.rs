@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use smol::channel::Sender;
+
+use crate::{AppEvent, JsonEvent};
+
+/// Outcome of dispatching one event: a JSON result or an error message.
+pub type EventResult = Result<serde_json::Value, String>;
+
+/// Response line written back to a client for every request that carried an `id`.
+///
+/// ```json
+/// {"id": 1, "ok": true, "result": {"windows": []}}
+/// {"id": 2, "ok": false, "error": "Failed to dispatch UIAction: Tab 'tab-9' not found"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventResponse {
+  pub id: u64,
+  pub ok: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub result: Option<serde_json::Value>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+impl EventResponse {
+  pub fn new(id: u64, result: EventResult) -> Self {
+    match result {
+      Ok(value) => Self {
+        id,
+        ok: true,
+        result: (!value.is_null()).then_some(value),
+        error: None,
+      },
+      Err(error) => Self {
+        id,
+        ok: false,
+        result: None,
+        error: Some(error),
+      },
+    }
+  }
+
+  /// Serialize as a single JSON line (without the trailing newline).
+  pub fn to_line(&self) -> String {
+    serde_json::to_string(self).unwrap_or_else(|error| {
+      format!(
+        r#"{{"id":{},"ok":false,"error":"failed to encode response: {}"}}"#,
+        self.id, error
+      )
+    })
+  }
+}
+
+/// Sends the response for a request back to the client connection it came from.
+pub(crate) struct Responder {
+  id: u64,
+  output: Sender<String>,
+}
+
+impl Responder {
+  pub(crate) fn new(id: u64, output: Sender<String>) -> Self {
+    Self { id, output }
+  }
+
+  pub(crate) fn respond(self, result: EventResult) {
+    let line = EventResponse::new(self.id, result).to_line();
+    if self.output.try_send(line).is_err() {
+      tracing::debug!("Client disconnected before response {} was sent", self.id);
+    }
+  }
+}
+
+/// An event queued for the main thread, plus where to send its response.
+pub(crate) struct QueuedEvent {
+  pub event: AppEvent,
+  pub responder: Option<Responder>,
+}
+
+impl QueuedEvent {
+  pub(crate) fn new(event: AppEvent) -> Self {
+    Self {
+      event,
+      responder: None,
+    }
+  }
+}
+
+/// A single line read from an external client.
+#[derive(Debug, PartialEq)]
+pub(crate) enum ClientRequest {
+  /// Forward an event to the application.
+  Event { id: Option<u64>, event: AppEvent },
+  /// Stream the named notifications (all of them when empty) to this client.
+  Subscribe {
+    id: Option<u64>,
+    notifications: Vec<String>,
+  },
+  /// Stop streaming notifications to this client.
+  Unsubscribe { id: Option<u64> },
+}
+
+#[derive(Deserialize)]
+struct SubscribeRequest {
+  #[serde(default)]
+  notifications: Vec<String>,
+}
+
+/// Parse one request line. On failure, returns the request id (if readable) and the error.
+pub(crate) fn parse_request(line: &str) -> Result<ClientRequest, (Option<u64>, String)> {
+  let value: serde_json::Value =
+    serde_json::from_str(line).map_err(|error| (None, error.to_string()))?;
+  let id = value.get("id").and_then(serde_json::Value::as_u64);
+
+  match value.get("event").and_then(serde_json::Value::as_str) {
+    Some("Subscribe") => {
+      let request: SubscribeRequest =
+        serde_json::from_value(value).map_err(|error| (id, error.to_string()))?;
+      Ok(ClientRequest::Subscribe {
+        id,
+        notifications: request.notifications,
+      })
+    }
+    Some("Unsubscribe") => Ok(ClientRequest::Unsubscribe { id }),
+    _ => {
+      let event: JsonEvent =
+        serde_json::from_value(value).map_err(|error| (id, error.to_string()))?;
+      Ok(ClientRequest::Event {
+        id,
+        event: event.into(),
+      })
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ClientRequest, EventResponse, parse_request};
+  use crate::AppEvent;
+
+  #[test]
+  fn parse_request_keeps_id_and_event() {
+    let request = parse_request(r#"{"id": 4, "event": "SnapshotUITree"}"#).unwrap();
+    assert_eq!(
+      request,
+      ClientRequest::Event {
+        id: Some(4),
+        event: AppEvent::SnapshotUITree,
+      }
+    );
+
+    let request = parse_request(r#"{"event": "NextTab"}"#).unwrap();
+    assert_eq!(
+      request,
+      ClientRequest::Event {
+        id: None,
+        event: AppEvent::NextTab,
+      }
+    );
+  }
+
+  #[test]
+  fn parse_request_handles_subscriptions() {
+    let request =
+      parse_request(r#"{"id": 1, "event": "Subscribe", "notifications": ["Bell"]}"#).unwrap();
+    assert_eq!(
+      request,
+      ClientRequest::Subscribe {
+        id: Some(1),
+        notifications: vec!["Bell".to_string()],
+      }
+    );
+
+    let request = parse_request(r#"{"event": "Unsubscribe"}"#).unwrap();
+    assert_eq!(request, ClientRequest::Unsubscribe { id: None });
+  }
+
+  #[test]
+  fn parse_request_reports_id_of_invalid_event() {
+    let (id, error) = parse_request(r#"{"id": 9, "event": "NoSuchEvent"}"#).unwrap_err();
+    assert_eq!(id, Some(9));
+    assert!(error.contains("NoSuchEvent"));
+
+    let (id, _) = parse_request("not json").unwrap_err();
+    assert_eq!(id, None);
+  }
+
+  #[test]
+  fn event_response_lines() {
+    assert_eq!(
+      EventResponse::new(1, Ok(serde_json::Value::Null)).to_line(),
+      r#"{"id":1,"ok":true}"#
+    );
+    assert_eq!(
+      EventResponse::new(2, Ok(serde_json::json!({"tabs": 1}))).to_line(),
+      r#"{"id":2,"ok":true,"result":{"tabs":1}}"#
+    );
+    assert_eq!(
+      EventResponse::new(3, Err("boom".to_string())).to_line(),
+      r#"{"id":3,"ok":false,"error":"boom"}"#
+    );
+  }
+}
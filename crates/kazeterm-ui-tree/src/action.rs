@@ -111,6 +111,16 @@ pub enum UIAction {
     window_id: String,
     tab_id: String,
   },
+  /// Record the program a terminal pane runs. A running terminal keeps its
+  /// process; the shell is used whenever the pane is built from the tree.
+  SetPaneShell {
+    window_id: String,
+    tab_id: String,
+    pane_id: String,
+    shell_path: String,
+    #[serde(default)]
+    shell_args: Vec<String>,
+  },
 
   // ── Pane content updates (from terminal) ──
   UpdatePaneTitle {
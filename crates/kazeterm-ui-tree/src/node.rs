@@ -187,7 +187,7 @@ pub struct TabNode {
   pub search: SearchState,
 }
 
-/// Shell configuration for a tab.
+/// Shell configuration for a tab or pane.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ShellConfig {
   pub path: String,
@@ -211,6 +211,9 @@ pub enum PaneNode {
     /// ID of the focused pane within this tab's tree.
     #[serde(default)]
     focused: bool,
+    /// Program run in this pane when it differs from the tab's shell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shell: Option<ShellConfig>,
   },
   Split {
     direction: SplitDirection,
@@ -357,6 +360,11 @@ pub enum OverlayNode {
   TabSwitcher {
     selected_index: usize,
   },
+  SearchPanel {
+    /// Search every tab instead of only the panes of the active tab.
+    #[serde(default)]
+    all_tabs: bool,
+  },
 }
 
 #[cfg(test)]
@@ -388,12 +396,14 @@ mod tests {
         working_directory: None,
         title: "shell".into(),
         focused: true,
+        shell: None,
       }),
       second: Box::new(PaneNode::Terminal {
         id: "p2".into(),
         working_directory: None,
         title: "shell".into(),
         focused: false,
+        shell: None,
       }),
     };
 
@@ -440,12 +450,14 @@ mod tests {
               working_directory: Some("D:\\Workspace".into()),
               title: "pwsh".into(),
               focused: true,
+              shell: None,
             }),
             second: Box::new(PaneNode::Terminal {
               id: "pane-2".into(),
               working_directory: None,
               title: "cargo".into(),
               focused: false,
+              shell: None,
             }),
           },
           search: SearchState::default(),
@@ -477,6 +489,7 @@ mod tests {
         message: "Shell crashed".into(),
       },
       OverlayNode::TabSwitcher { selected_index: 2 },
+      OverlayNode::SearchPanel { all_tabs: true },
     ];
 
     for overlay in &overlays {
@@ -493,6 +506,7 @@ mod tests {
       working_directory: Some("/home".into()),
       title: "bash".into(),
       focused: true,
+      shell: None,
     };
     let json = serde_json::to_string(&terminal).unwrap();
     let de: PaneNode = serde_json::from_str(&json).unwrap();
@@ -507,6 +521,7 @@ mod tests {
         working_directory: None,
         title: "zsh".into(),
         focused: false,
+        shell: None,
       }),
     };
     let json = serde_json::to_string(&split).unwrap();
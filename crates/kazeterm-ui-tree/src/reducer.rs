@@ -97,6 +97,7 @@ impl UITree {
             working_directory,
             title: String::new(),
             focused: true,
+            shell: None,
           },
           search: SearchState::default(),
         };
@@ -241,12 +242,21 @@ impl UITree {
           .tab_mut(&tab_id)
           .ok_or_else(|| anyhow::anyhow!("Tab '{}' not found", tab_id))?;
 
+        // Only remember the shell when it differs from the tab's
+        let shell =
+          (shell_path != tab.shell.path || shell_args != tab.shell.args).then(|| ShellConfig {
+            path: shell_path,
+            args: shell_args,
+            profile: tab.shell.profile.clone(),
+          });
+
         // Create the new terminal pane
         let new_terminal = PaneNode::Terminal {
           id: new_pane_id.clone(),
           working_directory,
           title: String::new(),
           focused: true,
+          shell,
         };
 
         // Find the target pane and replace it with a split
@@ -262,9 +272,6 @@ impl UITree {
           *focused = false;
         }
 
-        let _ = shell_path;
-        let _ = shell_args;
-
         let split = PaneNode::Split {
           direction,
           ratio: 0.5,
@@ -397,6 +404,36 @@ impl UITree {
         Ok(())
       }
 
+      UIAction::SetPaneShell {
+        window_id,
+        tab_id,
+        pane_id,
+        shell_path,
+        shell_args,
+      } => {
+        let win = self
+          .window_mut(&window_id)
+          .ok_or_else(|| anyhow::anyhow!("Window '{}' not found", window_id))?;
+        let (_, tab) = win
+          .tab_mut(&tab_id)
+          .ok_or_else(|| anyhow::anyhow!("Tab '{}' not found", tab_id))?;
+
+        // Only remember the shell when it differs from the tab's
+        let shell =
+          (shell_path != tab.shell.path || shell_args != tab.shell.args).then(|| ShellConfig {
+            path: shell_path,
+            args: shell_args,
+            profile: tab.shell.profile.clone(),
+          });
+        match tab.pane_tree.find_pane_mut(&pane_id) {
+          Some(PaneNode::Terminal { shell: s, .. }) => {
+            *s = shell;
+            Ok(())
+          }
+          _ => bail!("Terminal pane '{}' not found", pane_id),
+        }
+      }
+
       // ── Pane content updates ──
       UIAction::UpdatePaneTitle {
         window_id,
@@ -775,6 +812,98 @@ mod tests {
     assert_eq!(tab.pane_tree.terminal_count(), 1);
   }
 
+  #[test]
+  fn test_split_pane_keeps_shell_that_differs_from_tab() {
+    let (mut tree, win_id) = setup_tree_with_window();
+
+    tree
+      .apply(UIAction::AddTab {
+        window_id: win_id.clone(),
+        shell_path: "bash".into(),
+        shell_args: vec![],
+        profile: None,
+        working_directory: None,
+      })
+      .unwrap();
+
+    let tab_id = tree.window(&win_id).unwrap().tabs[0].id.clone();
+    let pane_id = tree.window(&win_id).unwrap().tabs[0]
+      .pane_tree
+      .terminal_ids()[0]
+      .to_string();
+
+    for (shell_path, shell_args) in [
+      ("bash", vec![]),
+      ("htop", vec!["-d".to_string(), "10".into()]),
+    ] {
+      tree
+        .apply(UIAction::SplitPane {
+          window_id: win_id.clone(),
+          tab_id: tab_id.clone(),
+          pane_id: pane_id.clone(),
+          direction: SplitDirection::Vertical,
+          shell_path: shell_path.into(),
+          shell_args,
+          working_directory: None,
+        })
+        .unwrap();
+    }
+
+    let tab = &tree.window(&win_id).unwrap().tabs[0];
+    let shells: Vec<_> = tab
+      .pane_tree
+      .terminal_ids()
+      .into_iter()
+      .map(|id| match tab.pane_tree.find_pane(id) {
+        Some(PaneNode::Terminal { shell, .. }) => shell.as_ref().map(|s| s.path.as_str()),
+        _ => unreachable!(),
+      })
+      .collect();
+    assert_eq!(shells, vec![None, Some("htop"), None]);
+  }
+
+  #[test]
+  fn test_set_pane_shell_records_only_a_shell_that_differs_from_tab() {
+    let (mut tree, win_id) = setup_tree_with_window();
+
+    tree
+      .apply(UIAction::AddTab {
+        window_id: win_id.clone(),
+        shell_path: "bash".into(),
+        shell_args: vec![],
+        profile: None,
+        working_directory: None,
+      })
+      .unwrap();
+
+    let tab_id = tree.window(&win_id).unwrap().tabs[0].id.clone();
+    let pane_id = tree.window(&win_id).unwrap().tabs[0]
+      .pane_tree
+      .terminal_ids()[0]
+      .to_string();
+    let pane_shell = |tree: &UITree| match tree.window(&win_id).unwrap().tabs[0]
+      .pane_tree
+      .find_pane(&pane_id)
+    {
+      Some(PaneNode::Terminal { shell, .. }) => shell.as_ref().map(|s| s.path.clone()),
+      _ => unreachable!(),
+    };
+
+    for (shell_path, expected) in [("cargo", Some("cargo".to_string())), ("bash", None)] {
+      tree
+        .apply(UIAction::SetPaneShell {
+          window_id: win_id.clone(),
+          tab_id: tab_id.clone(),
+          pane_id: pane_id.clone(),
+          shell_path: shell_path.into(),
+          shell_args: vec![],
+        })
+        .unwrap();
+      assert_eq!(pane_shell(&tree), expected);
+    }
+    assert_eq!(tree.window(&win_id).unwrap().tabs[0].shell.path, "bash");
+  }
+
   #[test]
   fn test_focus_cycle() {
     let (mut tree, win_id) = setup_tree_with_window();
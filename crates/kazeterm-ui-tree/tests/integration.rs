@@ -86,6 +86,7 @@ fn load_json_snapshot_and_verify_structure() {
       working_directory,
       title,
       focused,
+      ..
     } => {
       assert_eq!(id, "pane-1");
       assert_eq!(working_directory.as_deref(), Some("D:\\Workspace"));
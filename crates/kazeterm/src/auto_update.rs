@@ -10,7 +10,7 @@ use gpui::{AnyWindowHandle, App, AppContext, AsyncApp, Context as GpuiContext, W
 use semver::Version;
 use serde::Deserialize;
 
-use crate::components::MainWindow;
+use crate::components::{MainWindow, ScrollbackArchive};
 
 const GITHUB_OWNER: &str = "fallenwood";
 const GITHUB_REPO: &str = "kazeterm";
@@ -140,6 +140,7 @@ pub(crate) fn apply_prepared_update_in_context(
 ) -> anyhow::Result<()> {
   main_window.sync_ui_tree(cx);
   main_window.ui_tree.save_workspace();
+  main_window.save_scrollback(&ScrollbackArchive::workspace_dir(), cx);
   request_restore_workspace_once()?;
   launch_update_helper(&prepared_update)?;
   cx.quit();
@@ -0,0 +1,367 @@
+//! Crash-recovery autosave.
+//!
+//! Every running instance keeps the UI tree and scrollback archive of each
+//! of its windows under `<config dir>/autosave/<pid>-<start time>/`, next to
+//! a `lock` file it holds until it exits. A clean quit removes the directory.
+//! A directory whose lock can be taken at startup was left behind by a run
+//! that crashed or was killed, and its trees are offered for recovery. The
+//! start time keeps a reused pid from picking up a crashed run's directory.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gpui::{AnyWindowHandle, App, Global};
+use kazeterm_ui_tree::node::UITree;
+
+const LOCK_FILE: &str = "lock";
+
+/// Layouts left behind by runs that did not shut down cleanly.
+#[derive(Default)]
+pub(crate) struct Recovery {
+  pub(crate) windows: Vec<RecoveredWindow>,
+  dirs: Vec<PathBuf>,
+}
+
+pub(crate) struct RecoveredWindow {
+  pub(crate) tree: UITree,
+  /// The window's `ScrollbackArchive` directory, which may not exist.
+  pub(crate) scrollback: PathBuf,
+}
+
+impl Recovery {
+  /// Delete the recovered files once they have been restored or declined.
+  pub(crate) fn finish(self) {
+    for dir in self.dirs {
+      if let Err(e) = std::fs::remove_dir_all(&dir) {
+        tracing::error!("Failed to remove autosave {}: {e}", dir.display());
+      }
+    }
+  }
+}
+
+/// This instance's autosave directory and the lock marking it as running.
+pub(crate) struct Autosave {
+  dir: PathBuf,
+  _lock: File,
+  recovery: Option<Recovery>,
+}
+
+impl Global for Autosave {}
+
+fn autosave_root() -> PathBuf {
+  config::Config::get_config_path().join("autosave")
+}
+
+/// Claim an autosave directory for this process and collect what crashed
+/// runs left behind. Without the global, autosave is a no-op.
+pub(crate) fn init(cx: &mut App) {
+  let root = autosave_root();
+  let started = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis();
+  let dir = root.join(format!("{}-{started}", std::process::id()));
+
+  let lock = std::fs::create_dir_all(&dir)
+    .and_then(|()| File::create(dir.join(LOCK_FILE)))
+    .and_then(|file| {
+      file.try_lock()?;
+      Ok(file)
+    });
+  let lock = match lock {
+    Ok(lock) => lock,
+    Err(e) => {
+      tracing::error!("Failed to set up workspace autosave: {e}");
+      return;
+    }
+  };
+  // Our own directory is locked by now, so the scan passes over it.
+  let recovery = find_unclean_sessions(&root);
+
+  cx.set_global(Autosave {
+    dir,
+    _lock: lock,
+    recovery: (!recovery.windows.is_empty()).then_some(recovery),
+  });
+  cx.on_app_quit(|cx| {
+    if let Some(autosave) = cx.try_global::<Autosave>()
+      && let Err(e) = std::fs::remove_dir_all(&autosave.dir)
+    {
+      tracing::error!("Failed to remove workspace autosave: {e}");
+    }
+    async {}
+  })
+  .detach();
+}
+
+/// Take the layouts to offer for recovery. Only the first caller gets them.
+pub(crate) fn take_recovery(cx: &mut App) -> Option<Recovery> {
+  if !cx.has_global::<Autosave>() {
+    return None;
+  }
+  cx.global_mut::<Autosave>().recovery.take()
+}
+
+pub(crate) fn window_key(handle: AnyWindowHandle) -> String {
+  format!("window-{}", handle.window_id().as_u64())
+}
+
+fn scrollback_path(dir: &Path, key: &str) -> PathBuf {
+  dir.join(format!("{key}.scrollback"))
+}
+
+/// Where a window's scrollback archive is autosaved, next to its tree.
+pub(crate) fn scrollback_dir(key: &str, cx: &App) -> Option<PathBuf> {
+  cx.try_global::<Autosave>()
+    .map(|autosave| scrollback_path(&autosave.dir, key))
+}
+
+/// Where a window's tree is autosaved. `None` when autosave is off.
+pub(crate) fn window_path(key: &str, cx: &App) -> Option<PathBuf> {
+  cx.try_global::<Autosave>()
+    .map(|autosave| autosave.dir.join(format!("{key}.json")))
+}
+
+/// The scrollback each pane had at the last autosave, keyed like the
+/// archive, so rounds only render the panes that had output since.
+#[derive(Default)]
+pub(crate) struct ScrollbackCache {
+  /// Snapshot of each pane and the `(terminal entity, output generation)`
+  /// it was taken at.
+  panes: BTreeMap<String, ((u64, u64), Option<String>)>,
+}
+
+impl ScrollbackCache {
+  /// Replace the cached panes with `panes`, calling `snapshot` only for
+  /// those whose version differs from the cached one. Returns whether any
+  /// pane was added, changed or removed.
+  pub(crate) fn refresh<T>(
+    &mut self,
+    panes: Vec<(String, (u64, u64), T)>,
+    mut snapshot: impl FnMut(&T) -> Option<String>,
+  ) -> bool {
+    let mut changed = false;
+    let mut refreshed = BTreeMap::new();
+    for (key, version, pane) in panes {
+      let entry = match self.panes.remove(&key) {
+        Some(entry) if entry.0 == version => entry,
+        _ => {
+          changed = true;
+          (version, snapshot(&pane))
+        }
+      };
+      refreshed.insert(key, entry);
+    }
+    changed |= !self.panes.is_empty();
+    self.panes = refreshed;
+    changed
+  }
+
+  /// `(key, text)` of every cached pane with something to keep.
+  pub(crate) fn snapshots(&self) -> Vec<(String, String)> {
+    self
+      .panes
+      .iter()
+      .filter_map(|(key, (_, text))| Some((key.clone(), text.clone()?)))
+      .collect()
+  }
+}
+
+/// Write a window's tree to `path`, replacing its previous autosave. Blocks
+/// on the disk, so it runs on the background executor.
+pub(crate) fn save_window(path: &Path, tree: &UITree) {
+  if tree
+    .windows
+    .first()
+    .is_none_or(|window| window.tabs.is_empty())
+  {
+    return;
+  }
+  let result = serde_json::to_string_pretty(tree)
+    .map_err(std::io::Error::other)
+    .and_then(|json| write_atomically(path, json.as_bytes()));
+  if let Err(e) = result {
+    tracing::error!("Failed to autosave workspace: {e}");
+  }
+}
+
+/// Forget a window's autosave after it was closed on purpose.
+pub(crate) fn discard_window(handle: AnyWindowHandle, cx: &App) {
+  let Some(autosave) = cx.try_global::<Autosave>() else {
+    return;
+  };
+  let key = window_key(handle);
+  let path = autosave.dir.join(format!("{key}.json"));
+  if let Err(e) = std::fs::remove_file(&path)
+    && e.kind() != std::io::ErrorKind::NotFound
+  {
+    tracing::error!("Failed to remove window autosave: {e}");
+  }
+  if let Err(e) = std::fs::remove_dir_all(scrollback_path(&autosave.dir, &key))
+    && e.kind() != std::io::ErrorKind::NotFound
+  {
+    tracing::error!("Failed to remove window scrollback autosave: {e}");
+  }
+}
+
+/// Write `contents` to a sibling temp file and rename it over `path`, so a
+/// crash mid-write never leaves a truncated file behind.
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+  let tmp = path.with_extension("tmp");
+  let mut file = File::create(&tmp)?;
+  file.write_all(contents)?;
+  file.sync_all()?;
+  drop(file);
+  std::fs::rename(&tmp, path)
+}
+
+/// Trees from every autosave directory under `root` whose owner no longer
+/// holds its lock. Abandoned directories without a recoverable tree are
+/// deleted on the spot.
+fn find_unclean_sessions(root: &Path) -> Recovery {
+  let mut recovery = Recovery::default();
+  let Ok(entries) = std::fs::read_dir(root) else {
+    return recovery;
+  };
+  for entry in entries.flatten() {
+    let dir = entry.path();
+    if !dir.is_dir() {
+      continue;
+    }
+    // A live instance, this one included, still holds its lock; leave its
+    // autosaves alone. A directory without a lock file may belong to an
+    // instance that has not created it yet.
+    match File::open(dir.join(LOCK_FILE)) {
+      Ok(lock) if lock.try_lock().is_ok() => {}
+      _ => continue,
+    }
+    let Ok(files) = std::fs::read_dir(&dir) else {
+      continue;
+    };
+    let found = recovery.windows.len();
+    for file in files.flatten() {
+      let path = file.path();
+      if path.extension().is_none_or(|ext| ext != "json") {
+        continue;
+      }
+      match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<UITree>(&json).map_err(|e| e.to_string()))
+      {
+        Ok(tree) if tree.windows.first().is_some_and(|w| !w.tabs.is_empty()) => {
+          recovery.windows.push(RecoveredWindow {
+            tree,
+            scrollback: path.with_extension("scrollback"),
+          });
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!("Failed to read autosave {}: {e}", path.display()),
+      }
+    }
+    if recovery.windows.len() > found {
+      recovery.dirs.push(dir);
+      continue;
+    }
+    // The run crashed before its first autosave or left nothing readable.
+    if let Err(e) = std::fs::remove_dir_all(&dir) {
+      tracing::error!("Failed to remove autosave {}: {e}", dir.display());
+    }
+  }
+  recovery
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs::File;
+
+  use super::{LOCK_FILE, ScrollbackCache, find_unclean_sessions, write_atomically};
+  use crate::test_support::window_with_tabs;
+
+  #[test]
+  fn atomic_write_replaces_the_file_without_leftovers() {
+    let dir = std::env::temp_dir().join(format!("kazeterm-atomic-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("window-1.json");
+
+    write_atomically(&path, b"old").unwrap();
+    write_atomically(&path, b"new").unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+    assert!(!path.with_extension("tmp").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn only_unlocked_sessions_are_recovered() {
+    let root = std::env::temp_dir().join(format!("kazeterm-autosave-{}", std::process::id()));
+    let crashed = root.join("1-100");
+    let running = root.join("2-200");
+    for dir in [&crashed, &running] {
+      std::fs::create_dir_all(dir).unwrap();
+      std::fs::write(
+        dir.join("window-1.json"),
+        serde_json::to_string(&window_with_tabs(1)).unwrap(),
+      )
+      .unwrap();
+    }
+    File::create(crashed.join(LOCK_FILE)).unwrap();
+    let empty = root.join("3-300");
+    let unreadable = root.join("4-400");
+    let starting = root.join("5-500");
+    for dir in [&empty, &unreadable, &starting] {
+      std::fs::create_dir_all(dir).unwrap();
+    }
+    File::create(empty.join(LOCK_FILE)).unwrap();
+    File::create(unreadable.join(LOCK_FILE)).unwrap();
+    std::fs::write(unreadable.join("window-1.json"), "{").unwrap();
+    let held = File::create(running.join(LOCK_FILE)).unwrap();
+    held.try_lock().unwrap();
+
+    let recovery = find_unclean_sessions(&root);
+
+    assert_eq!(recovery.windows.len(), 1);
+    assert_eq!(
+      recovery.windows[0].scrollback,
+      crashed.join("window-1.scrollback")
+    );
+    assert_eq!(recovery.dirs, vec![crashed.clone()]);
+    assert!(!empty.exists());
+    assert!(!unreadable.exists());
+    assert!(starting.exists());
+    recovery.finish();
+    assert!(!crashed.exists());
+    assert!(running.exists());
+    drop(held);
+    std::fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn scrollback_cache_only_renders_panes_with_new_output() {
+    let mut cache = ScrollbackCache::default();
+    let mut rendered = Vec::new();
+    let mut refresh = |cache: &mut ScrollbackCache, panes: &[(&str, u64)]| {
+      let panes = panes
+        .iter()
+        .map(|&(key, generation)| (key.to_string(), (1, generation), key))
+        .collect();
+      cache.refresh(panes, |key| {
+        rendered.push(key.to_string());
+        Some(format!("{key} output"))
+      })
+    };
+
+    assert!(refresh(&mut cache, &[("a", 1), ("b", 1)]));
+    assert!(!refresh(&mut cache, &[("a", 1), ("b", 1)]));
+    assert!(refresh(&mut cache, &[("a", 1), ("b", 2)]));
+    // Closing a pane changes the archive without rendering anything.
+    assert!(refresh(&mut cache, &[("b", 2)]));
+    assert_eq!(rendered, vec!["a", "b", "b"]);
+    assert_eq!(
+      cache.snapshots(),
+      vec![("b".to_string(), "b output".to_string())]
+    );
+  }
+}
@@ -3,11 +3,16 @@ use std::sync::atomic::AtomicUsize;
 use gpui::*;
 use gpui_component::Size;
 use kazeterm_ui_tree::action::UIAction;
+use kazeterm_ui_tree::node::UITree;
 
 use crate::components::about_dialog::AboutDialog;
 use crate::components::close_confirm_dialog::CloseConfirmDialog;
 use crate::components::import_alacritty_dialog::ImportAlacrittyDialog;
+use crate::components::recovery_dialog::RecoveryDialog;
+use crate::components::scrollback_archive::ScrollbackArchive;
 use crate::components::search_bar::SearchBar;
+use crate::components::search_panel::SearchPanel;
+use crate::components::sessions_dialog::SessionsDialog;
 use crate::components::shell_error_dialog::ShellErrorDialog;
 use crate::components::tab_rename_dialog::TabRenameDialog;
 use crate::components::tab_switcher::TabSwitcher;
@@ -20,6 +25,8 @@ use super::dragged_tab::DraggedTab;
 pub(crate) use super::main_window_tab_item::TabItem;
 
 const VERTICAL_TABBAR_WIDTH_RATIO: f32 = 0.175;
+/// How often the periodic autosave checks whether it was turned back on.
+const AUTOSAVE_DISABLED_POLL: std::time::Duration = std::time::Duration::from_secs(60);
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub(crate) struct KeyDebugModifiers {
@@ -55,6 +62,9 @@ pub struct MainWindow {
   pub(crate) search_visible: bool,
   pub(crate) search_bar: Entity<SearchBar>,
   pub(crate) _search_bar_subscription: gpui::Subscription,
+  /// Search result panel listing matches across panes
+  pub(crate) search_panel: Option<Entity<SearchPanel>>,
+  pub(crate) _search_panel_subscription: Option<gpui::Subscription>,
   pub(crate) tab_scroll_handle: gpui::ScrollHandle,
   pub(crate) scroll_tabs_to_end: bool,
   pub(crate) scroll_to_active_tab: bool,
@@ -73,6 +83,10 @@ pub struct MainWindow {
   pub(crate) key_debug_modifiers: KeyDebugModifiers,
   pub(crate) key_debug_pressed_keys: Vec<KeyDebugPressedKey>,
   pub(crate) key_debug_recent_keys: Vec<KeyDebugRecentKey>,
+  /// Keystrokes of a multi-key main window shortcut typed so far.
+  pub(crate) pending_keystrokes: Vec<::config::ParsedKeybinding>,
+  /// Drops `pending_keystrokes` once the sequence times out.
+  pub(crate) pending_keystrokes_timeout: Task<()>,
   /// Tab rename dialog state
   pub(crate) rename_dialog: Option<Entity<TabRenameDialog>>,
   pub(crate) _rename_dialog_subscription: Option<gpui::Subscription>,
@@ -90,6 +104,13 @@ pub struct MainWindow {
   pub(crate) update_confirm_dialog: Option<Entity<UpdateConfirmDialog>>,
   pub(crate) _update_confirm_subscription: Option<gpui::Subscription>,
   pub(crate) pending_update: Option<crate::auto_update::PreparedUpdate>,
+  /// Named sessions dialog state
+  pub(crate) sessions_dialog: Option<Entity<SessionsDialog>>,
+  pub(crate) _sessions_subscription: Option<gpui::Subscription>,
+  /// Crash recovery prompt state
+  pub(crate) recovery_dialog: Option<Entity<RecoveryDialog>>,
+  pub(crate) _recovery_subscription: Option<gpui::Subscription>,
+  pub(crate) pending_recovery: Option<crate::autosave::Recovery>,
   /// Whether a UITree JSON file picker is currently active.
   pub(crate) ui_tree_json_prompt_pending: bool,
   /// Shell error dialog state
@@ -106,6 +127,21 @@ pub struct MainWindow {
   pub(crate) ui_tree: UITreeStore,
   /// Guards against re-dispatching while tree diffs are being reconciled.
   pub(crate) reconciling_ui_tree: bool,
+  /// Scrollback saved by the previous run, consumed while restoring its workspace.
+  pub(crate) pending_scrollback: ScrollbackArchive,
+  /// File name of this window's crash-recovery autosave.
+  pub(crate) autosave_key: String,
+  /// Debounces autosaves after layout changes.
+  pub(crate) autosave_task: Task<()>,
+  /// Autosaves every interval, for titles, working directories and output
+  /// that change without a layout action.
+  _periodic_autosave: Task<()>,
+  /// The tree the last autosave wrote, to skip rounds where nothing changed.
+  last_autosave: Option<UITree>,
+  /// The scrollback the last autosave wrote, per pane.
+  autosave_scrollback: crate::autosave::ScrollbackCache,
+  /// The autosave being written on the background executor.
+  autosave_write: Task<()>,
   /// Keeps the current programmatic window resize transition alive.
   pub(crate) window_resize_animation: Task<()>,
   pub(crate) event_source_config: EventSourceConfig,
@@ -228,6 +264,8 @@ impl MainWindow {
       search_visible: false,
       search_bar,
       _search_bar_subscription: search_bar_subscription,
+      search_panel: None,
+      _search_panel_subscription: None,
       tab_scroll_handle: gpui::ScrollHandle::new(),
       scroll_tabs_to_end: false,
       scroll_to_active_tab: false,
@@ -243,6 +281,8 @@ impl MainWindow {
       key_debug_modifiers: KeyDebugModifiers::default(),
       key_debug_pressed_keys: Vec::new(),
       key_debug_recent_keys: Vec::new(),
+      pending_keystrokes: Vec::new(),
+      pending_keystrokes_timeout: Task::ready(()),
       rename_dialog: None,
       _rename_dialog_subscription: None,
       close_confirm_dialog: None,
@@ -255,6 +295,11 @@ impl MainWindow {
       update_confirm_dialog: None,
       _update_confirm_subscription: None,
       pending_update: None,
+      sessions_dialog: None,
+      _sessions_subscription: None,
+      recovery_dialog: None,
+      _recovery_subscription: None,
+      pending_recovery: None,
       ui_tree_json_prompt_pending: false,
       shell_error_dialog: None,
       _shell_error_subscription: None,
@@ -264,6 +309,13 @@ impl MainWindow {
       _window_activation_subscription: window_activation_subscription,
       ui_tree: UITreeStore::new(),
       reconciling_ui_tree: false,
+      pending_scrollback: ScrollbackArchive::default(),
+      autosave_key: crate::autosave::window_key(window.window_handle()),
+      autosave_task: Task::ready(()),
+      _periodic_autosave: Self::periodic_autosave(cx),
+      last_autosave: None,
+      autosave_scrollback: crate::autosave::ScrollbackCache::default(),
+      autosave_write: Task::ready(()),
       window_resize_animation: Task::ready(()),
       event_source_config,
       active_tab_drag: None,
@@ -278,15 +330,32 @@ impl MainWindow {
     let restore_workspace_once = crate::auto_update::take_restore_workspace_once();
     if config.window.restore_workspace || restore_workspace_once {
       if let Some(tree) = UITreeStore::load_workspace() {
+        main_window.pending_scrollback = ScrollbackArchive::take(
+          &ScrollbackArchive::workspace_dir(),
+          config.terminal.get_persist_scrollback_max_bytes(),
+        );
         main_window.reconciling_ui_tree = true;
         main_window.restore_from_ui_tree(&tree, window, cx);
         main_window.reconciling_ui_tree = false;
+        main_window.pending_scrollback.clear();
         main_window.ui_tree = UITreeStore::from_tree(tree);
         UITreeStore::delete_workspace();
         return main_window;
       }
     }
 
+    let startup_layout = cx
+      .global::<::config::Config>()
+      .window
+      .startup_layout
+      .clone();
+    if let Some(name) = startup_layout {
+      match main_window.open_layout(&name, window, cx) {
+        Ok(()) => return main_window,
+        Err(err) => tracing::error!("{err}"),
+      }
+    }
+
     main_window.insert_new_tab(window, cx);
     main_window
   }
@@ -299,6 +368,88 @@ impl MainWindow {
     self.ui_tree = tree_store;
   }
 
+  /// Autosave the layout once it has been left alone for the configured
+  /// interval. Each call restarts the wait.
+  pub(crate) fn schedule_autosave(&mut self, cx: &mut Context<Self>) {
+    let Some(interval) = cx
+      .global::<::config::Config>()
+      .window
+      .get_autosave_interval()
+    else {
+      return;
+    };
+    self.autosave_task = cx.spawn(async move |this, cx| {
+      cx.background_executor().timer(interval).await;
+      let _ = this.update(cx, |main_window, cx| main_window.autosave(cx));
+    });
+  }
+
+  /// Autosave every interval, whether or not a layout action ran, since
+  /// titles, working directories and output change without one. Rounds where
+  /// nothing changed write nothing. The interval is re-read each round so
+  /// config reloads take effect.
+  fn periodic_autosave(cx: &mut Context<Self>) -> Task<()> {
+    cx.spawn(async move |this, cx| {
+      loop {
+        let Ok(interval) = this.update(cx, |_, cx| {
+          cx.global::<::config::Config>()
+            .window
+            .get_autosave_interval()
+        }) else {
+          return;
+        };
+        cx.background_executor()
+          .timer(interval.unwrap_or(AUTOSAVE_DISABLED_POLL))
+          .await;
+        let saved = this.update(cx, |main_window, cx| {
+          let enabled = cx
+            .global::<::config::Config>()
+            .window
+            .get_autosave_interval()
+            .is_some();
+          if enabled {
+            main_window.autosave(cx);
+          }
+        });
+        if saved.is_err() {
+          return;
+        }
+      }
+    })
+  }
+
+  /// Save the tree and scrollback unless they match the last autosave. Only
+  /// panes with new output are rendered here; serializing, compressing and
+  /// writing happen on the background executor.
+  fn autosave(&mut self, cx: &mut Context<Self>) {
+    let (Some(path), Some(dir)) = (
+      crate::autosave::window_path(&self.autosave_key, cx),
+      crate::autosave::scrollback_dir(&self.autosave_key, cx),
+    ) else {
+      return;
+    };
+    self.sync_ui_tree(cx);
+    let tree = self.ui_tree.tree().clone();
+    let mut cache = std::mem::take(&mut self.autosave_scrollback);
+    let scrollback_changed = self.refresh_scrollback_cache(&mut cache, cx);
+    let snapshots = scrollback_changed.then(|| cache.snapshots());
+    self.autosave_scrollback = cache;
+    if snapshots.is_none() && self.last_autosave.as_ref() == Some(&tree) {
+      return;
+    }
+    self.last_autosave = Some(tree.clone());
+
+    let previous = std::mem::replace(&mut self.autosave_write, Task::ready(()));
+    self.autosave_write = cx.background_spawn(async move {
+      // Rounds share files, so each waits for the one before it.
+      previous.await;
+      crate::autosave::save_window(&path, &tree);
+      if let Some(snapshots) = snapshots {
+        ScrollbackArchive::save(&dir, snapshots);
+      }
+    });
+  }
+
   /// Dump the current UI tree as a JSON string.
   pub fn snapshot_ui_tree(&mut self, cx: &mut Context<Self>) -> Result<String, serde_json::Error> {
     self.sync_ui_tree(cx);
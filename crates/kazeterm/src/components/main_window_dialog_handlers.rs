@@ -6,6 +6,9 @@ use super::main_window::MainWindow;
 use crate::components::about_dialog::{AboutDialog, AboutDialogCloseEvent, AboutDialogEvent};
 use crate::components::close_confirm_dialog::{CloseConfirmDialog, CloseConfirmEvent};
 use crate::components::import_alacritty_dialog::{ImportAlacrittyDialog, ImportAlacrittyEvent};
+use crate::components::recovery_dialog::{RecoveryDialog, RecoveryEvent};
+use crate::components::scrollback_archive::ScrollbackArchive;
+use crate::components::sessions_dialog::{SessionsDialog, SessionsEvent};
 use crate::components::shell_error_dialog::{ShellErrorCloseEvent, ShellErrorDialog};
 use crate::components::tab_rename_dialog::{TabRenameDialog, TabRenameEvent};
 use crate::components::update_confirm_dialog::{UpdateConfirmDialog, UpdateConfirmEvent};
@@ -95,6 +98,7 @@ impl MainWindow {
         // Save workspace state before closing
         self.sync_ui_tree(cx);
         self.ui_tree.save_workspace();
+        self.save_scrollback(&ScrollbackArchive::workspace_dir(), cx);
         self.close_confirm_dialog = None;
         self._close_confirm_subscription = None;
         crate::window_manager::close_window(window, cx);
@@ -303,6 +307,99 @@ impl MainWindow {
     }
   }
 
+  /// Show the dialog for saving, opening and deleting named sessions.
+  pub(crate) fn show_sessions_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+    if self.sessions_dialog.is_some() {
+      return;
+    }
+
+    let dialog = cx.new(|cx| SessionsDialog::new(window, cx));
+    let subscription = cx.subscribe_in(&dialog, window, Self::on_sessions_event);
+    dialog.update(cx, |dialog, cx| {
+      dialog.focus(window, cx);
+    });
+
+    self.sessions_dialog = Some(dialog);
+    self._sessions_subscription = Some(subscription);
+    cx.notify();
+  }
+
+  pub(crate) fn on_sessions_event(
+    &mut self,
+    dialog: &Entity<SessionsDialog>,
+    event: &SessionsEvent,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    let result = match event {
+      SessionsEvent::Save(name) => self.save_session(name, cx),
+      SessionsEvent::Open(name) => self.open_session(name, cx).map(|_| ()),
+      SessionsEvent::Cancel => Ok(()),
+    };
+    if let Err(e) = result {
+      dialog.update(cx, |dialog, cx| dialog.set_error(e, cx));
+      return;
+    }
+
+    self.sessions_dialog = None;
+    self._sessions_subscription = None;
+    self.refocus_active_terminal(window, cx);
+    cx.notify();
+  }
+
+  /// Offer to restore the layouts a crashed run left behind.
+  pub(crate) fn show_recovery_dialog(
+    &mut self,
+    recovery: crate::autosave::Recovery,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    let window_count = recovery.windows.len();
+    let dialog = cx.new(|cx| RecoveryDialog::new(window_count, window, cx));
+    let subscription = cx.subscribe_in(&dialog, window, Self::on_recovery_event);
+
+    self.pending_recovery = Some(recovery);
+    self.recovery_dialog = Some(dialog);
+    self._recovery_subscription = Some(subscription);
+    cx.notify();
+  }
+
+  pub(crate) fn on_recovery_event(
+    &mut self,
+    _dialog: &Entity<RecoveryDialog>,
+    event: &RecoveryEvent,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    self.recovery_dialog = None;
+    self._recovery_subscription = None;
+    let Some(recovery) = self.pending_recovery.take() else {
+      self.refocus_active_terminal(window, cx);
+      cx.notify();
+      return;
+    };
+
+    // Recovered layouts open in windows of their own, so the tabs this
+    // window already has (a `--session` or `startup_layout` included) stay.
+    if *event == RecoveryEvent::Restore {
+      let max_bytes = cx
+        .global::<::config::Config>()
+        .terminal
+        .get_persist_scrollback_max_bytes();
+      for recovered in &recovery.windows {
+        crate::window_manager::open_kazeterm_window_with_ui_tree(
+          recovered.tree.clone(),
+          ScrollbackArchive::load(&recovered.scrollback, max_bytes),
+          self.event_source_config.clone(),
+          cx,
+        );
+      }
+    }
+    recovery.finish();
+    self.refocus_active_terminal(window, cx);
+    cx.notify();
+  }
+
   /// Show import Alacritty config dialog
   pub fn show_import_alacritty_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
     if self.import_alacritty_dialog.is_some() {
@@ -9,7 +9,7 @@
 use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use gpui::{TestAppContext, WindowHandle};
+use gpui::{AppContext as _, TestAppContext, VisualTestContext, WindowHandle};
 use kazeterm_ui_tree::action::UIAction;
 use kazeterm_ui_tree::node::{OverlayNode, UITree};
 
@@ -870,3 +870,46 @@ fn pinned_tabs_round_trip_through_ui_tree_snapshots(cx: &mut TestAppContext) {
 
   clear_terminal_session_factory_for_testing();
 }
+
+#[gpui::test]
+fn key_sequence_prefix_reaches_focused_dialog_input(cx: &mut TestAppContext) {
+  let _guard = test_lock();
+  crate::test_support::init_test_app(cx);
+  cx.update(|cx| {
+    let mut config = cx.global::<::config::Config>().clone();
+    config.keybindings.next_tab = ::config::KeybindingList::new("ctrl-b n");
+    cx.set_global(config);
+  });
+  let _calls = install_fake_factory();
+
+  // Text inputs only paint under a `Root`, as in real windows.
+  let mut main_window = None;
+  let window = cx.add_window(|window, cx| {
+    let view = cx.new(|cx| MainWindow::new(window, cx));
+    main_window = Some(view.clone());
+    gpui_component::Root::new(view, window, cx)
+  });
+  let main_window = main_window.expect("main window should be created");
+  let cx = &mut VisualTestContext::from_window(window.into(), cx);
+  cx.run_until_parked();
+  main_window.update_in(cx, |root, window, cx| {
+    let tab_index = root.items[0].index;
+    root.show_rename_dialog(tab_index, window, cx);
+  });
+
+  // `ctrl-a` selects the current title; the input has no binding for the
+  // `ctrl-b` prefix, so only the key sequence handler could swallow the `n`.
+  cx.simulate_keystrokes("ctrl-a ctrl-b n o enter");
+
+  main_window.read_with(cx, |root, _cx| {
+    assert!(root.pending_keystrokes.is_empty());
+    assert!(root.rename_dialog.is_none());
+    // The test platform also inserts the `enter` keystroke's newline.
+    assert_eq!(
+      root.items[0].custom_title.as_deref().map(str::trim_end),
+      Some("no")
+    );
+  });
+
+  clear_terminal_session_factory_for_testing();
+}
@@ -0,0 +1,292 @@
+use ::config::{Config, Layout, LayoutSplit, PaneLayout, TabLayout};
+use anyhow::{anyhow, bail};
+use gpui::{Context, Window};
+use kazeterm_ui_tree::action::{SplitChild, UIAction};
+use kazeterm_ui_tree::node::{PaneNode, SplitDirection, UITree};
+
+use super::main_window::MainWindow;
+use super::main_window_tab_management::{get_working_directory_pathbuf, resolve_tab_launch};
+
+impl MainWindow {
+  /// Open the tabs of the configured layout `name` after the existing ones.
+  pub(crate) fn open_layout(
+    &mut self,
+    name: &str,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) -> Result<(), String> {
+    let window_id = self
+      .sync_ui_tree_and_window_id(cx)
+      .ok_or_else(|| "Window is not ready".to_string())?;
+    let config = cx.global::<Config>();
+    let layout = config
+      .layouts
+      .get(name)
+      .ok_or_else(|| format!("Layout '{name}' not found"))?;
+    let action = build_layout_action(config, layout, self.ui_tree.tree(), &window_id)
+      .map_err(|e| format!("Failed to build layout '{name}': {e}"))?;
+    self
+      .dispatch_ui_action(action, window, cx)
+      .map_err(|e| format!("Failed to open layout '{name}': {e}"))
+  }
+}
+
+/// Compile `layout` into one batch of `AddTab`, `SetPaneShell`, `SplitPane`
+/// and `ResizeSplit` actions for `window_id`. Pane ids are only known once an
+/// action has been applied, so each action is also applied to a copy of
+/// `tree` as it goes.
+fn build_layout_action(
+  config: &Config,
+  layout: &Layout,
+  tree: &UITree,
+  window_id: &str,
+) -> anyhow::Result<UIAction> {
+  let mut builder = LayoutBuilder {
+    tree: tree.clone(),
+    actions: Vec::new(),
+    window_id,
+  };
+  for tab in &layout.tabs {
+    builder.add_tab(config, tab)?;
+  }
+  Ok(UIAction::Batch {
+    actions: builder.actions,
+  })
+}
+
+struct LayoutBuilder<'a> {
+  tree: UITree,
+  actions: Vec<UIAction>,
+  window_id: &'a str,
+}
+
+impl LayoutBuilder<'_> {
+  fn push(&mut self, action: UIAction) -> anyhow::Result<()> {
+    self.tree.apply(action.clone())?;
+    self.actions.push(action);
+    Ok(())
+  }
+
+  fn add_tab(&mut self, config: &Config, tab: &TabLayout) -> anyhow::Result<()> {
+    let (shell_path, shell_args, _, _, cwd) =
+      resolve_tab_launch(config, tab.profile.as_deref(), tab.root.cwd.clone());
+    let tab_shell = (shell_path, shell_args);
+    let cwd = cwd.map(|path| path.to_string_lossy().into_owned());
+
+    let (first, first_cwd) = tab.root.first_terminal(cwd.as_deref());
+    self.push(UIAction::AddTab {
+      window_id: self.window_id.to_string(),
+      shell_path: tab_shell.0.clone(),
+      shell_args: tab_shell.1.clone(),
+      profile: tab.profile.clone(),
+      working_directory: resolve_cwd(first_cwd),
+    })?;
+
+    let tab_node = self
+      .tree
+      .window(self.window_id)
+      .and_then(|window| window.tabs.last())
+      .ok_or_else(|| anyhow!("Window '{}' has no tabs", self.window_id))?;
+    let tab_id = tab_node.id.clone();
+    let pane_id = tab_node.pane_tree.terminal_ids()[0].to_string();
+
+    // The first pane's command stays with that pane, so splitting it later
+    // starts the profile's shell rather than the command again.
+    if first.command.is_some() {
+      let (shell_path, shell_args) = pane_shell(first, &tab_shell);
+      self.push(UIAction::SetPaneShell {
+        window_id: self.window_id.to_string(),
+        tab_id: tab_id.clone(),
+        pane_id: pane_id.clone(),
+        shell_path,
+        shell_args,
+      })?;
+    }
+
+    if let Some(title) = &tab.title {
+      self.push(UIAction::RenameTab {
+        window_id: self.window_id.to_string(),
+        tab_id: tab_id.clone(),
+        title: Some(title.clone()),
+      })?;
+    }
+    self.split(
+      &tab_id,
+      &tab.root,
+      pane_id.clone(),
+      Vec::new(),
+      cwd.as_deref(),
+      &tab_shell,
+    )?;
+    self.push(UIAction::FocusPane {
+      window_id: self.window_id.to_string(),
+      tab_id,
+      pane_id,
+    })
+  }
+
+  /// Split the terminal `pane_id`, found at `path`, into the panes of `pane`.
+  fn split(
+    &mut self,
+    tab_id: &str,
+    pane: &PaneLayout,
+    pane_id: String,
+    path: Vec<SplitChild>,
+    cwd: Option<&str>,
+    tab_shell: &(String, Vec<String>),
+  ) -> anyhow::Result<()> {
+    let cwd = pane.cwd.as_deref().or(cwd);
+    let (Some(split), [first, second]) = (pane.split, pane.panes.as_slice()) else {
+      return Ok(());
+    };
+
+    let (terminal, terminal_cwd) = second.first_terminal(cwd);
+    let (shell_path, shell_args) = pane_shell(terminal, tab_shell);
+    self.push(UIAction::SplitPane {
+      window_id: self.window_id.to_string(),
+      tab_id: tab_id.to_string(),
+      pane_id: pane_id.clone(),
+      direction: match split {
+        LayoutSplit::Vertical => SplitDirection::Vertical,
+        LayoutSplit::Horizontal => SplitDirection::Horizontal,
+      },
+      shell_path,
+      shell_args,
+      working_directory: resolve_cwd(terminal_cwd),
+    })?;
+    if let Some(ratio) = pane.ratio {
+      self.push(UIAction::ResizeSplit {
+        window_id: self.window_id.to_string(),
+        tab_id: tab_id.to_string(),
+        split_path: path.clone(),
+        ratio,
+      })?;
+    }
+
+    let mut first_path = path.clone();
+    first_path.push(SplitChild::First);
+    let mut second_path = path;
+    second_path.push(SplitChild::Second);
+    let new_pane_id = self.terminal_id_at(tab_id, &second_path)?;
+    self.split(tab_id, first, pane_id, first_path, cwd, tab_shell)?;
+    self.split(tab_id, second, new_pane_id, second_path, cwd, tab_shell)
+  }
+
+  fn terminal_id_at(&self, tab_id: &str, path: &[SplitChild]) -> anyhow::Result<String> {
+    let (_, tab) = self
+      .tree
+      .window(self.window_id)
+      .and_then(|window| window.tab(tab_id))
+      .ok_or_else(|| anyhow!("Tab '{tab_id}' not found"))?;
+    let mut node = &tab.pane_tree;
+    for step in path {
+      node = match (node, step) {
+        (PaneNode::Split { first, .. }, SplitChild::First) => first,
+        (PaneNode::Split { second, .. }, SplitChild::Second) => second,
+        _ => bail!("Split path navigates into a terminal node"),
+      };
+    }
+    match node {
+      PaneNode::Terminal { id, .. } => Ok(id.clone()),
+      PaneNode::Split { .. } => bail!("Split path target is not a terminal node"),
+    }
+  }
+}
+
+fn pane_shell(pane: &PaneLayout, tab_shell: &(String, Vec<String>)) -> (String, Vec<String>) {
+  match &pane.command {
+    Some(command) => (command.clone(), pane.args.clone()),
+    None => tab_shell.clone(),
+  }
+}
+
+fn resolve_cwd(cwd: Option<&str>) -> Option<String> {
+  get_working_directory_pathbuf(cwd.map(str::to_string))
+    .map(|path| path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+  use ::config::{Config, Layout, LayoutSplit, PaneLayout, TabLayout};
+  use kazeterm_ui_tree::action::UIAction;
+  use kazeterm_ui_tree::node::{PaneNode, SplitDirection, UITree};
+
+  use super::{build_layout_action, resolve_tab_launch};
+
+  fn command(program: &str) -> PaneLayout {
+    PaneLayout {
+      command: Some(program.to_string()),
+      ..PaneLayout::default()
+    }
+  }
+
+  #[test]
+  fn layout_compiles_to_splits_with_ratios_and_commands() {
+    let mut tree = UITree::new();
+    tree
+      .apply(UIAction::AddWindow {
+        width: None,
+        height: None,
+      })
+      .unwrap();
+    let window_id = tree.windows[0].id.clone();
+    let layout = Layout {
+      tabs: vec![TabLayout {
+        title: Some("dev".to_string()),
+        profile: None,
+        root: PaneLayout {
+          split: Some(LayoutSplit::Vertical),
+          ratio: Some(0.7),
+          panes: vec![
+            command("cargo"),
+            PaneLayout {
+              split: Some(LayoutSplit::Horizontal),
+              panes: vec![command("htop"), PaneLayout::default()],
+              ..PaneLayout::default()
+            },
+          ],
+          ..PaneLayout::default()
+        },
+      }],
+    };
+
+    let config = Config::default();
+    let action = build_layout_action(&config, &layout, &tree, &window_id).unwrap();
+    tree.apply(action).unwrap();
+
+    let tab = &tree.windows[0].tabs[0];
+    assert_eq!(tab.custom_title.as_deref(), Some("dev"));
+    let (default_shell, ..) = resolve_tab_launch(&config, None, None);
+    assert_eq!(tab.shell.path, default_shell);
+    let PaneNode::Split {
+      direction,
+      ratio,
+      first,
+      second,
+    } = &tab.pane_tree
+    else {
+      panic!("expected a split");
+    };
+    assert_eq!(*direction, SplitDirection::Vertical);
+    assert_eq!(*ratio, 0.7);
+    assert_eq!(tab.pane_tree.focused_pane_id(), Some(first_id(first)));
+    assert_eq!(shell_path(first), Some("cargo"));
+    let PaneNode::Split { first, second, .. } = second.as_ref() else {
+      panic!("expected a nested split");
+    };
+    assert_eq!(shell_path(first), Some("htop"));
+    // Panes running the tab's shell don't record it.
+    assert_eq!(shell_path(second), None);
+  }
+
+  fn first_id(pane: &PaneNode) -> &str {
+    pane.terminal_ids()[0]
+  }
+
+  fn shell_path(pane: &PaneNode) -> Option<&str> {
+    match pane {
+      PaneNode::Terminal { shell, .. } => shell.as_ref().map(|shell| shell.path.as_str()),
+      PaneNode::Split { .. } => None,
+    }
+  }
+}
@@ -37,6 +37,8 @@ struct KeyDebugEntry {
 
 const KEY_DEBUG_RELEASE_PERSIST_DURATION: Duration = Duration::from_secs(3);
 const KEY_DEBUG_MAX_ROWS: usize = 16;
+/// How long a partially typed key sequence waits for its next keystroke.
+const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
 
 impl KeyDebugModifiers {
   fn is_empty(self) -> bool {
@@ -170,6 +172,239 @@ impl MainWindow {
     .detach();
   }
 
+  fn record_key_debug_key_down(
+    &mut self,
+    e: &KeyDownEvent,
+    keystrokes: &[::config::ParsedKeybinding],
+    cx: &mut Context<Self>,
+  ) {
+    let modifiers = KeyDebugModifiers {
+      control: e.keystroke.modifiers.control,
+      shift: e.keystroke.modifiers.shift,
+      alt: e.keystroke.modifiers.alt,
+      platform: e.keystroke.modifiers.platform,
+    };
+    self.set_key_debug_modifiers(modifiers, cx);
+    self.press_key_debug_key(&e.keystroke.key, modifiers, cx);
+    let action = resolve_key_debug_action(
+      cx.global::<::config::Config>(),
+      keystrokes,
+      self.search_visible,
+    )
+    .or_else(|| pending_sequence_text(&self.pending_keystrokes));
+    self.annotate_latest_key_debug_key(&e.keystroke.key, action, cx);
+  }
+
+  /// Whether a terminal pane of the active tab has keyboard focus, as opposed
+  /// to a search bar, panel or dialog input.
+  fn terminal_has_focus(&self, window: &Window, cx: &App) -> bool {
+    self
+      .active_tab_ix
+      .and_then(|ix| self.items.get(ix))
+      .is_some_and(|item| {
+        item
+          .split_container
+          .all_terminals()
+          .iter()
+          .any(|(_, terminal)| terminal.read(cx).focus_handle.is_focused(window))
+      })
+  }
+
+  /// Hold back keystrokes that start or continue a multi-key main window
+  /// shortcut so the focused terminal never receives them.
+  ///
+  /// Keys typed into text inputs are left alone, so a prefix such as `ctrl-b`
+  /// still reaches the search bar or a dialog.
+  fn capture_key_sequence(
+    &mut self,
+    e: &KeyDownEvent,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    if is_modifier_key(&e.keystroke.key) {
+      return;
+    }
+    if !self.terminal_has_focus(window, cx) {
+      self.pending_keystrokes.clear();
+      return;
+    }
+
+    let mut keystrokes = std::mem::take(&mut self.pending_keystrokes);
+    keystrokes.push(key_event_keystroke(e));
+
+    if cx
+      .global::<::config::Config>()
+      .keybindings
+      .continues_main_window_sequence(&keystrokes)
+    {
+      self.pending_keystrokes = keystrokes;
+      self.record_key_debug_key_down(e, &[], cx);
+      let view = cx.entity();
+      self.pending_keystrokes_timeout = cx.spawn(async move |_this, cx| {
+        Timer::after(KEY_SEQUENCE_TIMEOUT).await;
+        let _ = view.update(cx, |this, cx| {
+          this.pending_keystrokes.clear();
+          cx.notify();
+        });
+      });
+      cx.notify();
+      cx.stop_propagation();
+      return;
+    }
+
+    self.pending_keystrokes_timeout = Task::ready(());
+    if keystrokes.len() == 1 {
+      return;
+    }
+
+    // The keystroke ends a pending sequence: run the shortcut it completes, or
+    // drop it like tmux does for keys unbound after the prefix.
+    self.record_key_debug_key_down(e, &keystrokes, cx);
+    self.dispatch_main_window_shortcut(&keystrokes, window, cx);
+    cx.notify();
+    cx.stop_propagation();
+  }
+
+  /// Run the main window shortcut completed by `keystrokes`, if any.
+  fn dispatch_main_window_shortcut(
+    &mut self,
+    keystrokes: &[::config::ParsedKeybinding],
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) -> bool {
+    let keybindings = &cx.global::<::config::Config>().keybindings;
+
+    let kb_new_tab_profiles = [
+      &keybindings.new_tab_profile_1,
+      &keybindings.new_tab_profile_2,
+      &keybindings.new_tab_profile_3,
+      &keybindings.new_tab_profile_4,
+      &keybindings.new_tab_profile_5,
+      &keybindings.new_tab_profile_6,
+      &keybindings.new_tab_profile_7,
+      &keybindings.new_tab_profile_8,
+      &keybindings.new_tab_profile_9,
+    ];
+    let kb_select_tabs = [
+      &keybindings.select_tab_1,
+      &keybindings.select_tab_2,
+      &keybindings.select_tab_3,
+      &keybindings.select_tab_4,
+      &keybindings.select_tab_5,
+      &keybindings.select_tab_6,
+      &keybindings.select_tab_7,
+      &keybindings.select_tab_8,
+      &keybindings.select_last_tab,
+    ];
+    let tab_switcher_popup = cx.global::<::config::Config>().tab.switcher_popup;
+
+    if keybindings.next_tab.matches_sequence(keystrokes) {
+      if tab_switcher_popup {
+        self.show_tab_switcher(true, window, cx);
+      } else {
+        let current_ix = self.active_tab_ix.unwrap_or(0);
+        let next_ix = (current_ix + 1) % self.items.len();
+        self.set_active_tab(next_ix, window, cx);
+      }
+      true
+    } else if keybindings.previous_tab.matches_sequence(keystrokes) {
+      if tab_switcher_popup {
+        self.show_tab_switcher(false, window, cx);
+      } else {
+        let current_ix = self.active_tab_ix.unwrap_or(0);
+        let prev_ix = if current_ix == 0 {
+          self.items.len() - 1
+        } else {
+          current_ix - 1
+        };
+        self.set_active_tab(prev_ix, window, cx);
+      }
+      true
+    } else if keybindings.toggle_search.matches_sequence(keystrokes)
+      || (matches!(keystrokes, [keystroke] if keystroke.key == "Escape") && self.search_visible)
+    {
+      self.toggle_search(window, cx);
+      true
+    } else if keybindings.toggle_search_panel.matches_sequence(keystrokes) {
+      self.toggle_search_panel(window, cx);
+      true
+    } else if keybindings.split_horizontal.matches_sequence(keystrokes) {
+      self.split_pane_horizontal(window, cx);
+      true
+    } else if keybindings.split_vertical.matches_sequence(keystrokes) {
+      self.split_pane_vertical(window, cx);
+      true
+    } else if keybindings.close_pane.matches_sequence(keystrokes) {
+      self.close_active_pane(window, cx);
+      true
+    } else if keybindings.focus_next_pane.matches_sequence(keystrokes) {
+      self.focus_next_pane(window, cx);
+      true
+    } else if keybindings.focus_previous_pane.matches_sequence(keystrokes) {
+      self.focus_prev_pane(window, cx);
+      true
+    } else if keybindings.focus_pane_up.matches_sequence(keystrokes) {
+      self.focus_pane_up(window, cx);
+      true
+    } else if keybindings.focus_pane_down.matches_sequence(keystrokes) {
+      self.focus_pane_down(window, cx);
+      true
+    } else if keybindings.focus_pane_left.matches_sequence(keystrokes) {
+      self.focus_pane_left(window, cx);
+      true
+    } else if keybindings.focus_pane_right.matches_sequence(keystrokes) {
+      self.focus_pane_right(window, cx);
+      true
+    } else if keybindings.swap_split_panes.matches_sequence(keystrokes) {
+      self.swap_split_panes(window, cx);
+      true
+    } else if keybindings.toggle_hidden_panes.matches_sequence(keystrokes) {
+      self.toggle_hidden_split_panes(window, cx);
+      true
+    } else if keybindings.toggle_fullscreen.matches_sequence(keystrokes) {
+      window.toggle_fullscreen();
+      true
+    } else if keybindings.toggle_tab_bar.matches_sequence(keystrokes) {
+      self.toggle_tab_bar(window, cx);
+      true
+    } else if keybindings.new_tab.matches_sequence(keystrokes) {
+      self.insert_new_tab(window, cx);
+      true
+    } else if let Some((i, _)) = kb_select_tabs
+      .iter()
+      .enumerate()
+      .find(|(_, kb_select_tab)| kb_select_tab.matches_sequence(keystrokes))
+    {
+      self.select_tab_by_shortcut(i + 1, window, cx);
+      true
+    } else if keybindings.quit.matches_sequence(keystrokes) {
+      self.show_close_confirm_dialog(window, cx);
+      true
+    } else if let Some(text) = keybindings.send_text_for(keystrokes) {
+      let text = text.to_string();
+      self.send_text_to_active_terminal(&text, cx);
+      true
+    } else if let Some(preset) = keybindings.search_preset_for(keystrokes) {
+      let preset = preset.to_string();
+      self.run_search_preset(&preset, window, cx);
+      true
+    } else {
+      // Check profile-specific new tab shortcuts.
+      let profiles = cx.global::<::config::Config>().get_local_profile_names();
+      let mut found = false;
+      for (i, kb_profile) in kb_new_tab_profiles.iter().enumerate() {
+        if kb_profile.matches_sequence(keystrokes) {
+          if let Some(profile_name) = profiles.get(i) {
+            self.insert_new_tab_with_profile(Some(profile_name), None, window, cx);
+          }
+          found = true;
+          break;
+        }
+      }
+      found
+    }
+  }
+
   fn prune_expired_key_debug_history(&mut self, cx: &mut Context<Self>) {
     let len_before = self.key_debug_recent_keys.len();
     let now = Instant::now();
@@ -295,20 +530,44 @@ fn format_pressed_shortcut(modifiers: KeyDebugModifiers, key: &str) -> String {
   parts.join("+")
 }
 
-fn push_key_debug_action(
-  actions: &mut Vec<String>,
-  label: impl Into<String>,
-  bindings: &::config::KeybindingList,
-  modifiers: KeyDebugModifiers,
-  key: &str,
-) {
-  if bindings.matches(
+fn key_event_keystroke(e: &KeyDownEvent) -> ::config::ParsedKeybinding {
+  let modifiers = &e.keystroke.modifiers;
+  ::config::ParsedKeybinding::from_event(
+    modifiers.control,
+    modifiers.shift,
+    modifiers.alt,
+    modifiers.platform,
+    &e.keystroke.key,
+  )
+}
+
+fn key_debug_keystroke(modifiers: KeyDebugModifiers, key: &str) -> ::config::ParsedKeybinding {
+  ::config::ParsedKeybinding::from_event(
     modifiers.control,
     modifiers.shift,
     modifiers.alt,
     modifiers.platform,
     key,
-  ) {
+  )
+}
+
+/// Label for a shortcut sequence still waiting on its next keystroke.
+fn pending_sequence_text(keystrokes: &[::config::ParsedKeybinding]) -> Option<String> {
+  (!keystrokes.is_empty()).then(|| {
+    format!(
+      "{}, …",
+      ::config::ParsedKeybinding::display_sequence(keystrokes)
+    )
+  })
+}
+
+fn push_key_debug_action(
+  actions: &mut Vec<String>,
+  label: impl Into<String>,
+  bindings: &::config::KeybindingList,
+  keystrokes: &[::config::ParsedKeybinding],
+) {
+  if bindings.matches_sequence(keystrokes) {
     let label = label.into();
     if !actions.iter().any(|action| action == &label) {
       actions.push(label);
@@ -318,142 +577,152 @@ fn push_key_debug_action(
 
 fn resolve_key_debug_action(
   config: &::config::Config,
-  modifiers: KeyDebugModifiers,
-  key: &str,
+  keystrokes: &[::config::ParsedKeybinding],
   search_visible: bool,
 ) -> Option<String> {
   let keybindings = &config.keybindings;
   let mut actions = Vec::new();
 
-  push_key_debug_action(&mut actions, "No-op", &keybindings.noop, modifiers, key);
-  push_key_debug_action(&mut actions, "Copy", &keybindings.copy, modifiers, key);
-  push_key_debug_action(&mut actions, "Paste", &keybindings.paste, modifiers, key);
-  push_key_debug_action(
-    &mut actions,
-    "Zoom In",
-    &keybindings.zoom_in,
-    modifiers,
-    key,
-  );
-  push_key_debug_action(
-    &mut actions,
-    "Zoom Out",
-    &keybindings.zoom_out,
-    modifiers,
-    key,
-  );
+  push_key_debug_action(&mut actions, "No-op", &keybindings.noop, keystrokes);
+  push_key_debug_action(&mut actions, "Copy", &keybindings.copy, keystrokes);
+  push_key_debug_action(&mut actions, "Paste", &keybindings.paste, keystrokes);
+  push_key_debug_action(&mut actions, "Zoom In", &keybindings.zoom_in, keystrokes);
+  push_key_debug_action(&mut actions, "Zoom Out", &keybindings.zoom_out, keystrokes);
   push_key_debug_action(
     &mut actions,
     "Zoom Reset",
     &keybindings.zoom_reset,
-    modifiers,
-    key,
-  );
-  push_key_debug_action(
-    &mut actions,
-    "Next Tab",
-    &keybindings.next_tab,
-    modifiers,
-    key,
+    keystrokes,
   );
+  push_key_debug_action(&mut actions, "Next Tab", &keybindings.next_tab, keystrokes);
   push_key_debug_action(
     &mut actions,
     "Previous Tab",
     &keybindings.previous_tab,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Toggle Search",
     &keybindings.toggle_search,
-    modifiers,
-    key,
+    keystrokes,
   );
   if search_visible
-    && key.eq_ignore_ascii_case("escape")
+    && matches!(keystrokes, [keystroke] if keystroke.key.eq_ignore_ascii_case("escape"))
     && !actions.iter().any(|action| action == "Toggle Search")
   {
     actions.push("Toggle Search".to_string());
   }
+  push_key_debug_action(
+    &mut actions,
+    "Toggle Search Panel",
+    &keybindings.toggle_search_panel,
+    keystrokes,
+  );
+  push_key_debug_action(
+    &mut actions,
+    "Hint Mode",
+    &keybindings.hint_mode,
+    keystrokes,
+  );
+  push_key_debug_action(
+    &mut actions,
+    "Scroll To Previous Prompt",
+    &keybindings.scroll_to_previous_prompt,
+    keystrokes,
+  );
+  push_key_debug_action(
+    &mut actions,
+    "Scroll To Next Prompt",
+    &keybindings.scroll_to_next_prompt,
+    keystrokes,
+  );
+  push_key_debug_action(
+    &mut actions,
+    "Select Command Output",
+    &keybindings.select_command_output,
+    keystrokes,
+  );
+  push_key_debug_action(
+    &mut actions,
+    "Copy Last Command Output",
+    &keybindings.copy_last_command_output,
+    keystrokes,
+  );
+  push_key_debug_action(
+    &mut actions,
+    "Copy Mode",
+    &keybindings.copy_mode,
+    keystrokes,
+  );
   push_key_debug_action(
     &mut actions,
     "Split Horizontal",
     &keybindings.split_horizontal,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Split Vertical",
     &keybindings.split_vertical,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Close Pane",
     &keybindings.close_pane,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Focus Next Pane",
     &keybindings.focus_next_pane,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Focus Previous Pane",
     &keybindings.focus_previous_pane,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Swap Split Panes",
     &keybindings.swap_split_panes,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Toggle Hidden Panes",
     &keybindings.toggle_hidden_panes,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Toggle Fullscreen",
     &keybindings.toggle_fullscreen,
-    modifiers,
-    key,
+    keystrokes,
   );
   push_key_debug_action(
     &mut actions,
     "Toggle Tab Bar",
     &keybindings.toggle_tab_bar,
-    modifiers,
-    key,
-  );
-  push_key_debug_action(
-    &mut actions,
-    "New Tab",
-    &keybindings.new_tab,
-    modifiers,
-    key,
+    keystrokes,
   );
+  push_key_debug_action(&mut actions, "New Tab", &keybindings.new_tab, keystrokes);
   push_key_debug_action(
     &mut actions,
     "New Window",
     &keybindings.new_window,
-    modifiers,
-    key,
+    keystrokes,
   );
-  push_key_debug_action(&mut actions, "Quit", &keybindings.quit, modifiers, key);
+  push_key_debug_action(&mut actions, "Quit", &keybindings.quit, keystrokes);
+  if keybindings.send_text_for(keystrokes).is_some() {
+    actions.push("Send Text".to_string());
+  }
+  if keybindings.search_preset_for(keystrokes).is_some() {
+    actions.push("Search Preset".to_string());
+  }
 
   for (profile_name, binding) in config.get_local_profile_names().into_iter().zip([
     &keybindings.new_tab_profile_1,
@@ -470,8 +739,7 @@ fn resolve_key_debug_action(
       &mut actions,
       format!("New Tab: {}", profile_name),
       binding,
-      modifiers,
-      key,
+      keystrokes,
     );
   }
 
@@ -493,10 +761,13 @@ fn collect_key_debug_entries(
     .map(|recent| KeyDebugEntry {
       raw_key: recent.raw_key.clone(),
       shortcut: recent.shortcut.clone(),
-      action: recent
-        .action
-        .clone()
-        .or_else(|| resolve_key_debug_action(config, recent.modifiers, &recent.raw_key, false)),
+      action: recent.action.clone().or_else(|| {
+        resolve_key_debug_action(
+          config,
+          &[key_debug_keystroke(recent.modifiers, &recent.raw_key)],
+          false,
+        )
+      }),
     })
     .collect::<Vec<_>>();
 
@@ -511,15 +782,16 @@ fn collect_key_debug_entries(
     });
   }
 
-  entries.extend(pressed_keys.iter().map(|pressed| {
-    KeyDebugEntry {
-      raw_key: pressed.raw_key.clone(),
-      shortcut: format_pressed_shortcut(pressed.modifiers, &pressed.raw_key),
-      action: pressed
-        .action
-        .clone()
-        .or_else(|| resolve_key_debug_action(config, pressed.modifiers, &pressed.raw_key, false)),
-    }
+  entries.extend(pressed_keys.iter().map(|pressed| KeyDebugEntry {
+    raw_key: pressed.raw_key.clone(),
+    shortcut: format_pressed_shortcut(pressed.modifiers, &pressed.raw_key),
+    action: pressed.action.clone().or_else(|| {
+      resolve_key_debug_action(
+        config,
+        &[key_debug_keystroke(pressed.modifiers, &pressed.raw_key)],
+        false,
+      )
+    }),
   }));
 
   entries.truncate(KEY_DEBUG_MAX_ROWS);
@@ -529,6 +801,7 @@ fn collect_key_debug_entries(
 fn render_key_debug_overlay(
   entries: &[KeyDebugEntry],
   modifiers: KeyDebugModifiers,
+  pending_sequence: Option<String>,
   cx: &mut Context<MainWindow>,
 ) -> impl IntoElement {
   let theme = cx.theme().clone();
@@ -558,6 +831,15 @@ fn render_key_debug_overlay(
             .text_color(theme.muted_foreground)
             .child(header),
         )
+        .when_some(pending_sequence, |this, pending_sequence| {
+          this.child(
+            div()
+              .text_size(px(13.0))
+              .font_weight(FontWeight::SEMIBOLD)
+              .text_color(theme.foreground)
+              .child(format!("Waiting: {pending_sequence}")),
+          )
+        })
         .when(entries.is_empty(), |this| {
           this.child(
             div()
@@ -619,6 +901,7 @@ impl Render for MainWindow {
     let local_profiles = config.get_local_profiles_with_shells();
     let container_profiles = config.get_container_profiles_with_shells();
     let ssh_hosts = ::config::Config::get_ssh_hosts();
+    let layouts: Vec<String> = config.layouts.keys().cloned().collect();
     let new_tab_shortcut = config.keybindings.new_tab.display_text();
     let profile_shortcuts: Vec<String> = [
       &config.keybindings.new_tab_profile_1,
@@ -712,198 +995,13 @@ impl Render for MainWindow {
       .size_full()
       .opacity(self.configuration_transition_opacity)
       .key_context("MainWindow")
+      .capture_key_down(cx.listener(move |this, e: &KeyDownEvent, window, cx| {
+        this.capture_key_sequence(e, window, cx);
+      }))
       .on_key_down(cx.listener(move |this, e: &KeyDownEvent, window, cx| {
-        let key_debug_modifiers = KeyDebugModifiers {
-          control: e.keystroke.modifiers.control,
-          shift: e.keystroke.modifiers.shift,
-          alt: e.keystroke.modifiers.alt,
-          platform: e.keystroke.modifiers.platform,
-        };
-        this.set_key_debug_modifiers(key_debug_modifiers, cx);
-        this.press_key_debug_key(&e.keystroke.key, key_debug_modifiers, cx);
-        let key_debug_action = resolve_key_debug_action(
-          cx.global::<::config::Config>(),
-          key_debug_modifiers,
-          &e.keystroke.key,
-          this.search_visible,
-        );
-        this.annotate_latest_key_debug_key(&e.keystroke.key, key_debug_action, cx);
-
-        let keybindings = &cx.global::<config::Config>().keybindings;
-        let mods = &e.keystroke.modifiers;
-        let key = &e.keystroke.key;
-
-        let kb_new_tab_profiles = [
-          &keybindings.new_tab_profile_1,
-          &keybindings.new_tab_profile_2,
-          &keybindings.new_tab_profile_3,
-          &keybindings.new_tab_profile_4,
-          &keybindings.new_tab_profile_5,
-          &keybindings.new_tab_profile_6,
-          &keybindings.new_tab_profile_7,
-          &keybindings.new_tab_profile_8,
-          &keybindings.new_tab_profile_9,
-        ];
-        let kb_select_tabs = [
-          &keybindings.select_tab_1,
-          &keybindings.select_tab_2,
-          &keybindings.select_tab_3,
-          &keybindings.select_tab_4,
-          &keybindings.select_tab_5,
-          &keybindings.select_tab_6,
-          &keybindings.select_tab_7,
-          &keybindings.select_tab_8,
-          &keybindings.select_last_tab,
-        ];
-        let tab_switcher_popup = cx.global::<config::Config>().tab.switcher_popup;
-
-        let matched = if keybindings
-          .next_tab
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          if tab_switcher_popup {
-            this.show_tab_switcher(true, window, cx);
-          } else {
-            let current_ix = this.active_tab_ix.unwrap_or(0);
-            let next_ix = (current_ix + 1) % this.items.len();
-            this.set_active_tab(next_ix, window, cx);
-          }
-          true
-        } else if keybindings
-          .previous_tab
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          if tab_switcher_popup {
-            this.show_tab_switcher(false, window, cx);
-          } else {
-            let current_ix = this.active_tab_ix.unwrap_or(0);
-            let prev_ix = if current_ix == 0 {
-              this.items.len() - 1
-            } else {
-              current_ix - 1
-            };
-            this.set_active_tab(prev_ix, window, cx);
-          }
-          true
-        } else if keybindings
-          .toggle_search
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-          || (e.keystroke.key == "Escape" && this.search_visible)
-        {
-          this.toggle_search(window, cx);
-          true
-        } else if keybindings
-          .split_horizontal
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.split_pane_horizontal(window, cx);
-          true
-        } else if keybindings
-          .split_vertical
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.split_pane_vertical(window, cx);
-          true
-        } else if keybindings
-          .close_pane
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.close_active_pane(window, cx);
-          true
-        } else if keybindings
-          .focus_next_pane
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.focus_next_pane(window, cx);
-          true
-        } else if keybindings
-          .focus_previous_pane
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.focus_prev_pane(window, cx);
-          true
-        } else if keybindings
-          .focus_pane_up
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.focus_pane_up(window, cx);
-          true
-        } else if keybindings
-          .focus_pane_down
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.focus_pane_down(window, cx);
-          true
-        } else if keybindings
-          .focus_pane_left
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.focus_pane_left(window, cx);
-          true
-        } else if keybindings
-          .focus_pane_right
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.focus_pane_right(window, cx);
-          true
-        } else if keybindings
-          .swap_split_panes
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.swap_split_panes(window, cx);
-          true
-        } else if keybindings
-          .toggle_hidden_panes
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.toggle_hidden_split_panes(window, cx);
-          true
-        } else if keybindings
-          .toggle_fullscreen
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          window.toggle_fullscreen();
-          true
-        } else if keybindings
-          .toggle_tab_bar
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.toggle_tab_bar(window, cx);
-          true
-        } else if keybindings
-          .new_tab
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.insert_new_tab(window, cx);
-          true
-        } else if let Some((i, _)) = kb_select_tabs.iter().enumerate().find(|(_, kb_select_tab)| {
-          kb_select_tab.matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        }) {
-          this.select_tab_by_shortcut(i + 1, window, cx);
-          true
-        } else if keybindings
-          .quit
-          .matches(mods.control, mods.shift, mods.alt, mods.platform, key)
-        {
-          this.show_close_confirm_dialog(window, cx);
-          true
-        } else {
-          // Check profile-specific new tab shortcuts.
-          let profiles = cx.global::<config::Config>().get_local_profile_names();
-          let mut found = false;
-          for (i, kb_profile) in kb_new_tab_profiles.iter().enumerate() {
-            if kb_profile.matches(mods.control, mods.shift, mods.alt, mods.platform, key) {
-              if let Some(profile_name) = profiles.get(i) {
-                this.insert_new_tab_with_profile(Some(profile_name), None, window, cx);
-              }
-              found = true;
-              break;
-            }
-          }
-          found
-        };
-
-        if matched {
+        let keystroke = key_event_keystroke(e);
+        this.record_key_debug_key_down(e, std::slice::from_ref(&keystroke), cx);
+        if this.dispatch_main_window_shortcut(std::slice::from_ref(&keystroke), window, cx) {
           cx.stop_propagation();
         }
       }))
@@ -1261,6 +1359,7 @@ impl Render for MainWindow {
                             &local_profiles,
                             &container_profiles,
                             &ssh_hosts,
+                            &layouts,
                             &profile_shortcuts,
                           )
                         }
@@ -1319,7 +1418,12 @@ impl Render for MainWindow {
               }),
           )
           .when(key_debug_mode, |this| {
-            this.child(render_key_debug_overlay(&key_debug_entries, self.key_debug_modifiers, cx))
+            this.child(render_key_debug_overlay(
+              &key_debug_entries,
+              self.key_debug_modifiers,
+              pending_sequence_text(&self.pending_keystrokes),
+              cx,
+            ))
           })
           .when(self.tab_switcher_visible, |this| {
             if let Some(tab_switcher) = &self.tab_switcher {
@@ -1363,12 +1467,33 @@ impl Render for MainWindow {
               this
             }
           })
+          .when(self.search_panel.is_some(), |this| {
+            if let Some(search_panel) = &self.search_panel {
+              this.child(search_panel.clone())
+            } else {
+              this
+            }
+          })
           .when(self.update_confirm_dialog.is_some(), |this| {
             if let Some(update_confirm_dialog) = &self.update_confirm_dialog {
               this.child(update_confirm_dialog.clone())
             } else {
               this
             }
+          })
+          .when(self.sessions_dialog.is_some(), |this| {
+            if let Some(sessions_dialog) = &self.sessions_dialog {
+              this.child(sessions_dialog.clone())
+            } else {
+              this
+            }
+          })
+          .when(self.recovery_dialog.is_some(), |this| {
+            if let Some(recovery_dialog) = &self.recovery_dialog {
+              this.child(recovery_dialog.clone())
+            } else {
+              this
+            }
           });
 
         if vertical_tabs {
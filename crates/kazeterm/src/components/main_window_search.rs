@@ -1,8 +1,10 @@
-use gpui::{Context, Entity, Window};
+use gpui::{AppContext, Context, Entity, Window};
 use kazeterm_ui_tree::action::UIAction;
+use kazeterm_ui_tree::node::OverlayNode;
 
 use super::main_window::MainWindow;
 use crate::components::search_bar::{SearchBar, SearchBarCloseEvent};
+use crate::components::search_panel::{SearchPanel, SearchPanelEvent, SearchTarget};
 
 impl MainWindow {
   pub(crate) fn on_search_bar_event(
@@ -70,6 +72,160 @@ impl MainWindow {
     cx.notify();
   }
 
+  /// Open the search bar on the active terminal and run the named preset.
+  pub(crate) fn run_search_preset(
+    &mut self,
+    name: &str,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    let Some(preset) = cx
+      .global::<::config::Config>()
+      .search
+      .presets
+      .get(name)
+      .cloned()
+    else {
+      tracing::warn!("Unknown search preset '{name}'");
+      return;
+    };
+
+    if !self.search_visible {
+      self.toggle_search(window, cx);
+    }
+    self.search_bar.update(cx, |search_bar, cx| {
+      search_bar.apply_preset(&preset, window, cx);
+    });
+  }
+
+  pub(crate) fn toggle_search_panel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+    if !self.reconciling_ui_tree {
+      let Some(window_id) = self.sync_ui_tree_and_window_id(cx) else {
+        return;
+      };
+      let action = if self.search_panel.is_some() {
+        UIAction::DismissOverlay { window_id }
+      } else {
+        UIAction::ShowOverlay {
+          window_id,
+          overlay: OverlayNode::SearchPanel { all_tabs: false },
+        }
+      };
+      self.dispatch_default_ui_action(action, "toggle search panel", window, cx);
+      return;
+    }
+
+    if self.search_panel.is_some() {
+      self.dismiss_search_panel(cx);
+      self.focus_active_terminal(window, cx);
+      cx.notify();
+    } else {
+      self.show_search_panel(false, window, cx);
+    }
+  }
+
+  pub(crate) fn show_search_panel(
+    &mut self,
+    all_tabs: bool,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    if self.search_panel.is_some() {
+      return;
+    }
+
+    let panel = cx.new(|cx| SearchPanel::new(all_tabs, window, cx));
+    let subscription = cx.subscribe_in(&panel, window, Self::on_search_panel_event);
+    panel.update(cx, |panel, cx| panel.focus(window, cx));
+
+    self.search_panel = Some(panel);
+    self._search_panel_subscription = Some(subscription);
+    cx.notify();
+  }
+
+  /// Drop the search panel, unmarking its matches from the terminals.
+  pub(crate) fn dismiss_search_panel(&mut self, cx: &mut Context<Self>) {
+    if let Some(panel) = self.search_panel.take() {
+      panel.update(cx, |panel, cx| panel.clear_match_lines(cx));
+    }
+    self._search_panel_subscription = None;
+  }
+
+  fn on_search_panel_event(
+    &mut self,
+    panel: &Entity<SearchPanel>,
+    event: &SearchPanelEvent,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    match event {
+      SearchPanelEvent::Search { all_tabs } => {
+        let targets = self.search_targets(*all_tabs);
+        panel.update(cx, |panel, cx| panel.search(targets, cx));
+      }
+      SearchPanelEvent::Jump {
+        tab_ix,
+        pane_id,
+        terminal,
+        line,
+      } => {
+        if let Some(window_id) = self.sync_ui_tree_and_window_id(cx)
+          && let Some(item) = self.items.get(*tab_ix)
+        {
+          let tab_id = item.ui_tree_id.clone();
+          self.dispatch_default_ui_action(
+            UIAction::Batch {
+              actions: vec![
+                UIAction::ActivateTab {
+                  window_id: window_id.clone(),
+                  tab_index: *tab_ix,
+                },
+                UIAction::FocusPane {
+                  window_id,
+                  tab_id,
+                  pane_id: format!("pane-{}", pane_id.0),
+                },
+              ],
+            },
+            "jump to search result",
+            window,
+            cx,
+          );
+        }
+        terminal.update(cx, |term_view, cx| {
+          term_view.terminal.update(cx, |terminal, cx| {
+            terminal.scroll_to_search_result(*line, cx);
+          });
+        });
+      }
+      SearchPanelEvent::Close => {
+        self.toggle_search_panel(window, cx);
+      }
+    }
+  }
+
+  /// Every pane of the active tab, or of every tab when `all_tabs` is set.
+  fn search_targets(&self, all_tabs: bool) -> Vec<SearchTarget> {
+    self
+      .items
+      .iter()
+      .enumerate()
+      .filter(|(tab_ix, _)| all_tabs || Some(*tab_ix) == self.active_tab_ix)
+      .flat_map(|(tab_ix, item)| {
+        item
+          .split_container
+          .all_terminals()
+          .into_iter()
+          .map(move |(pane_id, terminal)| SearchTarget {
+            tab_ix,
+            tab_title: item.display_title().to_string(),
+            pane_id,
+            terminal,
+          })
+      })
+      .collect()
+  }
+
   pub(crate) fn toggle_tab_bar(&mut self, window: &mut Window, cx: &mut Context<Self>) {
     if !self.reconciling_ui_tree {
       let Some(window_id) = self.sync_ui_tree_and_window_id(cx) else {
@@ -11,6 +11,8 @@ pub struct TabItem {
   pub(crate) pinned: bool,
   pub(crate) shell_path: String,
   pub(crate) shell_args: Vec<String>,
+  /// Profile the tab was opened from, if any.
+  pub(crate) profile: Option<String>,
   pub(crate) _shell_name: String,
   pub(crate) split_container: SplitContainer,
   pub(crate) terminal_subscriptions: Vec<gpui::Subscription>,
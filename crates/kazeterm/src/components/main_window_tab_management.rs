@@ -9,6 +9,7 @@ use super::main_window_tab_item::TabItem;
 use super::notifications::NotificationReason;
 use crate::components::search_bar::SearchBarState;
 use crate::components::split_pane::SplitContainer;
+use crate::event_system::{Notification, publish_notification};
 
 fn shell_name_for(program: &str) -> String {
   std::path::Path::new(program)
@@ -18,7 +19,7 @@ fn shell_name_for(program: &str) -> String {
     .to_lowercase()
 }
 
-fn resolve_tab_launch(
+pub(super) fn resolve_tab_launch(
   config: &::config::Config,
   profile_name: Option<&str>,
   working_directory: Option<String>,
@@ -238,6 +239,7 @@ impl MainWindow {
       pinned: false,
       shell_path: shell_program,
       shell_args,
+      profile: profile_name.map(str::to_string),
       _shell_name: shell_name,
       split_container,
       terminal_subscriptions: vec![subscription],
@@ -262,6 +264,18 @@ impl MainWindow {
       .and_then(|item| item.split_container.get_active_terminal())
   }
 
+  /// Write `text` to the active terminal's PTY as if it had been typed.
+  pub(crate) fn send_text_to_active_terminal(&self, text: &str, cx: &mut Context<Self>) {
+    if let Some(terminal) = self.active_terminal() {
+      let bytes = text.as_bytes().to_vec();
+      terminal.update(cx, |view, cx| {
+        view.terminal().update(cx, |term, _cx| {
+          term.pty_tx.send_input(bytes.into());
+        });
+      });
+    }
+  }
+
   pub(crate) fn active_tab_item_mut(&mut self) -> Option<&mut TabItem> {
     self
       .active_tab_ix
@@ -300,6 +314,29 @@ impl MainWindow {
     terminal_entity.update(cx, |term, _cx| term.current_working_directory())
   }
 
+  /// UI tree `(window_id, tab_id, pane_id)` of the pane hosting `terminal_view`.
+  fn terminal_ui_tree_ids(
+    &self,
+    terminal_view: &gpui::Entity<TerminalView>,
+  ) -> Option<(String, String, String)> {
+    let window_id = self.ui_tree.window_id()?;
+    let terminal_entity_id = terminal_view.entity_id();
+    self.items.iter().find_map(|item| {
+      item
+        .split_container
+        .all_terminals()
+        .into_iter()
+        .find(|(_, terminal)| terminal.entity_id() == terminal_entity_id)
+        .map(|(pane_id, _)| {
+          (
+            window_id.to_string(),
+            item.ui_tree_id.clone(),
+            format!("pane-{}", pane_id.0),
+          )
+        })
+    })
+  }
+
   pub(crate) fn subscribe_terminal_view_event(
     this: &mut MainWindow,
     terminal_view: &gpui::Entity<TerminalView>,
@@ -352,12 +389,36 @@ impl MainWindow {
           // Bell also serves as a supplementary notification trigger
           // (catches subtask completions in interactive programs like Copilot CLI).
           this.maybe_send_notification(&terminal_view, NotificationReason::Bell, cx);
+          if let Some((window_id, tab_id, pane_id)) = this.terminal_ui_tree_ids(terminal_view) {
+            publish_notification(Notification::Bell {
+              window_id,
+              tab_id,
+              pane_id,
+            });
+          }
         }
         cx.notify();
       }
       terminal::TerminalEvent::CommandFinished => {
         // Prompt returned: notify when a long-running command finishes.
         this.maybe_send_notification(&terminal_view, NotificationReason::CommandFinished, cx);
+        if let Some((window_id, tab_id, pane_id)) = this.terminal_ui_tree_ids(terminal_view) {
+          publish_notification(Notification::PromptReturned {
+            window_id,
+            tab_id,
+            pane_id,
+          });
+        }
+      }
+      terminal::TerminalEvent::CwdChanged(working_directory) => {
+        if let Some((window_id, tab_id, pane_id)) = this.terminal_ui_tree_ids(terminal_view) {
+          publish_notification(Notification::CwdChanged {
+            window_id,
+            tab_id,
+            pane_id,
+            working_directory: working_directory.clone(),
+          });
+        }
       }
       terminal::TerminalEvent::UpdateTab => {
         // Update tab title only if no custom title is set
@@ -380,7 +441,14 @@ impl MainWindow {
             .title_text
             .clone();
           if item.title != new_title {
-            item.title = new_title;
+            item.title = new_title.clone();
+            if let Some(window_id) = this.ui_tree.window_id() {
+              publish_notification(Notification::TitleChanged {
+                window_id: window_id.to_string(),
+                tab_id: item.ui_tree_id.clone(),
+                title: new_title,
+              });
+            }
             cx.notify();
           }
         }
@@ -660,6 +728,7 @@ mod tests {
         shell: "bash".to_string(),
         args: vec!["--login".to_string(), "-i".to_string()],
         working_directory: None,
+        persist_scrollback: None,
       }],
       ..Config::default()
     };
@@ -680,6 +749,7 @@ mod tests {
         shell: "bash".to_string(),
         args: vec!["--login".to_string()],
         working_directory: None,
+        persist_scrollback: None,
       }],
       ..Config::default()
     };
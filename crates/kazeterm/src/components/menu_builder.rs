@@ -204,6 +204,7 @@ pub(super) fn build_new_tab_menu(
   local_profiles: &[(String, String)],
   container_profiles: &[(String, String)],
   ssh_hosts: &[String],
+  layouts: &[String],
   profile_shortcuts: &[String],
 ) -> PopupMenu {
   // Local profiles
@@ -324,6 +325,41 @@ pub(super) fn build_new_tab_menu(
     }
   }
 
+  // Layouts
+  if !layouts.is_empty() {
+    menu = menu.separator();
+    for name in layouts.iter() {
+      let layout_name = name.clone();
+      let display_name = format!("[layout] {}", name);
+      let view_clone = view.clone();
+      menu = menu.item(
+        PopupMenuItem::element(move |_window, _cx| {
+          h_flex()
+            .gap_2()
+            .items_center()
+            .child(
+              div()
+                .w(px(16.0))
+                .h(px(16.0))
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Icon::new(IconName::Maximize).size_4()),
+            )
+            .child(display_name.clone())
+            .into_any_element()
+        })
+        .on_click(move |_: &ClickEvent, window: &mut Window, cx: &mut App| {
+          view_clone.update(cx, |this, cx| {
+            if let Err(err) = this.open_layout(&layout_name, window, cx) {
+              tracing::error!("{err}");
+            }
+          });
+        }),
+      );
+    }
+  }
+
   // Config & About
   menu = menu.separator();
   menu = menu.item(
@@ -445,6 +481,30 @@ pub(super) fn build_new_tab_menu(
       });
     }),
   );
+  let view_sessions = view.clone();
+  menu = menu.item(
+    PopupMenuItem::element(|_window, _cx| {
+      h_flex()
+        .gap_2()
+        .items_center()
+        .child(
+          div()
+            .w(px(16.0))
+            .h(px(16.0))
+            .flex()
+            .items_center()
+            .justify_center()
+            .child(Icon::new(IconName::Folder).size_4()),
+        )
+        .child("Sessions...")
+        .into_any_element()
+    })
+    .on_click(move |_: &ClickEvent, window: &mut Window, cx: &mut App| {
+      view_sessions.update(cx, |this, cx| {
+        this.show_sessions_dialog(window, cx);
+      });
+    }),
+  );
 
   menu = menu.separator();
   let view_about = view.clone();
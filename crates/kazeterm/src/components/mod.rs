@@ -5,6 +5,7 @@ mod import_alacritty_dialog;
 mod main_window;
 mod main_window_dialog_handlers;
 mod main_window_e2e_tests;
+mod main_window_layouts;
 mod main_window_render;
 mod main_window_search;
 mod main_window_split_pane_actions;
@@ -17,7 +18,12 @@ mod main_window_transitions;
 mod main_window_window_lifecycle_tests;
 mod menu_builder;
 mod notifications;
+mod recovery_dialog;
+mod scrollback_archive;
 mod search_bar;
+mod search_history;
+mod search_panel;
+mod sessions_dialog;
 mod shell_error_dialog;
 mod shell_icon;
 mod split_pane;
@@ -33,4 +39,6 @@ pub(crate) mod workspace_state;
 
 pub(crate) use dragged_tab::DraggedTab;
 pub use main_window::MainWindow;
+pub(crate) use scrollback_archive::ScrollbackArchive;
+pub(crate) use search_history::SearchHistory;
 pub(crate) use split_pane::{PaneFocusDirection, PaneId, SplitDirection, SplitPane};
@@ -0,0 +1,186 @@
+use gpui::*;
+use gpui_component::ActiveTheme;
+use gpui_component::button::{Button, ButtonVariants};
+use themeing::SettingsStore;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RecoveryEvent {
+  Restore,
+  Discard,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct RecoveryContent {
+  window_count: usize,
+}
+
+impl RecoveryContent {
+  fn new(window_count: usize) -> Self {
+    Self { window_count }
+  }
+
+  fn description(&self) -> String {
+    let windows = if self.window_count == 1 {
+      "1 window".to_string()
+    } else {
+      format!("{} windows", self.window_count)
+    };
+    format!(
+      "Kazeterm did not shut down cleanly. Restore {windows} from the last autosave? The tabs in this window will be replaced."
+    )
+  }
+}
+
+pub struct RecoveryDialog {
+  focus_handle: FocusHandle,
+  content: RecoveryContent,
+}
+
+impl EventEmitter<RecoveryEvent> for RecoveryDialog {}
+
+impl RecoveryDialog {
+  pub fn new(window_count: usize, window: &mut Window, cx: &mut Context<Self>) -> Self {
+    let focus_handle = cx.focus_handle();
+    window.focus(&focus_handle);
+    Self {
+      focus_handle,
+      content: RecoveryContent::new(window_count),
+    }
+  }
+
+  fn restore(&mut self, cx: &mut Context<Self>) {
+    cx.emit(RecoveryEvent::Restore);
+  }
+
+  fn discard(&mut self, cx: &mut Context<Self>) {
+    cx.emit(RecoveryEvent::Discard);
+  }
+}
+
+impl Focusable for RecoveryDialog {
+  fn focus_handle(&self, _cx: &App) -> FocusHandle {
+    self.focus_handle.clone()
+  }
+}
+
+impl Render for RecoveryDialog {
+  fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    let theme = cx.theme();
+    let colors = cx.global::<SettingsStore>().theme().colors();
+
+    div()
+      .absolute()
+      .inset_0()
+      .flex()
+      .items_center()
+      .justify_center()
+      .bg(colors.overlay_background)
+      .on_mouse_down(gpui::MouseButton::Left, |_, _, cx| {
+        cx.stop_propagation();
+      })
+      .on_key_down(cx.listener(|this, e: &KeyDownEvent, _window, cx| {
+        if e.keystroke.key == "escape" {
+          this.discard(cx);
+        } else if e.keystroke.key == "enter" {
+          this.restore(cx);
+        }
+      }))
+      .child(
+        div()
+          .track_focus(&self.focus_handle)
+          .bg(theme.popover)
+          .text_color(theme.popover_foreground)
+          .rounded_md()
+          .shadow_lg()
+          .border_1()
+          .border_color(theme.border)
+          .p_4()
+          .w(px(440.0))
+          .child(
+            div()
+              .flex()
+              .flex_col()
+              .gap_3()
+              .w_full()
+              .child(
+                div()
+                  .text_base()
+                  .font_weight(FontWeight::SEMIBOLD)
+                  .child("Restore Workspace"),
+              )
+              .child(
+                div()
+                  .text_sm()
+                  .text_color(theme.muted_foreground)
+                  .child(self.content.description()),
+              )
+              .child(
+                gpui_component::h_flex()
+                  .gap_2()
+                  .justify_end()
+                  .child(
+                    Button::new("discard-recovery")
+                      .ghost()
+                      .label("Discard")
+                      .on_click(cx.listener(|this, _, _window, cx| {
+                        this.discard(cx);
+                      })),
+                  )
+                  .child(
+                    Button::new("restore-recovery")
+                      .primary()
+                      .label("Restore")
+                      .on_click(cx.listener(|this, _, _window, cx| {
+                        this.restore(cx);
+                      })),
+                  ),
+              ),
+          ),
+      )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{RecoveryContent, RecoveryDialog, RecoveryEvent};
+  use gpui::TestAppContext;
+  use std::{cell::RefCell, rc::Rc};
+
+  #[test]
+  fn description_counts_windows() {
+    assert!(
+      RecoveryContent::new(1)
+        .description()
+        .contains("Restore 1 window ")
+    );
+    assert!(
+      RecoveryContent::new(3)
+        .description()
+        .contains("Restore 3 windows ")
+    );
+  }
+
+  #[gpui::test]
+  fn restore_emits_restore_event(cx: &mut TestAppContext) {
+    crate::test_support::init_test_app(cx);
+    let window = cx.add_window(|window, cx| RecoveryDialog::new(2, window, cx));
+    cx.run_until_parked();
+
+    let received: Rc<RefCell<Vec<RecoveryEvent>>> = Default::default();
+    let received_clone = received.clone();
+    cx.update(|cx| {
+      let dialog = window.root(cx).unwrap();
+      cx.subscribe(&dialog, move |_entity, event: &RecoveryEvent, _cx| {
+        received_clone.borrow_mut().push(*event);
+      })
+      .detach();
+    });
+
+    window
+      .update(cx, |dialog, _, cx| dialog.restore(cx))
+      .unwrap();
+    cx.run_until_parked();
+
+    assert_eq!(received.borrow().as_slice(), &[RecoveryEvent::Restore]);
+  }
+}
@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+const FILE_EXTENSION: &str = "ansi.gz";
+
+/// Pane scrollback of one window: a directory holding one gzip-compressed
+/// file of styled text per pane, keyed by tab and pane id.
+#[derive(Default)]
+pub(crate) struct ScrollbackArchive {
+  panes: HashMap<String, String>,
+}
+
+impl ScrollbackArchive {
+  /// Archive of the window saved in `workspace.json`.
+  pub(crate) fn workspace_dir() -> PathBuf {
+    config::Config::get_config_path().join("scrollback")
+  }
+
+  /// File stem for a pane, keeping only characters that are safe in a path.
+  pub(crate) fn key(tab_id: &str, pane_id: &str) -> String {
+    format!("{tab_id}.{pane_id}")
+      .chars()
+      .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+      .collect()
+  }
+
+  /// Replace the archive in `dir` with `snapshots` of `(key, text)`. Each
+  /// file is written atomically, so a crash mid-save keeps the older copy.
+  pub(crate) fn save(dir: &Path, snapshots: Vec<(String, String)>) {
+    if snapshots.is_empty() {
+      if let Err(e) = std::fs::remove_dir_all(dir)
+        && e.kind() != std::io::ErrorKind::NotFound
+      {
+        tracing::error!("Failed to clear saved scrollback: {e}");
+      }
+      return;
+    }
+    if let Err(e) = std::fs::create_dir_all(dir) {
+      tracing::error!("Failed to create scrollback directory: {e}");
+      return;
+    }
+    let mut saved = HashSet::new();
+    for (key, text) in snapshots {
+      let path = dir.join(format!("{key}.{FILE_EXTENSION}"));
+      if let Err(e) = write_compressed(&path, &text) {
+        tracing::error!("Failed to write scrollback {}: {e}", path.display());
+      }
+      saved.insert(path);
+    }
+    // Drop panes that no longer exist.
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+      let path = entry.path();
+      if !saved.contains(&path)
+        && let Err(e) = std::fs::remove_file(&path)
+      {
+        tracing::error!("Failed to remove scrollback {}: {e}", path.display());
+      }
+    }
+  }
+
+  /// Read the archive in `dir`, which may not exist. Panes that decompress
+  /// to more than `max_bytes` are skipped.
+  pub(crate) fn load(dir: &Path, max_bytes: usize) -> Self {
+    let entries = match std::fs::read_dir(dir) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+      Err(e) => {
+        tracing::error!("Failed to read scrollback directory: {e}");
+        return Self::default();
+      }
+    };
+    let mut panes = HashMap::new();
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let Some(key) = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(&format!(".{FILE_EXTENSION}")))
+      else {
+        continue;
+      };
+      match read_compressed(&path, max_bytes) {
+        Ok(text) => {
+          panes.insert(key.to_string(), text);
+        }
+        Err(e) => {
+          tracing::error!("Failed to read scrollback {}: {e}", path.display());
+        }
+      }
+    }
+    Self { panes }
+  }
+
+  /// Read and delete the archive in `dir`, so it is replayed at most once.
+  pub(crate) fn take(dir: &Path, max_bytes: usize) -> Self {
+    let archive = Self::load(dir, max_bytes);
+    if let Err(e) = std::fs::remove_dir_all(dir)
+      && e.kind() != std::io::ErrorKind::NotFound
+    {
+      tracing::error!("Failed to remove saved scrollback: {e}");
+    }
+    archive
+  }
+
+  /// Bytes to feed a restored pane before its shell starts, if any were saved.
+  pub(crate) fn take_replay(&mut self, tab_id: &str, pane_id: &str) -> Option<Vec<u8>> {
+    self
+      .panes
+      .remove(&Self::key(tab_id, pane_id))
+      .map(|text| terminal::scrollback_replay_bytes(&text))
+  }
+
+  pub(crate) fn clear(&mut self) {
+    self.panes.clear();
+  }
+}
+
+fn write_compressed(path: &Path, text: &str) -> std::io::Result<()> {
+  let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+  encoder.write_all(text.as_bytes())?;
+  crate::autosave::write_atomically(path, &encoder.finish()?)
+}
+
+/// Decompress `path`, giving up once it inflates past `max_bytes` so that a
+/// corrupted or hand-edited file cannot exhaust memory.
+fn read_compressed(path: &Path, max_bytes: usize) -> std::io::Result<String> {
+  let mut text = String::new();
+  GzDecoder::new(std::fs::File::open(path)?)
+    .take(max_bytes as u64 + 1)
+    .read_to_string(&mut text)?;
+  if text.len() > max_bytes {
+    return Err(std::io::Error::new(
+      std::io::ErrorKind::InvalidData,
+      format!("decompresses to more than {max_bytes} bytes"),
+    ));
+  }
+  Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::ScrollbackArchive;
+
+  const MAX_BYTES: usize = 1024 * 1024;
+
+  #[test]
+  fn saved_scrollback_round_trips_per_pane() {
+    let dir = std::env::temp_dir().join(format!("kazeterm-scrollback-{}", std::process::id()));
+    let first = ScrollbackArchive::key("tab-1", "pane-0");
+    let second = ScrollbackArchive::key("tab-2", "pane-0");
+    ScrollbackArchive::save(
+      &dir,
+      vec![
+        (first, "\x1b[0;31mred\x1b[0m\nplain".to_string()),
+        (second, "other".to_string()),
+      ],
+    );
+
+    let mut archive = ScrollbackArchive::load(&dir, MAX_BYTES);
+    assert_eq!(
+      archive.take_replay("tab-1", "pane-0"),
+      Some(b"\x1b[0;31mred\x1b[0m\r\nplain\x1b[0m\r\n".to_vec())
+    );
+    assert_eq!(archive.take_replay("tab-1", "pane-0"), None);
+    assert!(archive.take_replay("tab-2", "pane-0").is_some());
+
+    ScrollbackArchive::save(&dir, Vec::new());
+    assert!(!dir.exists());
+  }
+
+  #[test]
+  fn saving_a_window_keeps_other_windows_and_drops_closed_panes() {
+    let root = std::env::temp_dir().join(format!(
+      "kazeterm-scrollback-windows-{}",
+      std::process::id()
+    ));
+    let (first, second) = (root.join("window-1"), root.join("window-2"));
+    let pane = |tab: &str| (ScrollbackArchive::key(tab, "pane-0"), tab.to_string());
+    ScrollbackArchive::save(&first, vec![pane("tab-1"), pane("tab-2")]);
+    ScrollbackArchive::save(&second, vec![pane("tab-1")]);
+
+    ScrollbackArchive::save(&first, vec![pane("tab-2")]);
+
+    let mut archive = ScrollbackArchive::load(&first, MAX_BYTES);
+    assert_eq!(archive.take_replay("tab-1", "pane-0"), None);
+    assert!(archive.take_replay("tab-2", "pane-0").is_some());
+    assert_eq!(std::fs::read_dir(&first).unwrap().count(), 1);
+    let mut archive = ScrollbackArchive::take(&second, MAX_BYTES);
+    assert!(archive.take_replay("tab-1", "pane-0").is_some());
+    assert!(!second.exists());
+    std::fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn panes_larger_than_the_limit_are_not_loaded() {
+    let dir =
+      std::env::temp_dir().join(format!("kazeterm-scrollback-limit-{}", std::process::id()));
+    let key = ScrollbackArchive::key("tab-1", "pane-0");
+    ScrollbackArchive::save(&dir, vec![(key, "x".repeat(64))]);
+
+    assert!(
+      ScrollbackArchive::load(&dir, 63)
+        .take_replay("tab-1", "pane-0")
+        .is_none()
+    );
+    assert!(
+      ScrollbackArchive::load(&dir, 64)
+        .take_replay("tab-1", "pane-0")
+        .is_some()
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn keys_stay_inside_the_scrollback_directory() {
+    assert_eq!(
+      ScrollbackArchive::key("../tab-1", "pane/0"),
+      "..tab-1.pane0"
+    );
+  }
+}
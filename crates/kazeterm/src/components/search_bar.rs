@@ -2,10 +2,13 @@ use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::input::Escape as InputEscape;
-use gpui_component::input::{Input, InputState};
+use gpui_component::input::{Input, InputState, MoveDown, MoveUp};
+use gpui_component::menu::{DropdownMenu, PopupMenuItem};
 use gpui_component::{ActiveTheme, IconName, Sizable};
 use terminal::TerminalView;
 
+use super::search_history::{HistoryCursor, SearchHistory};
+
 const DEFAULT_FONT_SIZE: f32 = 14.0;
 
 #[derive(Clone)]
@@ -55,6 +58,7 @@ pub struct SearchBar {
   drag_offset: Option<Point<Pixels>>,
   position: Point<Pixels>,
   font_size: f32,
+  history_cursor: HistoryCursor,
 }
 
 impl EventEmitter<SearchBarCloseEvent> for SearchBar {}
@@ -71,6 +75,7 @@ impl SearchBar {
         gpui_component::input::InputEvent::PressEnter { secondary } => {
           _ = secondary;
           view.query = state.read(cx).value().clone();
+          view.record_history(cx);
           view.perform_search(cx);
         }
         _ => {}
@@ -88,6 +93,7 @@ impl SearchBar {
       drag_offset: None,
       position: Point::new(px(0.), px(0.)),
       font_size: DEFAULT_FONT_SIZE,
+      history_cursor: HistoryCursor::default(),
     }
   }
 
@@ -130,6 +136,7 @@ impl SearchBar {
     self.match_whole = state.match_whole;
     self.use_regex = state.use_regex;
     self.position = state.position;
+    self.history_cursor.reset();
 
     // Update the input field text to match the restored query
     self.search_input_state.update(cx, |input_state, cx| {
@@ -181,6 +188,64 @@ impl SearchBar {
     cx.notify();
   }
 
+  /// Load a saved search into the bar and run it.
+  pub fn apply_preset(
+    &mut self,
+    preset: &::config::SearchPreset,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    self.query = SharedString::from(preset.pattern.clone());
+    self.match_case = preset.match_case;
+    self.match_whole = preset.match_whole;
+    self.use_regex = preset.regex;
+    self.history_cursor.reset();
+
+    self.search_input_state.update(cx, |input_state, cx| {
+      input_state.set_value(preset.pattern.clone(), window, cx);
+    });
+
+    self.perform_search(cx);
+  }
+
+  fn record_history(&mut self, cx: &mut Context<Self>) {
+    self.history_cursor.reset();
+    let limit = cx.global::<::config::Config>().search.history_size;
+    let query = self.query.to_string();
+    cx.update_default_global::<SearchHistory, _>(|history, _cx| {
+      history.record(&query, limit);
+    });
+  }
+
+  fn recall_older_query(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+    let current = self.search_input_state.read(cx).value().to_string();
+    let Some(history) = cx.try_global::<SearchHistory>() else {
+      return;
+    };
+    if let Some(query) = self
+      .history_cursor
+      .older(history.entries(), &current)
+      .map(str::to_string)
+    {
+      self.set_input_text(query, window, cx);
+    }
+  }
+
+  fn recall_newer_query(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+    let Some(history) = cx.try_global::<SearchHistory>() else {
+      return;
+    };
+    if let Some(query) = self.history_cursor.newer(history.entries()) {
+      self.set_input_text(query, window, cx);
+    }
+  }
+
+  fn set_input_text(&mut self, text: String, window: &mut Window, cx: &mut Context<Self>) {
+    self.search_input_state.update(cx, |input_state, cx| {
+      input_state.set_value(text, window, cx);
+    });
+  }
+
   fn toggle_match_case(&mut self, cx: &mut Context<Self>) {
     self.match_case = !self.match_case;
     self.perform_search(cx);
@@ -391,6 +456,11 @@ impl Render for SearchBar {
     let font_size = self.font_size;
 
     let entity_id = cx.entity_id();
+    let search_bar = cx.entity();
+    let presets = cx
+      .try_global::<::config::Config>()
+      .map(|config| config.search.presets.clone())
+      .unwrap_or_default();
 
     div()
       .id("search-bar-drag")
@@ -443,6 +513,14 @@ impl Render for SearchBar {
       .on_action(cx.listener(|this, _: &InputEscape, _window, cx| {
         this.close(cx);
       }))
+      .capture_action(cx.listener(|this, _: &MoveUp, window, cx| {
+        this.recall_older_query(window, cx);
+        cx.stop_propagation();
+      }))
+      .capture_action(cx.listener(|this, _: &MoveDown, window, cx| {
+        this.recall_newer_query(window, cx);
+        cx.stop_propagation();
+      }))
       .on_mouse_down(gpui::MouseButton::Right, |_, _, cx| {
         cx.stop_propagation();
       })
@@ -525,6 +603,27 @@ impl Render for SearchBar {
                   })),
               ),
           )
+          .when(!presets.is_empty(), |this| {
+            this.child(
+              Button::new("search-presets")
+                .ghost()
+                .xsmall()
+                .icon(IconName::ChevronDown)
+                .dropdown_menu(move |menu, _window, _cx| {
+                  presets.iter().fold(menu, |menu, (name, preset)| {
+                    let search_bar = search_bar.clone();
+                    let preset = preset.clone();
+                    menu.item(
+                      PopupMenuItem::new(name.clone()).on_click(move |_, window, cx| {
+                        search_bar.update(cx, |search_bar, cx| {
+                          search_bar.apply_preset(&preset, window, cx);
+                        });
+                      }),
+                    )
+                  })
+                }),
+            )
+          })
           .child(
             Button::new("close-search")
               .ghost()
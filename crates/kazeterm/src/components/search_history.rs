@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+
+use gpui::Global;
+
+/// Queries submitted from the search bar, oldest first, shared by every window
+/// and stored next to the workspace file.
+#[derive(Default)]
+pub(crate) struct SearchHistory {
+  entries: Vec<String>,
+  /// File the history is written to; `None` keeps it in memory only.
+  path: Option<PathBuf>,
+}
+
+impl Global for SearchHistory {}
+
+impl SearchHistory {
+  pub(crate) fn file_path() -> PathBuf {
+    config::Config::get_config_path().join("search_history.json")
+  }
+
+  /// Load the history file, starting empty when it is missing or unreadable.
+  pub(crate) fn load() -> Self {
+    let path = Self::file_path();
+    let entries = match std::fs::read_to_string(&path) {
+      Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::error!("Failed to parse search history: {e}");
+        Vec::new()
+      }),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+      Err(e) => {
+        tracing::error!("Failed to read search history file: {e}");
+        Vec::new()
+      }
+    };
+    Self {
+      entries,
+      path: Some(path),
+    }
+  }
+
+  pub(crate) fn entries(&self) -> &[String] {
+    &self.entries
+  }
+
+  /// Move `query` to the newest slot, keeping at most `limit` entries, and
+  /// write the history back to disk.
+  pub(crate) fn record(&mut self, query: &str, limit: usize) {
+    if query.is_empty() {
+      return;
+    }
+    self.entries.retain(|entry| entry != query);
+    self.entries.push(query.to_string());
+    let excess = self.entries.len().saturating_sub(limit);
+    self.entries.drain(..excess);
+    self.save();
+  }
+
+  fn save(&self) {
+    let Some(path) = &self.path else {
+      return;
+    };
+    if let Some(parent) = path.parent()
+      && let Err(e) = std::fs::create_dir_all(parent)
+    {
+      tracing::error!("Failed to create search history directory: {e}");
+      return;
+    }
+    match serde_json::to_string_pretty(&self.entries) {
+      Ok(json) => {
+        if let Err(e) = crate::autosave::write_atomically(path, json.as_bytes()) {
+          tracing::error!("Failed to write search history: {e}");
+        }
+      }
+      Err(e) => {
+        tracing::error!("Failed to serialize search history: {e}");
+      }
+    }
+  }
+}
+
+/// Position of a search input while stepping through [`SearchHistory`] with
+/// up/down.
+#[derive(Default)]
+pub(crate) struct HistoryCursor {
+  /// Entry shown in the input; `None` while editing a fresh query.
+  index: Option<usize>,
+  /// What was typed before the first step back, restored past the newest entry.
+  draft: String,
+}
+
+impl HistoryCursor {
+  /// Step to the next older entry, remembering `current` on the first step.
+  pub(crate) fn older<'a>(&mut self, entries: &'a [String], current: &str) -> Option<&'a str> {
+    if entries.is_empty() {
+      return None;
+    }
+    let index = match self.index {
+      None => {
+        self.draft = current.to_string();
+        entries.len() - 1
+      }
+      Some(index) => index.checked_sub(1)?,
+    };
+    self.index = Some(index);
+    entries.get(index).map(String::as_str)
+  }
+
+  /// Step to the next newer entry, or back to the draft after the newest one.
+  pub(crate) fn newer(&mut self, entries: &[String]) -> Option<String> {
+    let index = self.index?;
+    if index + 1 < entries.len() {
+      self.index = Some(index + 1);
+      entries.get(index + 1).cloned()
+    } else {
+      self.index = None;
+      Some(std::mem::take(&mut self.draft))
+    }
+  }
+
+  pub(crate) fn reset(&mut self) {
+    self.index = None;
+    self.draft.clear();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{HistoryCursor, SearchHistory};
+
+  #[test]
+  fn record_deduplicates_and_caps_entries() {
+    let mut history = SearchHistory::default();
+    for query in ["a", "b", "a", "c", "", "d"] {
+      history.record(query, 3);
+    }
+    assert_eq!(history.entries(), ["a", "c", "d"]);
+  }
+
+  #[test]
+  fn cursor_walks_history_and_restores_draft() {
+    let entries = vec!["one".to_string(), "two".to_string()];
+    let mut cursor = HistoryCursor::default();
+
+    assert_eq!(cursor.newer(&entries), None);
+    assert_eq!(cursor.older(&entries, "typed"), Some("two"));
+    assert_eq!(cursor.older(&entries, "two"), Some("one"));
+    assert_eq!(cursor.older(&entries, "one"), None);
+    assert_eq!(cursor.newer(&entries).as_deref(), Some("two"));
+    assert_eq!(cursor.newer(&entries).as_deref(), Some("typed"));
+    assert_eq!(cursor.newer(&entries), None);
+  }
+}
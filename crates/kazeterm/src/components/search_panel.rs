@@ -0,0 +1,509 @@
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::Escape as InputEscape;
+use gpui_component::input::{Input, InputState};
+use gpui_component::{ActiveTheme, IconName, Sizable, h_flex, v_flex};
+use terminal::{SearchResultLine, SearchState, TerminalView};
+
+use super::PaneId;
+
+const PANEL_WIDTH: f32 = 420.0;
+
+/// Lines of context listed above and below each matching line.
+const CONTEXT_LINES: usize = 1;
+
+/// A terminal the search panel runs over, with the tab and pane holding it.
+#[derive(Clone)]
+pub struct SearchTarget {
+  pub tab_ix: usize,
+  pub tab_title: String,
+  pub pane_id: PaneId,
+  pub terminal: Entity<TerminalView>,
+}
+
+struct SearchResultGroup {
+  target: SearchTarget,
+  lines: Vec<SearchResultLine>,
+}
+
+#[derive(Clone)]
+pub enum SearchPanelEvent {
+  /// The query, flags or scope changed; the panel needs fresh targets.
+  Search {
+    all_tabs: bool,
+  },
+  /// A result was clicked.
+  Jump {
+    tab_ix: usize,
+    pane_id: PaneId,
+    terminal: Entity<TerminalView>,
+    /// Absolute line of the clicked result.
+    line: i32,
+  },
+  Close,
+}
+
+/// Lists search matches of every pane in the active tab, or of every tab.
+///
+/// The matches live here; the terminals' own search bar queries and
+/// highlights are never touched.
+pub struct SearchPanel {
+  query: SharedString,
+  all_tabs: bool,
+  match_case: bool,
+  match_whole: bool,
+  use_regex: bool,
+  search_input_state: Entity<InputState>,
+  _subscription: Subscription,
+  results: Vec<SearchResultGroup>,
+  scroll_handle: ScrollHandle,
+}
+
+impl EventEmitter<SearchPanelEvent> for SearchPanel {}
+
+impl SearchPanel {
+  pub fn new(all_tabs: bool, window: &mut Window, cx: &mut Context<Self>) -> Self {
+    let search_input_state =
+      cx.new(|cx| InputState::new(window, cx).placeholder("Search all panes..."));
+
+    let subscription = cx.subscribe_in(
+      &search_input_state,
+      window,
+      |view, state, event, _window, cx| {
+        if let gpui_component::input::InputEvent::PressEnter { .. } = event {
+          view.query = state.read(cx).value();
+          view.request_search(cx);
+        }
+      },
+    );
+
+    Self {
+      query: SharedString::from(""),
+      all_tabs,
+      match_case: false,
+      match_whole: false,
+      use_regex: false,
+      search_input_state,
+      _subscription: subscription,
+      results: Vec::new(),
+      scroll_handle: ScrollHandle::new(),
+    }
+  }
+
+  pub fn all_tabs(&self) -> bool {
+    self.all_tabs
+  }
+
+  pub fn focus(&self, window: &mut Window, cx: &mut Context<Self>) {
+    let focus_handle = self.search_input_state.focus_handle(cx);
+    window.focus(&focus_handle);
+  }
+
+  /// Run the query over `targets` and list the matching lines. An empty
+  /// query or an invalid regex lists nothing.
+  ///
+  /// Each target's matching lines are also marked on its scrollbar and
+  /// minimap, focused or not.
+  pub fn search(&mut self, targets: Vec<SearchTarget>, cx: &mut Context<Self>) {
+    self.clear_match_lines(cx);
+
+    let search = SearchState::new(
+      self.query.to_string(),
+      self.match_case,
+      self.match_whole,
+      self.use_regex,
+    );
+    if let Some(search) = search {
+      for target in targets {
+        let lines = target
+          .terminal
+          .read(cx)
+          .terminal
+          .read(cx)
+          .search_result_lines(&search, CONTEXT_LINES);
+        if !lines.is_empty() {
+          let match_lines = lines.iter().map(|line| line.line.max(0) as usize).collect();
+          target.terminal.update(cx, |terminal_view, cx| {
+            terminal_view.terminal.update(cx, |terminal, _| {
+              terminal.search_panel_match_lines = match_lines;
+            });
+            cx.notify();
+          });
+          self.results.push(SearchResultGroup { target, lines });
+        }
+      }
+    }
+
+    cx.notify();
+  }
+
+  /// Drop the results and unmark their lines from the terminals' scrollbars
+  /// and minimaps. Called before each search and when the panel closes.
+  pub fn clear_match_lines(&mut self, cx: &mut Context<Self>) {
+    for group in self.results.drain(..) {
+      group.target.terminal.update(cx, |terminal_view, cx| {
+        terminal_view.terminal.update(cx, |terminal, _| {
+          terminal.search_panel_match_lines.clear();
+        });
+        cx.notify();
+      });
+    }
+  }
+
+  fn request_search(&mut self, cx: &mut Context<Self>) {
+    cx.emit(SearchPanelEvent::Search {
+      all_tabs: self.all_tabs,
+    });
+  }
+
+  fn toggle_all_tabs(&mut self, cx: &mut Context<Self>) {
+    self.all_tabs = !self.all_tabs;
+    self.request_search(cx);
+  }
+
+  fn toggle_match_case(&mut self, cx: &mut Context<Self>) {
+    self.match_case = !self.match_case;
+    self.request_search(cx);
+  }
+
+  fn toggle_match_whole(&mut self, cx: &mut Context<Self>) {
+    self.match_whole = !self.match_whole;
+    self.request_search(cx);
+  }
+
+  fn toggle_use_regex(&mut self, cx: &mut Context<Self>) {
+    self.use_regex = !self.use_regex;
+    self.request_search(cx);
+  }
+
+  fn jump(&mut self, group_ix: usize, line_ix: usize, cx: &mut Context<Self>) {
+    let Some(group) = self.results.get(group_ix) else {
+      return;
+    };
+    let Some(line) = group.lines.get(line_ix) else {
+      return;
+    };
+    cx.emit(SearchPanelEvent::Jump {
+      tab_ix: group.target.tab_ix,
+      pane_id: group.target.pane_id,
+      terminal: group.target.terminal.clone(),
+      line: line.line,
+    });
+  }
+
+  fn close(&mut self, cx: &mut Context<Self>) {
+    cx.emit(SearchPanelEvent::Close);
+  }
+
+  fn match_count(&self) -> usize {
+    self
+      .results
+      .iter()
+      .flat_map(|group| &group.lines)
+      .map(|line| line.highlights.len())
+      .sum()
+  }
+}
+
+impl Focusable for SearchPanel {
+  fn focus_handle(&self, cx: &App) -> FocusHandle {
+    self.search_input_state.focus_handle(cx)
+  }
+}
+
+impl Render for SearchPanel {
+  fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    let theme = cx.theme();
+    let active_bg = theme.accent;
+    let muted = theme.muted_foreground;
+    let hover_bg = theme.list_hover;
+    let match_style = HighlightStyle {
+      background_color: Some(theme.warning.opacity(0.4)),
+      font_weight: Some(FontWeight::BOLD),
+      ..Default::default()
+    };
+
+    let summary = if self.query.is_empty() {
+      String::new()
+    } else {
+      format!(
+        "{} matches in {} panes",
+        self.match_count(),
+        self.results.len()
+      )
+    };
+
+    let groups = self
+      .results
+      .iter()
+      .enumerate()
+      .map(|(group_ix, group)| {
+        let header = format!(
+          "{} · pane {}",
+          group.target.tab_title, group.target.pane_id.0
+        );
+        v_flex()
+          .id(("search-result-group", group_ix))
+          .gap_0p5()
+          .child(div().text_color(muted).text_xs().child(header))
+          .children(group.lines.iter().enumerate().map(|(line_ix, line)| {
+            let highlights = line
+              .highlights
+              .iter()
+              .map(|range| (range.clone(), match_style))
+              .collect::<Vec<_>>();
+            v_flex()
+              .id(("search-result", line_ix))
+              .px_1()
+              .rounded_sm()
+              .cursor_pointer()
+              .hover(|style| style.bg(hover_bg))
+              .on_click(cx.listener(move |this, _, _window, cx| {
+                this.jump(group_ix, line_ix, cx);
+              }))
+              .children(
+                line
+                  .before
+                  .iter()
+                  .map(|text| div().text_color(muted).child(text.clone())),
+              )
+              .child(StyledText::new(line.text.clone()).with_highlights(highlights))
+              .children(
+                line
+                  .after
+                  .iter()
+                  .map(|text| div().text_color(muted).child(text.clone())),
+              )
+          }))
+      })
+      .collect::<Vec<_>>();
+
+    v_flex()
+      .absolute()
+      .top_0()
+      .right_0()
+      .bottom_0()
+      .w(px(PANEL_WIDTH))
+      .gap_1()
+      .p_2()
+      .bg(theme.popover)
+      .text_color(theme.popover_foreground)
+      .border_l_1()
+      .border_color(theme.border)
+      .shadow_lg()
+      .on_action(cx.listener(|this, _: &InputEscape, _window, cx| {
+        this.close(cx);
+      }))
+      .on_mouse_down(MouseButton::Left, |_, _, cx| {
+        cx.stop_propagation();
+      })
+      .child(
+        h_flex()
+          .gap_1()
+          .items_center()
+          .child(
+            Input::new(&self.search_input_state)
+              .prefix(IconName::Search)
+              .flex_1()
+              .cursor_text(),
+          )
+          .child(
+            Button::new("search-panel-close")
+              .ghost()
+              .xsmall()
+              .label("×")
+              .on_click(cx.listener(|this, _, _window, cx| {
+                this.close(cx);
+              })),
+          ),
+      )
+      .child(
+        h_flex()
+          .gap_0p5()
+          .items_center()
+          .child(
+            Button::new("search-panel-scope")
+              .ghost()
+              .xsmall()
+              .label(if self.all_tabs {
+                "All tabs"
+              } else {
+                "This tab"
+              })
+              .on_click(cx.listener(|this, _, _window, cx| {
+                this.toggle_all_tabs(cx);
+              })),
+          )
+          .child(div().h(px(14.)).w(px(1.)).bg(theme.border))
+          .child(
+            Button::new("search-panel-match-case")
+              .ghost()
+              .xsmall()
+              .label("Aa")
+              .when(self.match_case, |btn| btn.bg(active_bg))
+              .on_click(cx.listener(|this, _, _window, cx| {
+                this.toggle_match_case(cx);
+              })),
+          )
+          .child(
+            Button::new("search-panel-match-whole")
+              .ghost()
+              .xsmall()
+              .label("\"\"")
+              .when(self.match_whole, |btn| btn.bg(active_bg))
+              .on_click(cx.listener(|this, _, _window, cx| {
+                this.toggle_match_whole(cx);
+              })),
+          )
+          .child(
+            Button::new("search-panel-regex")
+              .ghost()
+              .xsmall()
+              .label(".*")
+              .when(self.use_regex, |btn| btn.bg(active_bg))
+              .on_click(cx.listener(|this, _, _window, cx| {
+                this.toggle_use_regex(cx);
+              })),
+          )
+          .child(div().flex_1())
+          .child(div().text_color(muted).text_xs().child(summary)),
+      )
+      .child(
+        v_flex()
+          .id("search-panel-results")
+          .flex_1()
+          .gap_2()
+          .overflow_y_scroll()
+          .track_scroll(&self.scroll_handle)
+          .children(groups),
+      )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{SearchPanel, SearchPanelEvent, SearchTarget};
+  use crate::components::PaneId;
+  use gpui::{AppContext as _, Entity, SharedString, TestAppContext, Window};
+  use std::{cell::RefCell, rc::Rc};
+  use terminal::TerminalView;
+  use terminal::test_support::fake_terminal_with;
+
+  fn terminal_view(bytes: &[u8], window: &mut Window, cx: &mut gpui::App) -> Entity<TerminalView> {
+    let terminal = cx.new(|_| fake_terminal_with(4, 20, bytes));
+    cx.new(|cx| TerminalView::new(terminal, window, 0, cx))
+  }
+
+  #[gpui::test]
+  fn flag_toggles_request_a_new_search(cx: &mut TestAppContext) {
+    crate::test_support::init_test_app(cx);
+    let window = cx.add_window(|window, cx| SearchPanel::new(false, window, cx));
+    cx.run_until_parked();
+
+    let scopes: Rc<RefCell<Vec<bool>>> = Default::default();
+    let scopes_clone = scopes.clone();
+    cx.update(|cx| {
+      let panel = window.root(cx).unwrap();
+      cx.subscribe(&panel, move |_, event: &SearchPanelEvent, _cx| {
+        if let SearchPanelEvent::Search { all_tabs } = event {
+          scopes_clone.borrow_mut().push(*all_tabs);
+        }
+      })
+      .detach();
+    });
+
+    window
+      .update(cx, |panel, _, cx| {
+        panel.toggle_match_case(cx);
+        panel.toggle_all_tabs(cx);
+      })
+      .unwrap();
+    cx.run_until_parked();
+
+    assert_eq!(*scopes.borrow(), vec![false, true]);
+  }
+
+  #[gpui::test]
+  fn search_without_targets_lists_nothing(cx: &mut TestAppContext) {
+    crate::test_support::init_test_app(cx);
+    let window = cx.add_window(|window, cx| SearchPanel::new(true, window, cx));
+    cx.run_until_parked();
+
+    let (count, all_tabs) = window
+      .update(cx, |panel, _, cx| {
+        panel.query = SharedString::from("needle");
+        panel.search(Vec::new(), cx);
+        (panel.match_count(), panel.all_tabs())
+      })
+      .unwrap();
+    assert_eq!(count, 0);
+    assert!(all_tabs);
+  }
+
+  #[gpui::test]
+  fn matches_are_marked_on_unfocused_terminals(cx: &mut TestAppContext) {
+    crate::test_support::init_test_app(cx);
+    let window = cx.add_window(|window, cx| SearchPanel::new(false, window, cx));
+    cx.run_until_parked();
+
+    let (focused, unfocused) = window
+      .update(cx, |_, window, cx| {
+        let focused = terminal_view(b"needle\r\nhay", window, cx);
+        let unfocused = terminal_view(b"hay\r\nhay\r\nneedle", window, cx);
+        window.focus(&focused.read(cx).focus_handle);
+        (focused, unfocused)
+      })
+      .unwrap();
+
+    let target = |pane_id, terminal: &Entity<TerminalView>| SearchTarget {
+      tab_ix: 0,
+      tab_title: "tab".to_string(),
+      pane_id: PaneId(pane_id),
+      terminal: terminal.clone(),
+    };
+    let targets = vec![target(0, &focused), target(1, &unfocused)];
+    window
+      .update(cx, |panel, _, cx| {
+        panel.query = SharedString::from("needle");
+        panel.search(targets, cx);
+      })
+      .unwrap();
+    cx.update(|cx| {
+      assert_eq!(
+        focused.read(cx).terminal.read(cx).search_panel_match_lines,
+        vec![0]
+      );
+      assert_eq!(
+        unfocused
+          .read(cx)
+          .terminal
+          .read(cx)
+          .search_panel_match_lines,
+        vec![2]
+      );
+    });
+
+    window
+      .update(cx, |panel, _, cx| panel.clear_match_lines(cx))
+      .unwrap();
+    cx.update(|cx| {
+      assert!(
+        focused
+          .read(cx)
+          .terminal
+          .read(cx)
+          .search_panel_match_lines
+          .is_empty()
+      );
+      assert!(
+        unfocused
+          .read(cx)
+          .terminal
+          .read(cx)
+          .search_panel_match_lines
+          .is_empty()
+      );
+    });
+  }
+}
@@ -0,0 +1,248 @@
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::{ActiveTheme, Sizable};
+use themeing::SettingsStore;
+
+/// Event emitted by the sessions dialog
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SessionsEvent {
+  /// Save the open windows under the given name
+  Save(String),
+  /// Open the named session in new windows
+  Open(String),
+  /// User closed the dialog
+  Cancel,
+}
+
+pub struct SessionsDialog {
+  input_state: Entity<InputState>,
+  sessions: Vec<String>,
+  error_message: Option<String>,
+  _subscription: Subscription,
+}
+
+impl EventEmitter<SessionsEvent> for SessionsDialog {}
+
+impl SessionsDialog {
+  pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+    let input_state = cx.new(|cx| InputState::new(window, cx).placeholder("Session name"));
+
+    let subscription = cx.subscribe_in(&input_state, window, |view, _state, event, _window, cx| {
+      if let gpui_component::input::InputEvent::PressEnter { .. } = event {
+        view.save(cx);
+      }
+    });
+
+    Self {
+      input_state,
+      sessions: crate::sessions::list(),
+      error_message: None,
+      _subscription: subscription,
+    }
+  }
+
+  pub fn focus(&self, window: &mut Window, cx: &mut Context<Self>) {
+    let focus_handle = self.input_state.focus_handle(cx);
+    window.focus(&focus_handle);
+  }
+
+  /// Show an error from acting on an event, keeping the dialog open.
+  pub fn set_error(&mut self, message: String, cx: &mut Context<Self>) {
+    self.error_message = Some(message);
+    cx.notify();
+  }
+
+  fn save(&mut self, cx: &mut Context<Self>) {
+    let name = self.input_state.read(cx).value().trim().to_string();
+    match crate::sessions::validate_name(&name) {
+      Ok(()) => {
+        self.error_message = None;
+        cx.emit(SessionsEvent::Save(name));
+      }
+      Err(e) => self.set_error(e, cx),
+    }
+  }
+
+  fn open(&mut self, name: String, cx: &mut Context<Self>) {
+    cx.emit(SessionsEvent::Open(name));
+  }
+
+  fn delete(&mut self, name: &str, cx: &mut Context<Self>) {
+    self.error_message = crate::sessions::delete(name).err();
+    self.sessions = crate::sessions::list();
+    cx.notify();
+  }
+
+  fn cancel(&mut self, cx: &mut Context<Self>) {
+    cx.emit(SessionsEvent::Cancel);
+  }
+}
+
+impl Focusable for SessionsDialog {
+  fn focus_handle(&self, cx: &App) -> FocusHandle {
+    self.input_state.focus_handle(cx)
+  }
+}
+
+impl Render for SessionsDialog {
+  fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    let theme = cx.theme();
+    let colors = cx.global::<SettingsStore>().theme().colors();
+
+    let rows = self.sessions.iter().enumerate().map(|(ix, name)| {
+      let open_name = name.clone();
+      let delete_name = name.clone();
+      gpui_component::h_flex()
+        .w_full()
+        .gap_2()
+        .items_center()
+        .justify_between()
+        .child(div().text_sm().child(name.clone()))
+        .child(
+          gpui_component::h_flex()
+            .gap_1()
+            .child(
+              Button::new(("open-session", ix))
+                .ghost()
+                .small()
+                .label("Open")
+                .on_click(cx.listener(move |this, _, _window, cx| {
+                  this.open(open_name.clone(), cx);
+                })),
+            )
+            .child(
+              Button::new(("delete-session", ix))
+                .ghost()
+                .small()
+                .label("Delete")
+                .on_click(cx.listener(move |this, _, _window, cx| {
+                  this.delete(&delete_name, cx);
+                })),
+            ),
+        )
+    });
+
+    div()
+      .absolute()
+      .inset_0()
+      .flex()
+      .items_center()
+      .justify_center()
+      .bg(colors.overlay_background)
+      .on_mouse_down(gpui::MouseButton::Left, |_, _, cx| {
+        cx.stop_propagation();
+      })
+      .child(
+        div()
+          .bg(theme.popover)
+          .text_color(theme.popover_foreground)
+          .rounded_md()
+          .shadow_lg()
+          .border_1()
+          .border_color(theme.border)
+          .p_4()
+          .w(px(420.0))
+          .on_key_down(cx.listener(|this, e: &KeyDownEvent, _window, cx| {
+            if e.keystroke.key == "escape" {
+              this.cancel(cx);
+            }
+          }))
+          .child(
+            div()
+              .flex()
+              .flex_col()
+              .gap_3()
+              .w_full()
+              .child(
+                div()
+                  .text_base()
+                  .font_weight(FontWeight::SEMIBOLD)
+                  .child("Sessions"),
+              )
+              .child(div().text_xs().text_color(theme.muted_foreground).child(
+                "Save the open windows under a name, or open a saved session in new windows",
+              ))
+              .child(
+                gpui_component::h_flex()
+                  .w_full()
+                  .gap_2()
+                  .child(
+                    div()
+                      .flex_1()
+                      .child(Input::new(&self.input_state).w_full().cursor_text()),
+                  )
+                  .child(
+                    Button::new("save-session")
+                      .primary()
+                      .small()
+                      .label("Save")
+                      .on_click(cx.listener(|this, _, _window, cx| {
+                        this.save(cx);
+                      })),
+                  ),
+              )
+              .when(self.error_message.is_some(), |this: Div| {
+                if let Some(ref msg) = self.error_message {
+                  this.child(div().text_xs().text_color(theme.red).child(msg.clone()))
+                } else {
+                  this
+                }
+              })
+              .when(self.sessions.is_empty(), |this: Div| {
+                this.child(
+                  div()
+                    .text_sm()
+                    .text_color(theme.muted_foreground)
+                    .child("No saved sessions"),
+                )
+              })
+              .children(rows)
+              .child(
+                gpui_component::h_flex().justify_end().child(
+                  Button::new("close-sessions")
+                    .ghost()
+                    .small()
+                    .label("Close")
+                    .on_click(cx.listener(|this, _, _window, cx| {
+                      this.cancel(cx);
+                    })),
+                ),
+              ),
+          ),
+      )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{SessionsDialog, SessionsEvent};
+  use gpui::TestAppContext;
+  use std::{cell::RefCell, rc::Rc};
+
+  #[gpui::test]
+  fn invalid_name_shows_error_instead_of_saving(cx: &mut TestAppContext) {
+    crate::test_support::init_test_app(cx);
+    let window = cx.add_window(SessionsDialog::new);
+    cx.run_until_parked();
+
+    let received: Rc<RefCell<Vec<SessionsEvent>>> = Default::default();
+    let received_clone = received.clone();
+    cx.update(|cx| {
+      let dialog = window.root(cx).unwrap();
+      cx.subscribe(&dialog, move |_entity, event: &SessionsEvent, _cx| {
+        received_clone.borrow_mut().push(event.clone());
+      })
+      .detach();
+    });
+
+    window.update(cx, |this, _, cx| this.save(cx)).unwrap();
+    cx.run_until_parked();
+
+    assert!(received.borrow().is_empty());
+    window
+      .update(cx, |this, _, _cx| assert!(this.error_message.is_some()))
+      .unwrap();
+  }
+}
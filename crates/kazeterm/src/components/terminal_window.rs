@@ -51,6 +51,7 @@ fn create_terminal_session(
   args: Vec<String>,
   working_directory: Option<PathBuf>,
   app_config: &config::Config,
+  replay: Option<&[u8]>,
 ) -> Result<(terminal::Terminal, terminal_kernel::SessionEvents), String> {
   // Tests can install a factory to avoid spawning a child process.
   if let Some(slot) = SESSION_FACTORY_OVERRIDE.get()
@@ -69,6 +70,7 @@ fn create_terminal_session(
       working_directory,
       app_config,
       term_program_version,
+      replay,
     ),
     #[cfg(target_os = "linux")]
     TerminalKernel::Vte => terminal_kernel_vte::create_terminal_session(
@@ -77,6 +79,7 @@ fn create_terminal_session(
       working_directory,
       app_config,
       term_program_version,
+      replay,
     ),
     #[allow(unreachable_patterns)]
     other => Err(format!(
@@ -94,7 +97,28 @@ pub fn new_terminal_window_with_shell(
   working_directory: Option<PathBuf>,
   cx: &mut Context<MainWindow>,
 ) -> Result<Entity<TerminalView>, String> {
-  let app_config = cx.global::<config::Config>().clone();
+  new_terminal_window_with_replay(window, index, program, args, working_directory, None, cx)
+}
+
+/// Like [`new_terminal_window_with_shell`], but feeds `replay` into the
+/// terminal before the shell starts, e.g. scrollback saved by a previous run.
+pub fn new_terminal_window_with_replay(
+  window: &mut gpui::Window,
+  index: usize,
+  program: &str,
+  args: Vec<String>,
+  working_directory: Option<PathBuf>,
+  replay: Option<&[u8]>,
+  cx: &mut Context<MainWindow>,
+) -> Result<Entity<TerminalView>, String> {
+  let mut app_config = cx.global::<config::Config>().clone();
+  if let Some(socket) = crate::event_system::socket_path() {
+    app_config
+      .terminal
+      .env
+      .entry(crate::msg::SOCKET_ENV.to_string())
+      .or_insert_with(|| socket.to_string_lossy().into_owned());
+  }
   // Use global working_directory as fallback if no per-profile working directory
   let working_directory = working_directory.or_else(|| {
     app_config
@@ -103,8 +127,13 @@ pub fn new_terminal_window_with_shell(
       .as_ref()
       .map(|wd| PathBuf::from(wd))
   });
-  let (terminal, events_rx) =
-    create_terminal_session(program.to_string(), args, working_directory, &app_config)?;
+  let (terminal, events_rx) = create_terminal_session(
+    program.to_string(),
+    args,
+    working_directory,
+    &app_config,
+    replay,
+  )?;
   let mut events_rx = events_rx;
   let terminal = cx.new(|_| terminal);
   let weak_terminal = terminal.downgrade();
@@ -2,13 +2,15 @@ use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 
 use gpui::{Context, Entity, Pixels, Task, Window, px};
-use kazeterm_ui_tree::node::{PaneNode, TabNode, UITree};
+use kazeterm_ui_tree::node::{PaneNode, ShellConfig, TabNode, UITree};
 use serde::Deserialize;
 use terminal::TerminalView;
 
 use super::main_window::MainWindow;
 use super::main_window_tab_item::TabItem;
 use super::main_window_tab_management::get_working_directory_pathbuf;
+use crate::autosave::ScrollbackCache;
+use crate::components::scrollback_archive::ScrollbackArchive;
 use crate::components::search_bar::SearchBarState;
 use crate::components::split_pane::{PaneId, SplitContainer, SplitDirection, SplitPane};
 use crate::reconciler::UITreeStore;
@@ -32,7 +34,7 @@ impl UITreeStore {
     }
     match self.to_json() {
       Ok(json) => {
-        if let Err(e) = std::fs::write(&path, json) {
+        if let Err(e) = crate::autosave::write_atomically(&path, json.as_bytes()) {
           tracing::error!("Failed to write workspace state: {e}");
         } else {
           tracing::info!("Saved workspace state to {}", path.display());
@@ -129,6 +131,26 @@ impl MainWindow {
     self.reconciling_ui_tree = was_reconciling;
   }
 
+  /// Save this window and every other open window as session `name`.
+  pub(crate) fn save_session(&mut self, name: &str, cx: &mut Context<Self>) -> Result<(), String> {
+    crate::sessions::validate_name(name)?;
+    self.sync_ui_tree(cx);
+    let mut windows = vec![self.ui_tree.tree().clone()];
+    windows.extend(crate::window_manager::window_ui_trees(cx.entity_id(), cx));
+    crate::sessions::save(name, windows)?;
+    tracing::info!("Saved session '{name}'");
+    Ok(())
+  }
+
+  /// Open session `name` in new windows and return how many were opened.
+  pub(crate) fn open_session(
+    &mut self,
+    name: &str,
+    cx: &mut Context<Self>,
+  ) -> Result<usize, String> {
+    crate::sessions::open(name, self.event_source_config.clone(), cx)
+  }
+
   pub(crate) fn load_ui_tree_from_path(
     &mut self,
     path: &std::path::Path,
@@ -162,7 +184,12 @@ impl MainWindow {
     Ok(())
   }
 
-  fn replace_with_ui_tree(&mut self, tree: &UITree, window: &mut Window, cx: &mut Context<Self>) {
+  pub(crate) fn replace_with_ui_tree(
+    &mut self,
+    tree: &UITree,
+    window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
     let was_reconciling = self.reconciling_ui_tree;
     self.reconciling_ui_tree = true;
 
@@ -189,15 +216,20 @@ impl MainWindow {
     window: &mut Window,
     cx: &mut Context<Self>,
   ) {
-    let (split_container, terminal_subscriptions) =
-      match Self::build_split_container_from_tab_node(tab, &self.tab_index, window, cx) {
-        Ok(result) => result,
-        Err(err) => {
-          tracing::error!("Failed to restore tab: {err}");
-          self.show_shell_error_dialog(err, window, cx);
-          return;
-        }
-      };
+    let (split_container, terminal_subscriptions) = match Self::build_split_container_from_tab_node(
+      tab,
+      &self.tab_index,
+      &mut self.pending_scrollback,
+      window,
+      cx,
+    ) {
+      Ok(result) => result,
+      Err(err) => {
+        tracing::error!("Failed to restore tab: {err}");
+        self.show_shell_error_dialog(err, window, cx);
+        return;
+      }
+    };
 
     let index = self.tab_index.fetch_add(1, Ordering::SeqCst);
     let shell_name = Self::shell_name_for_tab(tab);
@@ -214,6 +246,7 @@ impl MainWindow {
       pinned: tab.pinned,
       shell_path: tab.shell.path.clone(),
       shell_args: tab.shell.args.clone(),
+      profile: tab.shell.profile.clone(),
       _shell_name: shell_name,
       split_container,
       terminal_subscriptions,
@@ -262,6 +295,7 @@ impl MainWindow {
       item.pinned = tab.pinned;
       item.shell_path = tab.shell.path.clone();
       item.shell_args = tab.shell.args.clone();
+      item.profile = tab.shell.profile.clone();
       item._shell_name = shell_name;
       item.split_container = split_container;
       item.terminal_subscriptions = terminal_subscriptions;
@@ -300,16 +334,19 @@ impl MainWindow {
   fn build_split_container_from_tab_node(
     tab: &TabNode,
     tab_index_counter: &std::sync::atomic::AtomicUsize,
+    scrollback: &mut ScrollbackArchive,
     window: &mut Window,
     cx: &mut Context<MainWindow>,
   ) -> Result<(SplitContainer, Vec<gpui::Subscription>), String> {
     let mut next_pane_id: usize = 0;
     let (root_pane, subscriptions) = Self::build_split_pane_from_node(
+      &tab.id,
       &tab.pane_tree,
       &tab.shell.path,
       &tab.shell.args,
       &mut next_pane_id,
       tab_index_counter,
+      scrollback,
       window,
       cx,
     )?;
@@ -359,6 +396,7 @@ impl MainWindow {
       PaneNode::Terminal {
         id,
         working_directory,
+        shell,
         ..
       } => {
         let pane_id = Self::parse_pane_id(id).unwrap_or(PaneId(*next_pane_id));
@@ -373,13 +411,9 @@ impl MainWindow {
 
         let index = tab_index_counter.fetch_add(1, Ordering::SeqCst);
         let wd = get_working_directory_pathbuf(working_directory.clone());
+        let (shell_path, shell_args) = pane_shell(shell.as_ref(), tab_shell, tab_shell_args);
         let terminal = crate::components::terminal_window::new_terminal_window_with_shell(
-          window,
-          index,
-          tab_shell,
-          tab_shell_args.to_vec(),
-          wd,
-          cx,
+          window, index, shell_path, shell_args, wd, cx,
         )?;
         let sub = cx.subscribe_in(&terminal, window, Self::subscribe_terminal_view_event);
         Ok((SplitPane::new_terminal(pane_id, terminal), vec![sub]))
@@ -427,11 +461,13 @@ impl MainWindow {
   }
 
   fn build_split_pane_from_node(
+    tab_id: &str,
     pane: &PaneNode,
     tab_shell: &str,
     tab_shell_args: &[String],
     next_pane_id: &mut usize,
     tab_index_counter: &std::sync::atomic::AtomicUsize,
+    scrollback: &mut ScrollbackArchive,
     window: &mut Window,
     cx: &mut Context<MainWindow>,
   ) -> Result<(SplitPane, Vec<gpui::Subscription>), String> {
@@ -439,16 +475,20 @@ impl MainWindow {
       PaneNode::Terminal {
         id,
         working_directory,
+        shell,
         ..
       } => {
         let index = tab_index_counter.fetch_add(1, Ordering::SeqCst);
         let wd = get_working_directory_pathbuf(working_directory.clone());
-        let terminal = crate::components::terminal_window::new_terminal_window_with_shell(
+        let replay = scrollback.take_replay(tab_id, id);
+        let (shell_path, shell_args) = pane_shell(shell.as_ref(), tab_shell, tab_shell_args);
+        let terminal = crate::components::terminal_window::new_terminal_window_with_replay(
           window,
           index,
-          tab_shell,
-          tab_shell_args.to_vec(),
+          shell_path,
+          shell_args,
           wd,
+          replay.as_deref(),
           cx,
         )?;
         let sub = cx.subscribe_in(&terminal, window, Self::subscribe_terminal_view_event);
@@ -463,20 +503,24 @@ impl MainWindow {
         ratio,
       } => {
         let (first_pane, mut subs) = Self::build_split_pane_from_node(
+          tab_id,
           first,
           tab_shell,
           tab_shell_args,
           next_pane_id,
           tab_index_counter,
+          scrollback,
           window,
           cx,
         )?;
         let (second_pane, subs2) = Self::build_split_pane_from_node(
+          tab_id,
           second,
           tab_shell,
           tab_shell_args,
           next_pane_id,
           tab_index_counter,
+          scrollback,
           window,
           cx,
         )?;
@@ -515,6 +559,82 @@ impl MainWindow {
   }
 }
 
+// ── Scrollback persistence ──
+
+impl MainWindow {
+  /// Save the scrollback of every pane whose tab profile allows it as this
+  /// window's archive in `dir`, next to wherever its UI tree is saved.
+  pub(crate) fn save_scrollback(&self, dir: &std::path::Path, cx: &gpui::App) {
+    ScrollbackArchive::save(dir, self.scrollback_snapshots(cx));
+  }
+
+  /// `(key, text)` of every pane whose scrollback is persisted.
+  pub(crate) fn scrollback_snapshots(&self, cx: &gpui::App) -> Vec<(String, String)> {
+    let (max_lines, max_bytes) = persist_scrollback_limits(cx);
+    self
+      .persisted_terminals(cx)
+      .into_iter()
+      .filter_map(|(key, terminal)| {
+        let text = terminal
+          .read(cx)
+          .terminal
+          .read(cx)
+          .scrollback_snapshot(max_lines, max_bytes)?;
+        Some((key, text))
+      })
+      .collect()
+  }
+
+  /// Bring `cache` up to date with the persisted panes, rendering only those
+  /// that had output since the last refresh. Returns whether any changed.
+  pub(crate) fn refresh_scrollback_cache(
+    &self,
+    cache: &mut ScrollbackCache,
+    cx: &gpui::App,
+  ) -> bool {
+    let (max_lines, max_bytes) = persist_scrollback_limits(cx);
+    let panes = self
+      .persisted_terminals(cx)
+      .into_iter()
+      .map(|(key, terminal)| {
+        let generation = terminal.read(cx).terminal.read(cx).output_generation;
+        (key, (terminal.entity_id().as_u64(), generation), terminal)
+      })
+      .collect();
+    cache.refresh(panes, |terminal| {
+      terminal
+        .read(cx)
+        .terminal
+        .read(cx)
+        .scrollback_snapshot(max_lines, max_bytes)
+    })
+  }
+
+  /// `(key, terminal)` of every pane whose tab profile persists scrollback.
+  fn persisted_terminals(&self, cx: &gpui::App) -> Vec<(String, Entity<TerminalView>)> {
+    let config = cx.global::<::config::Config>();
+    let mut terminals = Vec::new();
+    for item in &self.items {
+      if !config.persist_scrollback_for(item.profile.as_deref()) {
+        continue;
+      }
+      for (pane_id, terminal) in item.split_container.all_terminals() {
+        let key = ScrollbackArchive::key(&item.ui_tree_id, &format!("pane-{}", pane_id.0));
+        terminals.push((key, terminal));
+      }
+    }
+    terminals
+  }
+}
+
+fn persist_scrollback_limits(cx: &gpui::App) -> (usize, usize) {
+  let config = cx.global::<::config::Config>();
+  (
+    config.terminal.get_persist_scrollback_lines(),
+    config.terminal.get_persist_scrollback_max_bytes(),
+  )
+}
+
 // ── Legacy migration ──
 
 /// Legacy workspace state (v1). Used only for migration from old format.
@@ -553,6 +673,19 @@ enum LegacySplitDirectionState {
   Vertical,
 }
 
+/// The program a restored pane runs: the shell recorded on the pane, or the
+/// tab's shell when the pane has none.
+fn pane_shell<'a>(
+  shell: Option<&'a ShellConfig>,
+  tab_shell: &'a str,
+  tab_shell_args: &[String],
+) -> (&'a str, Vec<String>) {
+  match shell {
+    Some(shell) => (&shell.path, shell.args.clone()),
+    None => (tab_shell, tab_shell_args.to_vec()),
+  }
+}
+
 /// Attempt to load and migrate a legacy `workspace.json` to UITree format.
 fn migrate_legacy_workspace() -> Option<UITree> {
   let path = config::Config::get_config_path().join("workspace.json");
@@ -625,6 +758,7 @@ fn convert_legacy_pane_tree(pane: &LegacyPaneTreeState, tree: &mut UITree) -> Pa
       working_directory: working_directory.clone(),
       title: String::new(),
       focused: false,
+      shell: None,
     },
     LegacyPaneTreeState::Split {
       direction,
@@ -1,12 +1,14 @@
 //! Kazeterm-specific event handlers built on top of the shared event-system crate.
 
-use gpui::{AnyWindowHandle, App, Context, WeakEntity, Window};
-use kazeterm_event_system::EventBus;
+use gpui::{AnyWindowHandle, App, Context, Entity, WeakEntity, Window};
+use kazeterm_event_system::{EventBus, EventResult};
 use kazeterm_ui_tree::action::UIAction;
 use kazeterm_ui_tree::node::{OverlayNode, SplitDirection as TreeSplitDirection};
+use terminal::{TerminalView, TextRegion};
 
 pub use kazeterm_event_system::{
-  AppEvent, EventSourceConfig, JsonEvent, send_event, try_send_event,
+  AppEvent, EventSourceConfig, JsonEvent, Notification, TextRange, publish_notification,
+  send_event, socket_path, try_send_event,
 };
 
 use crate::components::{MainWindow, PaneFocusDirection};
@@ -16,24 +18,28 @@ pub fn build_default_event_bus(source_config: EventSourceConfig) -> EventBus<Mai
   let mut bus: EventBus<MainWindow> = EventBus::new();
   let source_config_for_new_window = source_config;
 
-  bus.subscribe("NewTerminalWithDefaultProfile", |mw, _event, window, cx| {
-    dispatch_add_tab_event(mw, None, None, window, cx);
+  bus.subscribe_with_response("NewTerminalWithDefaultProfile", |mw, event, window, cx| {
+    let AppEvent::NewTerminalWithDefaultProfile { working_directory } = event else {
+      return Ok(serde_json::Value::Null);
+    };
+    dispatch_add_tab_event(mw, None, working_directory, window, cx)
   });
 
-  bus.subscribe("NewTerminalWithProfile", |mw, event, window, cx| {
-    if let AppEvent::NewTerminalWithProfile {
+  bus.subscribe_with_response("NewTerminalWithProfile", |mw, event, window, cx| {
+    let AppEvent::NewTerminalWithProfile {
       profile_name,
       working_directory,
     } = event
-    {
-      dispatch_add_tab_event(
-        mw,
-        Some(profile_name.as_str()),
-        working_directory,
-        window,
-        cx,
-      );
-    }
+    else {
+      return Ok(serde_json::Value::Null);
+    };
+    dispatch_add_tab_event(
+      mw,
+      Some(profile_name.as_str()),
+      working_directory,
+      window,
+      cx,
+    )
   });
 
   bus.subscribe("CloseActiveTab", |mw, _event, window, cx| {
@@ -68,12 +74,12 @@ pub fn build_default_event_bus(source_config: EventSourceConfig) -> EventBus<Mai
     }
   });
 
-  bus.subscribe("SplitHorizontal", |mw, _event, window, cx| {
-    dispatch_split_pane_event(mw, TreeSplitDirection::Horizontal, window, cx);
+  bus.subscribe_with_response("SplitHorizontal", |mw, _event, window, cx| {
+    dispatch_split_pane_event(mw, TreeSplitDirection::Horizontal, window, cx)
   });
 
-  bus.subscribe("SplitVertical", |mw, _event, window, cx| {
-    dispatch_split_pane_event(mw, TreeSplitDirection::Vertical, window, cx);
+  bus.subscribe_with_response("SplitVertical", |mw, _event, window, cx| {
+    dispatch_split_pane_event(mw, TreeSplitDirection::Vertical, window, cx)
   });
 
   bus.subscribe("CloseActivePane", |mw, _event, window, cx| {
@@ -159,18 +165,68 @@ pub fn build_default_event_bus(source_config: EventSourceConfig) -> EventBus<Mai
     cx.quit();
   });
 
-  bus.subscribe("SendTextToTerminal", |mw, event, _window, cx| {
-    if let AppEvent::SendTextToTerminal { text } = event
-      && let Some(active_ix) = mw.active_tab_ix
-      && let Some(item) = mw.items.get(active_ix)
-      && let Some(terminal) = item.split_container.get_active_terminal()
+  bus.subscribe_with_response("SendTextToTerminal", |mw, event, _window, cx| {
+    if let AppEvent::SendTextToTerminal { text } = event {
+      if mw.active_terminal().is_none() {
+        return Err("No active terminal to send text to".to_string());
+      }
+      mw.send_text_to_active_terminal(&text, cx);
+    }
+    Ok(serde_json::Value::Null)
+  });
+
+  bus.subscribe_with_response("GetText", |mw, event, _window, cx| {
+    let AppEvent::GetText {
+      window_id,
+      tab_id,
+      pane_id,
+      range,
+      ansi,
+    } = event
+    else {
+      return Ok(serde_json::Value::Null);
+    };
+    if let Some(window_id) = window_id
+      && ui_tree_window_id(mw, cx)? != window_id
     {
-      terminal.update(cx, |view, cx| {
-        view.terminal().update(cx, |term, _cx| {
-          term.input(text.into_bytes());
-        });
-      });
+      return Err(format!("Window '{window_id}' not found"));
     }
+    let (tab_id, pane_id, view) = find_pane_terminal(mw, tab_id.as_deref(), pane_id.as_deref())?;
+    let region = match range {
+      TextRange::Screen => TextRegion::Screen,
+      TextRange::Scrollback => TextRegion::Scrollback,
+      TextRange::All => TextRegion::All,
+    };
+    let text = view.read(cx).terminal().read(cx).region_text(region, ansi);
+    Ok(serde_json::json!({ "text": text, "tab_id": tab_id, "pane_id": pane_id }))
+  });
+
+  bus.subscribe_with_response("SaveSession", |mw, event, _window, cx| {
+    let AppEvent::SaveSession { name } = event else {
+      return Ok(serde_json::Value::Null);
+    };
+    mw.save_session(&name, cx)?;
+    Ok(serde_json::Value::Null)
+  });
+
+  bus.subscribe_with_response("OpenSession", |mw, event, _window, cx| {
+    let AppEvent::OpenSession { name } = event else {
+      return Ok(serde_json::Value::Null);
+    };
+    let windows = mw.open_session(&name, cx)?;
+    Ok(serde_json::json!({ "windows": windows }))
+  });
+
+  bus.subscribe_with_response("DeleteSession", |_mw, event, _window, _cx| {
+    let AppEvent::DeleteSession { name } = event else {
+      return Ok(serde_json::Value::Null);
+    };
+    crate::sessions::delete(&name)?;
+    Ok(serde_json::Value::Null)
+  });
+
+  bus.subscribe_with_response("ListSessions", |_mw, _event, _window, _cx| {
+    Ok(serde_json::json!({ "sessions": crate::sessions::list() }))
   });
 
   bus.subscribe("Custom", |_mw, event, _window, _cx| {
@@ -179,30 +235,20 @@ pub fn build_default_event_bus(source_config: EventSourceConfig) -> EventBus<Mai
     }
   });
 
-  bus.subscribe("DispatchUIAction", |mw, event, window, cx| {
-    if let AppEvent::DispatchUIAction { action_json } = event {
-      match serde_json::from_str::<kazeterm_ui_tree::action::UIAction>(&action_json) {
-        Ok(action) => {
-          if let Err(e) = mw.dispatch_ui_action(action, window, cx) {
-            tracing::error!("Failed to dispatch UIAction: {e}");
-          }
-        }
-        Err(e) => {
-          tracing::error!("Failed to parse UIAction JSON: {e}");
-        }
-      }
-    }
+  bus.subscribe_with_response("DispatchUIAction", |mw, event, window, cx| {
+    let AppEvent::DispatchUIAction { action_json } = event else {
+      return Ok(serde_json::Value::Null);
+    };
+    let action = serde_json::from_str::<UIAction>(&action_json)
+      .map_err(|e| format!("Failed to parse UIAction JSON: {e}"))?;
+    mw.dispatch_ui_action(action, window, cx)
+      .map_err(|e| format!("Failed to dispatch UIAction: {e}"))?;
+    Ok(serde_json::Value::Null)
   });
 
-  bus.subscribe("SnapshotUITree", |mw, _event, _window, cx| {
-    match mw.snapshot_ui_tree(cx) {
-      Ok(json) => {
-        tracing::info!("UI tree snapshot:\n{}", json);
-      }
-      Err(e) => {
-        tracing::error!("Failed to snapshot UI tree: {e}");
-      }
-    }
+  bus.subscribe_with_response("SnapshotUITree", |mw, _event, _window, cx| {
+    mw.snapshot_ui_tree_value(cx)
+      .map_err(|e| format!("Failed to snapshot UI tree: {e}"))
   });
 
   bus
@@ -218,6 +264,76 @@ fn dispatch_event_ui_action(
   mw.dispatch_default_ui_action(action, action_name, window, cx);
 }
 
+/// Dispatch a `UIAction` on behalf of an external client, reporting failures back to it.
+fn dispatch_requested_ui_action(
+  mw: &mut MainWindow,
+  action: UIAction,
+  action_name: &str,
+  window: &mut Window,
+  cx: &mut Context<MainWindow>,
+) -> Result<(), String> {
+  mw.dispatch_ui_action(action, window, cx)
+    .map_err(|err| format!("Failed to {action_name}: {err}"))
+}
+
+fn ui_tree_window_id(mw: &mut MainWindow, cx: &mut Context<MainWindow>) -> Result<String, String> {
+  mw.sync_ui_tree_and_window_id(cx)
+    .ok_or_else(|| "Window has no UI tree yet".to_string())
+}
+
+/// Result for requests that end on a pane: the active tab and pane ids.
+fn active_pane_response(mw: &MainWindow) -> EventResult {
+  let (tab_id, pane_id) =
+    active_ui_tree_tab_and_pane_ids(mw).ok_or_else(|| "No active pane".to_string())?;
+  Ok(serde_json::json!({ "tab_id": tab_id, "pane_id": pane_id }))
+}
+
+/// Find the terminal of a pane by its UI tree ids.
+///
+/// Without `tab_id` the active tab is searched, or every tab when `pane_id`
+/// is given; without `pane_id` the tab's active pane is used.
+fn find_pane_terminal(
+  mw: &MainWindow,
+  tab_id: Option<&str>,
+  pane_id: Option<&str>,
+) -> Result<(String, String, Entity<TerminalView>), String> {
+  let items = match tab_id {
+    Some(tab_id) => vec![
+      mw.items
+        .iter()
+        .find(|item| item.ui_tree_id == tab_id)
+        .ok_or_else(|| format!("Tab '{tab_id}' not found"))?,
+    ],
+    None if pane_id.is_some() => mw.items.iter().collect(),
+    None => vec![
+      mw.active_tab_ix
+        .and_then(|tab_ix| mw.items.get(tab_ix))
+        .ok_or_else(|| "No active tab".to_string())?,
+    ],
+  };
+
+  items
+    .into_iter()
+    .find_map(|item| {
+      let (id, terminal) = match pane_id {
+        Some(pane_id) => item
+          .split_container
+          .all_terminals()
+          .into_iter()
+          .find(|(id, _)| format!("pane-{}", id.0) == pane_id)?,
+        None => (
+          item.split_container.active_pane_id?,
+          item.split_container.get_active_terminal()?,
+        ),
+      };
+      Some((item.ui_tree_id.clone(), format!("pane-{}", id.0), terminal))
+    })
+    .ok_or_else(|| match pane_id {
+      Some(pane_id) => format!("Pane '{pane_id}' not found"),
+      None => "No active pane".to_string(),
+    })
+}
+
 fn active_ui_tree_tab_id(mw: &MainWindow) -> Option<String> {
   mw.active_tab_ix
     .and_then(|tab_ix| mw.items.get(tab_ix))
@@ -239,12 +355,15 @@ fn dispatch_add_tab_event(
   working_directory: Option<String>,
   window: &mut Window,
   cx: &mut Context<MainWindow>,
-) {
-  let Some(window_id) = mw.sync_ui_tree_and_window_id(cx) else {
-    return;
-  };
+) -> EventResult {
+  let window_id = ui_tree_window_id(mw, cx)?;
   let action = MainWindow::build_add_tab_ui_action(window_id, profile_name, working_directory, cx);
-  dispatch_event_ui_action(mw, action, "add tab", window, cx);
+  let tab_count = mw.items.len();
+  dispatch_requested_ui_action(mw, action, "add tab", window, cx)?;
+  if mw.items.len() <= tab_count {
+    return Err("Failed to start the shell for the new tab".to_string());
+  }
+  active_pane_response(mw)
 }
 
 fn dispatch_close_tab_event(
@@ -302,23 +421,20 @@ fn dispatch_split_pane_event(
   direction: TreeSplitDirection,
   window: &mut Window,
   cx: &mut Context<MainWindow>,
-) {
+) -> EventResult {
   if mw.active_tab_has_hidden_panes() {
     match direction {
       TreeSplitDirection::Horizontal => mw.split_pane_horizontal(window, cx),
       TreeSplitDirection::Vertical => mw.split_pane_vertical(window, cx),
     }
-    return;
+    return active_pane_response(mw);
   }
 
   mw.sync_active_pane_from_focus(window, cx);
 
-  let Some(window_id) = mw.sync_ui_tree_and_window_id(cx) else {
-    return;
-  };
-  let Some((tab_id, pane_id)) = active_ui_tree_tab_and_pane_ids(mw) else {
-    return;
-  };
+  let window_id = ui_tree_window_id(mw, cx)?;
+  let (tab_id, pane_id) =
+    active_ui_tree_tab_and_pane_ids(mw).ok_or_else(|| "No active pane to split".to_string())?;
   let working_directory = mw.active_terminal_working_directory(cx);
   let (shell_path, shell_args) = mw
     .active_tab_ix
@@ -335,7 +451,7 @@ fn dispatch_split_pane_event(
     TreeSplitDirection::Vertical => "split pane vertically",
   };
 
-  dispatch_event_ui_action(
+  dispatch_requested_ui_action(
     mw,
     UIAction::SplitPane {
       window_id,
@@ -349,7 +465,8 @@ fn dispatch_split_pane_event(
     action_name,
     window,
     cx,
-  );
+  )?;
+  active_pane_response(mw)
 }
 
 fn dispatch_close_active_pane_event(
@@ -629,6 +746,11 @@ mod tests {
       "NewWindow",
       "Quit",
       "SendTextToTerminal",
+      "GetText",
+      "SaveSession",
+      "OpenSession",
+      "DeleteSession",
+      "ListSessions",
       "Custom",
       "DispatchUIAction",
       "SnapshotUITree",
@@ -20,7 +20,7 @@ extern crate objc;
 
 use std::path::PathBuf;
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use gpui::{App, Application, KeyBinding, WindowAppearance, actions};
 #[cfg(target_os = "macos")]
 use gpui::{Menu, MenuItem};
@@ -33,12 +33,15 @@ use ::config::Config;
 mod app_icon;
 mod assets;
 mod auto_update;
+mod autosave;
 mod build_info;
 mod components;
 mod config;
 mod config_watcher;
 pub mod event_system;
+mod msg;
 pub mod reconciler;
+mod sessions;
 mod window_manager;
 
 #[cfg(test)]
@@ -62,6 +65,19 @@ struct Args {
   /// Path to the event socket/pipe (required when event-source is "socket")
   #[arg(long)]
   event_socket: Option<PathBuf>,
+
+  /// Open a saved session instead of the usual first window
+  #[arg(long, value_name = "NAME")]
+  session: Option<String>,
+
+  #[command(subcommand)]
+  command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+  /// Control a running Kazeterm through its event socket
+  Msg(msg::MsgArgs),
 }
 
 /// Event source type for command-line parsing
@@ -156,6 +172,9 @@ pub(crate) fn system_is_dark(cx: &App) -> bool {
 fn main() {
   // Parse command-line arguments
   let args = Args::parse();
+  if let Some(Command::Msg(msg_args)) = args.command {
+    std::process::exit(msg::run(msg_args));
+  }
   let event_source_config = args.to_event_source_config();
 
   // Initialize tracing
@@ -177,6 +196,15 @@ fn main() {
   // Initialize theme system with embedded assets and custom path
   init_theme_system(&config);
 
+  let session = match args.session.as_deref().map(sessions::load).transpose() {
+    Ok(session) => session,
+    Err(error) => {
+      tracing::error!("{error}");
+      eprintln!("{error}");
+      std::process::exit(1);
+    }
+  };
+
   let app = Application::new().with_assets(Assets);
 
   // On macOS, clicking the dock icon while no windows are open should open a new window.
@@ -200,6 +228,8 @@ fn main() {
       system_is_dark(cx),
     ));
     cx.set_global(config.clone());
+    cx.set_global(components::SearchHistory::load());
+    autosave::init(cx);
 
     SettingsStore::init_gpui_component_theme(cx);
 
@@ -302,6 +332,11 @@ fn main() {
       MenuItem::action("Quit", Quit),
     ]);
 
-    window_manager::open_kazeterm_window(event_source_config.clone(), cx);
+    match session {
+      Some(session) => {
+        sessions::open_windows(session, event_source_config.clone(), cx);
+      }
+      None => window_manager::open_kazeterm_window(event_source_config.clone(), cx),
+    }
   });
 }
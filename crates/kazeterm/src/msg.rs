@@ -0,0 +1,325 @@
+//! `kazeterm msg`: remote control for a running instance over its event socket.
+//!
+//! Each subcommand sends one [`JsonEvent`] to the socket given by `--socket`
+//! (or `$KAZETERM_SOCKET`), prints the response and exits non-zero on failure.
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand, ValueEnum};
+
+use crate::event_system::{JsonEvent, TextRange};
+
+/// Environment variable used when `--socket` is not given. Shells started by
+/// an instance listening on a socket have it set.
+pub(crate) const SOCKET_ENV: &str = "KAZETERM_SOCKET";
+
+#[derive(Args, Debug)]
+pub(crate) struct MsgArgs {
+  /// Event socket of the running instance (defaults to $KAZETERM_SOCKET)
+  #[arg(long, global = true)]
+  socket: Option<PathBuf>,
+
+  #[command(subcommand)]
+  command: MsgCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum MsgCommand {
+  /// Open a new tab
+  NewTab {
+    /// Profile to launch (defaults to the configured default profile)
+    #[arg(long)]
+    profile: Option<String>,
+    /// Working directory for the new shell
+    #[arg(long)]
+    cwd: Option<String>,
+  },
+  /// Split the active pane
+  Split {
+    /// Direction of the split
+    #[arg(long, value_enum, default_value_t = SplitDirection::Horizontal)]
+    direction: SplitDirection,
+  },
+  /// Send text to the active terminal as if it were typed
+  SendText {
+    /// Text to send; read from stdin when omitted
+    text: Option<String>,
+  },
+  /// Print the UI tree of the running instance as JSON
+  Snapshot,
+  /// Print the text of a pane (defaults to the active pane)
+  GetText {
+    /// Tab id from `snapshot`
+    #[arg(long)]
+    tab: Option<String>,
+    /// Pane id from `snapshot`
+    #[arg(long)]
+    pane: Option<String>,
+    /// Lines to read
+    #[arg(long, value_enum, default_value_t = TextRangeArg::Screen)]
+    range: TextRangeArg,
+    /// Keep colors and attributes as ANSI escape sequences
+    #[arg(long)]
+    ansi: bool,
+  },
+  /// Save, open, delete or list named sessions
+  Session {
+    #[command(subcommand)]
+    command: SessionCommand,
+  },
+}
+
+#[derive(Subcommand, Debug)]
+enum SessionCommand {
+  /// Save every open window under a name
+  Save { name: String },
+  /// Open a saved session in new windows
+  Open { name: String },
+  /// Delete a saved session
+  Delete { name: String },
+  /// List the saved sessions
+  List,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SplitDirection {
+  Horizontal,
+  Vertical,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum TextRangeArg {
+  Screen,
+  Scrollback,
+  All,
+}
+
+impl MsgCommand {
+  fn to_json_event(&self) -> anyhow::Result<JsonEvent> {
+    Ok(match self {
+      MsgCommand::NewTab { profile, cwd } => match profile {
+        Some(profile_name) => JsonEvent::NewTerminalWithProfile {
+          profile_name: profile_name.clone(),
+          working_directory: cwd.clone(),
+        },
+        None => JsonEvent::NewTerminalWithDefaultProfile {
+          working_directory: cwd.clone(),
+        },
+      },
+      MsgCommand::Split { direction } => match direction {
+        SplitDirection::Horizontal => JsonEvent::SplitHorizontal,
+        SplitDirection::Vertical => JsonEvent::SplitVertical,
+      },
+      MsgCommand::SendText { text } => {
+        let text = match text {
+          Some(text) => text.clone(),
+          None => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+          }
+        };
+        JsonEvent::SendTextToTerminal { text }
+      }
+      MsgCommand::Snapshot => JsonEvent::SnapshotUITree,
+      MsgCommand::GetText {
+        tab,
+        pane,
+        range,
+        ansi,
+      } => JsonEvent::GetText {
+        window_id: None,
+        tab_id: tab.clone(),
+        pane_id: pane.clone(),
+        range: match range {
+          TextRangeArg::Screen => TextRange::Screen,
+          TextRangeArg::Scrollback => TextRange::Scrollback,
+          TextRangeArg::All => TextRange::All,
+        },
+        ansi: *ansi,
+      },
+      MsgCommand::Session { command } => match command {
+        SessionCommand::Save { name } => JsonEvent::SaveSession { name: name.clone() },
+        SessionCommand::Open { name } => JsonEvent::OpenSession { name: name.clone() },
+        SessionCommand::Delete { name } => JsonEvent::DeleteSession { name: name.clone() },
+        SessionCommand::List => JsonEvent::ListSessions,
+      },
+    })
+  }
+}
+
+impl MsgArgs {
+  fn socket_path(&self) -> anyhow::Result<PathBuf> {
+    self
+      .socket
+      .clone()
+      .or_else(|| std::env::var_os(SOCKET_ENV).map(PathBuf::from))
+      .ok_or_else(|| anyhow::anyhow!("No event socket given. Pass --socket or set ${SOCKET_ENV}."))
+  }
+}
+
+/// Run a `kazeterm msg` subcommand and return the process exit code.
+pub(crate) fn run(args: MsgArgs) -> i32 {
+  match send(&args) {
+    Ok(()) => 0,
+    Err(error) => {
+      eprintln!("kazeterm msg: {error:#}");
+      1
+    }
+  }
+}
+
+fn send(args: &MsgArgs) -> anyhow::Result<()> {
+  let socket_path = args.socket_path()?;
+  let event = args.command.to_json_event()?;
+  let response = kazeterm_event_system::send_request(&socket_path, &event)?;
+
+  if !response.ok {
+    anyhow::bail!(
+      response
+        .error
+        .unwrap_or_else(|| "request failed".to_string())
+    );
+  }
+  match (&args.command, response.result) {
+    // Pane text is printed as-is so it can be piped or redirected.
+    (MsgCommand::GetText { .. }, Some(result)) => {
+      print!("{}", result["text"].as_str().unwrap_or_default());
+    }
+    (_, Some(result)) => println!("{}", serde_json::to_string_pretty(&result)?),
+    (_, None) => {}
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use clap::Parser;
+
+  use crate::Args;
+  use crate::event_system::{JsonEvent, TextRange};
+
+  fn msg_event(args: &[&str]) -> JsonEvent {
+    let args = Args::try_parse_from(args).expect("arguments should parse");
+    let Some(crate::Command::Msg(msg)) = args.command else {
+      panic!("expected msg subcommand");
+    };
+    msg.command.to_json_event().unwrap()
+  }
+
+  #[test]
+  fn new_tab_maps_to_profile_events() {
+    assert_eq!(
+      msg_event(&[
+        "kazeterm",
+        "msg",
+        "new-tab",
+        "--profile",
+        "bash",
+        "--cwd",
+        "/tmp"
+      ]),
+      JsonEvent::NewTerminalWithProfile {
+        profile_name: "bash".to_string(),
+        working_directory: Some("/tmp".to_string()),
+      }
+    );
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "new-tab", "--cwd", "/tmp"]),
+      JsonEvent::NewTerminalWithDefaultProfile {
+        working_directory: Some("/tmp".to_string()),
+      }
+    );
+  }
+
+  #[test]
+  fn split_send_text_and_snapshot_map_to_events() {
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "split"]),
+      JsonEvent::SplitHorizontal
+    );
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "split", "--direction", "vertical"]),
+      JsonEvent::SplitVertical
+    );
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "send-text", "ls\n"]),
+      JsonEvent::SendTextToTerminal {
+        text: "ls\n".to_string(),
+      }
+    );
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "--socket", "/tmp/k.sock", "snapshot"]),
+      JsonEvent::SnapshotUITree
+    );
+  }
+
+  #[test]
+  fn get_text_maps_to_event() {
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "get-text"]),
+      JsonEvent::GetText {
+        window_id: None,
+        tab_id: None,
+        pane_id: None,
+        range: TextRange::Screen,
+        ansi: false,
+      }
+    );
+    assert_eq!(
+      msg_event(&[
+        "kazeterm",
+        "msg",
+        "get-text",
+        "--tab",
+        "tab-2",
+        "--pane",
+        "pane-1",
+        "--range",
+        "scrollback",
+        "--ansi"
+      ]),
+      JsonEvent::GetText {
+        window_id: None,
+        tab_id: Some("tab-2".to_string()),
+        pane_id: Some("pane-1".to_string()),
+        range: TextRange::Scrollback,
+        ansi: true,
+      }
+    );
+  }
+
+  #[test]
+  fn session_commands_map_to_events() {
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "session", "save", "oncall"]),
+      JsonEvent::SaveSession {
+        name: "oncall".to_string(),
+      }
+    );
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "session", "open", "oncall"]),
+      JsonEvent::OpenSession {
+        name: "oncall".to_string(),
+      }
+    );
+    assert_eq!(
+      msg_event(&["kazeterm", "msg", "session", "list"]),
+      JsonEvent::ListSessions
+    );
+  }
+
+  #[test]
+  fn socket_flag_is_accepted_after_subcommand() {
+    let args = Args::try_parse_from(["kazeterm", "msg", "snapshot", "--socket", "/tmp/k.sock"])
+      .expect("arguments should parse");
+    let Some(crate::Command::Msg(msg)) = args.command else {
+      panic!("expected msg subcommand");
+    };
+    assert_eq!(
+      msg.socket_path().unwrap(),
+      std::path::PathBuf::from("/tmp/k.sock")
+    );
+  }
+}
@@ -12,12 +12,13 @@ use kazeterm_ui_tree::action::UIAction;
 use kazeterm_ui_tree::diff::{self, Reconciler, TreeDiff};
 use kazeterm_ui_tree::node::*;
 
-use gpui::{Context, Task, Window, px};
+use gpui::{App, Context, Task, Window, px};
 
 use crate::components::transitions::{
   UI_TRANSITION_FRAME_DURATION, UI_TRANSITION_FRAMES, interpolate_size,
 };
 use crate::components::{MainWindow, PaneId, SplitDirection};
+use crate::event_system::{Notification, publish_notification};
 
 impl MainWindow {
   fn animate_window_resize(
@@ -143,9 +144,15 @@ impl UITreeStore {
       } else {
         item.search_bar_state.clone()
       };
+      let previous_panes = self
+        .tree
+        .window(&win_id)
+        .and_then(|window| window.tab(&item.ui_tree_id))
+        .map(|(_, tab)| &tab.pane_tree);
       let pane_tree = capture_split_pane(
         &item.split_container.root,
         item.split_container.active_pane_id,
+        previous_panes,
         cx,
       );
       tabs.push(TabNode {
@@ -155,7 +162,7 @@ impl UITreeStore {
         shell: ShellConfig {
           path: item.shell_path.clone(),
           args: item.shell_args.clone(),
-          profile: None,
+          profile: item.profile.clone(),
         },
         pane_tree,
         search: SearchState {
@@ -186,7 +193,7 @@ impl UITreeStore {
         ..SearchState::default()
       },
       tabs,
-      overlay: capture_overlay(main_window),
+      overlay: capture_overlay(main_window, cx),
       key_debug: KeyDebugState {
         enabled: config_key_debug,
       },
@@ -206,11 +213,21 @@ impl UITreeStore {
   ) {
     for d in diffs {
       match d {
-        TreeDiff::TabAdded { tab, .. } => {
+        TreeDiff::TabAdded { window_id, tab, .. } => {
           main_window.restore_tab_from_node(tab, window, cx);
+          if main_window
+            .items
+            .iter()
+            .any(|item| item.ui_tree_id == tab.id)
+          {
+            publish_notification(Notification::TabCreated {
+              window_id: window_id.clone(),
+              tab_id: tab.id.clone(),
+            });
+          }
         }
 
-        TreeDiff::TabRemoved { tab_id, .. } => {
+        TreeDiff::TabRemoved { window_id, tab_id } => {
           if let Some(item) = main_window
             .items
             .iter()
@@ -218,6 +235,10 @@ impl UITreeStore {
           {
             let index = item.index;
             main_window.remove_tab_by(index, window, cx);
+            publish_notification(Notification::TabClosed {
+              window_id: window_id.clone(),
+              tab_id: tab_id.clone(),
+            });
           }
         }
 
@@ -387,6 +408,9 @@ impl UITreeStore {
     main_window.reconciling_ui_tree = true;
     self.reconcile(&diffs, main_window, window, cx);
     main_window.reconciling_ui_tree = was_reconciling;
+    if !diffs.is_empty() {
+      main_window.schedule_autosave(cx);
+    }
     Ok(())
   }
 }
@@ -400,9 +424,12 @@ impl Reconciler for UITreeStore {
 
 // ── Capture helpers ──
 
+/// `previous` is the tab's pane tree from the last capture; panes keep the
+/// shell recorded there since the live terminal doesn't track it.
 fn capture_split_pane(
   pane: &crate::components::SplitPane,
   active_pane_id: Option<PaneId>,
+  previous: Option<&PaneNode>,
   cx: &mut Context<MainWindow>,
 ) -> PaneNode {
   match pane {
@@ -410,11 +437,18 @@ fn capture_split_pane(
       let terminal_entity = terminal.read(cx).terminal().clone();
       let title = terminal_entity.read(cx).title_text.clone();
       let cwd = terminal_entity.update(cx, |t, _cx| t.current_working_directory());
+      let focused = Some(*id) == active_pane_id;
+      let id = format!("pane-{}", id.0);
+      let shell = match previous.and_then(|previous| previous.find_pane(&id)) {
+        Some(PaneNode::Terminal { shell, .. }) => shell.clone(),
+        _ => None,
+      };
       PaneNode::Terminal {
-        id: format!("pane-{}", id.0),
+        id,
         working_directory: cwd,
         title,
-        focused: Some(*id) == active_pane_id,
+        focused,
+        shell,
       }
     }
     crate::components::SplitPane::Split {
@@ -428,13 +462,13 @@ fn capture_split_pane(
         SplitDirection::Vertical => kazeterm_ui_tree::node::SplitDirection::Vertical,
       },
       ratio: *ratio,
-      first: Box::new(capture_split_pane(first, active_pane_id, cx)),
-      second: Box::new(capture_split_pane(second, active_pane_id, cx)),
+      first: Box::new(capture_split_pane(first, active_pane_id, previous, cx)),
+      second: Box::new(capture_split_pane(second, active_pane_id, previous, cx)),
     },
   }
 }
 
-fn capture_overlay(main_window: &MainWindow) -> Option<OverlayNode> {
+fn capture_overlay(main_window: &MainWindow, cx: &App) -> Option<OverlayNode> {
   if main_window.about_dialog.is_some() {
     return Some(OverlayNode::AboutDialog);
   }
@@ -460,6 +494,11 @@ fn capture_overlay(main_window: &MainWindow) -> Option<OverlayNode> {
       message: String::new(),
     });
   }
+  if let Some(search_panel) = &main_window.search_panel {
+    return Some(OverlayNode::SearchPanel {
+      all_tabs: search_panel.read(cx).all_tabs(),
+    });
+  }
   None
 }
 
@@ -482,6 +521,7 @@ fn reconcile_overlay(
       main_window._import_alacritty_subscription = None;
       main_window.shell_error_dialog = None;
       main_window._shell_error_subscription = None;
+      main_window.dismiss_search_panel(cx);
       main_window.refocus_active_terminal(window, cx);
       cx.notify();
     }
@@ -504,6 +544,9 @@ fn reconcile_overlay(
     Some(OverlayNode::ShellError { message }) => {
       main_window.show_shell_error_dialog(message.clone(), window, cx);
     }
+    Some(OverlayNode::SearchPanel { all_tabs }) => {
+      main_window.show_search_panel(*all_tabs, window, cx);
+    }
     Some(OverlayNode::TabSwitcher { .. }) => {
       // Tab switcher is managed by keyboard state, not the reconciler
     }
@@ -0,0 +1,217 @@
+//! Named sessions.
+//!
+//! A session is a saved set of windows, stored as UI tree JSON in
+//! `<config dir>/sessions/<name>.json` with one window node per window.
+//! Opening a session restores each window in a new Kazeterm window, leaving
+//! the ones already open alone.
+
+use std::path::{Path, PathBuf};
+
+use gpui::App;
+use kazeterm_ui_tree::node::UITree;
+
+use crate::components::ScrollbackArchive;
+use crate::event_system::EventSourceConfig;
+
+const MAX_NAME_LEN: usize = 64;
+
+pub(crate) fn sessions_dir() -> PathBuf {
+  config::Config::get_config_path().join("sessions")
+}
+
+/// Names of the saved sessions, sorted.
+pub(crate) fn list() -> Vec<String> {
+  list_in(&sessions_dir())
+}
+
+/// Save `windows` as session `name`, replacing any session of that name.
+pub(crate) fn save(name: &str, windows: Vec<UITree>) -> Result<(), String> {
+  save_in(&sessions_dir(), name, windows)
+}
+
+pub(crate) fn load(name: &str) -> Result<UITree, String> {
+  load_in(&sessions_dir(), name)
+}
+
+pub(crate) fn delete(name: &str) -> Result<(), String> {
+  delete_in(&sessions_dir(), name)
+}
+
+/// Open every window of session `name` and return how many were opened.
+pub(crate) fn open(
+  name: &str,
+  event_source_config: EventSourceConfig,
+  cx: &mut App,
+) -> Result<usize, String> {
+  let tree = load(name)?;
+  Ok(open_windows(tree, event_source_config, cx))
+}
+
+/// Open one new window per window node of a loaded session.
+pub(crate) fn open_windows(
+  tree: UITree,
+  event_source_config: EventSourceConfig,
+  cx: &mut App,
+) -> usize {
+  let windows = split_windows(tree);
+  let count = windows.len();
+  for window in windows {
+    crate::window_manager::open_kazeterm_window_with_ui_tree(
+      window,
+      ScrollbackArchive::default(),
+      event_source_config.clone(),
+      cx,
+    );
+  }
+  count
+}
+
+/// Session names become file names, so only allow a safe subset.
+pub(crate) fn validate_name(name: &str) -> Result<(), String> {
+  if name.is_empty() {
+    return Err("Session name must not be empty".to_string());
+  }
+  if name.len() > MAX_NAME_LEN {
+    return Err(format!(
+      "Session name must be at most {MAX_NAME_LEN} characters"
+    ));
+  }
+  if name.starts_with('.')
+    || !name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+  {
+    return Err(format!(
+      "Invalid session name '{name}': use letters, digits, '-', '_' and '.'"
+    ));
+  }
+  Ok(())
+}
+
+fn session_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+  validate_name(name)?;
+  Ok(dir.join(format!("{name}.json")))
+}
+
+fn list_in(dir: &Path) -> Vec<String> {
+  let Ok(entries) = std::fs::read_dir(dir) else {
+    return Vec::new();
+  };
+  let mut names: Vec<String> = entries
+    .flatten()
+    .filter_map(|entry| {
+      let name = entry
+        .file_name()
+        .to_str()?
+        .strip_suffix(".json")?
+        .to_string();
+      validate_name(&name).is_ok().then_some(name)
+    })
+    .collect();
+  names.sort();
+  names
+}
+
+fn save_in(dir: &Path, name: &str, windows: Vec<UITree>) -> Result<(), String> {
+  let path = session_path(dir, name)?;
+  let tree = merge_windows(windows);
+  if tree.windows.iter().all(|window| window.tabs.is_empty()) {
+    return Err("There are no tabs to save".to_string());
+  }
+  let json =
+    serde_json::to_string_pretty(&tree).map_err(|e| format!("Failed to serialize session: {e}"))?;
+  std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create sessions directory: {e}"))?;
+  crate::autosave::write_atomically(&path, json.as_bytes())
+    .map_err(|e| format!("Failed to write session '{name}': {e}"))
+}
+
+fn load_in(dir: &Path, name: &str) -> Result<UITree, String> {
+  let path = session_path(dir, name)?;
+  let json = match std::fs::read_to_string(&path) {
+    Ok(json) => json,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+      return Err(format!("Session '{name}' not found"));
+    }
+    Err(e) => return Err(format!("Failed to read session '{name}': {e}")),
+  };
+  let tree: UITree =
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse session '{name}': {e}"))?;
+  if tree.windows.iter().all(|window| window.tabs.is_empty()) {
+    return Err(format!("Session '{name}' has no tabs"));
+  }
+  Ok(tree)
+}
+
+fn delete_in(dir: &Path, name: &str) -> Result<(), String> {
+  let path = session_path(dir, name)?;
+  match std::fs::remove_file(&path) {
+    Ok(()) => Ok(()),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+      Err(format!("Session '{name}' not found"))
+    }
+    Err(e) => Err(format!("Failed to delete session '{name}': {e}")),
+  }
+}
+
+/// Combine per-window trees into one, keeping their window order.
+fn merge_windows(windows: Vec<UITree>) -> UITree {
+  let mut merged = UITree::new();
+  for tree in windows {
+    merged.next_id = merged.next_id.max(tree.next_id);
+    merged.windows.extend(tree.windows);
+  }
+  merged
+}
+
+/// One tree per window with tabs, each able to seed its own window.
+fn split_windows(tree: UITree) -> Vec<UITree> {
+  tree
+    .windows
+    .into_iter()
+    .filter(|window| !window.tabs.is_empty())
+    .map(|window| UITree {
+      version: tree.version,
+      windows: vec![window],
+      next_id: tree.next_id,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{delete_in, list_in, load_in, save_in, split_windows, validate_name};
+  use crate::test_support::window_with_tabs;
+
+  #[test]
+  fn sessions_round_trip_every_window() {
+    let dir = std::env::temp_dir().join(format!("kazeterm-sessions-{}", std::process::id()));
+    save_in(
+      &dir,
+      "backend-dev",
+      vec![window_with_tabs(2), window_with_tabs(1)],
+    )
+    .unwrap();
+    save_in(&dir, "oncall", vec![window_with_tabs(1)]).unwrap();
+
+    assert_eq!(list_in(&dir), vec!["backend-dev", "oncall"]);
+    let windows = split_windows(load_in(&dir, "backend-dev").unwrap());
+    assert_eq!(windows.len(), 2);
+    assert_eq!(windows[0].windows[0].tabs.len(), 2);
+    assert_eq!(windows[1].windows[0].tabs.len(), 1);
+
+    delete_in(&dir, "oncall").unwrap();
+    assert_eq!(list_in(&dir), vec!["backend-dev"]);
+    assert!(load_in(&dir, "oncall").is_err());
+    assert!(save_in(&dir, "empty", vec![window_with_tabs(0)]).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn names_stay_inside_the_sessions_directory() {
+    assert!(validate_name("backend-dev_2.old").is_ok());
+    assert!(validate_name("").is_err());
+    assert!(validate_name("../oncall").is_err());
+    assert!(validate_name(".hidden").is_err());
+    assert!(validate_name("on call").is_err());
+  }
+}
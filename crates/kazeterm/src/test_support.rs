@@ -23,8 +23,12 @@
 
 use ::config::Config;
 use gpui::TestAppContext;
+use kazeterm_ui_tree::action::UIAction;
+use kazeterm_ui_tree::node::UITree;
 use themeing::SettingsStore;
 
+use crate::reconciler::UITreeStore;
+
 /// Install the globals every Kazeterm view expects: a default [`Config`] and a
 /// [`SettingsStore`] built from it, plus `gpui_component::init` so theme
 /// lookups resolve.
@@ -56,3 +60,28 @@ pub fn init_test_app(cx: &mut TestAppContext) {
     terminal::init(cx, &config.keybindings);
   });
 }
+
+/// A UI tree with one window holding `tabs` bash tabs, for the autosave and
+/// session tests.
+pub fn window_with_tabs(tabs: usize) -> UITree {
+  let mut store = UITreeStore::new();
+  store
+    .apply_action(UIAction::AddWindow {
+      width: None,
+      height: None,
+    })
+    .unwrap();
+  let window_id = store.tree().windows[0].id.clone();
+  for _ in 0..tabs {
+    store
+      .apply_action(UIAction::AddTab {
+        window_id: window_id.clone(),
+        shell_path: "bash".into(),
+        shell_args: vec![],
+        profile: None,
+        working_directory: None,
+      })
+      .unwrap();
+  }
+  store.tree().clone()
+}
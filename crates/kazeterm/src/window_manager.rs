@@ -2,11 +2,12 @@ use std::cell::Cell;
 use std::rc::Rc;
 
 use gpui::{
-  AnyWindowHandle, App, AppContext, Bounds, Entity, Global, Pixels, Point, Size, WeakEntity,
-  Window, WindowBackgroundAppearance, WindowBounds, WindowOptions, point, px,
+  AnyWindowHandle, App, AppContext, Bounds, Entity, EntityId, Global, Pixels, Point, Size,
+  WeakEntity, Window, WindowBackgroundAppearance, WindowBounds, WindowOptions, point, px,
 };
+use kazeterm_ui_tree::node::UITree;
 
-use crate::components::{DraggedTab, MainWindow};
+use crate::components::{DraggedTab, MainWindow, ScrollbackArchive};
 use crate::event_system::EventSourceConfig;
 use ::config::Config;
 
@@ -38,6 +39,34 @@ pub(crate) fn open_kazeterm_window(event_source_config: EventSourceConfig, cx: &
   .detach();
 }
 
+/// Open a window laid out from `tree`, leaving existing windows untouched.
+/// Panes found in `scrollback` replay it before their shells start.
+pub(crate) fn open_kazeterm_window_with_ui_tree(
+  tree: UITree,
+  scrollback: ScrollbackArchive,
+  event_source_config: EventSourceConfig,
+  cx: &mut App,
+) {
+  let options = window_options(cx.global::<Config>(), None);
+
+  cx.spawn(async move |cx| {
+    cx.open_window(options, |window, cx| {
+      let view = MainWindow::empty_view_with_event_source(window, event_source_config.clone(), cx);
+      view.update(cx, |main_window, cx| {
+        main_window.pending_scrollback = scrollback;
+        main_window.replace_with_ui_tree(&tree, window, cx);
+        main_window.pending_scrollback.clear();
+        main_window.schedule_autosave(cx);
+      });
+      initialize_window(&view, event_source_config, window, cx);
+      cx.new(|cx| gpui_component::Root::new(view, window, cx))
+    })?;
+
+    Ok::<_, anyhow::Error>(())
+  })
+  .detach();
+}
+
 pub(crate) fn open_detached_tab_window(dragged: DraggedTab, bounds: Bounds<Pixels>, cx: &mut App) {
   let event_source_config = dragged
     .source
@@ -111,6 +140,21 @@ pub(crate) fn drop_tab_on_existing_window(
   false
 }
 
+/// Current UI trees of every open window other than `skip`, front to back.
+pub(crate) fn window_ui_trees(skip: EntityId, cx: &mut App) -> Vec<UITree> {
+  registered_windows_front_to_back(cx)
+    .into_iter()
+    .filter_map(|registered| registered.view.upgrade())
+    .filter(|view| view.entity_id() != skip)
+    .map(|view| {
+      view.update(cx, |main_window, cx| {
+        main_window.sync_ui_tree(cx);
+        main_window.ui_tree.tree().clone()
+      })
+    })
+    .collect()
+}
+
 fn registered_windows_front_to_back(cx: &App) -> Vec<RegisteredWindow> {
   let mut registered = cx
     .try_global::<WindowRegistry>()
@@ -170,6 +214,7 @@ pub(crate) fn close_window(window: &mut Window, cx: &mut App) {
     .into_iter()
     .any(|handle| handle != current_window);
 
+  crate::autosave::discard_window(current_window, cx);
   window.remove_window();
   if !has_other_windows {
     cx.quit();
@@ -246,6 +291,17 @@ fn initialize_window(
   cx.defer(move |cx| {
     crate::auto_update::start_auto_update(main_window_weak, window_handle, cx);
   });
+
+  if let Some(recovery) = crate::autosave::take_recovery(cx) {
+    let main_window = view.clone();
+    cx.defer(move |cx| {
+      let _ = cx.update_window(window_handle, |_root, window, cx| {
+        main_window.update(cx, |main_window, cx| {
+          main_window.show_recovery_dialog(recovery, window, cx);
+        });
+      });
+    });
+  }
 }
 
 pub(crate) fn register_window(handle: AnyWindowHandle, view: &Entity<MainWindow>, cx: &mut App) {
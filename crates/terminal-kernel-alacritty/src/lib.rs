@@ -1,6 +1,5 @@
 use std::{borrow::Cow, collections::HashMap, path::PathBuf, sync::Arc};
 
-use alacritty_terminal::term::Osc52;
 use alacritty_terminal::vte::ansi::{CursorShape, CursorStyle};
 use futures::{channel::mpsc::UnboundedReceiver, channel::mpsc::unbounded};
 use terminal::{PtyProcessInfo, PtySender, Terminal, TerminalBounds, TerminalEventListener};
@@ -11,6 +10,7 @@ use terminal_kernel::{
   sync::FairMutex,
   term::Config,
   tty,
+  vte::ansi::Processor,
 };
 
 #[cfg(unix)]
@@ -48,21 +48,13 @@ fn parse_cursor_style(config: &config::Config) -> CursorStyle {
   }
 }
 
-fn parse_osc52(mode: &str) -> Osc52 {
-  match mode {
-    "disabled" => Osc52::Disabled,
-    "paste_only" => Osc52::OnlyPaste,
-    "copy_paste" => Osc52::CopyPaste,
-    _ => Osc52::OnlyCopy,
-  }
-}
-
 pub fn create_terminal_session(
   program: String,
   args: Vec<String>,
   working_directory: Option<PathBuf>,
   app_config: &config::Config,
   term_program_version: &str,
+  replay: Option<&[u8]>,
 ) -> Result<(Terminal, SessionEvents), String> {
   let mut env = HashMap::new();
   if std::env::var("LANG").is_err() {
@@ -152,9 +144,10 @@ pub fn create_terminal_session(
 
   let term = Term::new(
     Config {
-      scrolling_history: app_config.terminal.get_scrollback_lines(),
+      scrolling_history: app_config.terminal.get_scrollback_lines()
+        + terminal_kernel::SCROLLBACK_SLACK,
       default_cursor_style: parse_cursor_style(app_config),
-      osc52: parse_osc52(&app_config.terminal.osc52),
+      osc52: terminal_kernel::parse_osc52(&app_config.terminal.osc52),
       ..Config::default()
     },
     &TerminalBounds::default(),
@@ -163,6 +156,12 @@ pub fn create_terminal_session(
 
   let term = Arc::new(FairMutex::new(term));
 
+  // Restored scrollback goes in before the shell can print anything.
+  if let Some(bytes) = replay {
+    let mut parser: Processor = Processor::new();
+    parser.advance(&mut *term.lock(), bytes);
+  }
+
   let shell_program = program.clone();
   let pty_options = {
     let shell = tty::Shell::new(program, args);
@@ -181,7 +180,7 @@ pub fn create_terminal_session(
     .map_err(|e| format!("Could not start shell '{}': {}", shell_program, e))?;
 
   #[cfg(unix)]
-  let (pty_tx, pty_info, graphics_rx, pending_cnl, keyboard_flags, osc7_rx) = {
+  let (pty_tx, pty_info, graphics_rx, pending_cnl, keyboard_flags, osc7_rx, semantic_rx) = {
     let term_for_cursor = term.clone();
     let cursor_fn: Box<dyn Fn() -> Option<(i32, i32)> + Send + Sync> = Box::new(move || {
       let t = term_for_cursor.try_lock_unfair()?;
@@ -197,7 +196,7 @@ pub fn create_terminal_session(
       Some((cursor.line.0 + 1, cursor.column.0 as i32 + 1))
     });
 
-    let (filter, pending_cnl, keyboard_flags, graphics_rx, osc7_rx) =
+    let (filter, pending_cnl, keyboard_flags, graphics_rx, osc7_rx, semantic_rx) =
       GraphicsPtyFilter::new(pty, cursor_fn, dsr_cursor_fn).unwrap();
     let pty_info = PtyProcessInfo::from_raw(filter.pty_fd(), filter.child_pid());
 
@@ -220,11 +219,12 @@ pub fn create_terminal_session(
       Some(pending_cnl),
       keyboard_flags,
       Some(osc7_rx),
+      Some(semantic_rx),
     )
   };
 
   #[cfg(not(unix))]
-  let (pty_tx, pty_info, graphics_rx, pending_cnl, keyboard_flags, osc7_rx) = {
+  let (pty_tx, pty_info, graphics_rx, pending_cnl, keyboard_flags, osc7_rx, semantic_rx) = {
     let term_for_dsr = term.clone();
     let dsr_cursor_fn: WindowsDsrCursorFn = Box::new(move || {
       let t = term_for_dsr.try_lock_unfair()?;
@@ -247,10 +247,11 @@ pub fn create_terminal_session(
     let pty_tx = event_loop.channel();
     let _io_thread = event_loop.spawn();
 
-    (pty_tx, pty_info, None, None, keyboard_flags, None)
+    (pty_tx, pty_info, None, None, keyboard_flags, None, None)
   };
 
-  let backend = AlacrittyBackend::new(term);
+  let backend =
+    AlacrittyBackend::new(term).with_history_limit(app_config.terminal.get_scrollback_lines());
   let terminal = Terminal::new(
     Box::new(AlacrittyPtySender(pty_tx)),
     Box::new(backend),
@@ -259,6 +260,7 @@ pub fn create_terminal_session(
     pending_cnl,
     keyboard_flags,
     osc7_rx,
+    semantic_rx,
     Some(cwd_file),
   );
 
@@ -40,6 +40,7 @@ pub fn create_terminal_session(
   working_directory: Option<PathBuf>,
   app_config: &config::Config,
   term_program_version: &str,
+  replay: Option<&[u8]>,
 ) -> Result<(Terminal, SessionEvents), String> {
   let mut env = HashMap::new();
   if std::env::var("LANG").is_err() {
@@ -130,6 +131,7 @@ pub fn create_terminal_session(
   ) = unbounded();
 
   let (osc7_tx, osc7_rx) = std::sync::mpsc::channel();
+  let (semantic_tx, semantic_rx) = std::sync::mpsc::channel();
 
   // Default terminal dimensions.
   let bounds = TerminalBounds::default();
@@ -143,9 +145,16 @@ pub fn create_terminal_session(
     app_config.terminal.get_scrollback_lines(),
     events_tx,
     Some(osc7_tx),
+    Some(semantic_tx),
     app_config.cursor.blink,
+    terminal_kernel::parse_osc52(&app_config.terminal.osc52),
   )));
 
+  // Restored scrollback goes in before the shell can print anything.
+  if let Some(bytes) = replay {
+    vte::Parser::new().advance(&mut *state.lock(), bytes);
+  }
+
   // Spawn the child shell.
   let shell_program = program.clone();
   let pty_options = {
@@ -199,6 +208,7 @@ pub fn create_terminal_session(
     None,
     Arc::new(AtomicU32::new(0)),
     Some(osc7_rx),
+    Some(semantic_rx),
     Some(cwd_file),
   );
 
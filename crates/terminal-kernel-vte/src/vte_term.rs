@@ -1,14 +1,19 @@
 //! Core VTE terminal state, `vte::Perform` implementation, and `TerminalBackend` adapter.
 
 use std::collections::VecDeque;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as Base64;
 use parking_lot::Mutex;
-use terminal_kernel::grid::Scroll;
+use regex::Regex;
+use terminal::shell_integration::{SemanticMark, parse_osc133_params};
+use terminal_kernel::grid::{GridCell as _, Scroll};
 use terminal_kernel::index::{Boundary, Column, Direction, Line, Point as AlacPoint, Side};
 use terminal_kernel::selection::{Selection, SelectionRange, SelectionType};
-use terminal_kernel::term::cell::{Cell, Flags as CellFlags};
-use terminal_kernel::term::{RenderableCursor, TermMode};
+use terminal_kernel::term::cell::{Cell, Flags as CellFlags, Hyperlink};
+use terminal_kernel::term::{ClipboardType, Osc52, RenderableCursor, TermMode};
 use terminal_kernel::vte::ansi::{Color, CursorShape, CursorStyle, NamedColor, Rgb};
 use terminal_kernel::{
   ANSI_COLOR_COUNT, BACKGROUND_COLOR_INDEX, FOREGROUND_COLOR_INDEX, RenderableSnapshot,
@@ -46,6 +51,10 @@ pub struct VteTermInner {
   num_lines: usize,
   num_cols: usize,
   max_scrollback: usize,
+  // Lines dropped off the top of a full scrollback, and whether ED 3 erased
+  // it, until `trim_scrollback` reports them.
+  scrollback_evicted: usize,
+  scrollback_erased: bool,
 
   cursor: CursorState,
   saved_cursor: Option<SavedCursor>,
@@ -69,11 +78,17 @@ pub struct VteTermInner {
   using_alt_screen: bool,
   pending_wrap: bool,
 
+  // Clipboard access granted to OSC 52.
+  osc52: Osc52,
+
   // Channel for events consumed by the Terminal UI layer.
   event_tx: futures::channel::mpsc::UnboundedSender<terminal_kernel::event::Event>,
 
   // Channel for OSC 7 working directory updates.
   osc7_tx: Option<std::sync::mpsc::Sender<std::path::PathBuf>>,
+
+  // Channel for OSC 133 prompt and command marks.
+  semantic_tx: Option<std::sync::mpsc::Sender<SemanticMark>>,
 }
 
 // ---------------------------------------------------------------------------
@@ -92,6 +107,200 @@ fn default_tab_stops(cols: usize) -> Vec<bool> {
   (0..cols).map(|c| c % 8 == 0).collect()
 }
 
+/// Whether `row` soft-wraps into the row below it.
+///
+/// As in Alacritty, the wrap state of a row is the `WRAPLINE` flag on its
+/// last cell.
+fn row_is_wrapped(row: &[Cell]) -> bool {
+  row
+    .last()
+    .is_some_and(|cell| cell.flags.contains(CellFlags::WRAPLINE))
+}
+
+/// Truncate or pad `rows` to the new dimensions without rewrapping text.
+fn resize_rows_without_reflow(rows: &mut Vec<Vec<Cell>>, lines: usize, cols: usize) {
+  for row in rows.iter_mut() {
+    row.resize(cols, Cell::default());
+    for cell in row.iter_mut() {
+      cell.flags.remove(CellFlags::WRAPLINE);
+    }
+  }
+  rows.resize_with(lines, || blank_row(cols));
+}
+
+// ---------------------------------------------------------------------------
+// Reflow
+// ---------------------------------------------------------------------------
+
+/// Cursor position used while reflowing, as an index into the reflowed rows.
+#[derive(Clone, Copy)]
+struct ReflowCursor {
+  row: usize,
+  col: usize,
+  pending_wrap: bool,
+}
+
+struct Reflowed {
+  rows: Vec<Vec<Cell>>,
+  /// Row that now holds the content previously shown at the top of the screen.
+  screen_top: usize,
+  cursor: Option<ReflowCursor>,
+}
+
+/// Rewrap `rows` (scrollback followed by the primary screen) to `new_cols`.
+///
+/// Soft-wrapped rows are joined into logical lines, trailing blank cells are
+/// dropped, and each logical line is split again at the new width. Hard line
+/// breaks are preserved, and a wide character is never split from its spacer:
+/// as in Alacritty it moves to the next row, leaving a leading spacer behind.
+/// `screen_top` and `cursor` are mapped onto the rewrapped rows so the caller
+/// can rebuild the visible screen.
+fn reflow_rows(
+  rows: Vec<Vec<Cell>>,
+  old_cols: usize,
+  new_cols: usize,
+  screen_top: usize,
+  cursor: Option<ReflowCursor>,
+) -> Reflowed {
+  let mut reflowed = Vec::with_capacity(rows.len());
+  let mut new_screen_top = None;
+  let mut new_cursor = None;
+
+  let mut rows = rows.into_iter().enumerate();
+  while let Some((start, first)) = rows.next() {
+    let mut end = start;
+    let mut wrapped = row_is_wrapped(&first);
+    let mut cells = first;
+    // Offset into the logical line at which each joined row starts.
+    let mut row_starts = vec![0];
+    while wrapped {
+      let Some((index, next)) = rows.next() else {
+        break;
+      };
+      if cells
+        .last()
+        .is_some_and(|cell| cell.flags.contains(CellFlags::LEADING_WIDE_CHAR_SPACER))
+      {
+        cells.pop();
+      }
+      row_starts.push(cells.len());
+      wrapped = row_is_wrapped(&next);
+      cells.extend(next);
+      end = index;
+    }
+
+    for cell in &mut cells {
+      cell.flags.remove(CellFlags::WRAPLINE);
+    }
+    let len = cells
+      .iter()
+      .rposition(|cell| !cell.is_empty())
+      .map_or(0, |index| index + 1);
+    let wraps = wrap_points(&cells, len, new_cols);
+    let first_row = reflowed.len();
+
+    if (start..=end).contains(&screen_top) {
+      let offset = row_starts[screen_top - start];
+      new_screen_top = Some(first_row + wrapped_row(&wraps, offset));
+    }
+
+    if let Some(cursor) = cursor.filter(|cursor| (start..=end).contains(&cursor.row)) {
+      let offset = row_starts[cursor.row - start] + cursor.col.min(old_cols.saturating_sub(1));
+      let mut placed = place_reflowed_cursor(offset, cursor.pending_wrap, len, &wraps, new_cols);
+      placed.row += first_row;
+      new_cursor = Some(placed);
+    }
+
+    let mut cells = cells.into_iter().take(len);
+    for (line_row, &row_start) in wraps.iter().enumerate() {
+      let row_end = wraps.get(line_row + 1).copied().unwrap_or(len);
+      let mut row: Vec<Cell> = cells.by_ref().take(row_end - row_start).collect();
+      let moved_wide_char = row.len() < new_cols && line_row + 1 < wraps.len();
+      row.resize(new_cols, Cell::default());
+      if moved_wide_char {
+        row[new_cols - 1]
+          .flags
+          .insert(CellFlags::LEADING_WIDE_CHAR_SPACER);
+      }
+      if line_row + 1 < wraps.len() {
+        row[new_cols - 1].flags.insert(CellFlags::WRAPLINE);
+      }
+      reflowed.push(row);
+    }
+  }
+
+  Reflowed {
+    screen_top: new_screen_top.unwrap_or(reflowed.len()),
+    rows: reflowed,
+    cursor: new_cursor,
+  }
+}
+
+/// Offsets into a logical line of `len` cells at which each rewrapped row
+/// starts. A wide character that would land in the last column starts the
+/// next row instead.
+fn wrap_points(cells: &[Cell], len: usize, new_cols: usize) -> Vec<usize> {
+  let mut wraps = vec![0];
+  let mut start = 0;
+  while len - start > new_cols {
+    let mut end = start + new_cols;
+    if new_cols > 1 && cells[end - 1].flags.contains(CellFlags::WIDE_CHAR) {
+      end -= 1;
+    }
+    wraps.push(end);
+    start = end;
+  }
+  wraps
+}
+
+/// Index of the rewrapped row holding `offset`.
+fn wrapped_row(wraps: &[usize], offset: usize) -> usize {
+  wraps
+    .iter()
+    .rposition(|&start| start <= offset)
+    .unwrap_or(0)
+}
+
+/// Map a cursor at `offset` within a logical line of `len` cells onto the
+/// rewrapped rows of that line, which start at `wraps`. The returned row is
+/// relative to the line.
+fn place_reflowed_cursor(
+  offset: usize,
+  pending_wrap: bool,
+  len: usize,
+  wraps: &[usize],
+  new_cols: usize,
+) -> ReflowCursor {
+  // With a pending wrap the next character goes after the cursor cell.
+  let position = offset + usize::from(pending_wrap);
+  let row = wrapped_row(wraps, position);
+  let col = position - wraps[row];
+
+  if position > len {
+    // Past the end of the text only blanks remain; stay on the last row.
+    return ReflowCursor {
+      row,
+      col: col.min(new_cols - 1),
+      pending_wrap: false,
+    };
+  }
+
+  if col >= new_cols {
+    // The text exactly fills its last row, so the next character wraps.
+    return ReflowCursor {
+      row,
+      col: new_cols - 1,
+      pending_wrap: true,
+    };
+  }
+
+  ReflowCursor {
+    row,
+    col,
+    pending_wrap: false,
+  }
+}
+
 impl VteTermInner {
   pub fn new(
     lines: usize,
@@ -99,7 +308,9 @@ impl VteTermInner {
     max_scrollback: usize,
     event_tx: futures::channel::mpsc::UnboundedSender<terminal_kernel::event::Event>,
     osc7_tx: Option<std::sync::mpsc::Sender<std::path::PathBuf>>,
+    semantic_tx: Option<std::sync::mpsc::Sender<SemanticMark>>,
     initial_cursor_blink: bool,
+    osc52: Osc52,
   ) -> Self {
     Self {
       rows: blank_grid(lines, cols),
@@ -108,6 +319,8 @@ impl VteTermInner {
       num_lines: lines,
       num_cols: cols,
       max_scrollback,
+      scrollback_evicted: 0,
+      scrollback_erased: false,
       cursor: CursorState {
         point: AlacPoint::new(Line(0), Column(0)),
         style: CursorStyle {
@@ -128,13 +341,23 @@ impl VteTermInner {
       colors: [None; ANSI_COLOR_COUNT],
       using_alt_screen: false,
       pending_wrap: false,
+      osc52,
       event_tx,
       osc7_tx,
+      semantic_tx,
     }
   }
 
   // -- Grid helpers -------------------------------------------------------
 
+  /// Drop the oldest scrollback lines past `max_scrollback`, counting them
+  /// for `trim_scrollback`.
+  fn evict_scrollback(&mut self) {
+    let excess = self.scrollback.len().saturating_sub(self.max_scrollback);
+    self.scrollback.drain(..excess);
+    self.scrollback_evicted += excess;
+  }
+
   /// Scroll the scroll-region up by one line (content moves up, blank line at bottom).
   fn scroll_up_in_region(&mut self) {
     let top = self.scroll_top;
@@ -143,9 +366,7 @@ impl VteTermInner {
     // If the scroll region is the full screen, push to scrollback.
     if top == 0 && bottom == self.num_lines - 1 && !self.using_alt_screen {
       self.scrollback.push_back(removed);
-      while self.scrollback.len() > self.max_scrollback {
-        self.scrollback.pop_front();
-      }
+      self.evict_scrollback();
     }
     self.rows.insert(bottom, blank_row(self.num_cols));
   }
@@ -184,37 +405,128 @@ impl VteTermInner {
     if new_lines == 0 || new_cols == 0 {
       return;
     }
-
-    // Resize each row in the primary screen.
-    for row in &mut self.rows {
-      row.resize(new_cols, Cell::default());
-    }
-    // Add or remove lines.
-    while self.rows.len() < new_lines {
-      self.rows.push(blank_row(new_cols));
+    if new_lines == self.num_lines && new_cols == self.num_cols {
+      return;
     }
-    while self.rows.len() > new_lines {
-      let removed = self.rows.remove(0);
-      if !self.using_alt_screen {
-        self.scrollback.push_back(removed);
-        while self.scrollback.len() > self.max_scrollback {
-          self.scrollback.pop_front();
-        }
+
+    let old_lines = self.num_lines;
+    let old_cols = self.num_cols;
+
+    // The primary screen is reflowed together with the scrollback. While the
+    // alternate screen is active the primary buffer lives in `alt_rows` and
+    // its cursor is the one saved when entering the alternate screen.
+    let (primary, primary_cursor) = if self.using_alt_screen {
+      let cursor = self.saved_cursor.as_ref().map(|saved| ReflowCursor {
+        row: saved.point.line.0.max(0) as usize,
+        col: saved.point.column.0,
+        pending_wrap: false,
+      });
+      (std::mem::take(&mut self.alt_rows), cursor)
+    } else {
+      let cursor = ReflowCursor {
+        row: self.cursor.point.line.0.max(0) as usize,
+        col: self.cursor.point.column.0,
+        pending_wrap: self.pending_wrap,
+      };
+      (std::mem::take(&mut self.rows), Some(cursor))
+    };
+
+    // Blank rows below both the cursor and the last written row carry no
+    // content, so they are dropped instead of being wrapped into history.
+    let last_content = primary
+      .iter()
+      .rposition(|row| row.iter().any(|cell| !cell.is_empty()));
+    let keep = last_content
+      .max(primary_cursor.as_ref().map(|cursor| cursor.row))
+      .map_or(0, |row| row + 1);
+
+    let history = self.scrollback.len();
+
+    // Like Alacritty, the line count changes first: shrinking pushes rows
+    // above the cursor into history only as far as needed to keep it on
+    // screen and drops rows below the new bottom, while growing pulls rows
+    // back out of history. `bottom` is the old screen row just below the
+    // resized screen.
+    let narrowing = new_cols < old_cols;
+    let bottom = if new_lines >= old_lines {
+      new_lines - (new_lines - old_lines).min(history)
+    } else {
+      new_lines.max(primary_cursor.as_ref().map_or(0, |cursor| cursor.row + 1))
+    };
+    let keep = if narrowing { keep.min(bottom) } else { keep };
+
+    let mut lines: Vec<Vec<Cell>> = self.scrollback.drain(..).collect();
+    lines.extend(primary.into_iter().take(keep));
+
+    let reflowed = reflow_rows(
+      lines,
+      old_cols,
+      new_cols,
+      history,
+      primary_cursor.map(|cursor| ReflowCursor {
+        row: history + cursor.row,
+        ..cursor
+      }),
+    );
+
+    let mut all_rows = reflowed.rows;
+    let top = if narrowing {
+      // Narrowing keeps the bottom of the screen in place, so every row added
+      // by rewrapping pushes the rows above it into the scrollback. The blank
+      // rows dropped below `keep` still count towards the screen height.
+      let top = (all_rows.len() + bottom - keep).saturating_sub(new_lines);
+      reflowed
+        .cursor
+        .as_ref()
+        .map_or(top, |cursor| top.min(cursor.row))
+    } else {
+      // The top of the screen keeps showing the same text where possible, and
+      // growing the screen pulls lines back out of the scrollback. Like
+      // Alacritty, rows freed by joining wrapped lines also pull history down
+      // so the cursor stays on its screen line. Rows above the cursor move
+      // into the scrollback only when it would otherwise leave the screen.
+      let grow = new_lines.saturating_sub(old_lines);
+      let mut top = reflowed.screen_top.saturating_sub(grow);
+      if let (Some(cursor), Some(old_cursor)) = (&reflowed.cursor, &primary_cursor) {
+        top = top.min(cursor.row.saturating_sub(old_cursor.row + grow));
+        top = top.max((cursor.row + 1).saturating_sub(new_lines));
+      }
+      top
+    };
+
+    let mut screen = all_rows.split_off(top.min(all_rows.len()));
+    screen.truncate(new_lines);
+    screen.resize_with(new_lines, || blank_row(new_cols));
+
+    self.scrollback = all_rows.into();
+    self.evict_scrollback();
+
+    let reflowed_cursor = reflowed.cursor.map(|cursor| {
+      (
+        AlacPoint::new(Line((cursor.row - top) as i32), Column(cursor.col)),
+        cursor.pending_wrap,
+      )
+    });
+
+    if self.using_alt_screen {
+      self.alt_rows = screen;
+      if let (Some(saved), Some((point, _))) = (&mut self.saved_cursor, reflowed_cursor) {
+        saved.point = point;
       }
-    }
 
-    // Resize alt screen.
-    for row in &mut self.alt_rows {
-      row.resize(new_cols, Cell::default());
-    }
-    while self.alt_rows.len() < new_lines {
-      self.alt_rows.push(blank_row(new_cols));
-    }
-    self.alt_rows.truncate(new_lines);
+      // The alternate screen is not reflowed; applications redraw it on resize.
+      resize_rows_without_reflow(&mut self.rows, new_lines, new_cols);
+      self.cursor.point.line.0 = self.cursor.point.line.0.min(new_lines as i32 - 1).max(0);
+      self.cursor.point.column.0 = self.cursor.point.column.0.min(new_cols.saturating_sub(1));
+      self.pending_wrap = false;
+    } else {
+      self.rows = screen;
+      if let Some((point, pending_wrap)) = reflowed_cursor {
+        self.cursor.point = point;
+        self.pending_wrap = pending_wrap;
+      }
 
-    // Resize scrollback rows.
-    for row in &mut self.scrollback {
-      row.resize(new_cols, Cell::default());
+      resize_rows_without_reflow(&mut self.alt_rows, new_lines, new_cols);
     }
 
     self.num_lines = new_lines;
@@ -222,12 +534,14 @@ impl VteTermInner {
     self.scroll_top = 0;
     self.scroll_bottom = new_lines.saturating_sub(1);
     self.tab_stops = default_tab_stops(new_cols);
-
-    // Clamp cursor.
-    self.cursor.point.line.0 = self.cursor.point.line.0.min(new_lines as i32 - 1).max(0);
-    self.cursor.point.column.0 = self.cursor.point.column.0.min(new_cols.saturating_sub(1));
-    self.pending_wrap = false;
     self.display_offset = self.display_offset.min(self.scrollback.len());
+
+    // Reflow moves text between lines, so any selection would point at
+    // unrelated content afterwards.
+    if new_cols != old_cols {
+      self.selection = None;
+      self.selection_display = None;
+    }
   }
 
   pub(crate) fn send_event(&self, event: terminal_kernel::event::Event) {
@@ -253,6 +567,50 @@ impl VteTermInner {
     ));
   }
 
+  // -- Clipboard (OSC 52) --------------------------------------------------
+
+  fn clipboard_store(&self, clipboard: u8, base64: &[u8]) {
+    if !matches!(self.osc52, Osc52::OnlyCopy | Osc52::CopyPaste) {
+      return;
+    }
+
+    let Some(clipboard_type) = osc52_clipboard_type(clipboard) else {
+      return;
+    };
+
+    if let Ok(bytes) = Base64.decode(base64)
+      && let Ok(text) = String::from_utf8(bytes)
+    {
+      self.send_event(terminal_kernel::event::Event::ClipboardStore(
+        clipboard_type,
+        text,
+      ));
+    }
+  }
+
+  fn clipboard_load(&self, clipboard: u8, bell_terminated: bool) {
+    if !matches!(self.osc52, Osc52::OnlyPaste | Osc52::CopyPaste) {
+      return;
+    }
+
+    let Some(clipboard_type) = osc52_clipboard_type(clipboard) else {
+      return;
+    };
+
+    let terminator = if bell_terminated { "\x07" } else { "\x1b\\" };
+    self.send_event(terminal_kernel::event::Event::ClipboardLoad(
+      clipboard_type,
+      Arc::new(move |text| {
+        format!(
+          "\x1b]52;{};{}{}",
+          clipboard as char,
+          Base64.encode(text),
+          terminator,
+        )
+      }),
+    ));
+  }
+
   // -- Alternate screen ---------------------------------------------------
 
   fn enter_alt_screen(&mut self) {
@@ -317,19 +675,14 @@ fn line_search_left_state(s: &VteTermInner, point: AlacPoint) -> AlacPoint {
     let is_wrapped = if prev < 0 {
       let sb_idx = s.scrollback.len() as i32 + prev;
       if sb_idx >= 0 && (sb_idx as usize) < s.scrollback.len() {
-        let row = &s.scrollback[sb_idx as usize];
-        row
-          .last()
-          .is_some_and(|c| c.flags.contains(CellFlags::WRAPLINE))
+        row_is_wrapped(&s.scrollback[sb_idx as usize])
       } else {
         false
       }
     } else {
       let ri = prev as usize;
       if ri < s.num_lines {
-        s.rows[ri]
-          .last()
-          .is_some_and(|c| c.flags.contains(CellFlags::WRAPLINE))
+        row_is_wrapped(&s.rows[ri])
       } else {
         false
       }
@@ -351,19 +704,14 @@ fn line_search_right_state(s: &VteTermInner, point: AlacPoint) -> AlacPoint {
     let is_wrapped = if target_line < 0 {
       let sb_idx = s.scrollback.len() as i32 + target_line;
       if sb_idx >= 0 && (sb_idx as usize) < s.scrollback.len() {
-        let row = &s.scrollback[sb_idx as usize];
-        row
-          .last()
-          .is_some_and(|c| c.flags.contains(CellFlags::WRAPLINE))
+        row_is_wrapped(&s.scrollback[sb_idx as usize])
       } else {
         false
       }
     } else {
       let ri = target_line as usize;
       if ri < s.num_lines {
-        s.rows[ri]
-          .last()
-          .is_some_and(|c| c.flags.contains(CellFlags::WRAPLINE))
+        row_is_wrapped(&s.rows[ri])
       } else {
         false
       }
@@ -516,6 +864,99 @@ fn bounds_to_string_state(s: &VteTermInner, start: AlacPoint, end: AlacPoint) ->
   result
 }
 
+/// Extend an OSC 8 hyperlink at `point` over neighbouring cells with the same link.
+fn hyperlink_bounds_state(
+  s: &VteTermInner,
+  point: AlacPoint,
+  link: &Hyperlink,
+) -> RangeInclusive<AlacPoint> {
+  let min_line = -(s.scrollback.len() as i32);
+  let max_line = s.num_lines as i32 - 1;
+  let last_column = Column(s.num_cols.saturating_sub(1));
+  let same_link = |point| cell_at_state(s, point).hyperlink().as_ref() == Some(link);
+
+  let mut start = point;
+  loop {
+    let prev = if start.column.0 > 0 {
+      AlacPoint::new(start.line, start.column - 1)
+    } else if start.line.0 > min_line {
+      AlacPoint::new(start.line - 1, last_column)
+    } else {
+      break;
+    };
+    if !same_link(prev) {
+      break;
+    }
+    start = prev;
+  }
+
+  let mut end = point;
+  loop {
+    let next = if end.column < last_column {
+      AlacPoint::new(end.line, end.column + 1)
+    } else if end.line.0 < max_line {
+      AlacPoint::new(end.line + 1, Column(0))
+    } else {
+      break;
+    };
+    if !same_link(next) {
+      break;
+    }
+    end = next;
+  }
+
+  start..=end
+}
+
+/// Find the `regex` match covering `point` within its soft-wrapped line.
+fn regex_match_at_state(
+  s: &VteTermInner,
+  point: AlacPoint,
+  regex: &Regex,
+) -> Option<(String, RangeInclusive<AlacPoint>)> {
+  let line_start = line_search_left_state(s, point);
+  let line_end = line_search_right_state(s, point);
+
+  // Byte offset and grid point of every character in the line text.
+  let mut text = String::new();
+  let mut offsets = Vec::new();
+  let mut points = Vec::new();
+  for line in line_start.line.0..=line_end.line.0 {
+    for column in 0..s.num_cols {
+      let cell_point = AlacPoint::new(Line(line), Column(column));
+      let cell = cell_at_state(s, cell_point);
+      if cell
+        .flags
+        .intersects(CellFlags::WIDE_CHAR_SPACER | CellFlags::LEADING_WIDE_CHAR_SPACER)
+      {
+        continue;
+      }
+      offsets.push(text.len());
+      points.push(cell_point);
+      text.push(cell.c);
+    }
+  }
+
+  regex.find_iter(&text).find_map(|found| {
+    let first = offsets.partition_point(|&offset| offset < found.start());
+    let last = offsets
+      .partition_point(|&offset| offset < found.end())
+      .checked_sub(1)?;
+    let range = points[first]..=points[last];
+    range
+      .contains(&point)
+      .then(|| (found.as_str().to_owned(), range))
+  })
+}
+
+fn osc52_clipboard_type(clipboard: u8) -> Option<ClipboardType> {
+  match clipboard {
+    b'c' => Some(ClipboardType::Clipboard),
+    b'p' | b's' => Some(ClipboardType::Selection),
+    _ => None,
+  }
+}
+
 fn parse_osc_palette_index(param: &[u8]) -> Option<usize> {
   let index = std::str::from_utf8(param).ok()?.parse::<usize>().ok()?;
   (index < 256).then_some(index)
@@ -582,11 +1023,10 @@ impl vte::Perform for VteTermInner {
     let row = self.cursor.point.line.0 as usize;
     let col = self.cursor.point.column.0;
     if row < self.num_lines && col < self.num_cols {
+      // Cloning the template also carries its OSC 8 hyperlink.
       let cell = &mut self.rows[row][col];
+      *cell = self.template_cell.clone();
       cell.c = c;
-      cell.fg = self.template_cell.fg.clone();
-      cell.bg = self.template_cell.bg.clone();
-      cell.flags = self.template_cell.flags;
     }
 
     if col + 1 < self.num_cols {
@@ -740,6 +1180,7 @@ impl vte::Perform for VteTermInner {
               }
             }
             self.scrollback.clear();
+            self.scrollback_erased = true;
             self.display_offset = 0;
           }
           _ => {}
@@ -1077,6 +1518,38 @@ impl vte::Perform for VteTermInner {
           }
         }
       }
+      "8" if params.len() > 2 => {
+        // OSC 8 ; params ; URI ST. The parser splits on `;`, which may also
+        // appear unescaped inside the URI, so rebuild it from the remainder.
+        let mut uri = String::from_utf8_lossy(params[2]).into_owned();
+        for param in &params[3..] {
+          uri.push(';');
+          uri.push_str(&String::from_utf8_lossy(param));
+        }
+
+        // An empty URI ends the hyperlink.
+        if uri.is_empty() {
+          self.template_cell.set_hyperlink(None);
+          return;
+        }
+
+        // Link parameters are `key1=value1:key2=value2`; only `id` is defined.
+        let id = params[1]
+          .split(|&byte| byte == b':')
+          .find_map(|param| param.strip_prefix(b"id="))
+          .map(|id| String::from_utf8_lossy(id).into_owned());
+        self
+          .template_cell
+          .set_hyperlink(Some(Hyperlink::new(id, uri)));
+      }
+      "52" if params.len() > 2 => {
+        // Clipboard selection defaults to `c` when the parameter is empty.
+        let clipboard = params[1].first().copied().unwrap_or(b'c');
+        match params[2] {
+          b"?" => self.clipboard_load(clipboard, bell_terminated),
+          base64 => self.clipboard_store(clipboard, base64),
+        }
+      }
       "7" => {
         // Set working directory.
         if let Some(uri) = params.get(1) {
@@ -1095,6 +1568,20 @@ impl vte::Perform for VteTermInner {
           }
         }
       }
+      "133" => {
+        // Semantic prompt mark. Unlike the PTY filter, the parser knows the
+        // exact cursor line. Marks on the alternate screen are meaningless.
+        if self.using_alt_screen {
+          return;
+        }
+        if let (Some(tx), Some(kind)) = (&self.semantic_tx, parse_osc133_params(&params[1..])) {
+          let _ = tx.send(SemanticMark {
+            kind,
+            line: self.scrollback.len() as i32 + self.cursor.point.line.0,
+            at: std::time::Instant::now(),
+          });
+        }
+      }
       _ => {}
     }
   }
@@ -1336,11 +1823,16 @@ impl VteTermInner {
 
 pub struct VteBackend {
   state: Arc<Mutex<VteTermInner>>,
+  // Compiled URL regex, keyed by its pattern.
+  url_regex: Mutex<Option<(String, Regex)>>,
 }
 
 impl VteBackend {
   pub fn new(state: Arc<Mutex<VteTermInner>>) -> Self {
-    Self { state }
+    Self {
+      state,
+      url_regex: Mutex::new(None),
+    }
   }
 
   #[allow(dead_code)]
@@ -1358,6 +1850,14 @@ impl TerminalBackend for VteBackend {
     self.state.lock().scrollback.len()
   }
 
+  fn trim_scrollback(&self) -> Option<usize> {
+    // The scrollback is capped as lines scroll in; this only reports what
+    // was dropped since the last call.
+    let mut s = self.state.lock();
+    let evicted = std::mem::take(&mut s.scrollback_evicted);
+    (!std::mem::take(&mut s.scrollback_erased)).then_some(evicted)
+  }
+
   fn screen_lines(&self) -> usize {
     self.state.lock().num_lines
   }
@@ -1707,11 +2207,28 @@ impl TerminalBackend for VteBackend {
 
   fn find_hyperlink_at(
     &self,
-    _point: AlacPoint,
-    _url_regex_pattern: &str,
+    point: AlacPoint,
+    url_regex_pattern: &str,
   ) -> Option<(String, bool, std::ops::RangeInclusive<AlacPoint>)> {
-    // Regex-based URL detection not yet implemented for the VTE backend.
-    None
+    let s = self.state.lock();
+
+    // Check for explicit OSC 8 hyperlink first.
+    if let Some(link) = cell_at_state(&s, point).hyperlink() {
+      let range = hyperlink_bounds_state(&s, point, &link);
+      return Some((link.uri().to_owned(), true, range));
+    }
+
+    // Fall back to regex-based URL search.
+    let mut url_regex = self.url_regex.lock();
+    if url_regex
+      .as_ref()
+      .is_none_or(|(pattern, _)| pattern != url_regex_pattern)
+    {
+      let regex = Regex::new(url_regex_pattern).ok()?;
+      *url_regex = Some((url_regex_pattern.to_owned(), regex));
+    }
+    let (_, regex) = url_regex.as_ref()?;
+    regex_match_at_state(&s, point, regex).map(|(url, range)| (url, true, range))
   }
 }
 
@@ -1733,7 +2250,14 @@ mod tests {
   fn renderable_snapshot_exposes_simple_selection() {
     let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
     let state = Arc::new(Mutex::new(VteTermInner::new(
-      2, 5, 100, event_tx, None, true,
+      2,
+      5,
+      100,
+      event_tx,
+      None,
+      None,
+      true,
+      Osc52::default(),
     )));
 
     {
@@ -1759,7 +2283,14 @@ mod tests {
   fn selection_to_string_uses_visible_range() {
     let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
     let state = Arc::new(Mutex::new(VteTermInner::new(
-      2, 5, 100, event_tx, None, true,
+      2,
+      5,
+      100,
+      event_tx,
+      None,
+      None,
+      true,
+      Osc52::default(),
     )));
 
     {
@@ -1787,7 +2318,7 @@ mod tests {
   #[test]
   fn osc_4_sets_palette_entries_and_answers_queries() {
     let (event_tx, mut event_rx) = futures::channel::mpsc::unbounded();
-    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, true);
+    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, None, true, Osc52::default());
 
     feed(&mut inner, b"\x1b]4;1;rgb:12/34/56\x07");
     assert_eq!(
@@ -1819,7 +2350,7 @@ mod tests {
   #[test]
   fn osc_10_sets_default_foreground_and_answers_queries() {
     let (event_tx, mut event_rx) = futures::channel::mpsc::unbounded();
-    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, true);
+    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, None, true, Osc52::default());
 
     feed(&mut inner, b"\x1b]10;#aabbcc\x07");
     assert_eq!(
@@ -1851,7 +2382,7 @@ mod tests {
   #[test]
   fn osc_11_sets_default_background_and_answers_queries() {
     let (event_tx, mut event_rx) = futures::channel::mpsc::unbounded();
-    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, true);
+    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, None, true, Osc52::default());
 
     feed(&mut inner, b"\x1b]11;#123456\x07");
     assert_eq!(
@@ -1879,4 +2410,426 @@ mod tests {
       other => panic!("expected OSC 11 color request, got {other:?}"),
     }
   }
+
+  fn row_text(row: &[Cell]) -> String {
+    row
+      .iter()
+      .map(|cell| cell.c)
+      .collect::<String>()
+      .trim_end()
+      .to_string()
+  }
+
+  fn screen_text(inner: &VteTermInner) -> Vec<String> {
+    inner.rows.iter().map(|row| row_text(row)).collect()
+  }
+
+  fn history_text(inner: &VteTermInner) -> Vec<String> {
+    inner.scrollback.iter().map(|row| row_text(row)).collect()
+  }
+
+  #[test]
+  fn resize_narrower_rewraps_lines_into_scrollback() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(2, 10, 100, event_tx, None, None, true, Osc52::default());
+
+    feed(&mut inner, b"abcdefgh\r\n$ ");
+    inner.do_resize(2, 4);
+
+    assert_eq!(history_text(&inner), vec!["abcd"]);
+    assert_eq!(screen_text(&inner), vec!["efgh", "$"]);
+    assert!(row_is_wrapped(&inner.scrollback[0]));
+    assert!(!row_is_wrapped(&inner.rows[0]));
+    assert_eq!(inner.cursor.point, AlacPoint::new(Line(1), Column(2)));
+  }
+
+  #[test]
+  fn resize_narrower_keeps_cursor_on_its_screen_line() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(4, 8, 100, event_tx, None, None, true, Osc52::default());
+
+    feed(&mut inner, b"one\r\nabcdefgh\r\n$ ");
+    inner.do_resize(4, 4);
+
+    assert_eq!(history_text(&inner), vec!["one"]);
+    assert_eq!(screen_text(&inner), vec!["abcd", "efgh", "$", ""]);
+    assert_eq!(inner.cursor.point, AlacPoint::new(Line(2), Column(2)));
+  }
+
+  #[test]
+  fn reflow_moves_wide_chars_whole_to_the_next_row() {
+    let wide = |c| {
+      let mut cell = Cell::default();
+      cell.c = c;
+      cell.flags.insert(CellFlags::WIDE_CHAR);
+      cell
+    };
+    let spacer = || {
+      let mut cell = Cell::default();
+      cell.flags.insert(CellFlags::WIDE_CHAR_SPACER);
+      cell
+    };
+    let narrow = |c| {
+      let mut cell = Cell::default();
+      cell.c = c;
+      cell
+    };
+    let row = vec![
+      narrow('a'),
+      narrow('b'),
+      wide('字'),
+      spacer(),
+      narrow('c'),
+      Cell::default(),
+    ];
+
+    let reflowed = reflow_rows(vec![row], 6, 3, 0, None);
+    assert_eq!(reflowed.rows.len(), 2);
+    assert_eq!(row_text(&reflowed.rows[0]), "ab");
+    assert!(
+      reflowed.rows[0][2]
+        .flags
+        .contains(CellFlags::LEADING_WIDE_CHAR_SPACER)
+    );
+    assert!(row_is_wrapped(&reflowed.rows[0]));
+    assert!(reflowed.rows[1][0].flags.contains(CellFlags::WIDE_CHAR));
+    assert!(
+      reflowed.rows[1][1]
+        .flags
+        .contains(CellFlags::WIDE_CHAR_SPACER)
+    );
+    assert_eq!(reflowed.rows[1][2].c, 'c');
+
+    let joined = reflow_rows(reflowed.rows, 3, 6, 0, None);
+    assert_eq!(joined.rows.len(), 1);
+    assert_eq!(row_text(&joined.rows[0]), "ab字 c");
+    assert!(
+      joined.rows[0]
+        .iter()
+        .all(|cell| !cell.flags.contains(CellFlags::LEADING_WIDE_CHAR_SPACER))
+    );
+  }
+
+  #[test]
+  fn resize_wider_joins_soft_wrapped_rows() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(4, 4, 100, event_tx, None, None, true, Osc52::default());
+
+    feed(&mut inner, b"abcdefghij\r\nxy");
+    assert_eq!(screen_text(&inner), vec!["abcd", "efgh", "ij", "xy"]);
+
+    inner.do_resize(4, 12);
+
+    assert!(inner.scrollback.is_empty());
+    assert_eq!(screen_text(&inner), vec!["abcdefghij", "xy", "", ""]);
+    assert!(!row_is_wrapped(&inner.rows[0]));
+    assert_eq!(inner.cursor.point, AlacPoint::new(Line(1), Column(2)));
+  }
+
+  #[test]
+  fn resize_keeps_hard_line_breaks() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(3, 4, 100, event_tx, None, None, true, Osc52::default());
+
+    feed(&mut inner, b"abcd\r\nefgh");
+    inner.do_resize(3, 8);
+
+    assert_eq!(screen_text(&inner), vec!["abcd", "efgh", ""]);
+  }
+
+  #[test]
+  fn resize_round_trip_restores_scrollback_layout() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(2, 6, 100, event_tx, None, None, true, Osc52::default());
+
+    feed(&mut inner, b"first line\r\nsecond\r\nthird!");
+    let history = history_text(&inner);
+    let screen = screen_text(&inner);
+
+    inner.do_resize(2, 3);
+    inner.do_resize(2, 6);
+
+    assert_eq!(history_text(&inner), history);
+    assert_eq!(screen_text(&inner), screen);
+  }
+
+  #[test]
+  fn resize_preserves_pending_wrap_at_exact_width() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(3, 6, 100, event_tx, None, None, true, Osc52::default());
+
+    feed(&mut inner, b"abcdef");
+    assert!(inner.pending_wrap);
+
+    inner.do_resize(3, 3);
+    assert_eq!(history_text(&inner), vec!["abc"]);
+    assert_eq!(screen_text(&inner), vec!["def", "", ""]);
+    assert_eq!(inner.cursor.point, AlacPoint::new(Line(0), Column(2)));
+    assert!(inner.pending_wrap);
+
+    feed(&mut inner, b"g");
+    assert_eq!(screen_text(&inner), vec!["def", "g", ""]);
+
+    inner.do_resize(3, 10);
+    assert_eq!(screen_text(&inner), vec!["abcdefg", "", ""]);
+    assert_eq!(inner.cursor.point, AlacPoint::new(Line(0), Column(7)));
+    assert!(!inner.pending_wrap);
+  }
+
+  #[test]
+  fn resize_taller_pulls_lines_back_from_scrollback() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, None, true, Osc52::default());
+
+    feed(&mut inner, b"one\r\ntwo\r\nthree");
+    assert_eq!(history_text(&inner), vec!["one"]);
+
+    inner.do_resize(4, 5);
+
+    assert!(inner.scrollback.is_empty());
+    assert_eq!(screen_text(&inner), vec!["one", "two", "three", ""]);
+    assert_eq!(inner.cursor.point, AlacPoint::new(Line(2), Column(4)));
+    assert!(inner.pending_wrap);
+  }
+
+  #[test]
+  fn resize_reflows_primary_screen_behind_alt_screen() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(2, 8, 100, event_tx, None, None, true, Osc52::default());
+
+    feed(&mut inner, b"abcdefgh\r\n$ \x1b[?1049h\x1b[Halt");
+    inner.do_resize(2, 4);
+
+    assert_eq!(screen_text(&inner), vec!["alt", ""]);
+
+    feed(&mut inner, b"\x1b[?1049l");
+    assert_eq!(history_text(&inner), vec!["abcd"]);
+    assert_eq!(screen_text(&inner), vec!["efgh", "$"]);
+    assert_eq!(inner.cursor.point, AlacPoint::new(Line(1), Column(2)));
+  }
+
+  #[test]
+  fn trim_scrollback_counts_lines_dropped_from_full_scrollback() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let state = Arc::new(Mutex::new(VteTermInner::new(
+      2,
+      10,
+      2,
+      event_tx,
+      None,
+      None,
+      true,
+      Osc52::default(),
+    )));
+    let backend = VteBackend::new(state.clone());
+
+    feed(&mut state.lock(), b"1\r\n2\r\n3\r\n4\r\n5");
+    assert_eq!(backend.history_size(), 2);
+    assert_eq!(backend.trim_scrollback(), Some(1));
+    assert_eq!(history_text(&state.lock()), vec!["2", "3"]);
+    assert_eq!(backend.trim_scrollback(), Some(0));
+
+    feed(&mut state.lock(), b"\r\n6\r\n7\r\n8\r\n9");
+    assert_eq!(backend.trim_scrollback(), Some(4));
+    assert_eq!(history_text(&state.lock()), vec!["6", "7"]);
+
+    feed(&mut state.lock(), b"\x1b[3J");
+    assert_eq!(backend.trim_scrollback(), None);
+    assert_eq!(backend.trim_scrollback(), Some(0));
+  }
+
+  const TEST_URL_REGEX: &str = r"https?://[^\s]+";
+
+  #[test]
+  fn osc_8_hyperlink_is_stored_on_printed_cells() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let state = Arc::new(Mutex::new(VteTermInner::new(
+      2,
+      20,
+      100,
+      event_tx,
+      None,
+      None,
+      true,
+      Osc52::default(),
+    )));
+
+    feed(
+      &mut state.lock(),
+      b"see \x1b]8;id=doc;https://example.com/a;b\x1b\\docs\x1b]8;;\x1b\\ now",
+    );
+
+    let backend = VteBackend::new(state);
+    let link = backend
+      .cell_at(AlacPoint::new(Line(0), Column(5)))
+      .hyperlink()
+      .unwrap();
+    assert_eq!(link.id(), "doc");
+    assert_eq!(link.uri(), "https://example.com/a;b");
+    assert!(
+      backend
+        .cell_at(AlacPoint::new(Line(0), Column(8)))
+        .hyperlink()
+        .is_none()
+    );
+
+    let (url, is_url, range) = backend
+      .find_hyperlink_at(AlacPoint::new(Line(0), Column(6)), TEST_URL_REGEX)
+      .unwrap();
+    assert_eq!(url, "https://example.com/a;b");
+    assert!(is_url);
+    assert_eq!(
+      range,
+      AlacPoint::new(Line(0), Column(4))..=AlacPoint::new(Line(0), Column(7))
+    );
+  }
+
+  #[test]
+  fn osc_8_hyperlink_range_follows_wrapped_rows() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let state = Arc::new(Mutex::new(VteTermInner::new(
+      3,
+      5,
+      100,
+      event_tx,
+      None,
+      None,
+      true,
+      Osc52::default(),
+    )));
+
+    feed(
+      &mut state.lock(),
+      b"ab\x1b]8;;https://x.test\x07linked\x1b]8;;\x07!",
+    );
+
+    let backend = VteBackend::new(state);
+    let (url, _, range) = backend
+      .find_hyperlink_at(AlacPoint::new(Line(1), Column(0)), TEST_URL_REGEX)
+      .unwrap();
+    assert_eq!(url, "https://x.test");
+    assert_eq!(
+      range,
+      AlacPoint::new(Line(0), Column(2))..=AlacPoint::new(Line(1), Column(2))
+    );
+  }
+
+  #[test]
+  fn find_hyperlink_at_matches_url_regex_across_wrapped_rows() {
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let state = Arc::new(Mutex::new(VteTermInner::new(
+      3,
+      10,
+      100,
+      event_tx,
+      None,
+      None,
+      true,
+      Osc52::default(),
+    )));
+
+    feed(&mut state.lock(), b"go https://a.test/path ok");
+
+    let backend = VteBackend::new(state);
+    let (url, is_url, range) = backend
+      .find_hyperlink_at(AlacPoint::new(Line(1), Column(4)), TEST_URL_REGEX)
+      .unwrap();
+    assert_eq!(url, "https://a.test/path");
+    assert!(is_url);
+    assert_eq!(
+      range,
+      AlacPoint::new(Line(0), Column(3))..=AlacPoint::new(Line(2), Column(1))
+    );
+
+    assert!(
+      backend
+        .find_hyperlink_at(AlacPoint::new(Line(0), Column(0)), TEST_URL_REGEX)
+        .is_none()
+    );
+    assert!(
+      backend
+        .find_hyperlink_at(AlacPoint::new(Line(2), Column(3)), TEST_URL_REGEX)
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn osc_52_store_emits_clipboard_event_when_copy_is_allowed() {
+    let (event_tx, mut event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, None, true, Osc52::OnlyCopy);
+
+    feed(&mut inner, b"\x1b]52;c;aGVsbG8=\x07");
+    match block_on(event_rx.next()) {
+      Some(terminal_kernel::event::Event::ClipboardStore(ClipboardType::Clipboard, text)) => {
+        assert_eq!(text, "hello");
+      }
+      other => panic!("expected OSC 52 clipboard store, got {other:?}"),
+    }
+
+    // Loading is denied in copy-only mode.
+    feed(&mut inner, b"\x1b]52;c;?\x07");
+    drop(inner);
+    assert!(block_on(event_rx.next()).is_none());
+  }
+
+  #[test]
+  fn osc_52_query_answers_with_clipboard_contents_when_paste_is_allowed() {
+    let (event_tx, mut event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, None, true, Osc52::CopyPaste);
+
+    feed(&mut inner, b"\x1b]52;p;?\x1b\\");
+    match block_on(event_rx.next()) {
+      Some(terminal_kernel::event::Event::ClipboardLoad(ClipboardType::Selection, formatter)) => {
+        assert_eq!(formatter("hello"), "\x1b]52;p;aGVsbG8=\x1b\\");
+      }
+      other => panic!("expected OSC 52 clipboard load, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn osc_52_is_ignored_when_disabled() {
+    let (event_tx, mut event_rx) = futures::channel::mpsc::unbounded();
+    let mut inner = VteTermInner::new(2, 5, 100, event_tx, None, None, true, Osc52::Disabled);
+
+    feed(&mut inner, b"\x1b]52;c;aGVsbG8=\x07\x1b]52;c;?\x07");
+    drop(inner);
+    assert!(block_on(event_rx.next()).is_none());
+  }
+
+  #[test]
+  fn osc_133_marks_carry_absolute_cursor_line() {
+    use terminal::shell_integration::SemanticMarkKind;
+
+    let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+    let (semantic_tx, semantic_rx) = std::sync::mpsc::channel();
+    let mut inner = VteTermInner::new(
+      2,
+      5,
+      100,
+      event_tx,
+      None,
+      Some(semantic_tx),
+      true,
+      Osc52::default(),
+    );
+
+    // Three lines on a two-line screen push one line into scrollback.
+    feed(
+      &mut inner,
+      b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\x1b]133;D;1\x07",
+    );
+    let marks: Vec<_> = semantic_rx
+      .try_iter()
+      .map(|mark| (mark.kind, mark.line))
+      .collect();
+    assert_eq!(
+      marks,
+      vec![
+        (SemanticMarkKind::PromptStart, 0),
+        (SemanticMarkKind::InputStart, 0),
+        (SemanticMarkKind::OutputStart, 1),
+        (SemanticMarkKind::CommandFinished { exit_code: Some(1) }, 2),
+      ]
+    );
+  }
 }
@@ -0,0 +1,107 @@
+//! Runs the shared kernel conformance fixtures against `VteBackend`.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use terminal_kernel::TerminalBackend;
+use terminal_kernel::conformance::{self, Check, KernelHarness, KnownGap};
+use terminal_kernel::term::Osc52;
+use terminal_kernel_vte::vte_term::{VteBackend, VteTermInner};
+
+const TRAILING: &str = "trailing blanks on the last line are not trimmed";
+const WRAP: &str = "soft-wrapped rows are joined with a newline";
+
+const KNOWN_GAPS: &[KnownGap] = &[
+  KnownGap {
+    fixture: "plain_text",
+    check: Check::Bounds,
+    reason: TRAILING,
+  },
+  KnownGap {
+    fixture: "soft_wrap",
+    check: Check::Bounds,
+    reason: WRAP,
+  },
+  KnownGap {
+    fixture: "soft_wrap",
+    check: Check::Selection,
+    reason: WRAP,
+  },
+  KnownGap {
+    fixture: "erase_display",
+    check: Check::Scrollback,
+    reason: "erasing the display does not save the screen to scrollback",
+  },
+  KnownGap {
+    fixture: "tab_stops",
+    check: Check::Bounds,
+    reason: "tabs are stored as spaces, so copied text loses them",
+  },
+  KnownGap {
+    fixture: "wide_chars",
+    check: Check::Cursor,
+    reason: "wide characters occupy a single cell",
+  },
+  KnownGap {
+    fixture: "wide_chars",
+    check: Check::Bounds,
+    reason: TRAILING,
+  },
+  KnownGap {
+    fixture: "scrollback",
+    check: Check::Bounds,
+    reason: TRAILING,
+  },
+  KnownGap {
+    fixture: "selection_lines",
+    check: Check::Selection,
+    reason: TRAILING,
+  },
+  KnownGap {
+    fixture: "resize_reflow_shrink",
+    check: Check::Bounds,
+    reason: WRAP,
+  },
+];
+
+struct Vte {
+  state: Arc<Mutex<VteTermInner>>,
+  backend: VteBackend,
+  parser: vte::Parser,
+}
+
+impl KernelHarness for Vte {
+  fn feed(&mut self, bytes: &[u8]) {
+    self.parser.advance(&mut *self.state.lock(), bytes);
+  }
+
+  fn backend(&self) -> &dyn TerminalBackend {
+    &self.backend
+  }
+}
+
+#[test]
+fn vte_backend_conforms() {
+  conformance::run(
+    "vte",
+    |lines, cols| {
+      let (event_tx, _event_rx) = futures::channel::mpsc::unbounded();
+      let state = Arc::new(Mutex::new(VteTermInner::new(
+        lines,
+        cols,
+        10_000,
+        event_tx,
+        None,
+        None,
+        false,
+        Osc52::OnlyCopy,
+      )));
+      Vte {
+        backend: VteBackend::new(state.clone()),
+        state,
+        parser: vte::Parser::new(),
+      }
+    },
+    KNOWN_GAPS,
+  );
+}
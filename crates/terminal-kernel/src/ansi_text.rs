@@ -0,0 +1,186 @@
+//! ANSI-preserving text extraction shared by every `TerminalBackend`.
+
+use std::fmt::Write as _;
+
+use crate::backend::TerminalBackend;
+use crate::index::{Column, Line, Point as AlacPoint};
+use crate::term::cell::{Cell, Flags};
+use crate::vte::ansi::{Color, NamedColor};
+
+/// Like `bounds_to_string`, but every change of colors or attributes is
+/// emitted as an SGR sequence so the text renders the same in another terminal.
+///
+/// Lines that end in a soft wrap are joined without a newline, and trailing
+/// unstyled blanks are trimmed as in the plain variant.
+pub(crate) fn bounds_to_ansi_string<B: TerminalBackend + ?Sized>(
+  backend: &B,
+  start: AlacPoint,
+  end: AlacPoint,
+) -> String {
+  let last_column = backend.last_column();
+  let mut result = String::new();
+  let mut active = String::new();
+
+  for line in start.line.0..=end.line.0 {
+    let start_col = if line == start.line.0 {
+      start.column.0
+    } else {
+      0
+    };
+    let end_col = if line == end.line.0 {
+      end.column.0
+    } else {
+      last_column.0
+    };
+    let cells = (start_col..=end_col)
+      .map(|col| backend.cell_at(AlacPoint::new(Line(line), Column(col))))
+      .collect::<Vec<_>>();
+    let wrapped = line != end.line.0
+      && backend
+        .cell_at(AlacPoint::new(Line(line), last_column))
+        .flags
+        .contains(Flags::WRAPLINE);
+    let len = if wrapped {
+      cells.len()
+    } else {
+      cells
+        .iter()
+        .rposition(|cell| !is_blank(cell))
+        .map_or(0, |ix| ix + 1)
+    };
+
+    for cell in &cells[..len] {
+      if cell
+        .flags
+        .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+      {
+        continue;
+      }
+      let params = sgr_params(cell);
+      if params != active {
+        let _ = write!(result, "\x1b[0{params}m");
+        active = params;
+      }
+      result.push(cell.c);
+      if let Some(zerowidth) = cell.zerowidth() {
+        result.extend(zerowidth);
+      }
+    }
+
+    if line != end.line.0 && !wrapped {
+      if !active.is_empty() {
+        result.push_str("\x1b[0m");
+        active.clear();
+      }
+      result.push('\n');
+    }
+  }
+
+  if !active.is_empty() {
+    result.push_str("\x1b[0m");
+  }
+  result
+}
+
+/// A cell that renders as empty space with the default background.
+fn is_blank(cell: &Cell) -> bool {
+  cell.c == ' '
+    && cell.bg == Color::Named(NamedColor::Background)
+    && !cell
+      .flags
+      .intersects(Flags::INVERSE | Flags::ALL_UNDERLINES | Flags::STRIKEOUT)
+    && cell.zerowidth().is_none()
+}
+
+/// SGR parameters (each prefixed with `;`) that reproduce the cell's style.
+/// Empty for the default style.
+fn sgr_params(cell: &Cell) -> String {
+  let mut params = String::new();
+  for (flag, code) in [
+    (Flags::BOLD, 1),
+    (Flags::DIM, 2),
+    (Flags::ITALIC, 3),
+    (Flags::ALL_UNDERLINES, 4),
+    (Flags::INVERSE, 7),
+    (Flags::HIDDEN, 8),
+    (Flags::STRIKEOUT, 9),
+  ] {
+    if cell.flags.intersects(flag) {
+      let _ = write!(params, ";{code}");
+    }
+  }
+  push_color(&mut params, cell.fg, 30, 90, 38);
+  push_color(&mut params, cell.bg, 40, 100, 48);
+  params
+}
+
+fn push_color(params: &mut String, color: Color, base: usize, bright_base: usize, extended: u8) {
+  match color {
+    Color::Named(named) => {
+      let index = named as usize;
+      let dim_black = NamedColor::DimBlack as usize;
+      let code = match index {
+        0..=7 => base + index,
+        8..=15 => bright_base + index - 8,
+        _ if (dim_black..dim_black + 8).contains(&index) => base + index - dim_black,
+        // Default foreground/background and the other special slots.
+        _ => return,
+      };
+      let _ = write!(params, ";{code}");
+    }
+    Color::Indexed(index) => {
+      let _ = write!(params, ";{extended};5;{index}");
+    }
+    Color::Spec(rgb) => {
+      let _ = write!(params, ";{extended};2;{};{};{}", rgb.r, rgb.g, rgb.b);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::event::VoidListener;
+  use crate::index::{Column, Line, Point as AlacPoint};
+  use crate::test_support::backend_with;
+  use crate::{AlacrittyBackend, TerminalBackend};
+
+  fn screen_ansi(backend: &AlacrittyBackend<VoidListener>) -> String {
+    backend.bounds_to_ansi_string(
+      AlacPoint::new(Line(0), Column(0)),
+      AlacPoint::new(Line(2), Column(7)),
+    )
+  }
+
+  #[test]
+  fn styled_runs_are_wrapped_in_sgr() {
+    let backend = backend_with(
+      3,
+      8,
+      b"\x1b[1;31mred\x1b[0m ok\r\n\x1b[38;5;200;44mx\x1b[0m",
+    );
+    assert_eq!(
+      screen_ansi(&backend),
+      "\x1b[0;1;31mred\x1b[0m ok\n\x1b[0;38;5;200;44mx\x1b[0m\n"
+    );
+  }
+
+  #[test]
+  fn soft_wrapped_lines_are_joined() {
+    let backend = backend_with(3, 8, b"abcdefghij\x1b[38;2;1;2;3mk");
+    assert_eq!(
+      screen_ansi(&backend),
+      "abcdefghij\x1b[0;38;2;1;2;3mk\x1b[0m\n"
+    );
+  }
+
+  #[test]
+  fn plain_text_matches_bounds_to_string() {
+    let backend = backend_with(3, 8, b"one\r\ntwo  \r\n");
+    let start = AlacPoint::new(Line(0), Column(0));
+    let end = AlacPoint::new(Line(1), Column(7));
+    assert_eq!(
+      backend.bounds_to_ansi_string(start, end),
+      backend.bounds_to_string(start, end)
+    );
+  }
+}
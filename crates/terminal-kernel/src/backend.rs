@@ -47,6 +47,18 @@ pub trait TerminalBackend: Send + Sync {
   fn total_lines(&self) -> usize {
     self.history_size() + self.screen_lines()
   }
+  /// Report how many lines left the top of the scrollback since the last
+  /// call, dropping lines beyond the configured limit first if the kernel
+  /// defers that to here. Callers holding absolute line numbers (top of the
+  /// scrollback = 0) subtract the count to keep them on the same text.
+  ///
+  /// `None` means lines were lost without being counted — the scrollback
+  /// was erased (ED 3), or more lines were dropped than the kernel could
+  /// track. Absolute line numbers taken before the call are then
+  /// meaningless and have to be discarded, not rebased.
+  fn trim_scrollback(&self) -> Option<usize> {
+    Some(0)
+  }
   fn topmost_line(&self) -> Line;
   fn bottommost_line(&self) -> Line;
   fn last_column(&self) -> Column;
@@ -73,6 +85,10 @@ pub trait TerminalBackend: Send + Sync {
 
   fn selection_to_string(&self) -> Option<String>;
   fn bounds_to_string(&self, start: AlacPoint, end: AlacPoint) -> String;
+  /// `bounds_to_string` with colors and attributes kept as SGR sequences.
+  fn bounds_to_ansi_string(&self, start: AlacPoint, end: AlacPoint) -> String {
+    crate::ansi_text::bounds_to_ansi_string(self, start, end)
+  }
 
   // --- Selection state ---
 
@@ -127,11 +143,25 @@ pub trait TerminalBackend: Send + Sync {
 /// access the same `Term`. All trait methods lock internally.
 pub struct AlacrittyBackend<L: EventListener + Send> {
   term: Arc<FairMutex<Term<L>>>,
+  /// Scrollback kept by `trim_scrollback`. `Term` has no count of the lines
+  /// it evicts, so it is created with `SCROLLBACK_SLACK` extra lines of
+  /// history and the lines past the limit are dropped and counted here.
+  history_limit: Option<usize>,
 }
 
 impl<L: EventListener + Send> AlacrittyBackend<L> {
   pub fn new(term: Arc<FairMutex<Term<L>>>) -> Self {
-    Self { term }
+    Self {
+      term,
+      history_limit: None,
+    }
+  }
+
+  /// Trim scrollback to `limit` lines in `trim_scrollback`. The `Term` must
+  /// have been created with `limit + SCROLLBACK_SLACK` lines of history.
+  pub fn with_history_limit(mut self, limit: usize) -> Self {
+    self.history_limit = Some(limit);
+    self
   }
 
   /// Get the shared term Arc for the EventLoop.
@@ -145,6 +175,24 @@ impl<L: EventListener + Send> TerminalBackend for AlacrittyBackend<L> {
     self.term.lock().history_size()
   }
 
+  fn trim_scrollback(&self) -> Option<usize> {
+    let Some(limit) = self.history_limit else {
+      return Some(0);
+    };
+    let mut term = self.term.lock();
+    let history = term.history_size();
+    if history <= limit {
+      return Some(0);
+    }
+    // Shrinking the history drops its oldest lines; the slack is restored
+    // straight away so the next lines scroll in without evicting anything.
+    term.grid_mut().update_history(limit);
+    term
+      .grid_mut()
+      .update_history(limit + crate::SCROLLBACK_SLACK);
+    (history < limit + crate::SCROLLBACK_SLACK).then_some(history - limit)
+  }
+
   fn screen_lines(&self) -> usize {
     self.term.lock().screen_lines()
   }
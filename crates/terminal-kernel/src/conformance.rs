@@ -0,0 +1,540 @@
+//! Conformance fixtures shared by every `TerminalBackend` implementation.
+//!
+//! Each fixture feeds the same escape sequences to a fresh kernel and checks
+//! what the UI observes through the trait: the rendered screen, the cursor,
+//! scrollback, selection text and `bounds_to_string`. Kernels run the whole
+//! suite from their integration tests and list the checks they are known to
+//! get wrong, so divergences stay visible instead of being discovered through
+//! rendering bugs.
+//!
+//! Exposed the same way as `test_support`; see that module for why.
+
+use std::fmt;
+
+use crate::backend::{SelectionDisplay, TerminalBackend};
+use crate::grid::Scroll;
+use crate::index::{Column, Line, Point as AlacPoint, Side};
+use crate::selection::{Selection, SelectionType};
+use crate::term::cell::Flags;
+
+/// A kernel instance the suite can drive.
+pub trait KernelHarness {
+  /// Parse `bytes` as if they had been read from the PTY.
+  fn feed(&mut self, bytes: &[u8]);
+  fn backend(&self) -> &dyn TerminalBackend;
+}
+
+/// One assertion made against a fixture.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Check {
+  Screen,
+  Cursor,
+  Bounds,
+  Selection,
+  Scrollback,
+}
+
+/// A check a kernel is known to fail, with the reason it is tolerated.
+///
+/// The runner fails when a listed gap starts passing, so the list has to be
+/// trimmed as kernels catch up.
+pub struct KnownGap {
+  pub fixture: &'static str,
+  pub check: Check,
+  pub reason: &'static str,
+}
+
+pub struct SelectionCase {
+  pub ty: SelectionType,
+  pub start: (i32, usize, Side),
+  pub end: (i32, usize, Side),
+  pub text: &'static str,
+}
+
+pub struct BoundsCase {
+  pub start: (i32, usize),
+  pub end: (i32, usize),
+  pub text: &'static str,
+}
+
+pub struct Fixture {
+  pub name: &'static str,
+  pub lines: usize,
+  pub cols: usize,
+  pub input: &'static [u8],
+  /// `(lines, cols)` to resize to once `input` has been fed.
+  pub resize: Option<(usize, usize)>,
+  /// Visible rows with trailing blanks trimmed; trailing empty rows omitted.
+  pub screen: &'static [&'static str],
+  pub cursor: (i32, usize),
+  /// History rows, oldest first.
+  pub scrollback: &'static [&'static str],
+  pub selection: Option<SelectionCase>,
+  pub bounds: Option<BoundsCase>,
+}
+
+impl Fixture {
+  const fn new(
+    name: &'static str,
+    lines: usize,
+    cols: usize,
+    input: &'static [u8],
+    screen: &'static [&'static str],
+    cursor: (i32, usize),
+  ) -> Self {
+    Self {
+      name,
+      lines,
+      cols,
+      input,
+      resize: None,
+      screen,
+      cursor,
+      scrollback: &[],
+      selection: None,
+      bounds: None,
+    }
+  }
+
+  const fn resize(mut self, lines: usize, cols: usize) -> Self {
+    self.resize = Some((lines, cols));
+    self
+  }
+
+  const fn scrollback(mut self, rows: &'static [&'static str]) -> Self {
+    self.scrollback = rows;
+    self
+  }
+
+  const fn selection(mut self, selection: SelectionCase) -> Self {
+    self.selection = Some(selection);
+    self
+  }
+
+  const fn bounds(mut self, start: (i32, usize), end: (i32, usize), text: &'static str) -> Self {
+    self.bounds = Some(BoundsCase { start, end, text });
+    self
+  }
+}
+
+const fn select(
+  ty: SelectionType,
+  start: (i32, usize, Side),
+  end: (i32, usize, Side),
+  text: &'static str,
+) -> SelectionCase {
+  SelectionCase {
+    ty,
+    start,
+    end,
+    text,
+  }
+}
+
+pub const FIXTURES: &[Fixture] = &[
+  Fixture::new(
+    "plain_text",
+    3,
+    10,
+    b"hello\r\nworld",
+    &["hello", "world"],
+    (1, 5),
+  )
+  .bounds((0, 0), (1, 9), "hello\nworld"),
+  Fixture::new("soft_wrap", 3, 5, b"abcdefgh", &["abcde", "fgh"], (1, 3))
+    .bounds((0, 0), (1, 4), "abcdefgh")
+    .selection(select(
+      SelectionType::Simple,
+      (0, 2, Side::Left),
+      (1, 1, Side::Right),
+      "cdefg",
+    )),
+  Fixture::new("pending_wrap_cursor", 2, 5, b"abcde", &["abcde"], (0, 4)),
+  Fixture::new(
+    "cursor_positioning",
+    3,
+    10,
+    b"abcdef\x1b[1;1HXY\x1b[3;4HZ",
+    &["XYcdef", "", "   Z"],
+    (2, 4),
+  ),
+  Fixture::new(
+    "relative_cursor_moves",
+    3,
+    10,
+    b"abc\x1b[2D\x1b[1B\x1b[3CX\x1b[1A\x1b[1Dy",
+    &["abc y", "    X"],
+    (0, 5),
+  ),
+  Fixture::new(
+    "erase_in_line",
+    2,
+    10,
+    b"abcdefgh\x1b[4G\x1b[K\r\n12345678\x1b[4G\x1b[1K",
+    &["abc", "    5678"],
+    (1, 3),
+  ),
+  Fixture::new(
+    "erase_display",
+    3,
+    10,
+    b"junk\r\nmore\r\nstuff\x1b[2J\x1b[Hfresh",
+    &["fresh"],
+    (0, 5),
+  )
+  .scrollback(&["junk", "more", "stuff"]),
+  Fixture::new(
+    "erase_below",
+    3,
+    10,
+    b"one\r\ntwo\r\nthree\x1b[2;2H\x1b[J",
+    &["one", "t"],
+    (1, 1),
+  ),
+  Fixture::new(
+    "insert_delete_chars",
+    2,
+    10,
+    b"abcdef\x1b[1;2H\x1b[2P\r\n123456\x1b[2;3H\x1b[2@",
+    &["adef", "12  3456"],
+    (1, 2),
+  ),
+  Fixture::new(
+    "insert_delete_lines",
+    4,
+    10,
+    b"a\r\nb\r\nc\r\nd\x1b[2;1H\x1b[1L\x1b[4;1H\x1b[1M",
+    &["a", "", "b"],
+    (3, 0),
+  ),
+  Fixture::new(
+    "scroll_region",
+    4,
+    10,
+    b"a\r\nb\r\nc\r\nd\x1b[2;3r\x1b[3;1H\n",
+    &["a", "c", "", "d"],
+    (2, 0),
+  ),
+  Fixture::new(
+    "tab_stops",
+    1,
+    20,
+    b"a\tb\tc",
+    &["a       b       c"],
+    (0, 17),
+  )
+  .bounds((0, 0), (0, 19), "a\tb\tc"),
+  Fixture::new("wide_chars", 2, 10, "a你b".as_bytes(), &["a你b"], (0, 4)).bounds(
+    (0, 0),
+    (0, 9),
+    "a你b",
+  ),
+  Fixture::new(
+    "alternate_screen",
+    3,
+    10,
+    b"main\x1b[?1049h\x1b[Halt\x1b[?1049l",
+    &["main"],
+    (0, 4),
+  ),
+  Fixture::new(
+    "scrollback",
+    2,
+    10,
+    b"one\r\ntwo\r\nthree\r\nfour",
+    &["three", "four"],
+    (1, 4),
+  )
+  .scrollback(&["one", "two"])
+  .bounds((-2, 0), (1, 9), "one\ntwo\nthree\nfour"),
+  Fixture::new(
+    "selection_simple",
+    3,
+    12,
+    b"hello world\r\nsecond line",
+    &["hello world", "second line"],
+    (1, 11),
+  )
+  .selection(select(
+    SelectionType::Simple,
+    (0, 6, Side::Left),
+    (1, 5, Side::Right),
+    "world\nsecond",
+  )),
+  Fixture::new(
+    "selection_lines",
+    3,
+    12,
+    b"first\r\nsecond\r\nthird",
+    &["first", "second", "third"],
+    (2, 5),
+  )
+  .selection(select(
+    SelectionType::Lines,
+    (0, 3, Side::Left),
+    (1, 1, Side::Right),
+    "first\nsecond\n",
+  )),
+  Fixture::new(
+    "selection_block",
+    3,
+    10,
+    b"abcdef\r\nghijkl\r\nmnopqr",
+    &["abcdef", "ghijkl", "mnopqr"],
+    (2, 6),
+  )
+  .selection(select(
+    SelectionType::Block,
+    (0, 1, Side::Left),
+    (2, 3, Side::Right),
+    "bcd\nhij\nnop",
+  )),
+  Fixture::new(
+    "resize_reflow_grow",
+    3,
+    4,
+    b"abcdefgh",
+    &["abcdefgh"],
+    (0, 8),
+  )
+  .resize(3, 10),
+  Fixture::new(
+    "resize_reflow_shrink",
+    3,
+    10,
+    b"abcdefgh",
+    &["efgh"],
+    (0, 3),
+  )
+  .resize(3, 4)
+  .scrollback(&["abcd"])
+  .bounds((-1, 0), (0, 3), "abcdefgh"),
+];
+
+fn expect<T: PartialEq + fmt::Debug>(what: &str, actual: T, expected: T) -> Result<(), String> {
+  if actual == expected {
+    Ok(())
+  } else {
+    Err(format!("{what}: expected {expected:?}, got {actual:?}"))
+  }
+}
+
+fn point(line: i32, column: usize) -> AlacPoint {
+  AlacPoint::new(Line(line), Column(column))
+}
+
+fn is_spacer(flags: Flags) -> bool {
+  flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+}
+
+/// Kernels may keep `\t` in the cell a tab started from; it renders blank.
+fn display_char(c: char) -> char {
+  match c {
+    '\0' | '\t' => ' ',
+    c => c,
+  }
+}
+
+/// The visible rows of the latest snapshot, trailing blanks trimmed.
+fn visible_rows(backend: &dyn TerminalBackend) -> Result<Vec<String>, String> {
+  let snapshot = backend.renderable_snapshot();
+  let lines = backend.screen_lines();
+  expect(
+    "snapshot cell count",
+    snapshot.cells.len(),
+    lines * backend.columns(),
+  )?;
+
+  let mut rows = vec![String::new(); lines];
+  for (point, cell) in &snapshot.cells {
+    let row = point.line.0 + snapshot.display_offset as i32;
+    let Some(text) = usize::try_from(row).ok().and_then(|row| rows.get_mut(row)) else {
+      return Err(format!("snapshot cell outside the viewport at {point:?}"));
+    };
+    if !is_spacer(cell.flags) {
+      text.push(display_char(cell.c));
+    }
+  }
+
+  for row in &mut rows {
+    row.truncate(row.trim_end().len());
+  }
+  while rows.last().is_some_and(String::is_empty) {
+    rows.pop();
+  }
+  Ok(rows)
+}
+
+fn row_text(backend: &dyn TerminalBackend, line: i32) -> String {
+  let mut text = String::new();
+  for column in 0..backend.columns() {
+    let cell = backend.cell_at(point(line, column));
+    if !is_spacer(cell.flags) {
+      text.push(display_char(cell.c));
+    }
+  }
+  text.truncate(text.trim_end().len());
+  text
+}
+
+fn check_screen(backend: &dyn TerminalBackend, fixture: &Fixture) -> Result<(), String> {
+  expect("display offset", backend.display_offset(), 0)?;
+  expect("screen", visible_rows(backend)?, owned(fixture.screen))
+}
+
+fn check_cursor(backend: &dyn TerminalBackend, fixture: &Fixture) -> Result<(), String> {
+  let (line, column) = fixture.cursor;
+  expect("cursor", backend.cursor_point(), point(line, column))
+}
+
+fn check_bounds(backend: &dyn TerminalBackend, case: &BoundsCase) -> Result<(), String> {
+  let start = point(case.start.0, case.start.1);
+  let end = point(case.end.0, case.end.1);
+  expect(
+    "bounds_to_string",
+    backend.bounds_to_string(start, end),
+    case.text.to_string(),
+  )
+}
+
+/// Apply the selection the way `Terminal` does: the alacritty `Selection`
+/// plus the display anchors kernels without their own selection model use.
+fn check_selection(backend: &dyn TerminalBackend, case: &SelectionCase) -> Result<(), String> {
+  let (start_line, start_column, start_side) = case.start;
+  let (end_line, end_column, end_side) = case.end;
+  let start = point(start_line, start_column);
+  let end = point(end_line, end_column);
+
+  let mut selection = Selection::new(case.ty, start, start_side);
+  selection.update(end, end_side);
+  backend.set_selection(Some(selection));
+  backend.sync_selection_display(Some(SelectionDisplay {
+    ty: case.ty,
+    start,
+    start_side,
+    end,
+    end_side,
+  }));
+
+  let text = backend.selection_to_string();
+
+  backend.set_selection(None);
+  backend.sync_selection_display(None);
+  expect("selection_to_string", text, Some(case.text.to_string()))
+}
+
+fn check_scrollback(backend: &dyn TerminalBackend, fixture: &Fixture) -> Result<(), String> {
+  let history = fixture.scrollback.len();
+  expect("history size", backend.history_size(), history)?;
+  expect(
+    "total lines",
+    backend.total_lines(),
+    history + backend.screen_lines(),
+  )?;
+  expect(
+    "topmost line",
+    backend.topmost_line(),
+    Line(-(history as i32)),
+  )?;
+
+  let rows: Vec<String> = (-(history as i32)..0)
+    .map(|line| row_text(backend, line))
+    .collect();
+  expect("history", rows, owned(fixture.scrollback))?;
+
+  if history == 0 {
+    return Ok(());
+  }
+
+  backend.scroll_display(Scroll::Top);
+  let offset = backend.display_offset();
+  let top = visible_rows(backend);
+  backend.scroll_display(Scroll::Bottom);
+
+  expect("display offset at top", offset, history)?;
+  expect(
+    "first row scrolled to top",
+    top?.first().cloned(),
+    Some(fixture.scrollback[0].to_string()),
+  )?;
+  expect("display offset at bottom", backend.display_offset(), 0)
+}
+
+fn owned(rows: &[&str]) -> Vec<String> {
+  rows.iter().map(|row| row.to_string()).collect()
+}
+
+fn run_fixture(
+  harness: &mut dyn KernelHarness,
+  fixture: &Fixture,
+) -> Vec<(Check, Result<(), String>)> {
+  harness.feed(fixture.input);
+  let backend = harness.backend();
+  if let Some((lines, cols)) = fixture.resize {
+    backend.resize(lines, cols);
+  }
+
+  let mut results = vec![
+    (Check::Screen, check_screen(backend, fixture)),
+    (Check::Cursor, check_cursor(backend, fixture)),
+  ];
+  if let Some(bounds) = &fixture.bounds {
+    results.push((Check::Bounds, check_bounds(backend, bounds)));
+  }
+  if let Some(selection) = &fixture.selection {
+    results.push((Check::Selection, check_selection(backend, selection)));
+  }
+  results.push((Check::Scrollback, check_scrollback(backend, fixture)));
+  results
+}
+
+/// Run every fixture against kernels built by `new_kernel(lines, cols)`.
+///
+/// Panics with one line per unexpected failure, and for every known gap that
+/// now passes or names a check the suite never makes.
+pub fn run<H: KernelHarness>(
+  kernel: &str,
+  new_kernel: impl Fn(usize, usize) -> H,
+  known_gaps: &[KnownGap],
+) {
+  let mut failures = Vec::new();
+  let mut gaps_seen = vec![false; known_gaps.len()];
+
+  for fixture in FIXTURES {
+    let mut harness = new_kernel(fixture.lines, fixture.cols);
+    for (check, result) in run_fixture(&mut harness, fixture) {
+      let gap = known_gaps
+        .iter()
+        .position(|gap| gap.fixture == fixture.name && gap.check == check);
+      if let Some(index) = gap {
+        gaps_seen[index] = true;
+      }
+
+      match (result, gap) {
+        (Ok(()), None) | (Err(_), Some(_)) => {}
+        (Err(mismatch), None) => {
+          failures.push(format!("{}/{check:?}: {mismatch}", fixture.name));
+        }
+        (Ok(()), Some(index)) => failures.push(format!(
+          "{}/{check:?} passes but is listed as a known gap ({}); remove it",
+          fixture.name, known_gaps[index].reason,
+        )),
+      }
+    }
+  }
+
+  for (gap, seen) in known_gaps.iter().zip(gaps_seen) {
+    if !seen {
+      failures.push(format!(
+        "{}/{:?} is listed as a known gap but no such check runs",
+        gap.fixture, gap.check,
+      ));
+    }
+  }
+
+  assert!(
+    failures.is_empty(),
+    "{kernel} kernel conformance failures:\n  {}",
+    failures.join("\n  "),
+  );
+}
@@ -42,13 +42,36 @@ pub mod vte {
 
 pub type SessionEvents = UnboundedReceiver<event::Event>;
 
+/// Map the `terminal.osc52` config value to the clipboard access it allows.
+pub fn parse_osc52(mode: &str) -> term::Osc52 {
+  match mode {
+    "disabled" => term::Osc52::Disabled,
+    "paste_only" => term::Osc52::OnlyPaste,
+    "copy_paste" => term::Osc52::CopyPaste,
+    _ => term::Osc52::OnlyCopy,
+  }
+}
+
 // ---------------------------------------------------------------------------
 // TerminalBackend trait
 // ---------------------------------------------------------------------------
 
+mod ansi_text;
 mod backend;
 pub use backend::{AlacrittyBackend, RenderableSnapshot, SelectionDisplay, TerminalBackend};
 
+/// Lines of history an [`AlacrittyBackend`] with a history limit keeps
+/// beyond it until [`TerminalBackend::trim_scrollback`] drops and counts
+/// them. It bounds the lines that can scroll in between two syncs without
+/// losing track of the count, and matches the rows `Term` already keeps
+/// cached past its history.
+pub const SCROLLBACK_SLACK: usize = 1000;
+
+#[doc(hidden)]
+pub mod conformance;
+#[doc(hidden)]
+pub mod test_support;
+
 // ---------------------------------------------------------------------------
 // Color conversion helpers
 // ---------------------------------------------------------------------------
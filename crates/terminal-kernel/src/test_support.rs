@@ -0,0 +1,39 @@
+//! Test-only helpers for building in-memory `AlacrittyBackend`s.
+//!
+//! Ungated and `#[doc(hidden)]` for the same reasons as `terminal::test_support`.
+
+use std::sync::Arc;
+
+use crate::AlacrittyBackend;
+use crate::event::VoidListener;
+use crate::grid::Dimensions;
+use crate::sync::FairMutex;
+use crate::term::{Config, Term};
+use crate::vte::ansi::Processor;
+
+/// Grid size for constructing a `Term` without a window.
+pub struct StubDims {
+  pub cols: usize,
+  pub lines: usize,
+}
+
+impl Dimensions for StubDims {
+  fn total_lines(&self) -> usize {
+    self.lines
+  }
+  fn screen_lines(&self) -> usize {
+    self.lines
+  }
+  fn columns(&self) -> usize {
+    self.cols
+  }
+}
+
+/// A `lines` x `cols` backend that has parsed `bytes`.
+pub fn backend_with(lines: usize, cols: usize, bytes: &[u8]) -> AlacrittyBackend<VoidListener> {
+  let term = Term::new(Config::default(), &StubDims { cols, lines }, VoidListener);
+  let term = Arc::new(FairMutex::new(term));
+  let mut parser: Processor = Processor::new();
+  parser.advance(&mut *term.lock(), bytes);
+  AlacrittyBackend::new(term)
+}
@@ -0,0 +1,51 @@
+//! Runs the shared kernel conformance fixtures against `AlacrittyBackend`.
+
+use std::sync::Arc;
+
+use terminal_kernel::AlacrittyBackend;
+use terminal_kernel::TerminalBackend;
+use terminal_kernel::conformance::{self, KernelHarness, KnownGap};
+use terminal_kernel::event::VoidListener;
+use terminal_kernel::sync::FairMutex;
+use terminal_kernel::term::{Config, Term};
+use terminal_kernel::test_support::StubDims;
+use terminal_kernel::vte::ansi::Processor;
+
+/// Alacritty is the reference kernel; anything it fails is a fixture bug.
+const KNOWN_GAPS: &[KnownGap] = &[];
+
+struct Alacritty {
+  term: Arc<FairMutex<Term<VoidListener>>>,
+  backend: AlacrittyBackend<VoidListener>,
+  parser: Processor,
+}
+
+impl KernelHarness for Alacritty {
+  fn feed(&mut self, bytes: &[u8]) {
+    self.parser.advance(&mut *self.term.lock(), bytes);
+  }
+
+  fn backend(&self) -> &dyn TerminalBackend {
+    &self.backend
+  }
+}
+
+#[test]
+fn alacritty_backend_conforms() {
+  conformance::run(
+    "alacritty",
+    |lines, cols| {
+      let term = Arc::new(FairMutex::new(Term::new(
+        Config::default(),
+        &StubDims { cols, lines },
+        VoidListener,
+      )));
+      Alacritty {
+        backend: AlacrittyBackend::new(term.clone()),
+        term,
+        parser: Processor::new(),
+      }
+    },
+    KNOWN_GAPS,
+  );
+}
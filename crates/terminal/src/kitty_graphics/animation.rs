@@ -0,0 +1,291 @@
+//! Frame timing and composition for animated images (`a=f`, `a=a`).
+
+use std::time::{Duration, Instant};
+
+/// Gap after a frame when the client doesn't give one.
+pub const DEFAULT_FRAME_GAP_MS: u32 = 40;
+
+/// Playback state set with `a=a,s=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationState {
+  /// Show the current frame only (s=1).
+  Stopped,
+  /// Play, but wait on the last frame for more to arrive (s=2).
+  Loading,
+  /// Play and loop (s=3).
+  Running,
+}
+
+/// Frame gaps, loop count and playback position of an image.
+#[derive(Debug, Clone)]
+pub struct Animation {
+  /// Milliseconds each frame is shown for. 0 marks a gapless frame, which
+  /// is skipped during playback.
+  pub gaps: Vec<u32>,
+  pub state: AnimationState,
+  /// Index of the frame shown.
+  pub current: usize,
+  /// Loops to play before stopping on the last frame (0 = forever).
+  pub max_loops: u32,
+  loops_played: u32,
+  /// When the current frame's gap is over, while playing.
+  deadline: Option<Instant>,
+}
+
+impl Animation {
+  /// A still image: one root frame, stopped.
+  pub fn new() -> Self {
+    Self {
+      gaps: vec![DEFAULT_FRAME_GAP_MS],
+      state: AnimationState::Stopped,
+      current: 0,
+      max_loops: 0,
+      loops_played: 0,
+      deadline: None,
+    }
+  }
+
+  pub fn frame_count(&self) -> usize {
+    self.gaps.len()
+  }
+
+  /// Append a frame. A loading animation waiting on its last frame resumes.
+  pub fn push_frame(&mut self, gap_ms: u32, now: Instant) {
+    self.gaps.push(gap_ms);
+    if self.state == AnimationState::Loading && self.deadline.is_none() {
+      self.schedule(now);
+    }
+  }
+
+  /// Drop every frame but the root frame.
+  pub fn truncate_to_root(&mut self) {
+    self.gaps.truncate(1);
+    self.current = 0;
+    self.deadline = None;
+  }
+
+  pub fn set_state(&mut self, state: AnimationState, now: Instant) {
+    self.state = state;
+    self.loops_played = 0;
+    if state == AnimationState::Stopped {
+      self.deadline = None;
+    } else if self.deadline.is_none() {
+      self.schedule(now);
+    }
+  }
+
+  /// Show frame `index` and restart its gap.
+  pub fn set_current(&mut self, index: usize, now: Instant) {
+    self.current = index.min(self.gaps.len() - 1);
+    if self.state != AnimationState::Stopped {
+      self.schedule(now);
+    }
+  }
+
+  /// When the shown frame has to change, if the animation is playing.
+  pub fn deadline(&self) -> Option<Instant> {
+    self.deadline
+  }
+
+  /// Move to the next frame if the current one's gap is over.
+  /// Returns whether the shown frame changed.
+  pub fn advance(&mut self, now: Instant) -> bool {
+    let Some(deadline) = self.deadline else {
+      return false;
+    };
+    if now < deadline {
+      return false;
+    }
+    match self.next_frame() {
+      Some(next) => {
+        let changed = next != self.current;
+        self.current = next;
+        self.schedule(now);
+        changed
+      }
+      None => {
+        self.deadline = None;
+        if self.state == AnimationState::Running {
+          self.state = AnimationState::Stopped;
+        }
+        false
+      }
+    }
+  }
+
+  /// The frame after the current one, skipping gapless frames, or `None`
+  /// when playback has to wait or stop on the current frame.
+  fn next_frame(&mut self) -> Option<usize> {
+    let shown = |ix: &usize| self.gaps[*ix] > 0;
+    if let Some(next) = (self.current + 1..self.gaps.len()).find(shown) {
+      return Some(next);
+    }
+    match self.state {
+      AnimationState::Running if self.max_loops == 0 || self.loops_played + 1 < self.max_loops => {
+        self.loops_played += 1;
+        (0..self.gaps.len()).find(shown)
+      }
+      _ => None,
+    }
+  }
+
+  fn schedule(&mut self, now: Instant) {
+    let gap = self.gaps[self.current].max(1);
+    self.deadline = Some(now + Duration::from_millis(gap as u64));
+  }
+}
+
+impl Default for Animation {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// A BGRA canvas filled with `background`, given as `0xRRGGBBAA` like `Y=`.
+pub fn blank_canvas(width: u32, height: u32, background: u32) -> Vec<u8> {
+  let [r, g, b, a] = background.to_be_bytes();
+  [b, g, r, a].repeat(width as usize * height as usize)
+}
+
+/// Draw the BGRA pixels `src` onto `canvas` with their top-left corner at
+/// (`x`, `y`), alpha blending them unless `overwrite` is set. Pixels outside
+/// the canvas are dropped.
+#[allow(clippy::too_many_arguments)]
+pub fn compose(
+  canvas: &mut [u8],
+  canvas_width: u32,
+  canvas_height: u32,
+  src: &[u8],
+  src_width: u32,
+  src_height: u32,
+  x: u32,
+  y: u32,
+  overwrite: bool,
+) {
+  let columns = src_width.min(canvas_width.saturating_sub(x)) as usize;
+  let rows = src_height.min(canvas_height.saturating_sub(y)) as usize;
+  if columns == 0 || rows == 0 {
+    return;
+  }
+  for row in 0..rows {
+    let src_start = row * src_width as usize * 4;
+    let dst_start = ((y as usize + row) * canvas_width as usize + x as usize) * 4;
+    let src_row = &src[src_start..src_start + columns * 4];
+    let dst_row = &mut canvas[dst_start..dst_start + columns * 4];
+    if overwrite {
+      dst_row.copy_from_slice(src_row);
+      continue;
+    }
+    for (dst, src) in dst_row.chunks_exact_mut(4).zip(src_row.chunks_exact(4)) {
+      blend(dst, src);
+    }
+  }
+}
+
+/// Porter-Duff "over" on one non-premultiplied BGRA pixel.
+fn blend(dst: &mut [u8], src: &[u8]) {
+  let src_a = src[3] as u32;
+  let dst_a = dst[3] as u32 * (255 - src_a) / 255;
+  let out_a = src_a + dst_a;
+  if out_a == 0 {
+    dst.copy_from_slice(&[0, 0, 0, 0]);
+    return;
+  }
+  for channel in 0..3 {
+    dst[channel] = ((src[channel] as u32 * src_a + dst[channel] as u32 * dst_a) / out_a) as u8;
+  }
+  dst[3] = out_a as u8;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn animation(gaps: &[u32], state: AnimationState, now: Instant) -> Animation {
+    let mut animation = Animation::new();
+    animation.gaps = gaps.to_vec();
+    animation.set_state(state, now);
+    animation
+  }
+
+  #[test]
+  fn test_running_animation_loops_and_skips_gapless_frames() {
+    let start = Instant::now();
+    let mut anim = animation(&[10, 0, 20], AnimationState::Running, start);
+
+    assert!(!anim.advance(start + Duration::from_millis(5)));
+    let t = start + Duration::from_millis(10);
+    assert!(anim.advance(t));
+    assert_eq!(anim.current, 2);
+    assert_eq!(anim.deadline(), Some(t + Duration::from_millis(20)));
+
+    assert!(anim.advance(t + Duration::from_millis(20)));
+    assert_eq!(anim.current, 0);
+  }
+
+  #[test]
+  fn test_loop_count_stops_on_last_frame() {
+    let start = Instant::now();
+    let mut anim = animation(&[10, 10], AnimationState::Running, start);
+    anim.max_loops = 2;
+
+    let mut now = start;
+    let mut shown = vec![anim.current];
+    for _ in 0..5 {
+      now += Duration::from_millis(10);
+      anim.advance(now);
+      shown.push(anim.current);
+    }
+    assert_eq!(shown, vec![0, 1, 0, 1, 1, 1]);
+    assert_eq!(anim.state, AnimationState::Stopped);
+    assert_eq!(anim.deadline(), None);
+  }
+
+  #[test]
+  fn test_loading_waits_for_more_frames() {
+    let start = Instant::now();
+    let mut anim = animation(&[10, 10], AnimationState::Loading, start);
+
+    let now = start + Duration::from_millis(10);
+    assert!(anim.advance(now));
+    assert!(!anim.advance(now + Duration::from_millis(10)));
+    assert_eq!(anim.current, 1);
+    assert_eq!(anim.deadline(), None);
+
+    let now = now + Duration::from_millis(15);
+    anim.push_frame(10, now);
+    assert!(anim.advance(now + Duration::from_millis(10)));
+    assert_eq!(anim.current, 2);
+  }
+
+  #[test]
+  fn test_stopped_animation_does_not_advance() {
+    let start = Instant::now();
+    let mut anim = animation(&[10, 10], AnimationState::Stopped, start);
+    assert_eq!(anim.deadline(), None);
+    assert!(!anim.advance(start + Duration::from_secs(1)));
+    anim.set_current(1, start);
+    assert_eq!(anim.current, 1);
+  }
+
+  #[test]
+  fn test_compose_blends_and_clips() {
+    // 2x1 opaque blue canvas (BGRA).
+    let mut canvas = blank_canvas(2, 1, 0x0000ffff);
+    assert_eq!(canvas, [255, 0, 0, 255, 255, 0, 0, 255]);
+
+    // Half-transparent red, 2x1, placed at x=1 so one pixel is clipped.
+    let src = [0, 0, 255, 128, 0, 0, 255, 128];
+    compose(&mut canvas, 2, 1, &src, 2, 1, 1, 0, false);
+    assert_eq!(&canvas[..4], [255, 0, 0, 255]);
+    assert_eq!(&canvas[4..], [127, 0, 128, 255]);
+
+    compose(&mut canvas, 2, 1, &src, 2, 1, 0, 0, true);
+    assert_eq!(canvas, src);
+
+    // Entirely off the canvas: nothing is drawn.
+    compose(&mut canvas, 2, 1, &[0; 8], 2, 1, 5, 0, true);
+    compose(&mut canvas, 2, 1, &[0; 8], 2, 1, 0, 3, true);
+    assert_eq!(canvas, src);
+  }
+}
@@ -2,6 +2,8 @@ use std::sync::Arc;
 
 use gpui::RenderImage;
 
+use super::animation::{Animation, AnimationState};
+
 /// Kitty graphics protocol action types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KittyAction {
@@ -15,6 +17,10 @@ pub enum KittyAction {
   Delete,
   /// Query terminal for graphics support.
   Query,
+  /// Transmit data for an animation frame.
+  Frame,
+  /// Control an animation.
+  Animate,
 }
 
 /// Image data format.
@@ -39,6 +45,12 @@ impl Default for KittyFormat {
 pub enum KittyTransmission {
   /// Direct (inline base64 data).
   Direct,
+  /// The payload is the path of a file to read.
+  File,
+  /// The payload is the path of a temporary file to read and then delete.
+  TempFile,
+  /// The payload is the name of a POSIX shared memory object to read and unlink.
+  SharedMemory,
 }
 
 impl Default for KittyTransmission {
@@ -47,26 +59,74 @@ impl Default for KittyTransmission {
   }
 }
 
-/// What to delete.
+/// What to delete. Rows and columns are 0-based screen coordinates.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KittyDelete {
-  /// Delete all images visible on screen.
+  /// Delete all placements visible on screen.
   All,
-  /// Delete image by ID (and optionally placement).
-  ById {
-    image_id: u32,
+  /// Delete all placements of an image by ID.
+  ById { image_id: u32 },
+  /// Delete one placement of an image.
+  ByPlacement { image_id: u32, placement_id: u32 },
+  /// Delete the placements of the newest image with an image number
+  /// (and optionally only one placement).
+  ByNumber {
+    image_number: u32,
     placement_id: Option<u32>,
   },
-  /// Delete all placements at the cursor position.
+  /// Delete all images with an ID in `first..=last`.
+  IdRange { first: u32, last: u32 },
+  /// Delete all placements intersecting the cursor position.
   AtCursor,
-  /// Delete all images with z-index matching a given value.
-  ByZIndex(i32),
-  /// Delete all images on the current column.
+  /// Delete all placements intersecting a cell, optionally only those on
+  /// one z-index.
+  AtCell {
+    column: u32,
+    row: u32,
+    z_index: Option<i32>,
+  },
+  /// Delete all placements intersecting a column.
   AtColumn(u32),
-  /// Delete all images on the current row.
+  /// Delete all placements intersecting a row.
   AtRow(u32),
+  /// Delete all placements with z-index matching a given value.
+  ByZIndex(i32),
   /// Delete all animation frames.
   AnimationFrames,
+  /// A specifier this terminal doesn't know; rejected with `EINVAL`.
+  Unsupported(String),
+}
+
+/// Frame composition for `a=f`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KittyFrame {
+  /// 1-based frame to edit (r=); 0 appends a new frame.
+  pub frame: u32,
+  /// 1-based frame a new frame starts as a copy of (c=); 0 = background.
+  pub base_frame: u32,
+  /// Where the data goes in the frame, in pixels (x=, y=).
+  pub x: u32,
+  pub y: u32,
+  /// Replace pixels instead of alpha blending (X=1).
+  pub overwrite: bool,
+  /// Background of a new frame as 0xRRGGBBAA (Y=).
+  pub background: u32,
+  /// Gap in milliseconds (z=): 0 = unchanged or default, negative = gapless.
+  pub gap_ms: i32,
+}
+
+/// Animation control for `a=a`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KittyAnimationControl {
+  /// New playback state (s=).
+  pub state: Option<AnimationState>,
+  /// 1-based frame whose gap to change (r=), with the gap (z=).
+  pub frame: u32,
+  pub gap_ms: i32,
+  /// 1-based frame to show (c=).
+  pub current_frame: u32,
+  /// Loop count (v=): 0 = unchanged, 1 = forever, n = n - 1 loops.
+  pub loops: u32,
 }
 
 /// A parsed Kitty graphics protocol command.
@@ -77,6 +137,8 @@ pub struct KittyCommand {
   pub transmission: KittyTransmission,
   /// Image ID (0 = auto-assign).
   pub image_id: u32,
+  /// Image number (I=, 0 = none), resolved to the newest image stored with it.
+  pub image_number: u32,
   /// Placement ID (0 = none).
   pub placement_id: u32,
   /// Image width in pixels (for raw formats).
@@ -101,14 +163,26 @@ pub struct KittyCommand {
   pub crop_height: u32,
   /// Z-index for layering (default 0).
   pub z_index: i32,
+  /// Bytes to read from a file or shared memory (0 = all).
+  pub data_size: u32,
+  /// Byte offset to start reading a file or shared memory at.
+  pub data_offset: u32,
   /// Whether more chunks follow (m=1).
   pub more_chunks: bool,
   /// Quiet mode: 0=default, 1=suppress OK, 2=suppress errors too.
   pub quiet: u8,
   /// Cursor movement policy: 0=move cursor, 1=don't move.
   pub cursor_movement: u8,
+  /// Create a virtual placement shown through placeholder cells (U=1).
+  pub unicode_placeholder: bool,
   /// Delete specification (only for Delete action).
   pub delete: Option<KittyDelete>,
+  /// Uppercase delete: also free images left without placements.
+  pub delete_image_data: bool,
+  /// Frame parameters (only for Frame action).
+  pub frame: Option<KittyFrame>,
+  /// Animation parameters (only for Animate action).
+  pub animation: Option<KittyAnimationControl>,
   /// The base64-encoded payload data.
   pub payload: Vec<u8>,
 }
@@ -120,6 +194,7 @@ impl Default for KittyCommand {
       format: KittyFormat::default(),
       transmission: KittyTransmission::default(),
       image_id: 0,
+      image_number: 0,
       placement_id: 0,
       source_width: 0,
       source_height: 0,
@@ -132,65 +207,142 @@ impl Default for KittyCommand {
       crop_width: 0,
       crop_height: 0,
       z_index: 0,
+      data_size: 0,
+      data_offset: 0,
       more_chunks: false,
       quiet: 0,
       cursor_movement: 0,
+      unicode_placeholder: false,
       delete: None,
+      delete_image_data: false,
+      frame: None,
+      animation: None,
       payload: Vec::new(),
     }
   }
 }
 
+/// Why a command failed, with the POSIX-style code the protocol reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KittyError {
+  /// Error code such as `ENOENT`, `EINVAL` or `EFBIG`.
+  pub code: &'static str,
+  pub message: String,
+}
+
+impl KittyError {
+  pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+    Self {
+      code,
+      message: message.into(),
+    }
+  }
+}
+
+impl std::fmt::Display for KittyError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}:{}", self.code, self.message)
+  }
+}
+
 /// Response sent back through the PTY to the client application.
 #[derive(Debug, Clone)]
 pub struct KittyResponse {
   pub image_id: u32,
+  /// Image number (I=) of the command, echoed so the client learns the ID
+  /// assigned to it.
+  pub image_number: u32,
   pub placement_id: u32,
   pub message: String,
   pub ok: bool,
+  /// Error code reported before the message when `ok` is false.
+  pub code: &'static str,
 }
 
 impl KittyResponse {
   pub fn ok(image_id: u32) -> Self {
     Self {
       image_id,
+      image_number: 0,
       placement_id: 0,
       message: "OK".to_string(),
       ok: true,
+      code: "",
     }
   }
 
   pub fn ok_with_placement(image_id: u32, placement_id: u32) -> Self {
     Self {
       image_id,
+      image_number: 0,
       placement_id,
       message: "OK".to_string(),
       ok: true,
+      code: "",
     }
   }
 
   pub fn error(image_id: u32, msg: impl Into<String>) -> Self {
     Self {
       image_id,
+      image_number: 0,
       placement_id: 0,
       message: msg.into(),
       ok: false,
+      code: "ENOENT",
+    }
+  }
+
+  pub fn from_error(image_id: u32, error: &KittyError) -> Self {
+    Self {
+      code: error.code,
+      ..Self::error(image_id, error.message.clone())
+    }
+  }
+
+  /// The reply to `cmd`, if it wants one. `result` holds the ID of the image
+  /// the command acted on, which for `I=` transmissions is the one just
+  /// assigned. Commands with neither an image ID nor a number get no reply,
+  /// and `q=1`/`q=2` silence successes and errors respectively.
+  pub fn for_command(cmd: &KittyCommand, result: Result<u32, KittyError>) -> Option<Self> {
+    if cmd.image_id == 0 && cmd.image_number == 0 {
+      return None;
+    }
+    match result {
+      Ok(image_id) if cmd.quiet == 0 => Some(Self {
+        image_number: cmd.image_number,
+        ..Self::ok_with_placement(image_id, cmd.placement_id)
+      }),
+      Err(error) if cmd.quiet < 2 => Some(Self {
+        image_number: cmd.image_number,
+        placement_id: cmd.placement_id,
+        ..Self::from_error(cmd.image_id, &error)
+      }),
+      _ => None,
     }
   }
 
-  /// Encode as an APC response: `\x1b_Gi=<id>,I=<pid>;OK\x1b\\`
+  /// Encode as an APC response: `\x1b_Gi=<id>,I=<number>,p=<pid>;OK\x1b\\`.
+  /// Keys that are zero are left out.
   pub fn encode(&self) -> Vec<u8> {
+    let keys = [
+      ("i", self.image_id),
+      ("I", self.image_number),
+      ("p", self.placement_id),
+    ]
+    .into_iter()
+    .filter(|&(_, value)| value != 0)
+    .map(|(key, value)| format!("{key}={value}"))
+    .collect::<Vec<_>>();
     let mut buf = Vec::with_capacity(64);
     buf.extend_from_slice(b"\x1b_G");
-    buf.extend_from_slice(format!("i={}", self.image_id).as_bytes());
-    if self.placement_id != 0 {
-      buf.extend_from_slice(format!(",I={}", self.placement_id).as_bytes());
-    }
+    buf.extend_from_slice(keys.join(",").as_bytes());
     buf.push(b';');
     if self.ok {
       buf.extend_from_slice(self.message.as_bytes());
     } else {
-      buf.extend_from_slice(b"ENOENT:");
+      buf.extend_from_slice(self.code.as_bytes());
+      buf.push(b':');
       buf.extend_from_slice(self.message.as_bytes());
     }
     buf.extend_from_slice(b"\x1b\\");
@@ -202,11 +354,26 @@ impl KittyResponse {
 #[derive(Clone)]
 pub struct StoredImage {
   pub id: u32,
-  pub render_image: Arc<RenderImage>,
+  /// Frames in playback order, the root frame first. Still images have one.
+  pub frames: Vec<Arc<RenderImage>>,
   pub width: u32,
   pub height: u32,
-  /// Estimated memory usage in bytes.
+  /// Estimated memory usage in bytes, across all frames.
   pub memory_bytes: usize,
+  /// Frame gaps, loop count and the frame shown.
+  pub animation: Animation,
+}
+
+impl StoredImage {
+  /// The frame to draw now.
+  pub fn current_frame(&self) -> &Arc<RenderImage> {
+    &self.frames[self.animation.current]
+  }
+
+  /// Bytes one frame takes up.
+  pub fn frame_bytes(&self) -> usize {
+    (self.width as usize) * (self.height as usize) * 4
+  }
 }
 
 /// An active image placement in the terminal grid.
@@ -231,9 +398,33 @@ pub struct ImagePlacement {
   pub y_offset: u32,
 }
 
+/// A `U=1` placement. It has no position of its own: it shows wherever
+/// placeholder cells name its image.
+#[derive(Debug, Clone)]
+pub struct VirtualPlacement {
+  pub image_id: u32,
+  pub placement_id: u32,
+  /// Size of the whole image in cells.
+  pub columns: u32,
+  pub rows: u32,
+  pub z_index: i32,
+}
+
+/// A virtual placement with its image data, for drawing placeholder cells.
+#[derive(Clone)]
+pub struct PlaceholderImage {
+  pub image_id: u32,
+  pub placement_id: u32,
+  pub render_image: Arc<RenderImage>,
+  pub columns: u32,
+  pub rows: u32,
+  pub z_index: i32,
+}
+
 /// Raw graphics command with cursor position captured at intercept time.
 pub struct RawGraphicsCommand {
-  /// The raw APC content (everything after 'G' prefix).
+  /// The raw APC content (everything after 'G' prefix), or for a sixel
+  /// image the DCS content (everything after `\x1bP`).
   pub data: Vec<u8>,
   /// Absolute line number in the grid when APC was intercepted.
   pub cursor_line: i32,
@@ -241,6 +432,14 @@ pub struct RawGraphicsCommand {
   pub cursor_column: i32,
   /// When true, signals that all images should be cleared (terminal reset/clear).
   pub clear_all: bool,
+  /// When true, the scrollback was erased (`ESC[3J` or a reset) along with the
+  /// images, so absolute lines recorded before it no longer apply.
+  pub clear_scrollback: bool,
+  /// When true, `data` is a sixel image rather than a Kitty command.
+  pub sixel: bool,
+  /// When true, the PTY filter already moved the cursor below the image, so
+  /// placing it must not ask for another advance through `pending_cnl`.
+  pub cursor_advanced: bool,
 }
 
 /// A placement that's been resolved for the current viewport with its image data.
@@ -260,4 +459,51 @@ pub struct VisiblePlacement {
   /// Pixel offsets within the starting cell.
   pub x_offset: u32,
   pub y_offset: u32,
+  /// For a slice shown by placeholder cells: the cells it is clipped to, as
+  /// (viewport line, column, width in cells).
+  pub clip: Option<(i32, i32, u32)>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_response_for_command() {
+    let mut cmd = KittyCommand {
+      image_id: 7,
+      placement_id: 2,
+      ..Default::default()
+    };
+    let ok = KittyResponse::for_command(&cmd, Ok(7)).unwrap();
+    assert_eq!(ok.encode(), b"\x1b_Gi=7,p=2;OK\x1b\\");
+    let error = KittyError::new("EBADPNG", "bad");
+    let err = KittyResponse::for_command(&cmd, Err(error.clone())).unwrap();
+    assert_eq!(err.encode(), b"\x1b_Gi=7,p=2;EBADPNG:bad\x1b\\");
+
+    cmd.quiet = 1;
+    assert!(KittyResponse::for_command(&cmd, Ok(7)).is_none());
+    assert!(KittyResponse::for_command(&cmd, Err(error.clone())).is_some());
+    cmd.quiet = 2;
+    assert!(KittyResponse::for_command(&cmd, Err(error.clone())).is_none());
+
+    cmd.quiet = 0;
+    cmd.image_id = 0;
+    assert!(KittyResponse::for_command(&cmd, Err(error)).is_none());
+  }
+
+  #[test]
+  fn test_response_for_image_number() {
+    // a=t,I=5: the reply carries the ID the image was stored under.
+    let cmd = KittyCommand {
+      action: KittyAction::Transmit,
+      image_number: 5,
+      ..Default::default()
+    };
+    let ok = KittyResponse::for_command(&cmd, Ok(12)).unwrap();
+    assert_eq!(ok.encode(), b"\x1b_Gi=12,I=5;OK\x1b\\");
+    let error = KittyError::new("EINVAL", "bad");
+    let err = KittyResponse::for_command(&cmd, Err(error)).unwrap();
+    assert_eq!(err.encode(), b"\x1b_GI=5;EINVAL:bad\x1b\\");
+  }
 }
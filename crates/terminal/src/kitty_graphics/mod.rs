@@ -1,14 +1,21 @@
+pub mod animation;
 pub mod command;
 pub mod parser;
+pub mod placeholder;
 pub mod placement;
 pub mod pty_filter;
+pub mod sixel;
 pub mod storage;
+pub mod transmission;
 
+pub use animation::{Animation, AnimationState};
 pub use command::{
-  ImagePlacement, KittyAction, KittyCommand, KittyDelete, KittyFormat, KittyResponse,
-  KittyTransmission, RawGraphicsCommand, StoredImage, VisiblePlacement,
+  ImagePlacement, KittyAction, KittyAnimationControl, KittyCommand, KittyDelete, KittyError,
+  KittyFormat, KittyFrame, KittyResponse, KittyTransmission, PlaceholderImage, RawGraphicsCommand,
+  StoredImage, VirtualPlacement, VisiblePlacement,
 };
 pub use parser::KittyParser;
+pub use placeholder::{PLACEHOLDER, PlaceholderCell, PlaceholderRun};
 pub use placement::PlacementManager;
 #[cfg(unix)]
 pub use pty_filter::GraphicsPtyFilter;
@@ -1,16 +1,26 @@
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 
-use super::command::{KittyAction, KittyCommand, KittyDelete, KittyFormat, KittyTransmission};
+use super::{
+  animation::AnimationState,
+  command::{
+    KittyAction, KittyAnimationControl, KittyCommand, KittyDelete, KittyFormat, KittyFrame,
+    KittyTransmission,
+  },
+  transmission::MAX_TRANSMISSION_BYTES,
+};
 
 /// Parser for Kitty graphics protocol commands.
 ///
 /// Handles chunked transfers by accumulating payload data across
-/// multiple APC sequences until `more_chunks` is false.
+/// multiple APC sequences until `more_chunks` is false. A transfer that grows
+/// past [`MAX_TRANSMISSION_BYTES`] is dropped along with its remaining chunks.
 pub struct KittyParser {
   /// Accumulated payload for chunked transfers.
   chunk_buffer: Vec<u8>,
   /// The command from the first chunk (holds params while accumulating).
   pending_command: Option<KittyCommand>,
+  /// Set after an oversized transfer was dropped, until its last chunk.
+  discarding: bool,
 }
 
 impl KittyParser {
@@ -18,6 +28,7 @@ impl KittyParser {
     Self {
       chunk_buffer: Vec::new(),
       pending_command: None,
+      discarding: false,
     }
   }
 
@@ -37,6 +48,20 @@ impl KittyParser {
       Vec::new()
     };
 
+    if self.discarding {
+      // Rest of a dropped transfer.
+      self.discarding = cmd.more_chunks;
+      return None;
+    }
+    if self.pending_command.is_some()
+      && self.chunk_buffer.len() + decoded.len() > MAX_TRANSMISSION_BYTES
+    {
+      tracing::warn!("Dropping kitty image transfer larger than {MAX_TRANSMISSION_BYTES} bytes");
+      self.reset();
+      self.discarding = cmd.more_chunks;
+      return None;
+    }
+
     if cmd.more_chunks {
       // Intermediate chunk: accumulate and wait for more.
       if self.pending_command.is_none() {
@@ -61,8 +86,9 @@ impl KittyParser {
 
   /// Reset any in-progress chunked transfer.
   pub fn reset(&mut self) {
-    self.chunk_buffer.clear();
+    self.chunk_buffer = Vec::new();
     self.pending_command = None;
+    self.discarding = false;
   }
 }
 
@@ -79,6 +105,7 @@ fn split_params_payload(raw: &[u8]) -> (&[u8], &[u8]) {
 fn parse_params(params: &[u8]) -> KittyCommand {
   let mut cmd = KittyCommand::default();
   let params_str = std::str::from_utf8(params).unwrap_or("");
+  let mut delete = None;
 
   for pair in params_str.split(',') {
     let pair = pair.trim();
@@ -97,6 +124,8 @@ fn parse_params(params: &[u8]) -> KittyCommand {
           "p" => KittyAction::Display,
           "d" => KittyAction::Delete,
           "q" => KittyAction::Query,
+          "f" => KittyAction::Frame,
+          "a" => KittyAction::Animate,
           _ => KittyAction::TransmitAndDisplay,
         };
       }
@@ -110,12 +139,15 @@ fn parse_params(params: &[u8]) -> KittyCommand {
       }
       "t" => {
         cmd.transmission = match value {
-          "d" => KittyTransmission::Direct,
+          "f" => KittyTransmission::File,
+          "t" => KittyTransmission::TempFile,
+          "s" => KittyTransmission::SharedMemory,
           _ => KittyTransmission::Direct,
         };
       }
       "i" => cmd.image_id = value.parse().unwrap_or(0),
-      "I" => cmd.placement_id = value.parse().unwrap_or(0),
+      "I" => cmd.image_number = value.parse().unwrap_or(0),
+      "p" => cmd.placement_id = value.parse().unwrap_or(0),
       "s" => cmd.source_width = value.parse().unwrap_or(0),
       "v" => cmd.source_height = value.parse().unwrap_or(0),
       "c" => cmd.display_columns = value.parse().unwrap_or(0),
@@ -127,39 +159,98 @@ fn parse_params(params: &[u8]) -> KittyCommand {
       "X" => cmd.x_offset = value.parse().unwrap_or(0),
       "Y" => cmd.y_offset = value.parse().unwrap_or(0),
       "z" => cmd.z_index = value.parse().unwrap_or(0),
+      "S" => cmd.data_size = value.parse().unwrap_or(0),
+      "O" => cmd.data_offset = value.parse().unwrap_or(0),
       "m" => cmd.more_chunks = value == "1",
       "q" => cmd.quiet = value.parse().unwrap_or(0),
       "C" => cmd.cursor_movement = value.parse().unwrap_or(0),
-      "d" => {
-        cmd.delete = Some(parse_delete(value, &cmd));
-      }
+      "U" => cmd.unicode_placeholder = value == "1",
+      "d" => delete = Some(value),
       _ => {} // Ignore unknown keys for forward-compatibility.
     }
   }
 
+  // The delete specifier's arguments (i, I, p, x, y, z) may follow it.
+  if let Some(value) = delete {
+    cmd.delete_image_data = value.starts_with(|c: char| c.is_ascii_uppercase());
+    cmd.delete = Some(parse_delete(value, &cmd));
+  }
+  // Frame and animation commands give their own meanings to the keys
+  // parsed above.
+  match cmd.action {
+    KittyAction::Frame => {
+      cmd.frame = Some(KittyFrame {
+        frame: cmd.display_rows,
+        base_frame: cmd.display_columns,
+        x: cmd.crop_x,
+        y: cmd.crop_y,
+        overwrite: cmd.x_offset == 1,
+        background: cmd.y_offset,
+        gap_ms: cmd.z_index,
+      });
+    }
+    KittyAction::Animate => {
+      cmd.animation = Some(KittyAnimationControl {
+        state: match cmd.source_width {
+          1 => Some(AnimationState::Stopped),
+          2 => Some(AnimationState::Loading),
+          3 => Some(AnimationState::Running),
+          _ => None,
+        },
+        frame: cmd.display_rows,
+        gap_ms: cmd.z_index,
+        current_frame: cmd.display_columns,
+        loops: cmd.source_height,
+      });
+    }
+    _ => {}
+  }
   cmd
 }
 
-/// Parse the delete specifier.
-fn parse_delete(value: &str, _cmd: &KittyCommand) -> KittyDelete {
-  // Single-character codes: a=all, i=by id, c=at cursor, etc.
-  if value.is_empty() {
-    return KittyDelete::All;
-  }
-
-  let first = value.as_bytes()[0];
-  match first {
-    b'a' | b'A' => KittyDelete::All,
-    b'i' | b'I' => KittyDelete::ById {
-      image_id: 0, // Will be filled from i= param.
-      placement_id: None,
+/// Parse the delete specifier, taking its arguments from `cmd`.
+///
+/// `x` and `y` are 1-based on the wire (and share keys with the crop
+/// rectangle); they are converted to 0-based screen coordinates.
+fn parse_delete(value: &str, cmd: &KittyCommand) -> KittyDelete {
+  let column = cmd.crop_x.saturating_sub(1);
+  let row = cmd.crop_y.saturating_sub(1);
+  let placement_id = (cmd.placement_id != 0).then_some(cmd.placement_id);
+  match value.bytes().next().map(|b| b.to_ascii_lowercase()) {
+    None | Some(b'a') => KittyDelete::All,
+    Some(b'i') => match placement_id {
+      Some(placement_id) => KittyDelete::ByPlacement {
+        image_id: cmd.image_id,
+        placement_id,
+      },
+      None => KittyDelete::ById {
+        image_id: cmd.image_id,
+      },
+    },
+    Some(b'n') => KittyDelete::ByNumber {
+      image_number: cmd.image_number,
+      placement_id,
+    },
+    Some(b'r') => KittyDelete::IdRange {
+      first: cmd.crop_x,
+      last: cmd.crop_y,
     },
-    b'c' | b'C' => KittyDelete::AtCursor,
-    b'p' | b'P' => KittyDelete::AtColumn(0),
-    b'q' | b'Q' => KittyDelete::AtRow(0),
-    b'x' | b'X' => KittyDelete::ByZIndex(0),
-    b'f' | b'F' => KittyDelete::AnimationFrames,
-    _ => KittyDelete::All,
+    Some(b'c') => KittyDelete::AtCursor,
+    Some(b'p') => KittyDelete::AtCell {
+      column,
+      row,
+      z_index: None,
+    },
+    Some(b'q') => KittyDelete::AtCell {
+      column,
+      row,
+      z_index: Some(cmd.z_index),
+    },
+    Some(b'x') => KittyDelete::AtColumn(column),
+    Some(b'y') => KittyDelete::AtRow(row),
+    Some(b'z') => KittyDelete::ByZIndex(cmd.z_index),
+    Some(b'f') => KittyDelete::AnimationFrames,
+    _ => KittyDelete::Unsupported(value.to_string()),
   }
 }
 
@@ -220,6 +311,25 @@ mod tests {
     assert!(!cmd.payload.is_empty());
   }
 
+  #[test]
+  fn test_oversized_chunked_transfer_is_dropped() {
+    let mut parser = KittyParser::new();
+    assert!(parser.parse(b"a=T,f=32,i=5,m=1;AAAA").is_none());
+    parser.chunk_buffer = vec![0; MAX_TRANSMISSION_BYTES - 1];
+
+    // This chunk crosses the limit; the transfer and its remaining chunks
+    // are dropped without producing a command.
+    assert!(parser.parse(b"m=1;AAAA").is_none());
+    assert!(parser.chunk_buffer.is_empty());
+    assert!(parser.parse(b"m=1;AAAA").is_none());
+    assert!(parser.parse(b"m=0;AAAA").is_none());
+    assert!(parser.chunk_buffer.is_empty());
+
+    // The next command parses normally.
+    let cmd = parser.parse(b"a=T,f=100,i=6;AAAA").unwrap();
+    assert_eq!(cmd.image_id, 6);
+  }
+
   #[test]
   fn test_parse_delete() {
     let mut parser = KittyParser::new();
@@ -228,22 +338,162 @@ mod tests {
 
     assert_eq!(cmd.action, KittyAction::Delete);
     assert!(matches!(cmd.delete, Some(KittyDelete::All)));
+    assert!(!cmd.delete_image_data);
+  }
+
+  #[test]
+  fn test_parse_delete_variants() {
+    let mut parser = KittyParser::new();
+    for (raw, expected, frees) in [
+      (
+        "a=d,d=I,i=7,p=3",
+        KittyDelete::ByPlacement {
+          image_id: 7,
+          placement_id: 3,
+        },
+        true,
+      ),
+      ("a=d,d=i,i=7", KittyDelete::ById { image_id: 7 }, false),
+      (
+        "a=d,d=N,I=12",
+        KittyDelete::ByNumber {
+          image_number: 12,
+          placement_id: None,
+        },
+        true,
+      ),
+      (
+        "a=d,d=n,I=12,p=2",
+        KittyDelete::ByNumber {
+          image_number: 12,
+          placement_id: Some(2),
+        },
+        false,
+      ),
+      (
+        "a=d,d=r,x=2,y=9",
+        KittyDelete::IdRange { first: 2, last: 9 },
+        false,
+      ),
+      (
+        "a=d,d=p,x=3,y=4",
+        KittyDelete::AtCell {
+          column: 2,
+          row: 3,
+          z_index: None,
+        },
+        false,
+      ),
+      (
+        "a=d,x=1,y=1,z=-2,d=Q",
+        KittyDelete::AtCell {
+          column: 0,
+          row: 0,
+          z_index: Some(-2),
+        },
+        true,
+      ),
+      ("a=d,d=x,x=5", KittyDelete::AtColumn(4), false),
+      ("a=d,d=Y,y=2", KittyDelete::AtRow(1), true),
+      ("a=d,d=z,z=4", KittyDelete::ByZIndex(4), false),
+      ("a=d,d=w", KittyDelete::Unsupported("w".to_string()), false),
+    ] {
+      let cmd = parser.parse(raw.as_bytes()).unwrap();
+      assert_eq!(cmd.delete, Some(expected), "{raw}");
+      assert_eq!(cmd.delete_image_data, frees, "{raw}");
+    }
   }
 
   #[test]
   fn test_parse_display_placement() {
     let mut parser = KittyParser::new();
-    let raw = b"a=p,i=10,I=2,c=40,r=20,z=-1";
+    let raw = b"a=p,i=10,p=2,c=40,r=20,z=-1";
     let cmd = parser.parse(raw).expect("should produce command");
 
     assert_eq!(cmd.action, KittyAction::Display);
     assert_eq!(cmd.image_id, 10);
+    assert_eq!(cmd.image_number, 0);
     assert_eq!(cmd.placement_id, 2);
     assert_eq!(cmd.display_columns, 40);
     assert_eq!(cmd.display_rows, 20);
     assert_eq!(cmd.z_index, -1);
   }
 
+  #[test]
+  fn test_parse_image_number() {
+    let mut parser = KittyParser::new();
+    let cmd = parser.parse(b"a=p,I=4,p=9").unwrap();
+    assert_eq!(cmd.image_id, 0);
+    assert_eq!(cmd.image_number, 4);
+    assert_eq!(cmd.placement_id, 9);
+  }
+
+  #[test]
+  fn test_parse_file_transmission() {
+    let mut parser = KittyParser::new();
+    // "/tmp/img.rgba" in base64.
+    let raw = b"a=T,f=32,t=f,s=2,v=2,S=16,O=4;L3RtcC9pbWcucmdiYQ==";
+    let cmd = parser.parse(raw).expect("should produce command");
+
+    assert_eq!(cmd.transmission, KittyTransmission::File);
+    assert_eq!(cmd.data_size, 16);
+    assert_eq!(cmd.data_offset, 4);
+    assert_eq!(cmd.payload, b"/tmp/img.rgba");
+
+    for (medium, expected) in [
+      ("t", KittyTransmission::TempFile),
+      ("s", KittyTransmission::SharedMemory),
+      ("d", KittyTransmission::Direct),
+    ] {
+      let raw = format!("a=t,t={medium}");
+      let cmd = parser.parse(raw.as_bytes()).unwrap();
+      assert_eq!(cmd.transmission, expected);
+    }
+  }
+
+  #[test]
+  fn test_parse_frame_and_animation() {
+    let mut parser = KittyParser::new();
+    let cmd = parser
+      .parse(b"a=f,i=3,r=2,c=1,x=4,y=5,X=1,Y=4278190335,z=-1,s=2,v=2;AAAA")
+      .unwrap();
+    assert_eq!(cmd.action, KittyAction::Frame);
+    assert_eq!(
+      cmd.frame,
+      Some(KittyFrame {
+        frame: 2,
+        base_frame: 1,
+        x: 4,
+        y: 5,
+        overwrite: true,
+        background: 0xff0000ff,
+        gap_ms: -1,
+      })
+    );
+    assert_eq!(cmd.source_width, 2);
+
+    let cmd = parser.parse(b"a=a,i=3,s=3,v=1,c=2,r=1,z=100").unwrap();
+    assert_eq!(cmd.action, KittyAction::Animate);
+    assert_eq!(
+      cmd.animation,
+      Some(KittyAnimationControl {
+        state: Some(AnimationState::Running),
+        frame: 1,
+        gap_ms: 100,
+        current_frame: 2,
+        loops: 1,
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_unicode_placeholder() {
+    let mut parser = KittyParser::new();
+    let cmd = parser.parse(b"a=p,U=1,i=5,c=10,r=3").unwrap();
+    assert!(cmd.unicode_placeholder);
+    assert_eq!((cmd.display_columns, cmd.display_rows), (10, 3));
+  }
+
   #[test]
   fn test_no_payload() {
     let mut parser = KittyParser::new();
@@ -1,15 +1,19 @@
-use super::command::{ImagePlacement, VisiblePlacement};
+use super::command::{ImagePlacement, PlaceholderImage, VirtualPlacement, VisiblePlacement};
 use super::storage::KittyImageStorage;
 
 /// Manages image placements in the terminal grid.
 pub struct PlacementManager {
   placements: Vec<ImagePlacement>,
+  /// `U=1` placements, drawn through placeholder cells. Deletes by position
+  /// don't touch them.
+  virtual_placements: Vec<VirtualPlacement>,
 }
 
 impl PlacementManager {
   pub fn new() -> Self {
     Self {
       placements: Vec::new(),
+      virtual_placements: Vec::new(),
     }
   }
 
@@ -24,35 +28,115 @@ impl PlacementManager {
     self.placements.push(placement);
   }
 
+  /// Add a virtual placement, replacing one with the same image and
+  /// placement ID.
+  pub fn add_virtual(&mut self, placement: VirtualPlacement) {
+    self
+      .virtual_placements
+      .retain(|p| !(p.image_id == placement.image_id && p.placement_id == placement.placement_id));
+    self.virtual_placements.push(placement);
+  }
+
   /// Remove all placements for a given image ID.
   pub fn remove_by_image(&mut self, image_id: u32) {
     self.placements.retain(|p| p.image_id != image_id);
+    self.virtual_placements.retain(|p| p.image_id != image_id);
   }
 
   /// Remove a specific placement.
   pub fn remove_by_id(&mut self, image_id: u32, placement_id: Option<u32>) {
+    let matches = |id: u32, pid: u32| id == image_id && placement_id.is_none_or(|p| p == pid);
+    self
+      .placements
+      .retain(|p| !matches(p.image_id, p.placement_id));
+    self
+      .virtual_placements
+      .retain(|p| !matches(p.image_id, p.placement_id));
+  }
+
+  /// Remove all placements of images with an ID in `first..=last`.
+  pub fn remove_by_image_range(&mut self, first: u32, last: u32) -> Vec<u32> {
+    let range = first..=last;
+    self
+      .virtual_placements
+      .retain(|p| !range.contains(&p.image_id));
+    self.remove_where(|p| range.contains(&p.image_id))
+  }
+
+  /// Remove all placements covering a given grid position.
+  pub fn remove_at_cursor(&mut self, line: i32, column: i32) -> Vec<u32> {
+    self.remove_at_cell(line, column, None)
+  }
+
+  /// Remove all placements covering a cell, optionally only those on one
+  /// z-index.
+  pub fn remove_at_cell(&mut self, line: i32, column: i32, z_index: Option<i32>) -> Vec<u32> {
+    self.remove_where(|p| {
+      covers_line(p, line) && covers_column(p, column) && z_index.is_none_or(|z| p.z_index == z)
+    })
+  }
+
+  /// Remove all placements covering any cell of a column.
+  pub fn remove_in_column(&mut self, column: i32) -> Vec<u32> {
+    self.remove_where(|p| covers_column(p, column))
+  }
+
+  /// Remove all placements covering any of `count` lines from `top`.
+  pub fn remove_in_lines(&mut self, top: i32, count: u32) -> Vec<u32> {
+    let bottom = top + count as i32;
+    self.remove_where(|p| p.line < bottom && p.line + p.height_cells as i32 > top)
+  }
+
+  /// Remove all placements on a z-index.
+  pub fn remove_by_z_index(&mut self, z_index: i32) -> Vec<u32> {
+    self.remove_where(|p| p.z_index == z_index)
+  }
+
+  /// Whether any placement still shows `image_id`.
+  pub fn has_image(&self, image_id: u32) -> bool {
+    self.placements.iter().any(|p| p.image_id == image_id)
+      || self
+        .virtual_placements
+        .iter()
+        .any(|p| p.image_id == image_id)
+  }
+
+  /// Remove the placements matching `pred`, returning the IDs of their images.
+  fn remove_where(&mut self, pred: impl Fn(&ImagePlacement) -> bool) -> Vec<u32> {
+    let mut image_ids = Vec::new();
     self.placements.retain(|p| {
-      if p.image_id != image_id {
+      if !pred(p) {
         return true;
       }
-      if let Some(pid) = placement_id {
-        p.placement_id != pid
-      } else {
-        false
+      if !image_ids.contains(&p.image_id) {
+        image_ids.push(p.image_id);
       }
+      false
     });
-  }
-
-  /// Remove all placements at a given grid position.
-  pub fn remove_at_cursor(&mut self, line: i32, column: i32) {
-    self
-      .placements
-      .retain(|p| !(p.line == line && p.column == column));
+    image_ids
   }
 
   /// Remove all placements.
   pub fn clear(&mut self) {
     self.placements.clear();
+    self.virtual_placements.clear();
+  }
+
+  /// Move every placement up after `lines` lines were dropped from the top
+  /// of the scrollback, dropping those that scrolled out entirely.
+  pub fn rebase(&mut self, lines: usize) {
+    let lines = i32::try_from(lines).unwrap_or(i32::MAX);
+    self.placements.retain_mut(|p| {
+      p.line = p.line.saturating_sub(lines);
+      p.line + p.height_cells as i32 > 0
+    });
+  }
+
+  /// Remove the placements tied to grid lines, for when scrollback was lost
+  /// without knowing how much. Virtual placements are drawn through
+  /// placeholder cells and stay.
+  pub fn clear_grid_placements(&mut self) {
+    self.placements.clear();
   }
 
   /// Remove placements for images that no longer exist in storage.
@@ -60,6 +144,29 @@ impl PlacementManager {
     self
       .placements
       .retain(|p| storage.peek(p.image_id).is_some());
+    self
+      .virtual_placements
+      .retain(|p| storage.peek(p.image_id).is_some());
+  }
+
+  /// The virtual placements with the frames they show now, for drawing
+  /// placeholder cells.
+  pub fn placeholder_images(&self, storage: &KittyImageStorage) -> Vec<PlaceholderImage> {
+    self
+      .virtual_placements
+      .iter()
+      .filter_map(|p| {
+        let stored = storage.peek(p.image_id)?;
+        Some(PlaceholderImage {
+          image_id: p.image_id,
+          placement_id: p.placement_id,
+          render_image: stored.current_frame().clone(),
+          columns: p.columns,
+          rows: p.rows,
+          z_index: p.z_index,
+        })
+      })
+      .collect()
   }
 
   /// Get all placements visible in the current viewport.
@@ -87,7 +194,7 @@ impl PlacementManager {
         let stored = storage.peek(p.image_id)?;
 
         Some(VisiblePlacement {
-          render_image: stored.render_image.clone(),
+          render_image: stored.current_frame().clone(),
           viewport_line: p.line - viewport_top,
           column: p.column,
           width_cells: p.width_cells,
@@ -95,6 +202,7 @@ impl PlacementManager {
           z_index: p.z_index,
           x_offset: p.x_offset,
           y_offset: p.y_offset,
+          clip: None,
         })
       })
       .collect()
@@ -105,6 +213,14 @@ impl PlacementManager {
   }
 }
 
+fn covers_line(p: &ImagePlacement, line: i32) -> bool {
+  (p.line..p.line + p.height_cells as i32).contains(&line)
+}
+
+fn covers_column(p: &ImagePlacement, column: i32) -> bool {
+  (p.column..p.column + p.width_cells as i32).contains(&column)
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -152,6 +268,91 @@ mod tests {
     assert_eq!(mgr.placement_count(), 1);
   }
 
+  #[test]
+  fn test_remove_at_cell_covers_whole_placement() {
+    let mut mgr = PlacementManager::new();
+    mgr.add(make_placement(1, 5, 3));
+    let mut above = make_placement(2, 5, 3);
+    above.z_index = 1;
+    mgr.add(above);
+    mgr.add(make_placement(3, 20, 0));
+
+    // Bottom-right cell of the 10x5 placements at (5, 3).
+    assert_eq!(mgr.remove_at_cell(9, 12, Some(1)), vec![2]);
+    assert!(mgr.remove_at_cell(10, 12, None).is_empty());
+    assert_eq!(mgr.remove_at_cell(9, 12, None), vec![1]);
+    assert!(mgr.has_image(3));
+    assert!(!mgr.has_image(1));
+  }
+
+  #[test]
+  fn test_remove_in_column_and_lines() {
+    let mut mgr = PlacementManager::new();
+    mgr.add(make_placement(1, 0, 0));
+    mgr.add(make_placement(2, 0, 10));
+    mgr.add(make_placement(3, 10, 0));
+
+    assert_eq!(mgr.remove_in_column(10), vec![2]);
+    assert!(mgr.remove_in_lines(5, 5).is_empty());
+    assert_eq!(mgr.remove_in_lines(4, 7), vec![1, 3]);
+    assert_eq!(mgr.placement_count(), 0);
+  }
+
+  #[test]
+  fn test_remove_by_z_index_and_range() {
+    let mut mgr = PlacementManager::new();
+    for id in 1..=4 {
+      let mut placement = make_placement(id, 0, 0);
+      placement.z_index = if id % 2 == 0 { -1 } else { 0 };
+      mgr.add(placement);
+    }
+
+    assert_eq!(mgr.remove_by_z_index(-1), vec![2, 4]);
+    assert_eq!(mgr.remove_by_image_range(3, 10), vec![3]);
+    assert!(mgr.has_image(1));
+    assert_eq!(mgr.placement_count(), 1);
+  }
+
+  #[test]
+  fn test_virtual_placements_survive_position_deletes() {
+    let mut mgr = PlacementManager::new();
+    mgr.add(make_placement(1, 0, 0));
+    mgr.add_virtual(VirtualPlacement {
+      image_id: 2,
+      placement_id: 0,
+      columns: 4,
+      rows: 2,
+      z_index: 0,
+    });
+
+    mgr.remove_in_lines(0, 100);
+    mgr.remove_by_z_index(0);
+    assert!(mgr.has_image(2));
+    mgr.remove_by_id(2, None);
+    assert!(!mgr.has_image(2));
+  }
+
+  #[test]
+  fn test_rebase_moves_placements_and_drops_evicted_ones() {
+    let mut mgr = PlacementManager::new();
+    mgr.add(make_placement(1, 2, 0));
+    mgr.add(make_placement(2, 4, 0));
+    mgr.add(make_placement(3, 10, 0));
+
+    // Placement 2 still has its bottom row in the scrollback.
+    mgr.rebase(8);
+    assert!(!mgr.has_image(1));
+    assert!(mgr.has_image(2));
+    assert_eq!(
+      mgr
+        .placements
+        .iter()
+        .map(|p| (p.image_id, p.line))
+        .collect::<Vec<_>>(),
+      vec![(2, -4), (3, 2)]
+    );
+  }
+
   #[test]
   fn test_clear() {
     let mut mgr = PlacementManager::new();
@@ -19,6 +19,10 @@
 //!                   Terminal::sync()   VTE parser
 //! ```
 //!
+//! Sixel images (`\x1bP...q...\x1b\\`) are intercepted the same way and sent
+//! down the same channel, since alacritty doesn't draw them either. Other
+//! DCS sequences pass through.
+//!
 //! No pipe or filter thread is needed. The filtering happens inline in
 //! `Read::read()`, using the same PTY master fd that alacritty expects.
 //! The wrapper delegates all poll registration, writing, signal handling,
@@ -192,6 +196,7 @@ mod unix {
   use std::fs::File;
   use std::io::{self, Read};
   use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+  use std::os::unix::net::UnixStream;
   use std::sync::atomic::{AtomicU32, Ordering};
 
   /// Extra blank lines inserted below an image before the next prompt.
@@ -204,8 +209,10 @@ mod unix {
   use terminal_kernel::tty::{ChildEvent, EventedPty, EventedReadWrite, Pty};
 
   use super::super::command::RawGraphicsCommand;
+  use super::super::sixel::{self, MAX_SIXEL_BYTES};
   use super::KeyboardModeTracker;
   use crate::osc7;
+  use crate::shell_integration::{self, SemanticMark, SemanticMarkKind};
 
   /// Callback that tries to get the cursor position from the terminal.
   /// Returns `Some((absolute_line, column))` on success, `None` if lock unavailable.
@@ -215,6 +222,17 @@ mod unix {
   /// Returns `Some((row_1based, col_1based))` screen-relative, or `None` if lock unavailable.
   pub type DsrCursorFn = Box<dyn Fn() -> Option<(i32, i32)> + Send + Sync>;
 
+  /// A filtering reader with its shared state and channels:
+  /// `(reader, pending_cnl, keyboard_flags, graphics_rx, osc7_rx, semantic_rx)`.
+  pub type Filtered<T> = (
+    T,
+    Arc<AtomicU32>,
+    Arc<AtomicU32>,
+    mpsc::Receiver<RawGraphicsCommand>,
+    mpsc::Receiver<std::path::PathBuf>,
+    mpsc::Receiver<SemanticMark>,
+  );
+
   /// APC filter state machine states.
   #[derive(Debug, Clone, Copy, PartialEq)]
   enum FilterState {
@@ -228,6 +246,13 @@ mod unix {
     ApcEscape,
     /// Inside CSI sequence (\x1b[...), collecting parameter/intermediate bytes.
     CsiCollect,
+    /// Inside DCS (\x1bP...), collecting parameters until the final byte
+    /// shows whether it's a sixel image.
+    DcsParams,
+    /// Inside a sixel DCS (\x1bP...q...), collecting bytes.
+    SixelCollect,
+    /// Inside a sixel DCS, saw ESC — waiting for '\' to end sequence.
+    SixelEscape,
   }
 
   /// A `Read` adapter that filters APC graphics sequences from PTY output.
@@ -244,6 +269,7 @@ mod unix {
     pending_pos: usize,
     graphics_tx: mpsc::Sender<RawGraphicsCommand>,
     osc7_tx: mpsc::Sender<std::path::PathBuf>,
+    semantic_tx: mpsc::Sender<SemanticMark>,
     /// Callback to try-lock the terminal and get cursor position.
     cursor_fn: CursorFn,
     /// Cached cursor position from last successful try-lock.
@@ -261,6 +287,22 @@ mod unix {
     last_dsr_cursor: (i32, i32),
     keyboard_mode: KeyboardModeTracker,
     keyboard_flags: Arc<AtomicU32>,
+    /// Buffer for collecting DCS bytes (after ESC P).
+    dcs_buf: Vec<u8>,
+    /// Whether the sixel being collected outgrew `MAX_SIXEL_BYTES`.
+    sixel_overflow: bool,
+    /// An OSC 133 mark already returned to the parser, whose line is read
+    /// from the cursor once the parser has run over it.
+    unresolved_mark: Option<(SemanticMarkKind, std::time::Instant)>,
+    /// Longest `buf` a read was given. The event loop reads into the
+    /// unparsed tail of its buffer, so a read given the whole buffer means
+    /// every byte returned so far has been parsed.
+    max_read_len: usize,
+    /// Registered with the event loop's poller next to the PTY, so a read
+    /// that stopped to resolve a mark is called again even when the shell
+    /// prints nothing more.
+    wake_rx: UnixStream,
+    wake_tx: UnixStream,
   }
 
   /// Parsed APC parameters relevant to cursor advancement.
@@ -286,6 +328,8 @@ mod unix {
       more_chunks: false,
       is_display: true, // default action is TransmitAndDisplay
     };
+    let mut unicode_placeholder = false;
+    let mut cursor_fixed = false;
     for pair in params.split(',') {
       if let Some((key, value)) = pair.split_once('=') {
         match key.trim() {
@@ -294,10 +338,15 @@ mod unix {
           "f" => result.format = value.parse().unwrap_or(32),
           "m" => result.more_chunks = value == "1",
           "a" => result.is_display = matches!(value, "T" | "p"),
+          "U" => unicode_placeholder = value == "1",
+          "C" => cursor_fixed = value == "1",
           _ => {}
         }
       }
     }
+    // Virtual placements are drawn by placeholder text and leave the
+    // cursor alone, as does C=1.
+    result.is_display &= !unicode_placeholder && !cursor_fixed;
     result
   }
 
@@ -356,6 +405,58 @@ mod unix {
   }
 
   impl FilteringReader {
+    /// Filter the PTY output read from `inner`. Returns the reader with the
+    /// shared state and channels `GraphicsPtyFilter::new` hands out.
+    fn new(
+      inner: File,
+      cursor_fn: CursorFn,
+      dsr_cursor_fn: DsrCursorFn,
+    ) -> io::Result<Filtered<Self>> {
+      let (wake_rx, wake_tx) = UnixStream::pair()?;
+      wake_rx.set_nonblocking(true)?;
+      wake_tx.set_nonblocking(true)?;
+      let (graphics_tx, graphics_rx) = mpsc::channel();
+      let (osc7_tx, osc7_rx) = mpsc::channel();
+      let (semantic_tx, semantic_rx) = mpsc::channel();
+      let pending_cnl = Arc::new(AtomicU32::new(0));
+      let keyboard_flags = Arc::new(AtomicU32::new(0));
+
+      let reader = Self {
+        inner,
+        state: FilterState::Normal,
+        apc_buf: Vec::with_capacity(4096),
+        pending: Vec::with_capacity(8192),
+        pending_pos: 0,
+        graphics_tx,
+        osc7_tx,
+        semantic_tx,
+        cursor_fn,
+        last_cursor: (0, 0),
+        cnl_injected: false,
+        pending_cnl: Arc::clone(&pending_cnl),
+        csi_buf: Vec::with_capacity(64),
+        dsr_cursor_fn,
+        last_dsr_cursor: (1, 1),
+        keyboard_mode: KeyboardModeTracker::default(),
+        keyboard_flags: Arc::clone(&keyboard_flags),
+        dcs_buf: Vec::with_capacity(64),
+        sixel_overflow: false,
+        unresolved_mark: None,
+        max_read_len: 0,
+        wake_rx,
+        wake_tx,
+      };
+
+      Ok((
+        reader,
+        pending_cnl,
+        keyboard_flags,
+        graphics_rx,
+        osc7_rx,
+        semantic_rx,
+      ))
+    }
+
     /// Try to capture cursor position. Updates cache on success.
     fn capture_cursor(&mut self) -> (i32, i32) {
       if let Some(pos) = (self.cursor_fn)() {
@@ -421,8 +522,9 @@ mod unix {
     fn handle_device_attributes(&mut self) -> bool {
       let response: Option<String> = if self.csi_buf.is_empty() || self.csi_buf == b"0" {
         // Primary DA (CSI c / CSI 0 c).
-        // Report as VT220 (level 2) with ANSI color/VT525 support.
-        Some("\x1b[?62;22c".to_string())
+        // Report as VT220 (level 2) with sixel graphics and ANSI color/VT525
+        // support.
+        Some("\x1b[?62;4;22c".to_string())
       } else if self.csi_buf.first() == Some(&b'>') {
         let param = &self.csi_buf[1..];
         if param.is_empty() || param == b"0" {
@@ -530,6 +632,262 @@ mod unix {
       self.csi_buf.clear();
     }
 
+    /// Copy filtered bytes into `buf`, stopping right after the first OSC
+    /// 133 mark so its line can be read from the cursor once the parser has
+    /// run over everything before it.
+    fn take_pending(&mut self, buf: &mut [u8]) -> usize {
+      let avail = &self.pending[self.pending_pos..];
+      let mut n = avail.len().min(buf.len());
+      if let Some((mark, kind)) = shell_integration::extract_osc133_marks(avail)
+        .into_iter()
+        .next()
+        && mark.start < n
+      {
+        if mark.end <= n {
+          n = mark.end;
+          self.unresolved_mark = Some((kind, std::time::Instant::now()));
+        } else if mark.start > 0 {
+          // Keep the whole mark for the next read.
+          n = mark.start;
+        }
+      }
+
+      buf[..n].copy_from_slice(&avail[..n]);
+      self.pending_pos += n;
+      if self.pending_pos >= self.pending.len() {
+        self.pending.clear();
+        self.pending_pos = 0;
+      }
+      n
+    }
+
+    /// Send the mark returned by the last read with the cursor line, once
+    /// the parser has seen it. Returns false while that isn't known yet.
+    fn resolve_mark(&mut self, buf_len: usize) -> bool {
+      let Some((kind, at)) = self.unresolved_mark else {
+        return true;
+      };
+      // Bytes are still waiting to be parsed, or the terminal is locked
+      // (the event loop holds it while it parses).
+      if buf_len < self.max_read_len {
+        return false;
+      }
+      let Some((line, _)) = (self.cursor_fn)() else {
+        return false;
+      };
+      let _ = self.semantic_tx.send(SemanticMark { kind, line, at });
+      self.unresolved_mark = None;
+      true
+    }
+
+    /// Make the event loop's poller report the PTY readable.
+    fn wake(&mut self) {
+      use std::io::Write;
+      let _ = self.wake_tx.write(&[0]);
+    }
+
+    fn drain_wakes(&mut self) {
+      let mut drain = [0u8; 64];
+      while matches!(self.wake_rx.read(&mut drain), Ok(n) if n > 0) {}
+    }
+
+    /// Pass a DCS that isn't a sixel image through to alacritty.
+    fn flush_dcs_to_pending(&mut self) {
+      self.pending.extend_from_slice(b"\x1bP");
+      self.pending.extend_from_slice(&self.dcs_buf);
+      self.dcs_buf.clear();
+    }
+
+    /// Send a completed sixel image to the terminal, unless it was too large
+    /// to buffer, and advance the cursor past it right away.
+    fn send_sixel(&mut self) {
+      let data = std::mem::take(&mut self.dcs_buf);
+      if self.sixel_overflow {
+        tracing::warn!("Dropping sixel image larger than {MAX_SIXEL_BYTES} bytes");
+        return;
+      }
+      let (cursor_line, cursor_column) = self.capture_cursor();
+      // Estimate ~20 pixels per cell row, as for kitty images.
+      let rows = sixel::height_hint(&data).div_ceil(20);
+      if rows > 0 {
+        self.inject_cnl(rows);
+        // A sixel arrives whole, so the next image can inject again.
+        self.cnl_injected = false;
+      }
+      let _ = self.graphics_tx.send(RawGraphicsCommand {
+        data,
+        cursor_line,
+        cursor_column,
+        clear_all: false,
+        clear_scrollback: false,
+        sixel: true,
+        cursor_advanced: rows > 0,
+      });
+    }
+
+    /// Run one PTY byte through the filter state machine.
+    fn filter_byte(&mut self, byte: u8) {
+      match self.state {
+        FilterState::Normal => {
+          if byte == 0x1B {
+            self.state = FilterState::Escape;
+          } else {
+            self.pending.push(byte);
+          }
+        }
+        FilterState::Escape => {
+          if byte == b'_' {
+            self.state = FilterState::ApcCollect;
+            self.apc_buf.clear();
+          } else if byte == b'[' {
+            self.state = FilterState::CsiCollect;
+            self.csi_buf.clear();
+          } else if byte == b'P' {
+            self.state = FilterState::DcsParams;
+            self.dcs_buf.clear();
+          } else {
+            // Not APC, CSI or DCS — pass through the ESC and this byte.
+            self.pending.push(0x1B);
+            self.pending.push(byte);
+            self.state = FilterState::Normal;
+          }
+        }
+        FilterState::CsiCollect => {
+          match byte {
+            // Parameter bytes (digits, semicolons, and private-mode markers like '?').
+            0x30..=0x3F => {
+              self.csi_buf.push(byte);
+              // Cap at 256 bytes to prevent unbounded buffering.
+              if self.csi_buf.len() > 256 {
+                self.flush_csi_to_pending(None);
+                self.state = FilterState::Normal;
+              }
+            }
+            // Intermediate bytes (space through '/').
+            0x20..=0x2F => {
+              self.csi_buf.push(byte);
+            }
+            // Final byte — CSI sequence is complete.
+            0x40..=0x7E => {
+              if !self.handle_csi_final(byte) {
+                // Not a DSR we handle — flush to pending for alacritty.
+                self.flush_csi_to_pending(Some(byte));
+              } else {
+                self.csi_buf.clear();
+              }
+              self.state = FilterState::Normal;
+            }
+            // Control character or other invalid byte inside CSI.
+            _ => {
+              self.flush_csi_to_pending(None);
+              self.pending.push(byte);
+              self.state = FilterState::Normal;
+            }
+          }
+        }
+        FilterState::DcsParams => match byte {
+          // Sixel introducer parameters (P1;P2;P3).
+          b'0'..=b'9' | b';' if self.dcs_buf.len() < 64 => {
+            self.dcs_buf.push(byte);
+          }
+          b'q' => {
+            self.dcs_buf.push(byte);
+            self.sixel_overflow = false;
+            self.state = FilterState::SixelCollect;
+          }
+          // Any other DCS (DECRQSS, XTGETTCAP, ...) is alacritty's.
+          0x1B => {
+            self.flush_dcs_to_pending();
+            self.state = FilterState::Escape;
+          }
+          _ => {
+            self.flush_dcs_to_pending();
+            self.pending.push(byte);
+            self.state = FilterState::Normal;
+          }
+        },
+        FilterState::SixelCollect => {
+          if byte == 0x1B {
+            self.state = FilterState::SixelEscape;
+          } else if self.dcs_buf.len() < MAX_SIXEL_BYTES {
+            self.dcs_buf.push(byte);
+          } else {
+            self.sixel_overflow = true;
+          }
+        }
+        FilterState::SixelEscape => {
+          if byte == b'\\' {
+            self.send_sixel();
+            self.state = FilterState::Normal;
+          } else {
+            // Any other escape cancels the sixel and starts a sequence of
+            // its own.
+            self.dcs_buf.clear();
+            self.state = FilterState::Escape;
+            self.filter_byte(byte);
+          }
+        }
+        FilterState::ApcCollect => {
+          if byte == 0x1B {
+            self.state = FilterState::ApcEscape;
+          } else {
+            self.apc_buf.push(byte);
+          }
+        }
+        FilterState::ApcEscape => {
+          if byte == b'\\' {
+            // Complete APC sequence. Check for Kitty graphics prefix 'G'.
+            if self.apc_buf.first() == Some(&b'G') {
+              let (cursor_line, cursor_column) = self.capture_cursor();
+              let cmd_data = self.apc_buf[1..].to_vec();
+              let params = parse_apc_params(&cmd_data);
+
+              // Inject cursor advancement on display actions.
+              // For multi-chunk: inject on first chunk (which has r=/v= params),
+              // skip subsequent chunks via cnl_injected flag.
+              if params.is_display && !self.cnl_injected {
+                let effective_rows = if params.display_rows > 0 {
+                  params.display_rows
+                } else if params.source_height > 0 {
+                  // Estimate ~20 pixels per cell row.
+                  params.source_height.div_ceil(20)
+                } else if params.format == 100 && !params.more_chunks {
+                  // PNG: try reading height from image header.
+                  let h = try_png_height_from_payload(&cmd_data);
+                  h.div_ceil(20)
+                } else {
+                  0
+                };
+                self.inject_cnl(effective_rows);
+              }
+
+              // Reset cnl_injected flag after last chunk so next image can inject.
+              if !params.more_chunks {
+                self.cnl_injected = false;
+              }
+
+              let _ = self.graphics_tx.send(RawGraphicsCommand {
+                data: cmd_data,
+                cursor_line,
+                cursor_column,
+                clear_all: false,
+                clear_scrollback: false,
+                sixel: false,
+                cursor_advanced: false,
+              });
+            }
+            self.apc_buf.clear();
+            self.state = FilterState::Normal;
+          } else {
+            // ESC inside APC not followed by '\' — keep collecting.
+            self.apc_buf.push(0x1B);
+            self.apc_buf.push(byte);
+            self.state = FilterState::ApcCollect;
+          }
+        }
+      }
+    }
+
     /// Inject CNL escape into the pending buffer to advance cursor past image.
     fn inject_cnl(&mut self, rows: u32) {
       if rows > 0 {
@@ -543,6 +901,15 @@ mod unix {
 
   impl Read for FilteringReader {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+      self.max_read_len = self.max_read_len.max(buf.len());
+      self.drain_wakes();
+      if !self.resolve_mark(buf.len()) {
+        // Let the event loop parse what it holds and release the terminal,
+        // then come back through the wake.
+        self.wake();
+        return Err(io::Error::from(io::ErrorKind::WouldBlock));
+      }
+
       // Check for terminal-computed CNL feedback (from place_image).
       let feedback_rows = self.pending_cnl.swap(0, Ordering::AcqRel);
       if feedback_rows > 0 && !self.cnl_injected {
@@ -560,15 +927,7 @@ mod unix {
 
       // Drain any leftover filtered bytes from a previous read.
       if self.pending_pos < self.pending.len() {
-        let avail = &self.pending[self.pending_pos..];
-        let n = avail.len().min(buf.len());
-        buf[..n].copy_from_slice(&avail[..n]);
-        self.pending_pos += n;
-        if self.pending_pos >= self.pending.len() {
-          self.pending.clear();
-          self.pending_pos = 0;
-        }
-        return Ok(n);
+        return Ok(self.take_pending(buf));
       }
 
       // Read raw bytes from the PTY master.
@@ -583,137 +942,28 @@ mod unix {
       self.pending_pos = 0;
 
       for &byte in &raw[..n] {
-        match self.state {
-          FilterState::Normal => {
-            if byte == 0x1B {
-              self.state = FilterState::Escape;
-            } else {
-              self.pending.push(byte);
-            }
-          }
-          FilterState::Escape => {
-            if byte == b'_' {
-              self.state = FilterState::ApcCollect;
-              self.apc_buf.clear();
-            } else if byte == b'[' {
-              self.state = FilterState::CsiCollect;
-              self.csi_buf.clear();
-            } else {
-              // Not APC or CSI — pass through the ESC and this byte.
-              self.pending.push(0x1B);
-              self.pending.push(byte);
-              self.state = FilterState::Normal;
-            }
-          }
-          FilterState::CsiCollect => {
-            match byte {
-              // Parameter bytes (digits, semicolons, and private-mode markers like '?').
-              0x30..=0x3F => {
-                self.csi_buf.push(byte);
-                // Cap at 256 bytes to prevent unbounded buffering.
-                if self.csi_buf.len() > 256 {
-                  self.flush_csi_to_pending(None);
-                  self.state = FilterState::Normal;
-                }
-              }
-              // Intermediate bytes (space through '/').
-              0x20..=0x2F => {
-                self.csi_buf.push(byte);
-              }
-              // Final byte — CSI sequence is complete.
-              0x40..=0x7E => {
-                if !self.handle_csi_final(byte) {
-                  // Not a DSR we handle — flush to pending for alacritty.
-                  self.flush_csi_to_pending(Some(byte));
-                } else {
-                  self.csi_buf.clear();
-                }
-                self.state = FilterState::Normal;
-              }
-              // Control character or other invalid byte inside CSI.
-              _ => {
-                self.flush_csi_to_pending(None);
-                self.pending.push(byte);
-                self.state = FilterState::Normal;
-              }
-            }
-          }
-          FilterState::ApcCollect => {
-            if byte == 0x1B {
-              self.state = FilterState::ApcEscape;
-            } else {
-              self.apc_buf.push(byte);
-            }
-          }
-          FilterState::ApcEscape => {
-            if byte == b'\\' {
-              // Complete APC sequence. Check for Kitty graphics prefix 'G'.
-              if self.apc_buf.first() == Some(&b'G') {
-                let (cursor_line, cursor_column) = self.capture_cursor();
-                let cmd_data = self.apc_buf[1..].to_vec();
-                let params = parse_apc_params(&cmd_data);
-
-                // Inject cursor advancement on display actions.
-                // For multi-chunk: inject on first chunk (which has r=/v= params),
-                // skip subsequent chunks via cnl_injected flag.
-                if params.is_display && !self.cnl_injected {
-                  let effective_rows = if params.display_rows > 0 {
-                    params.display_rows
-                  } else if params.source_height > 0 {
-                    // Estimate ~20 pixels per cell row.
-                    (params.source_height + 19) / 20
-                  } else if params.format == 100 && !params.more_chunks {
-                    // PNG: try reading height from image header.
-                    let h = try_png_height_from_payload(&cmd_data);
-                    if h > 0 { (h + 19) / 20 } else { 0 }
-                  } else {
-                    0
-                  };
-                  self.inject_cnl(effective_rows);
-                }
-
-                // Reset cnl_injected flag after last chunk so next image can inject.
-                if !params.more_chunks {
-                  self.cnl_injected = false;
-                }
-
-                let _ = self.graphics_tx.send(RawGraphicsCommand {
-                  data: cmd_data,
-                  cursor_line,
-                  cursor_column,
-                  clear_all: false,
-                });
-              }
-              self.apc_buf.clear();
-              self.state = FilterState::Normal;
-            } else {
-              // ESC inside APC not followed by '\' — keep collecting.
-              self.apc_buf.push(0x1B);
-              self.apc_buf.push(byte);
-              self.state = FilterState::ApcCollect;
-            }
-          }
-        }
+        self.filter_byte(byte);
       }
 
       // Detect terminal clear/reset sequences in pass-through bytes.
       // ESC[2J (erase display), ESC[3J (erase display+scrollback), ESC c (RIS).
-      let has_clear = self
-        .pending
-        .windows(4)
-        .any(|w| w == b"\x1b[2J" || w == b"\x1b[3J")
+      let clear_scrollback = self.pending.windows(4).any(|w| w == b"\x1b[3J")
         || self.pending.windows(2).any(|w| w == b"\x1bc");
+      let has_clear = clear_scrollback || self.pending.windows(4).any(|w| w == b"\x1b[2J");
       if has_clear {
         let _ = self.graphics_tx.send(RawGraphicsCommand {
           data: Vec::new(),
           cursor_line: 0,
           cursor_column: 0,
           clear_all: true,
+          clear_scrollback,
+          sixel: false,
+          cursor_advanced: false,
         });
       }
 
       if self.pending.is_empty() {
-        // All bytes were APC data — signal "no data yet" to the EventLoop.
+        // All bytes were APC or sixel data — signal "no data yet" to the EventLoop.
         return Err(io::Error::from(io::ErrorKind::WouldBlock));
       }
 
@@ -722,14 +972,7 @@ mod unix {
         let _ = self.osc7_tx.send(cwd);
       }
 
-      let n = self.pending.len().min(buf.len());
-      buf[..n].copy_from_slice(&self.pending[..n]);
-      self.pending_pos = n;
-      if self.pending_pos >= self.pending.len() {
-        self.pending.clear();
-        self.pending_pos = 0;
-      }
-      Ok(n)
+      Ok(self.take_pending(buf))
     }
   }
 
@@ -747,27 +990,23 @@ mod unix {
     /// Create a graphics-filtering PTY wrapper that takes ownership of a `Pty`.
     ///
     /// `cursor_fn` is called (via try-lock) to capture the cursor position
-    /// when an APC graphics sequence is intercepted.
+    /// when an APC graphics sequence is intercepted, and to read the line of
+    /// an OSC 133 mark once the parser has run over it.
     ///
     /// `dsr_cursor_fn` is called to capture the screen-relative cursor position
     /// (1-based row, col) for DECXCPR responses.
     ///
-    /// Returns `(filter, pending_cnl, keyboard_flags, graphics_rx, osc7_rx)`:
+    /// Returns `(filter, pending_cnl, keyboard_flags, graphics_rx, osc7_rx, semantic_rx)`:
     /// - `pending_cnl`: shared atomic for terminal to request cursor advancement
     /// - `keyboard_flags`: shared atomic exposing active kitty keyboard flags
     /// - `graphics_rx`: receives Kitty graphics commands with cursor positions
     /// - `osc7_rx`: receives CWD paths extracted from OSC 7 sequences
+    /// - `semantic_rx`: receives OSC 133 prompt and command marks
     pub fn new(
       pty: Pty,
       cursor_fn: CursorFn,
       dsr_cursor_fn: DsrCursorFn,
-    ) -> io::Result<(
-      Self,
-      Arc<AtomicU32>,
-      Arc<AtomicU32>,
-      mpsc::Receiver<RawGraphicsCommand>,
-      mpsc::Receiver<std::path::PathBuf>,
-    )> {
+    ) -> io::Result<Filtered<Self>> {
       // Dup the master fd so the FilteringReader has its own fd for reading.
       // The original fd stays in the Pty for poll registration and writing.
       let master_fd = pty.file().as_raw_fd();
@@ -777,29 +1016,8 @@ mod unix {
       }
       let read_file = unsafe { File::from_raw_fd(read_fd) };
 
-      let (graphics_tx, graphics_rx) = mpsc::channel();
-      let (osc7_tx, osc7_rx) = mpsc::channel();
-      let pending_cnl = Arc::new(AtomicU32::new(0));
-      let keyboard_flags = Arc::new(AtomicU32::new(0));
-
-      let reader = FilteringReader {
-        inner: read_file,
-        state: FilterState::Normal,
-        apc_buf: Vec::with_capacity(4096),
-        pending: Vec::with_capacity(8192),
-        pending_pos: 0,
-        graphics_tx,
-        osc7_tx,
-        cursor_fn,
-        last_cursor: (0, 0),
-        cnl_injected: false,
-        pending_cnl: Arc::clone(&pending_cnl),
-        csi_buf: Vec::with_capacity(64),
-        dsr_cursor_fn,
-        last_dsr_cursor: (1, 1),
-        keyboard_mode: KeyboardModeTracker::default(),
-        keyboard_flags: Arc::clone(&keyboard_flags),
-      };
+      let (reader, pending_cnl, keyboard_flags, graphics_rx, osc7_rx, semantic_rx) =
+        FilteringReader::new(read_file, cursor_fn, dsr_cursor_fn)?;
 
       Ok((
         GraphicsPtyFilter { reader, pty },
@@ -807,6 +1025,7 @@ mod unix {
         keyboard_flags,
         graphics_rx,
         osc7_rx,
+        semantic_rx,
       ))
     }
 
@@ -835,7 +1054,14 @@ mod unix {
       interest: Event,
       poll_mode: PollMode,
     ) -> io::Result<()> {
-      unsafe { self.pty.register(poll, interest, poll_mode) }
+      unsafe {
+        self.pty.register(poll, interest, poll_mode)?;
+        poll.add_with_mode(
+          &self.reader.wake_rx,
+          Event::readable(interest.key),
+          poll_mode,
+        )
+      }
     }
 
     #[inline]
@@ -845,12 +1071,18 @@ mod unix {
       interest: Event,
       poll_mode: PollMode,
     ) -> io::Result<()> {
-      self.pty.reregister(poll, interest, poll_mode)
+      self.pty.reregister(poll, interest, poll_mode)?;
+      poll.modify_with_mode(
+        &self.reader.wake_rx,
+        Event::readable(interest.key),
+        poll_mode,
+      )
     }
 
     #[inline]
     fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
-      self.pty.deregister(poll)
+      self.pty.deregister(poll)?;
+      poll.delete(&self.reader.wake_rx)
     }
 
     #[inline]
@@ -877,6 +1109,132 @@ mod unix {
       self.pty.on_resize(window_size);
     }
   }
+
+  #[cfg(test)]
+  mod tests {
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::os::fd::OwnedFd;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::mpsc;
+
+    use terminal_kernel::event::VoidListener;
+    use terminal_kernel::grid::Dimensions;
+    use terminal_kernel::sync::FairMutex;
+    use terminal_kernel::term::{Config, Term};
+    use terminal_kernel::test_support::StubDims;
+    use terminal_kernel::vte::ansi::Processor;
+
+    use super::FilteringReader;
+    use crate::kitty_graphics::command::RawGraphicsCommand;
+    use crate::shell_integration::SemanticMarkKind;
+
+    fn filter(
+      bytes: &[u8],
+    ) -> (
+      FilteringReader,
+      Arc<AtomicU32>,
+      mpsc::Receiver<RawGraphicsCommand>,
+    ) {
+      let (reader, mut writer) = io::pipe().unwrap();
+      writer.write_all(bytes).unwrap();
+      drop(writer);
+      let (filter, pending_cnl, _, graphics_rx, _, _) = FilteringReader::new(
+        File::from(OwnedFd::from(reader)),
+        Box::new(|| Some((0, 0))),
+        Box::new(|| Some((1, 1))),
+      )
+      .unwrap();
+      (filter, pending_cnl, graphics_rx)
+    }
+
+    /// The bytes one `read` hands to the parser, skipping reads that were
+    /// all filtered out.
+    fn read(filter: &mut FilteringReader) -> Vec<u8> {
+      let mut buf = [0u8; 4096];
+      loop {
+        match filter.read(&mut buf) {
+          Ok(n) => return buf[..n].to_vec(),
+          Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+          Err(e) => panic!("{e}"),
+        }
+      }
+    }
+
+    #[test]
+    fn marks_take_the_line_the_parser_left_the_cursor_on() {
+      let term = Arc::new(FairMutex::new(Term::new(
+        Config::default(),
+        &StubDims { cols: 10, lines: 5 },
+        VoidListener,
+      )));
+      let cursor_term = term.clone();
+      let (reader, mut writer) = io::pipe().unwrap();
+      // The first line wraps, so counting line feeds would put the prompt a
+      // line too high.
+      writer
+        .write_all(b"0123456789abc\r\n\x1b]133;A\x07$ ")
+        .unwrap();
+      drop(writer);
+      let (mut filter, _, _, _, _, semantic_rx) = FilteringReader::new(
+        File::from(OwnedFd::from(reader)),
+        Box::new(move || {
+          let term = cursor_term.try_lock_unfair()?;
+          let cursor = term.grid().cursor.point;
+          Some((
+            term.history_size() as i32 + cursor.line.0,
+            cursor.column.0 as i32,
+          ))
+        }),
+        Box::new(|| Some((1, 1))),
+      )
+      .unwrap();
+      let mut parser: Processor = Processor::new();
+      let mut buf = [0u8; 4096];
+
+      let n = filter.read(&mut buf).unwrap();
+      assert_eq!(&buf[..n], b"0123456789abc\r\n\x1b]133;A\x07");
+      // Nothing is known while the event loop still holds unparsed bytes.
+      let held = filter.read(&mut buf[n..]).unwrap_err();
+      assert_eq!(held.kind(), io::ErrorKind::WouldBlock);
+      assert!(semantic_rx.try_recv().is_err());
+
+      parser.advance(&mut *term.lock(), &buf[..n]);
+      let n = filter.read(&mut buf).unwrap();
+      assert_eq!(&buf[..n], b"$ ");
+      let mark = semantic_rx.try_recv().unwrap();
+      assert_eq!(mark.kind, SemanticMarkKind::PromptStart);
+      assert_eq!(mark.line, 2);
+    }
+
+    #[test]
+    fn escape_inside_a_sixel_cancels_it() {
+      let (mut filter, _, graphics_rx) = filter(b"\x1bPq#0~~\x1b[31mred");
+
+      assert_eq!(read(&mut filter), b"\x1b[31mred");
+      assert!(graphics_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn image_after_a_bad_sixel_still_advances_the_cursor() {
+      // Too many pixels to decode, and a PNG too short to read a height from.
+      let (mut filter, pending_cnl, graphics_rx) =
+        filter(b"\x1bPq\"1;1;4096;4097#0~\x1b\\\x1b_Ga=T,f=100;AAAA\x1b\\$ ");
+
+      // 4097 pixels is 205 rows, plus the padding.
+      assert_eq!(read(&mut filter), b"\x1b[207E$ ");
+      let sixel = graphics_rx.try_recv().unwrap();
+      assert!(sixel.sixel && sixel.cursor_advanced);
+      let kitty = graphics_rx.try_recv().unwrap();
+      assert!(!kitty.sixel && !kitty.cursor_advanced);
+
+      // The sixel fails to decode and asks for nothing; placing the kitty
+      // image asks for its rows.
+      pending_cnl.store(3, Ordering::Release);
+      assert_eq!(read(&mut filter), b"\x1b[5E");
+    }
+  }
 }
 
 #[cfg(unix)]
@@ -0,0 +1,427 @@
+//! DEC sixel image decoding.
+//!
+//! Sixel images arrive as DCS sequences (`\x1bP P1;P2;P3 q ... \x1b\\`). The
+//! PTY filter hands their content to the terminal, which decodes it here into
+//! RGBA pixels and then stores and places the result like a kitty image.
+
+use super::command::KittyError;
+
+/// Largest sixel sequence the PTY filter buffers, in bytes.
+pub const MAX_SIXEL_BYTES: usize = 64 * 1024 * 1024;
+/// Largest width or height of a decoded image, in pixels.
+pub const MAX_SIXEL_DIMENSION: u32 = 8192;
+/// Largest decoded image, in pixels (64MB of RGBA).
+pub const MAX_SIXEL_PIXELS: u64 = 4096 * 4096;
+
+/// Number of color registers. Higher register numbers wrap around.
+const COLOR_REGISTERS: usize = 1024;
+
+/// The VT340 default palette, in RGB percent.
+const DEFAULT_PALETTE: [[u32; 3]; 16] = [
+  [0, 0, 0],
+  [20, 20, 80],
+  [80, 13, 13],
+  [20, 80, 20],
+  [80, 20, 80],
+  [20, 80, 80],
+  [80, 80, 20],
+  [53, 53, 53],
+  [26, 26, 26],
+  [33, 33, 60],
+  [60, 26, 26],
+  [33, 60, 33],
+  [60, 33, 60],
+  [33, 60, 60],
+  [60, 60, 33],
+  [80, 80, 80],
+];
+
+/// A decoded sixel image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SixelImage {
+  pub width: u32,
+  pub height: u32,
+  /// RGBA pixels, row by row.
+  pub rgba: Vec<u8>,
+}
+
+/// Decode the content of a sixel DCS sequence: everything between `\x1bP`
+/// and the string terminator, starting with the `P1;P2;P3q` introducer.
+///
+/// Pixels no sixel covers are transparent when `P2` is 1 and take color
+/// register 0 otherwise. Images over the size limits are rejected with
+/// `EFBIG`.
+pub fn decode(content: &[u8]) -> Result<SixelImage, KittyError> {
+  let start = content
+    .iter()
+    .position(|&b| b == b'q')
+    .ok_or_else(|| KittyError::new("EINVAL", "Not a sixel sequence"))?;
+  let (params, _) = read_params(content, &mut 0);
+  let transparent = params[1] == 1;
+
+  let mut decoder = Decoder::new();
+  decoder.run(&content[start + 1..])?;
+  decoder.finish(transparent)
+}
+
+/// Pixel height of a sixel sequence, read without decoding it: `Pv` from
+/// the raster attributes when given, otherwise six pixels for every band the
+/// `-` graphics new lines start.
+pub fn height_hint(content: &[u8]) -> u32 {
+  let Some(start) = content.iter().position(|&b| b == b'q') else {
+    return 0;
+  };
+  let data = &content[start + 1..];
+  if let Some(raster) = data.iter().position(|&b| b == b'"') {
+    let (params, _) = read_params(data, &mut (raster + 1));
+    if params[3] > 0 {
+      return params[3];
+    }
+  }
+  let bands = data
+    .split(|&b| b == b'-')
+    .filter(|band| band.iter().any(|b| matches!(b, b'?'..=b'~')))
+    .count() as u32;
+  bands.saturating_mul(6)
+}
+
+/// Sixel drawing state and the canvas drawn so far.
+struct Decoder {
+  /// Colors as packed `0xRRGGBBFF`.
+  palette: Vec<u32>,
+  color: usize,
+  x: u32,
+  y: u32,
+  /// Allocated canvas, `stride` pixels wide. Undrawn pixels are 0.
+  pixels: Vec<u32>,
+  stride: u32,
+  rows: u32,
+  /// Image size: the raster attributes or the area drawn, whichever is larger.
+  width: u32,
+  height: u32,
+}
+
+impl Decoder {
+  fn new() -> Self {
+    let mut palette = vec![pack(0, 0, 0); COLOR_REGISTERS];
+    for (register, [r, g, b]) in DEFAULT_PALETTE.iter().enumerate() {
+      palette[register] = pack(percent(*r), percent(*g), percent(*b));
+    }
+    Self {
+      palette,
+      color: 0,
+      x: 0,
+      y: 0,
+      pixels: Vec::new(),
+      stride: 0,
+      rows: 0,
+      width: 0,
+      height: 0,
+    }
+  }
+
+  fn run(&mut self, data: &[u8]) -> Result<(), KittyError> {
+    let mut i = 0;
+    while let Some(&byte) = data.get(i) {
+      i += 1;
+      match byte {
+        b'?'..=b'~' => self.draw(byte - b'?', 1)?,
+        // Repeat introducer: `!Pn` followed by the sixel to repeat.
+        b'!' => {
+          let (params, _) = read_params(data, &mut i);
+          if let Some(sixel @ b'?'..=b'~') = data.get(i).copied() {
+            i += 1;
+            self.draw(sixel - b'?', params[0].max(1))?;
+          }
+        }
+        // Color introducer: `#Pc` selects a register, `#Pc;Pu;Px;Py;Pz`
+        // also defines it.
+        b'#' => {
+          let (params, separators) = read_params(data, &mut i);
+          let register = params[0] as usize % COLOR_REGISTERS;
+          if separators > 0
+            && let Some(color) = define_color(params[1], params[2], params[3], params[4])
+          {
+            self.palette[register] = color;
+          }
+          self.color = register;
+        }
+        // Raster attributes: `"Pan;Pad;Ph;Pv`. The aspect ratio is ignored.
+        b'"' => {
+          let (params, _) = read_params(data, &mut i);
+          self.grow(params[2], params[3])?;
+        }
+        b'$' => self.x = 0,
+        b'-' => {
+          self.x = 0;
+          self.y = self.y.saturating_add(6);
+        }
+        _ => {}
+      }
+    }
+    Ok(())
+  }
+
+  /// Draw the six pixels of one sixel `repeat` times and move right.
+  fn draw(&mut self, bits: u8, repeat: u32) -> Result<(), KittyError> {
+    let end = self.x.saturating_add(repeat);
+    if bits == 0 {
+      self.grow(end, 0)?;
+      self.x = end;
+      return Ok(());
+    }
+
+    let bottom = self.y.saturating_add(8 - bits.leading_zeros());
+    self.grow(end, bottom)?;
+    self.reserve(end, bottom);
+    let color = self.palette[self.color];
+    for bit in 0..6 {
+      if bits & (1 << bit) != 0 {
+        let start = ((self.y + bit) * self.stride + self.x) as usize;
+        self.pixels[start..start + repeat as usize].fill(color);
+      }
+    }
+    self.x = end;
+    Ok(())
+  }
+
+  /// Extend the image to at least `width` x `height`, within the limits.
+  fn grow(&mut self, width: u32, height: u32) -> Result<(), KittyError> {
+    let width = self.width.max(width);
+    let height = self.height.max(height);
+    if width > MAX_SIXEL_DIMENSION
+      || height > MAX_SIXEL_DIMENSION
+      || width as u64 * height as u64 > MAX_SIXEL_PIXELS
+    {
+      return Err(KittyError::new(
+        "EFBIG",
+        format!("{width}x{height} sixel image is larger than the sixel size limit"),
+      ));
+    }
+    self.width = width;
+    self.height = height;
+    Ok(())
+  }
+
+  /// Make the canvas at least `width` x `height`. It grows in doubling
+  /// steps so drawing without raster attributes stays linear. Once doubling
+  /// would pass the pixel limit, it narrows to the image width and takes
+  /// the rows that still fit; `grow` keeps the image itself within the
+  /// limit, and nothing is drawn outside the image.
+  fn reserve(&mut self, width: u32, height: u32) {
+    if width <= self.stride && height <= self.rows {
+      return;
+    }
+    let mut stride = width.max(self.stride * 2).min(MAX_SIXEL_DIMENSION);
+    let mut rows = height.max(self.rows * 2).min(MAX_SIXEL_DIMENSION);
+    if stride as u64 * rows as u64 > MAX_SIXEL_PIXELS {
+      stride = self.width.max(width);
+      rows = rows
+        .min((MAX_SIXEL_PIXELS / stride as u64) as u32)
+        .max(self.height.max(height));
+    }
+    let mut pixels = vec![0; stride as usize * rows as usize];
+    if self.stride > 0 {
+      let copied = self.stride.min(stride) as usize;
+      for (row, old) in self
+        .pixels
+        .chunks_exact(self.stride as usize)
+        .take(rows as usize)
+        .enumerate()
+      {
+        let start = row * stride as usize;
+        pixels[start..start + copied].copy_from_slice(&old[..copied]);
+      }
+    }
+    self.pixels = pixels;
+    self.stride = stride;
+    self.rows = rows;
+  }
+
+  fn finish(self, transparent: bool) -> Result<SixelImage, KittyError> {
+    if self.width == 0 || self.height == 0 {
+      return Err(KittyError::new("ENODATA", "Sixel image has no pixels"));
+    }
+    let background = if transparent { 0 } else { self.palette[0] };
+    let mut rgba = Vec::with_capacity(self.width as usize * self.height as usize * 4);
+    for y in 0..self.height {
+      for x in 0..self.width {
+        let pixel = if x < self.stride && y < self.rows {
+          self.pixels[(y * self.stride + x) as usize]
+        } else {
+          0
+        };
+        let pixel = if pixel == 0 { background } else { pixel };
+        rgba.extend_from_slice(&pixel.to_be_bytes());
+      }
+    }
+    Ok(SixelImage {
+      width: self.width,
+      height: self.height,
+      rgba,
+    })
+  }
+}
+
+/// Read `;`-separated decimal parameters at `data[*i]`, moving `*i` past
+/// them. Returns the first five (missing ones are 0) and the number of `;`.
+fn read_params(data: &[u8], i: &mut usize) -> ([u32; 5], usize) {
+  let mut params = [0u32; 5];
+  let mut separators = 0;
+  while let Some(&byte) = data.get(*i) {
+    match byte {
+      b'0'..=b'9' => {
+        if let Some(param) = params.get_mut(separators) {
+          *param = param
+            .saturating_mul(10)
+            .saturating_add((byte - b'0') as u32);
+        }
+      }
+      b';' => separators += 1,
+      _ => break,
+    }
+    *i += 1;
+  }
+  (params, separators)
+}
+
+/// A color definition in HLS (`Pu` = 1) or RGB percent (`Pu` = 2).
+fn define_color(system: u32, x: u32, y: u32, z: u32) -> Option<u32> {
+  match system {
+    1 => {
+      let [r, g, b] = hls_to_rgb(x, y, z);
+      Some(pack(r, g, b))
+    }
+    2 => Some(pack(percent(x), percent(y), percent(z))),
+    _ => None,
+  }
+}
+
+/// Convert a DEC HLS color (hue in degrees with blue at 0, lightness and
+/// saturation in percent) to RGB.
+fn hls_to_rgb(hue: u32, lightness: u32, saturation: u32) -> [u8; 3] {
+  // DEC hues start at blue; shift them onto the usual red-based wheel.
+  let h = ((hue % 360 + 240) % 360) as f32 / 60.0;
+  let l = lightness.min(100) as f32 / 100.0;
+  let s = saturation.min(100) as f32 / 100.0;
+  let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+  let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+  let (r, g, b) = match h as u32 {
+    0 => (c, x, 0.0),
+    1 => (x, c, 0.0),
+    2 => (0.0, c, x),
+    3 => (0.0, x, c),
+    4 => (x, 0.0, c),
+    _ => (c, 0.0, x),
+  };
+  let m = l - c / 2.0;
+  [r, g, b].map(|v| ((v + m) * 255.0).round() as u8)
+}
+
+fn percent(value: u32) -> u8 {
+  ((value.min(100) * 255 + 50) / 100) as u8
+}
+
+/// Pack an opaque color, so that 0 can mark undrawn pixels.
+fn pack(r: u8, g: u8, b: u8) -> u32 {
+  u32::from_be_bytes([r, g, b, 255])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Strip the DCS introducer and string terminator from a fixture.
+  fn fixture_content(fixture: &[u8]) -> &[u8] {
+    fixture
+      .strip_prefix(b"\x1bP")
+      .and_then(|content| content.strip_suffix(b"\x1b\\"))
+      .expect("fixture is a DCS sequence")
+  }
+
+  fn pixel(image: &SixelImage, x: u32, y: u32) -> [u8; 4] {
+    let start = ((y * image.width + x) * 4) as usize;
+    image.rgba[start..start + 4].try_into().unwrap()
+  }
+
+  #[test]
+  fn test_raster_size_and_background() {
+    let fixture = include_bytes!("../../tests/fixtures/sixel/red_square.six");
+    let image = decode(fixture_content(fixture)).unwrap();
+    assert_eq!((image.width, image.height), (6, 6));
+    assert_eq!(pixel(&image, 0, 0), [255, 0, 0, 255]);
+    assert_eq!(pixel(&image, 3, 5), [255, 0, 0, 255]);
+    // Undrawn pixels take register 0, black in the default palette.
+    assert_eq!(pixel(&image, 4, 0), [0, 0, 0, 255]);
+    assert_eq!(pixel(&image, 5, 5), [0, 0, 0, 255]);
+  }
+
+  #[test]
+  fn test_bands_overlay_and_transparency() {
+    let fixture = include_bytes!("../../tests/fixtures/sixel/bands.six");
+    let image = decode(fixture_content(fixture)).unwrap();
+    // No raster attributes: the size is the area drawn.
+    assert_eq!((image.width, image.height), (4, 7));
+
+    let blue = [0, 0, 255, 255];
+    let red = [255, 0, 0, 255];
+    assert_eq!(pixel(&image, 1, 5), blue);
+    // `$` returned to the start of the band; `?` left columns 0-1 alone.
+    assert_eq!(pixel(&image, 0, 0), blue);
+    assert_eq!(pixel(&image, 2, 0), red);
+    assert_eq!(pixel(&image, 3, 5), red);
+    // The second band only set its top row, three times.
+    assert_eq!(pixel(&image, 2, 6), blue);
+    assert_eq!(pixel(&image, 3, 6), [0, 0, 0, 0]);
+  }
+
+  #[test]
+  fn test_size_limits() {
+    let fixture = include_bytes!("../../tests/fixtures/sixel/oversized.six");
+    let error = decode(fixture_content(fixture)).unwrap_err();
+    assert_eq!(error.code, "EFBIG");
+
+    let repeat = format!("q#0!{}~", MAX_SIXEL_DIMENSION + 1);
+    assert_eq!(decode(repeat.as_bytes()).unwrap_err().code, "EFBIG");
+
+    // Each dimension fits, but not the pixel count.
+    let tall = b"q\"1;1;4096;4097#0~";
+    assert_eq!(decode(tall).unwrap_err().code, "EFBIG");
+
+    assert_eq!(decode(b"q").unwrap_err().code, "ENODATA");
+    assert_eq!(decode(b"1;2").unwrap_err().code, "EINVAL");
+  }
+
+  #[test]
+  fn test_canvas_stays_within_pixel_limit() {
+    // The first band doubles the canvas to 4096 columns for a 2049 pixel
+    // wide image; bands then grow it past 4096 rows.
+    let mut data = b"!2048~~".to_vec();
+    for _ in 0..700 {
+      data.extend_from_slice(b"-~");
+    }
+    let mut decoder = Decoder::new();
+    decoder.run(&data).unwrap();
+    assert_eq!((decoder.width, decoder.height), (2049, 4206));
+    assert!(decoder.pixels.len() as u64 <= MAX_SIXEL_PIXELS);
+  }
+
+  #[test]
+  fn test_height_hint() {
+    let fixture = include_bytes!("../../tests/fixtures/sixel/red_square.six");
+    assert_eq!(height_hint(fixture_content(fixture)), 6);
+    // No raster attributes: two bands, the first ended by `-`.
+    let fixture = include_bytes!("../../tests/fixtures/sixel/bands.six");
+    assert_eq!(height_hint(fixture_content(fixture)), 12);
+    assert_eq!(height_hint(b"q#0~-~-"), 12);
+    assert_eq!(height_hint(b"1;2"), 0);
+  }
+
+  #[test]
+  fn test_hls_colors() {
+    assert_eq!(hls_to_rgb(0, 50, 100), [0, 0, 255]);
+    assert_eq!(hls_to_rgb(120, 50, 100), [255, 0, 0]);
+    assert_eq!(hls_to_rgb(240, 50, 100), [0, 255, 0]);
+    assert_eq!(hls_to_rgb(0, 100, 0), [255, 255, 255]);
+  }
+}
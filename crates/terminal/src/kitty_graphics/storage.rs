@@ -1,10 +1,14 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use gpui::RenderImage;
 use image::{ImageBuffer, Rgba};
 use tracing::warn;
 
-use super::command::{KittyCommand, KittyFormat, StoredImage};
+use super::{
+  animation::{Animation, DEFAULT_FRAME_GAP_MS, blank_canvas, compose},
+  command::{KittyCommand, KittyError, KittyFormat, StoredImage},
+  transmission::load_payload,
+};
 
 const DEFAULT_MAX_MEMORY: usize = 320 * 1024 * 1024; // 320 MB
 
@@ -19,6 +23,8 @@ pub struct KittyImageStorage {
   max_memory: usize,
   /// Next auto-assigned image ID.
   next_id: u32,
+  /// Image number (I=) to the ID of the newest image stored with it.
+  numbers: HashMap<u32, u32>,
 }
 
 impl KittyImageStorage {
@@ -29,27 +35,31 @@ impl KittyImageStorage {
       total_memory: 0,
       max_memory: DEFAULT_MAX_MEMORY,
       next_id: 1,
+      numbers: HashMap::new(),
     }
   }
 
   /// Store a decoded image from a completed Kitty command.
-  /// Returns the assigned image ID, or an error message.
-  pub fn store(&mut self, cmd: &KittyCommand) -> Result<u32, String> {
+  /// Returns the assigned image ID, or why the image was rejected.
+  pub fn store(&mut self, cmd: &KittyCommand) -> Result<u32, KittyError> {
+    let payload = load_payload(cmd)?;
+    let (width, height, rgba_data) =
+      decode_image_data(cmd.format, cmd.source_width, cmd.source_height, &payload)?;
+
     let image_id = if cmd.image_id == 0 {
       self.allocate_id()
     } else {
       cmd.image_id
     };
 
-    let (width, height, rgba_data) = decode_image_data(cmd)?;
-
-    let img_buf: ImageBuffer<Rgba<u8>, Vec<u8>> =
-      ImageBuffer::from_raw(width, height, rgba_data.clone())
-        .ok_or_else(|| "Failed to create image buffer".to_string())?;
-
-    let frame = image::Frame::new(img_buf);
-    let render_image = Arc::new(RenderImage::new(vec![frame]));
+    let render_image = render_image(width, height, rgba_data)?;
     let memory_bytes = (width as usize) * (height as usize) * 4;
+    if memory_bytes > self.max_memory {
+      return Err(KittyError::new(
+        "EFBIG",
+        format!("{width}x{height} image is larger than the image memory limit"),
+      ));
+    }
 
     // Evict old images if needed.
     while self.total_memory + memory_bytes > self.max_memory && !self.access_order.is_empty() {
@@ -66,18 +76,192 @@ impl KittyImageStorage {
       image_id,
       StoredImage {
         id: image_id,
-        render_image,
+        frames: vec![render_image],
         width,
         height,
         memory_bytes,
+        animation: Animation::new(),
       },
     );
     self.access_order.push(image_id);
     self.total_memory += memory_bytes;
+    if cmd.image_number != 0 {
+      self.numbers.insert(cmd.image_number, image_id);
+    }
 
     Ok(image_id)
   }
 
+  /// Check that a command's image loads and decodes, without storing it.
+  /// This answers `a=q` queries.
+  pub fn check(cmd: &KittyCommand) -> Result<(), KittyError> {
+    let payload = load_payload(cmd)?;
+    decode_image_data(cmd.format, cmd.source_width, cmd.source_height, &payload).map(|_| ())
+  }
+
+  /// Add or edit an animation frame of a stored image (`a=f`).
+  ///
+  /// A new frame starts as a copy of its base frame, or as a blank canvas,
+  /// and the payload is composed onto it. New frames count toward the
+  /// memory limit, evicting other images when needed.
+  pub fn add_frame(&mut self, cmd: &KittyCommand, now: Instant) -> Result<(), KittyError> {
+    let params = cmd
+      .frame
+      .ok_or_else(|| KittyError::new("EINVAL", "Missing frame parameters"))?;
+    let image_id = cmd.image_id;
+    let image = self
+      .images
+      .get(&image_id)
+      .ok_or_else(|| KittyError::new("ENOENT", format!("No image with id {image_id}")))?;
+    let (width, height) = (image.width, image.height);
+    if params.x >= width || params.y >= height {
+      return Err(KittyError::new(
+        "EINVAL",
+        format!(
+          "Frame offset {},{} is outside the {width}x{height} image",
+          params.x, params.y
+        ),
+      ));
+    }
+    let frame_count = image.frames.len();
+    let edit = match params.frame {
+      0 => None,
+      frame => Some(frame_index(frame, frame_count)?),
+    };
+
+    let mut canvas = match (edit, params.base_frame) {
+      (Some(ix), _) => frame_pixels(&image.frames[ix])?,
+      (None, 0) => blank_canvas(width, height, params.background),
+      (None, base) => frame_pixels(&image.frames[frame_index(base, frame_count)?])?,
+    };
+    let payload = load_payload(cmd)?;
+    if !payload.is_empty() {
+      // Raw frame data defaults to the size of the image.
+      let (data_width, data_height) = if cmd.source_width == 0 || cmd.source_height == 0 {
+        (width, height)
+      } else {
+        (cmd.source_width, cmd.source_height)
+      };
+      let (data_width, data_height, data) =
+        decode_image_data(cmd.format, data_width, data_height, &payload)?;
+      compose(
+        &mut canvas,
+        width,
+        height,
+        &data,
+        data_width,
+        data_height,
+        params.x,
+        params.y,
+        params.overwrite,
+      );
+    }
+    let frame = render_image(width, height, canvas)?;
+    let gap = frame_gap(params.gap_ms);
+
+    if let Some(ix) = edit {
+      let image = self.images.get_mut(&image_id).expect("image checked above");
+      image.frames[ix] = frame;
+      if let Some(gap) = gap {
+        image.animation.gaps[ix] = gap;
+      }
+      self.touch(image_id);
+      return Ok(());
+    }
+
+    let frame_bytes = (width as usize) * (height as usize) * 4;
+    self.make_room(image_id, frame_bytes)?;
+    let image = self.images.get_mut(&image_id).expect("image checked above");
+    image.frames.push(frame);
+    image
+      .animation
+      .push_frame(gap.unwrap_or(DEFAULT_FRAME_GAP_MS), now);
+    image.memory_bytes += frame_bytes;
+    self.total_memory += frame_bytes;
+    Ok(())
+  }
+
+  /// Apply an animation control command (`a=a`).
+  pub fn control_animation(&mut self, cmd: &KittyCommand, now: Instant) -> Result<(), KittyError> {
+    let control = cmd
+      .animation
+      .ok_or_else(|| KittyError::new("EINVAL", "Missing animation parameters"))?;
+    let image_id = cmd.image_id;
+    let image = self
+      .images
+      .get_mut(&image_id)
+      .ok_or_else(|| KittyError::new("ENOENT", format!("No image with id {image_id}")))?;
+    let animation = &mut image.animation;
+    let frame_count = animation.frame_count();
+
+    if control.frame != 0
+      && let Some(gap) = frame_gap(control.gap_ms)
+    {
+      animation.gaps[frame_index(control.frame, frame_count)?] = gap;
+    }
+    if control.current_frame != 0 {
+      animation.set_current(frame_index(control.current_frame, frame_count)?, now);
+    }
+    match control.loops {
+      0 => {}
+      1 => animation.max_loops = 0,
+      loops => animation.max_loops = loops - 1,
+    }
+    if let Some(state) = control.state {
+      animation.set_state(state, now);
+    }
+    Ok(())
+  }
+
+  /// Drop all frames but the root frame of `image_id`, or of every image
+  /// when it is 0.
+  pub fn remove_animation_frames(&mut self, image_id: u32) {
+    for image in self.images.values_mut() {
+      if image_id != 0 && image.id != image_id {
+        continue;
+      }
+      let freed = image.frame_bytes() * (image.frames.len() - 1);
+      image.frames.truncate(1);
+      image.animation.truncate_to_root();
+      image.memory_bytes -= freed;
+      self.total_memory = self.total_memory.saturating_sub(freed);
+    }
+  }
+
+  /// Move playing animations on to the frames due at `now`.
+  /// Returns whether any image now shows a different frame.
+  pub fn advance_animations(&mut self, now: Instant) -> bool {
+    let mut changed = false;
+    for image in self.images.values_mut() {
+      changed |= image.animation.advance(now);
+    }
+    changed
+  }
+
+  /// The earliest time a playing animation changes frame.
+  pub fn next_animation_deadline(&self) -> Option<Instant> {
+    self
+      .images
+      .values()
+      .filter_map(|image| image.animation.deadline())
+      .min()
+  }
+
+  /// IDs of the stored images in `first..=last`.
+  pub fn ids_in_range(&self, first: u32, last: u32) -> Vec<u32> {
+    self
+      .images
+      .keys()
+      .copied()
+      .filter(|id| (first..=last).contains(id))
+      .collect()
+  }
+
+  /// ID of the newest stored image transmitted with `image_number`.
+  pub fn id_for_number(&self, image_number: u32) -> Option<u32> {
+    self.numbers.get(&image_number).copied()
+  }
+
   /// Get a stored image, updating access order for LRU.
   pub fn get(&mut self, image_id: u32) -> Option<&StoredImage> {
     if self.images.contains_key(&image_id) {
@@ -98,6 +282,7 @@ impl KittyImageStorage {
     if let Some(img) = self.images.remove(&image_id) {
       self.total_memory = self.total_memory.saturating_sub(img.memory_bytes);
       self.access_order.retain(|&id| id != image_id);
+      self.numbers.retain(|_, id| *id != image_id);
       true
     } else {
       false
@@ -107,6 +292,7 @@ impl KittyImageStorage {
   /// Delete all images.
   pub fn clear(&mut self) {
     self.images.clear();
+    self.numbers.clear();
     self.access_order.clear();
     self.total_memory = 0;
   }
@@ -121,6 +307,21 @@ impl KittyImageStorage {
     id
   }
 
+  /// Evict other images until `bytes` more fit for `image_id`.
+  fn make_room(&mut self, image_id: u32, bytes: usize) -> Result<(), KittyError> {
+    self.touch(image_id);
+    while self.total_memory + bytes > self.max_memory && self.access_order.len() > 1 {
+      self.evict_oldest();
+    }
+    if self.total_memory + bytes > self.max_memory {
+      return Err(KittyError::new(
+        "EFBIG",
+        format!("Frames of image {image_id} exceed the image memory limit"),
+      ));
+    }
+    Ok(())
+  }
+
   fn touch(&mut self, image_id: u32) {
     self.access_order.retain(|&id| id != image_id);
     self.access_order.push(image_id);
@@ -131,6 +332,7 @@ impl KittyImageStorage {
       if let Some(img) = self.images.remove(&oldest_id) {
         self.total_memory = self.total_memory.saturating_sub(img.memory_bytes);
       }
+      self.numbers.retain(|_, id| *id != oldest_id);
       self.access_order.remove(0);
     }
   }
@@ -139,42 +341,57 @@ impl KittyImageStorage {
 /// Decode raw image data from a Kitty command payload into BGRA pixels.
 ///
 /// GPUI's `paint_image` expects BGRA format, so all decode paths convert to BGRA.
-fn decode_image_data(cmd: &KittyCommand) -> Result<(u32, u32, Vec<u8>), String> {
-  let (w, h, mut data) = match cmd.format {
-    KittyFormat::Png => decode_png(&cmd.payload)?,
+fn decode_image_data(
+  format: KittyFormat,
+  width: u32,
+  height: u32,
+  payload: &[u8],
+) -> Result<(u32, u32, Vec<u8>), KittyError> {
+  let (w, h, mut data) = match format {
+    KittyFormat::Png => decode_png(payload)?,
     KittyFormat::Rgba => {
-      let w = cmd.source_width;
-      let h = cmd.source_height;
+      let (w, h) = (width, height);
       if w == 0 || h == 0 {
-        return Err("RGBA format requires s= and v= (width/height)".to_string());
+        return Err(KittyError::new(
+          "EINVAL",
+          "RGBA format requires s= and v= (width/height)",
+        ));
       }
       let expected = (w as usize) * (h as usize) * 4;
-      if cmd.payload.len() != expected {
-        return Err(format!(
-          "RGBA payload size mismatch: expected {} got {}",
-          expected,
-          cmd.payload.len()
+      if payload.len() != expected {
+        return Err(KittyError::new(
+          "ENODATA",
+          format!(
+            "RGBA payload size mismatch: expected {} got {}",
+            expected,
+            payload.len()
+          ),
         ));
       }
-      (w, h, cmd.payload.clone())
+      (w, h, payload.to_vec())
     }
     KittyFormat::Rgb => {
-      let w = cmd.source_width;
-      let h = cmd.source_height;
+      let (w, h) = (width, height);
       if w == 0 || h == 0 {
-        return Err("RGB format requires s= and v= (width/height)".to_string());
+        return Err(KittyError::new(
+          "EINVAL",
+          "RGB format requires s= and v= (width/height)",
+        ));
       }
       let expected = (w as usize) * (h as usize) * 3;
-      if cmd.payload.len() != expected {
-        return Err(format!(
-          "RGB payload size mismatch: expected {} got {}",
-          expected,
-          cmd.payload.len()
+      if payload.len() != expected {
+        return Err(KittyError::new(
+          "ENODATA",
+          format!(
+            "RGB payload size mismatch: expected {} got {}",
+            expected,
+            payload.len()
+          ),
         ));
       }
       // Convert RGB to RGBA.
       let mut rgba = Vec::with_capacity((w as usize) * (h as usize) * 4);
-      for chunk in cmd.payload.chunks_exact(3) {
+      for chunk in payload.chunks_exact(3) {
         rgba.push(chunk[0]);
         rgba.push(chunk[1]);
         rgba.push(chunk[2]);
@@ -192,7 +409,41 @@ fn decode_image_data(cmd: &KittyCommand) -> Result<(u32, u32, Vec<u8>), String>
   Ok((w, h, data))
 }
 
-fn decode_png(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+/// Wrap BGRA pixels in a single-frame `RenderImage`.
+fn render_image(width: u32, height: u32, bgra: Vec<u8>) -> Result<Arc<RenderImage>, KittyError> {
+  let img_buf: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_raw(width, height, bgra)
+    .ok_or_else(|| KittyError::new("EINVAL", "Failed to create image buffer"))?;
+  Ok(Arc::new(RenderImage::new(vec![image::Frame::new(img_buf)])))
+}
+
+fn frame_pixels(frame: &RenderImage) -> Result<Vec<u8>, KittyError> {
+  frame
+    .as_bytes(0)
+    .map(<[u8]>::to_vec)
+    .ok_or_else(|| KittyError::new("EINVAL", "Frame has no pixel data"))
+}
+
+/// 0-based index of the 1-based frame number `frame`.
+fn frame_index(frame: u32, frame_count: usize) -> Result<usize, KittyError> {
+  if frame == 0 || frame as usize > frame_count {
+    return Err(KittyError::new(
+      "ENOENT",
+      format!("No frame {frame}; the image has {frame_count}"),
+    ));
+  }
+  Ok(frame as usize - 1)
+}
+
+/// A frame gap from `z=`: `None` leaves the gap alone, negative is gapless.
+fn frame_gap(gap_ms: i32) -> Option<u32> {
+  match gap_ms {
+    0 => None,
+    gap if gap < 0 => Some(0),
+    gap => Some(gap as u32),
+  }
+}
+
+fn decode_png(data: &[u8]) -> Result<(u32, u32, Vec<u8>), KittyError> {
   let img = image::load_from_memory_with_format(data, image::ImageFormat::Png)
     .or_else(|_| {
       // Fall back to auto-detect format (some clients send JPEG as f=100).
@@ -200,7 +451,7 @@ fn decode_png(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
     })
     .map_err(|e| {
       warn!("Failed to decode image: {}", e);
-      format!("Failed to decode image: {e}")
+      KittyError::new("EBADPNG", format!("Failed to decode image: {e}"))
     })?;
 
   let rgba = img.to_rgba8();
@@ -211,6 +462,7 @@ fn decode_png(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::kitty_graphics::command::{KittyAction, KittyFrame};
 
   fn make_test_command(image_id: u32, width: u32, height: u32) -> KittyCommand {
     let mut rgba_data = vec![0u8; (width as usize) * (height as usize) * 4];
@@ -247,6 +499,21 @@ mod tests {
     assert!(id > 0);
   }
 
+  #[test]
+  fn test_image_numbers_resolve_to_newest_image() {
+    let mut storage = KittyImageStorage::new();
+    let mut cmd = make_test_command(0, 2, 2);
+    cmd.image_number = 7;
+    let first = storage.store(&cmd).unwrap();
+    let second = storage.store(&cmd).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(storage.id_for_number(7), Some(second));
+    assert_eq!(storage.id_for_number(8), None);
+
+    storage.remove(second);
+    assert_eq!(storage.id_for_number(7), None);
+  }
+
   #[test]
   fn test_remove() {
     let mut storage = KittyImageStorage::new();
@@ -269,6 +536,90 @@ mod tests {
     assert_eq!(storage.image_count(), 0);
   }
 
+  #[test]
+  fn test_store_errors() {
+    let mut storage = KittyImageStorage::new();
+    let mut cmd = make_test_command(1, 2, 2);
+    cmd.payload.pop();
+    assert_eq!(storage.store(&cmd).unwrap_err().code, "ENODATA");
+
+    cmd.source_width = 0;
+    assert_eq!(storage.store(&cmd).unwrap_err().code, "EINVAL");
+
+    storage.max_memory = 4 * 4 * 4;
+    let cmd = make_test_command(2, 5, 5);
+    assert_eq!(storage.store(&cmd).unwrap_err().code, "EFBIG");
+    assert_eq!(storage.image_count(), 0);
+  }
+
+  #[test]
+  fn test_check() {
+    let mut cmd = make_test_command(1, 2, 2);
+    assert!(KittyImageStorage::check(&cmd).is_ok());
+    cmd.source_height = 3;
+    assert_eq!(KittyImageStorage::check(&cmd).unwrap_err().code, "ENODATA");
+  }
+
+  #[test]
+  fn test_frames_count_toward_memory() {
+    let mut storage = KittyImageStorage::new();
+    storage.store(&make_test_command(1, 2, 2)).unwrap();
+    let frame = KittyCommand {
+      action: KittyAction::Frame,
+      image_id: 1,
+      frame: Some(KittyFrame {
+        frame: 0,
+        base_frame: 1,
+        x: 0,
+        y: 0,
+        overwrite: false,
+        background: 0,
+        gap_ms: 0,
+      }),
+      ..Default::default()
+    };
+    let now = Instant::now();
+    storage.add_frame(&frame, now).unwrap();
+    let image = storage.peek(1).unwrap();
+    assert_eq!(image.frames.len(), 2);
+    assert_eq!(image.animation.gaps, vec![DEFAULT_FRAME_GAP_MS; 2]);
+    assert_eq!(image.memory_bytes, 32);
+    assert_eq!(storage.total_memory, 32);
+
+    storage.max_memory = 40;
+    assert_eq!(storage.add_frame(&frame, now).unwrap_err().code, "EFBIG");
+    storage.remove_animation_frames(1);
+    assert_eq!(storage.peek(1).unwrap().frames.len(), 1);
+    assert_eq!(storage.total_memory, 16);
+  }
+
+  #[test]
+  fn test_frame_offset_outside_image_is_rejected() {
+    let mut storage = KittyImageStorage::new();
+    storage.store(&make_test_command(1, 2, 1)).unwrap();
+    let mut frame = KittyCommand {
+      action: KittyAction::Frame,
+      image_id: 1,
+      frame: Some(KittyFrame {
+        frame: 0,
+        base_frame: 0,
+        x: 5,
+        y: 0,
+        overwrite: false,
+        background: 0,
+        gap_ms: 0,
+      }),
+      ..make_test_command(1, 1, 1)
+    };
+    let now = Instant::now();
+    assert_eq!(storage.add_frame(&frame, now).unwrap_err().code, "EINVAL");
+
+    frame.frame.as_mut().unwrap().x = 0;
+    frame.frame.as_mut().unwrap().y = 1;
+    assert_eq!(storage.add_frame(&frame, now).unwrap_err().code, "EINVAL");
+    assert_eq!(storage.peek(1).unwrap().frames.len(), 1);
+  }
+
   #[test]
   fn test_lru_eviction() {
     let mut storage = KittyImageStorage::new();
@@ -0,0 +1,413 @@
+//! Loading image data sent by reference.
+//!
+//! With `t=f`, `t=t` and `t=s` the payload is a file path or a shared memory
+//! name instead of the image itself. Only regular files are read, never
+//! anything under `/proc`, `/sys` or `/dev` (apart from `/dev/shm`), and
+//! every read is capped at [`MAX_TRANSMISSION_BYTES`]. Reads can still take
+//! a while, so the terminal runs [`load_payload`] for these commands on the
+//! background executor.
+
+use std::{
+  borrow::Cow,
+  fs::{File, OpenOptions},
+  io::{Read, Seek, SeekFrom},
+  path::{Path, PathBuf},
+};
+
+use super::command::{KittyAction, KittyCommand, KittyError, KittyTransmission};
+
+/// Largest amount of image data accepted from any transmission medium.
+pub const MAX_TRANSMISSION_BYTES: usize = 256 * 1024 * 1024;
+
+/// Temporary files are only deleted when their name contains this marker.
+const TEMP_FILE_MARKER: &str = "tty-graphics-protocol";
+
+/// Whether `cmd` needs image data that was sent by reference, so loading it
+/// reads a file or shared memory.
+pub fn loads_by_reference(cmd: &KittyCommand) -> bool {
+  cmd.transmission != KittyTransmission::Direct
+    && matches!(
+      cmd.action,
+      KittyAction::Transmit
+        | KittyAction::TransmitAndDisplay
+        | KittyAction::Query
+        | KittyAction::Frame
+    )
+}
+
+/// The image data of `cmd`, read from the medium it was transmitted with.
+pub fn load_payload(cmd: &KittyCommand) -> Result<Cow<'_, [u8]>, KittyError> {
+  match cmd.transmission {
+    KittyTransmission::Direct => {
+      check_size(cmd.payload.len())?;
+      Ok(Cow::Borrowed(&cmd.payload))
+    }
+    KittyTransmission::File => {
+      let path = payload_path(cmd)?;
+      read_file(&path, cmd).map(Cow::Owned)
+    }
+    KittyTransmission::TempFile => {
+      let path = payload_path(cmd)?;
+      let path = canonicalize(&path)?;
+      if !is_temp_file(&path) {
+        return Err(KittyError::new(
+          "EPERM",
+          format!(
+            "temporary file {} must be in a temporary directory and contain '{TEMP_FILE_MARKER}' in its name",
+            path.display()
+          ),
+        ));
+      }
+      let data = read_file(&path, cmd);
+      let _ = std::fs::remove_file(&path);
+      data.map(Cow::Owned)
+    }
+    KittyTransmission::SharedMemory => {
+      let name = payload_str(cmd)?;
+      read_shared_memory(name, cmd).map(Cow::Owned)
+    }
+  }
+}
+
+fn payload_str(cmd: &KittyCommand) -> Result<&str, KittyError> {
+  let name = std::str::from_utf8(&cmd.payload)
+    .map_err(|_| KittyError::new("EINVAL", "path is not valid UTF-8"))?;
+  if name.is_empty() || name.contains('\0') {
+    return Err(KittyError::new("EINVAL", "missing path"));
+  }
+  Ok(name)
+}
+
+fn payload_path(cmd: &KittyCommand) -> Result<PathBuf, KittyError> {
+  let path = PathBuf::from(payload_str(cmd)?);
+  if !path.is_absolute() {
+    return Err(KittyError::new(
+      "EINVAL",
+      format!("path {} is not absolute", path.display()),
+    ));
+  }
+  Ok(path)
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, KittyError> {
+  std::fs::canonicalize(path).map_err(|e| io_error(path, &e))
+}
+
+fn read_file(path: &Path, cmd: &KittyCommand) -> Result<Vec<u8>, KittyError> {
+  let path = canonicalize(path)?;
+  if is_forbidden(&path) {
+    return Err(KittyError::new(
+      "EPERM",
+      format!("refusing to read {}", path.display()),
+    ));
+  }
+  let mut file = open_regular_file(&path)?;
+  let metadata = file.metadata().map_err(|e| io_error(&path, &e))?;
+  let len = read_len(metadata.len(), cmd)?;
+  file
+    .seek(SeekFrom::Start(cmd.data_offset as u64))
+    .map_err(|e| io_error(&path, &e))?;
+  let mut data = Vec::with_capacity(len);
+  file
+    .take(len as u64)
+    .read_to_end(&mut data)
+    .map_err(|e| io_error(&path, &e))?;
+  if data.len() < len {
+    return Err(KittyError::new(
+      "ENODATA",
+      format!("{} ended before {len} bytes", path.display()),
+    ));
+  }
+  Ok(data)
+}
+
+/// Open `path` for reading if it is a regular file. Opening a FIFO blocks
+/// until something writes to it, so the type is checked before opening, and
+/// again on the opened file in case the path was replaced in between.
+fn open_regular_file(path: &Path) -> Result<File, KittyError> {
+  let not_regular = || {
+    KittyError::new(
+      "EINVAL",
+      format!("{} is not a regular file", path.display()),
+    )
+  };
+  let metadata = std::fs::metadata(path).map_err(|e| io_error(path, &e))?;
+  if !metadata.is_file() {
+    return Err(not_regular());
+  }
+
+  let mut options = OpenOptions::new();
+  options.read(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    options.custom_flags(libc::O_NONBLOCK);
+  }
+  let file = options.open(path).map_err(|e| io_error(path, &e))?;
+  let metadata = file.metadata().map_err(|e| io_error(path, &e))?;
+  if !metadata.is_file() {
+    return Err(not_regular());
+  }
+  Ok(file)
+}
+
+/// Bytes to read from an object of `total` bytes, honouring `O=` and `S=`.
+fn read_len(total: u64, cmd: &KittyCommand) -> Result<usize, KittyError> {
+  let offset = cmd.data_offset as u64;
+  if offset > total {
+    return Err(KittyError::new(
+      "EINVAL",
+      format!("offset {offset} is past the end of the data ({total} bytes)"),
+    ));
+  }
+  let available = total - offset;
+  let len = match cmd.data_size {
+    0 => available,
+    size if size as u64 > available => {
+      return Err(KittyError::new(
+        "ENODATA",
+        format!("requested {size} bytes but only {available} are available"),
+      ));
+    }
+    size => size as u64,
+  };
+  let len = usize::try_from(len).unwrap_or(usize::MAX);
+  check_size(len)?;
+  Ok(len)
+}
+
+fn check_size(len: usize) -> Result<(), KittyError> {
+  if len > MAX_TRANSMISSION_BYTES {
+    return Err(KittyError::new(
+      "EFBIG",
+      format!("{len} bytes is over the {MAX_TRANSMISSION_BYTES} byte limit"),
+    ));
+  }
+  Ok(())
+}
+
+fn is_forbidden(path: &Path) -> bool {
+  if path.starts_with("/dev/shm") {
+    return false;
+  }
+  ["/proc", "/sys", "/dev"]
+    .iter()
+    .any(|dir| path.starts_with(dir))
+}
+
+fn is_temp_file(path: &Path) -> bool {
+  let named = path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .is_some_and(|name| name.contains(TEMP_FILE_MARKER));
+  let temp_dirs = [std::env::temp_dir(), "/tmp".into(), "/dev/shm".into()];
+  named
+    && temp_dirs
+      .iter()
+      .any(|dir| std::fs::canonicalize(dir).is_ok_and(|dir| path.parent() == Some(dir.as_path())))
+}
+
+fn io_error(path: &Path, e: &std::io::Error) -> KittyError {
+  let code = match e.kind() {
+    std::io::ErrorKind::NotFound => "ENOENT",
+    std::io::ErrorKind::PermissionDenied => "EPERM",
+    _ => "EIO",
+  };
+  KittyError::new(code, format!("{}: {e}", path.display()))
+}
+
+#[cfg(unix)]
+fn read_shared_memory(name: &str, cmd: &KittyCommand) -> Result<Vec<u8>, KittyError> {
+  use std::{ffi::CString, os::fd::FromRawFd};
+
+  let name = if name.starts_with('/') {
+    name.to_string()
+  } else {
+    format!("/{name}")
+  };
+  if name[1..].contains('/') {
+    return Err(KittyError::new(
+      "EINVAL",
+      format!("invalid shared memory name {name}"),
+    ));
+  }
+  let c_name = CString::new(name.as_str())
+    .map_err(|_| KittyError::new("EINVAL", "invalid shared memory name"))?;
+
+  let fd = unsafe { libc::shm_open(c_name.as_ptr(), libc::O_RDONLY, 0) };
+  if fd < 0 {
+    return Err(io_error(Path::new(&name), &std::io::Error::last_os_error()));
+  }
+  // The client hands the object over; unlink it whether or not it is usable.
+  unsafe { libc::shm_unlink(c_name.as_ptr()) };
+  let file = unsafe { File::from_raw_fd(fd) };
+
+  let total = file
+    .metadata()
+    .map_err(|e| io_error(Path::new(&name), &e))?
+    .len();
+  let len = read_len(total, cmd)?;
+  if len == 0 {
+    return Ok(Vec::new());
+  }
+  // Shared memory can't be read(2) on every platform, so map it instead.
+  let map_len = cmd.data_offset as usize + len;
+  let ptr = unsafe {
+    libc::mmap(
+      std::ptr::null_mut(),
+      map_len,
+      libc::PROT_READ,
+      libc::MAP_SHARED,
+      fd,
+      0,
+    )
+  };
+  if ptr == libc::MAP_FAILED {
+    return Err(io_error(Path::new(&name), &std::io::Error::last_os_error()));
+  }
+  let data = unsafe {
+    std::slice::from_raw_parts((ptr as *const u8).add(cmd.data_offset as usize), len).to_vec()
+  };
+  unsafe { libc::munmap(ptr, map_len) };
+  Ok(data)
+}
+
+#[cfg(not(unix))]
+fn read_shared_memory(_name: &str, _cmd: &KittyCommand) -> Result<Vec<u8>, KittyError> {
+  Err(KittyError::new(
+    "EINVAL",
+    "shared memory transmission is not supported on this platform",
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn command(transmission: KittyTransmission, path: &Path) -> KittyCommand {
+    KittyCommand {
+      transmission,
+      payload: path.to_str().unwrap().as_bytes().to_vec(),
+      ..Default::default()
+    }
+  }
+
+  fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{name}-{}", std::process::id()))
+  }
+
+  #[test]
+  fn test_file_honours_offset_and_size() {
+    let path = temp_path("kazeterm-kitty-file");
+    std::fs::write(&path, b"0123456789").unwrap();
+
+    let mut cmd = command(KittyTransmission::File, &path);
+    assert_eq!(&*load_payload(&cmd).unwrap(), b"0123456789");
+    cmd.data_offset = 2;
+    cmd.data_size = 3;
+    assert_eq!(&*load_payload(&cmd).unwrap(), b"234");
+    cmd.data_size = 20;
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "ENODATA");
+    cmd.data_offset = 20;
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "EINVAL");
+
+    assert!(path.exists(), "t=f must not delete the file");
+    std::fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_file_sandboxing() {
+    let cmd = command(KittyTransmission::File, Path::new("relative.png"));
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "EINVAL");
+
+    let cmd = command(
+      KittyTransmission::File,
+      &temp_path("kazeterm-kitty-missing"),
+    );
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "ENOENT");
+
+    if Path::new("/proc/self/status").exists() {
+      let cmd = command(KittyTransmission::File, Path::new("/proc/self/status"));
+      assert_eq!(load_payload(&cmd).unwrap_err().code, "EPERM");
+    }
+
+    let cmd = command(KittyTransmission::File, &std::env::temp_dir());
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "EINVAL");
+  }
+
+  #[test]
+  fn test_temp_file_is_deleted_only_when_marked() {
+    let marked = temp_path("kazeterm-tty-graphics-protocol");
+    std::fs::write(&marked, b"pixels").unwrap();
+    let cmd = command(KittyTransmission::TempFile, &marked);
+    assert_eq!(&*load_payload(&cmd).unwrap(), b"pixels");
+    assert!(!marked.exists());
+
+    let unmarked = temp_path("kazeterm-kitty-unmarked");
+    std::fs::write(&unmarked, b"pixels").unwrap();
+    let cmd = command(KittyTransmission::TempFile, &unmarked);
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "EPERM");
+    assert!(unmarked.exists());
+    std::fs::remove_file(&unmarked).unwrap();
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_fifo_is_rejected_without_opening_it() {
+    use std::ffi::CString;
+
+    // Nothing ever writes to the FIFO, so opening it would hang the test.
+    let fifo = temp_path("kazeterm-fifo-tty-graphics-protocol");
+    let c_path = CString::new(fifo.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+    let cmd = command(KittyTransmission::File, &fifo);
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "EINVAL");
+    let cmd = command(KittyTransmission::TempFile, &fifo);
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "EINVAL");
+    let _ = std::fs::remove_file(&fifo);
+  }
+
+  #[test]
+  fn test_size_limit() {
+    let cmd = KittyCommand::default();
+    assert!(read_len(MAX_TRANSMISSION_BYTES as u64, &cmd).is_ok());
+    assert_eq!(
+      read_len(MAX_TRANSMISSION_BYTES as u64 + 1, &cmd)
+        .unwrap_err()
+        .code,
+      "EFBIG"
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_shared_memory_is_read_and_unlinked() {
+    use std::{ffi::CString, io::Write, os::fd::FromRawFd};
+
+    let name = format!("/kazeterm-kitty-shm-{}", std::process::id());
+    let c_name = CString::new(name.as_str()).unwrap();
+    let fd = unsafe {
+      libc::shm_open(
+        c_name.as_ptr(),
+        libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+        0o600,
+      )
+    };
+    assert!(fd >= 0, "shm_open failed");
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.set_len(6).unwrap();
+    file.write_all(b"pixels").unwrap();
+    drop(file);
+
+    let cmd = KittyCommand {
+      transmission: KittyTransmission::SharedMemory,
+      payload: name.as_bytes().to_vec(),
+      data_offset: 1,
+      data_size: 4,
+      ..Default::default()
+    };
+    assert_eq!(&*load_payload(&cmd).unwrap(), b"ixel");
+    assert_eq!(load_payload(&cmd).unwrap_err().code, "ENOENT");
+  }
+}
@@ -14,6 +14,7 @@ mod mouse;
 pub mod osc7;
 mod pty_info;
 pub mod scrollbar;
+pub mod shell_integration;
 mod terminal;
 mod terminal_bounds;
 mod terminal_content;
@@ -26,15 +27,19 @@ mod terminal_view;
 pub mod test_support;
 
 pub use pty_info::PtyProcessInfo;
-pub use terminal::{PtySender, SelectionPhase, Terminal, TerminalEventListener};
+pub use terminal::{
+  PtySender, SearchResultLine, SearchState, SelectionPhase, Terminal, TerminalEventListener,
+  TextRegion, scrollback_replay_bytes,
+};
 pub use terminal_bounds::TerminalBounds;
 pub use terminal_view::{TerminalEvent, TerminalView};
 
 use config::KeybindingConfig;
 use gpui::{App, KeyBinding};
 use terminal_view::{
-  Copy, Paste, ScrollPageDown, ScrollPageUp, SendPageDown, SendPageUp, SendTab, SendTabPrev,
-  ZoomIn, ZoomOut, ZoomReset,
+  Copy, CopyLastCommandOutput, CopyMode, HintMode, Paste, ScrollPageDown, ScrollPageUp,
+  ScrollToNextPrompt, ScrollToPreviousPrompt, SelectCommandOutput, SendPageDown, SendPageUp,
+  SendTab, SendTabPrev, ZoomIn, ZoomOut, ZoomReset,
 };
 
 pub fn init(cx: &mut App, keybindings: &KeybindingConfig) {
@@ -67,32 +72,68 @@ pub fn bind_terminal_keys(cx: &mut App, keybindings: &KeybindingConfig) {
   bindings.extend(
     keybindings
       .copy
-      .iter()
-      .map(|binding| KeyBinding::new(binding, Copy, Some("Terminal"))),
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, Copy, Some("Terminal"))),
   );
   bindings.extend(
     keybindings
       .paste
-      .iter()
-      .map(|binding| KeyBinding::new(binding, Paste, Some("Terminal"))),
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, Paste, Some("Terminal"))),
   );
   bindings.extend(
     keybindings
       .zoom_in
-      .iter()
-      .map(|binding| KeyBinding::new(binding, ZoomIn, Some("Terminal"))),
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, ZoomIn, Some("Terminal"))),
   );
   bindings.extend(
     keybindings
       .zoom_out
-      .iter()
-      .map(|binding| KeyBinding::new(binding, ZoomOut, Some("Terminal"))),
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, ZoomOut, Some("Terminal"))),
   );
   bindings.extend(
     keybindings
       .zoom_reset
-      .iter()
-      .map(|binding| KeyBinding::new(binding, ZoomReset, Some("Terminal"))),
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, ZoomReset, Some("Terminal"))),
+  );
+  bindings.extend(
+    keybindings
+      .hint_mode
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, HintMode, Some("Terminal"))),
+  );
+  bindings.extend(
+    keybindings
+      .scroll_to_previous_prompt
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, ScrollToPreviousPrompt, Some("Terminal"))),
+  );
+  bindings.extend(
+    keybindings
+      .scroll_to_next_prompt
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, ScrollToNextPrompt, Some("Terminal"))),
+  );
+  bindings.extend(
+    keybindings
+      .select_command_output
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, SelectCommandOutput, Some("Terminal"))),
+  );
+  bindings.extend(
+    keybindings
+      .copy_last_command_output
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, CopyLastCommandOutput, Some("Terminal"))),
+  );
+  bindings.extend(
+    keybindings
+      .copy_mode
+      .keymap_bindings()
+      .map(|binding| KeyBinding::new(&binding, CopyMode, Some("Terminal"))),
   );
 
   cx.bind_keys(bindings);
@@ -23,6 +23,10 @@ pub struct MinimapState {
   pub display_offset: usize,
   /// History size (max scroll offset)
   pub history_size: usize,
+  /// Absolute lines (0 = oldest history line) of failed commands
+  pub failed_command_lines: Vec<usize>,
+  /// Absolute lines (0 = oldest history line) holding search matches
+  pub search_match_lines: Vec<usize>,
 }
 
 impl MinimapState {
@@ -37,9 +41,21 @@ impl MinimapState {
       visible_lines,
       display_offset,
       history_size,
+      failed_command_lines: Vec::new(),
+      search_match_lines: Vec::new(),
     }
   }
 
+  pub fn with_failed_command_lines(mut self, failed_command_lines: Vec<usize>) -> Self {
+    self.failed_command_lines = failed_command_lines;
+    self
+  }
+
+  pub fn with_search_match_lines(mut self, search_match_lines: Vec<usize>) -> Self {
+    self.search_match_lines = search_match_lines;
+    self
+  }
+
   /// Calculate the viewport indicator position and size
   pub fn viewport_metrics(&self, minimap_height: Pixels) -> (Pixels, Pixels) {
     if self.total_lines == 0 {
@@ -92,6 +108,8 @@ pub fn paint_minimap(
   color_table: &[Option<Rgb>; ANSI_COLOR_COUNT],
   background_color: Hsla,
   viewport_color: Hsla,
+  failed_command_color: Hsla,
+  search_match_color: Hsla,
   window: &mut Window,
 ) {
   // Paint background
@@ -160,6 +178,32 @@ pub fn paint_minimap(
     window.paint_quad(fill(char_bounds, minimap_color));
   }
 
+  // Mark search matches and failed commands across the full width of their line
+  for (lines, color) in [
+    (&state.search_match_lines, search_match_color),
+    (&state.failed_command_lines, failed_command_color),
+  ] {
+    for &line in lines {
+      let tick_top = bounds.origin.y + px(line as f32 * MINIMAP_LINE_HEIGHT);
+      if tick_top >= bounds.origin.y + bounds.size.height {
+        break;
+      }
+      window.paint_quad(fill(
+        Bounds {
+          origin: Point {
+            x: bounds.origin.x,
+            y: tick_top,
+          },
+          size: gpui::Size {
+            width: bounds.size.width,
+            height: minimap_line_height,
+          },
+        },
+        color.opacity(0.6),
+      ));
+    }
+  }
+
   // Paint viewport indicator
   let (viewport_top, viewport_height) = state.viewport_metrics(bounds.size.height);
   let viewport_bounds = Bounds {
@@ -6,6 +6,9 @@ pub const SCROLLBAR_WIDTH: f32 = 12.0;
 /// Minimum thumb height in pixels
 pub const MIN_THUMB_HEIGHT: f32 = 20.0;
 
+/// Height of a failed-command or search-match tick in pixels
+const TICK_HEIGHT: f32 = 2.0;
+
 /// Scrollbar state for rendering
 #[derive(Clone, Debug)]
 pub struct ScrollbarState {
@@ -17,6 +20,10 @@ pub struct ScrollbarState {
   pub display_offset: usize,
   /// History size (max scroll offset)
   pub history_size: usize,
+  /// Absolute lines (0 = oldest history line) of failed commands
+  pub failed_command_lines: Vec<usize>,
+  /// Absolute lines (0 = oldest history line) holding search matches
+  pub search_match_lines: Vec<usize>,
 }
 
 impl ScrollbarState {
@@ -31,7 +38,27 @@ impl ScrollbarState {
       visible_lines,
       display_offset,
       history_size,
+      failed_command_lines: Vec::new(),
+      search_match_lines: Vec::new(),
+    }
+  }
+
+  pub fn with_failed_command_lines(mut self, failed_command_lines: Vec<usize>) -> Self {
+    self.failed_command_lines = failed_command_lines;
+    self
+  }
+
+  pub fn with_search_match_lines(mut self, search_match_lines: Vec<usize>) -> Self {
+    self.search_match_lines = search_match_lines;
+    self
+  }
+
+  /// Position of an absolute line along the track (0.0 = top, 1.0 = bottom)
+  pub fn line_ratio(&self, line: usize) -> f32 {
+    if self.total_lines == 0 {
+      return 0.0;
     }
+    (line as f32 / self.total_lines as f32).min(1.0)
   }
 
   /// Returns true if the scrollbar should be shown (there's content to scroll)
@@ -151,11 +178,14 @@ impl ScrollbarState {
 }
 
 /// Paint a scrollbar into the given bounds
+#[allow(clippy::too_many_arguments)]
 pub fn paint_scrollbar(
   bounds: Bounds<Pixels>,
   state: &ScrollbarState,
   track_color: Hsla,
   thumb_color: Hsla,
+  tick_color: Hsla,
+  search_tick_color: Hsla,
   hovered: bool,
   window: &mut Window,
 ) {
@@ -202,6 +232,49 @@ pub fn paint_scrollbar(
     Hsla::transparent_black(),
     BorderStyle::default(),
   ));
+
+  // Paint ticks over the thumb so they stay visible, failed commands on top
+  paint_ticks(
+    bounds,
+    state,
+    &state.search_match_lines,
+    search_tick_color,
+    window,
+  );
+  paint_ticks(
+    bounds,
+    state,
+    &state.failed_command_lines,
+    tick_color,
+    window,
+  );
+}
+
+fn paint_ticks(
+  bounds: Bounds<Pixels>,
+  state: &ScrollbarState,
+  lines: &[usize],
+  color: Hsla,
+  window: &mut Window,
+) {
+  let track_height = bounds.size.height;
+  for &line in lines {
+    let tick_top = (bounds.origin.y + track_height * state.line_ratio(line))
+      .min(bounds.origin.y + track_height - px(TICK_HEIGHT));
+    window.paint_quad(fill(
+      Bounds {
+        origin: Point {
+          x: bounds.origin.x + px(2.0),
+          y: tick_top,
+        },
+        size: gpui::Size {
+          width: bounds.size.width - px(4.0),
+          height: px(TICK_HEIGHT),
+        },
+      },
+      color,
+    ));
+  }
 }
 
 #[cfg(test)]
@@ -241,6 +314,15 @@ mod tests {
     assert_eq!(state.position_to_offset(0.5), 40);
   }
 
+  #[test]
+  fn test_line_ratio() {
+    let state = ScrollbarState::new(100, 20, 0, 80).with_failed_command_lines(vec![25]);
+    assert_eq!(state.failed_command_lines, vec![25]);
+    assert!((state.line_ratio(0) - 0.0).abs() < 0.001);
+    assert!((state.line_ratio(25) - 0.25).abs() < 0.001);
+    assert!((state.line_ratio(150) - 1.0).abs() < 0.001);
+  }
+
   #[test]
   fn test_is_on_thumb() {
     // Scrollbar at bottom (display_offset = 0), thumb is at the bottom
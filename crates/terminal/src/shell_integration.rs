@@ -0,0 +1,573 @@
+//! OSC 133 semantic prompt marks (FinalTerm shell integration).
+//!
+//! Shells that source the snippets in `assets/shell-integration` report the
+//! phases of every command with `ESC ] 133 ; <kind> [; <args>] BEL`:
+//!
+//! - `A`: the prompt starts
+//! - `B`: the prompt ends and command input starts
+//! - `C`: the command was submitted and its output starts
+//! - `D [; <exit code>]`: the command finished
+//!
+//! Marks carry the absolute line they were seen on (0 = oldest history line),
+//! the same coordinate space used by image placements.
+
+use std::collections::BTreeMap;
+use std::ops::{Range, RangeInclusive};
+use std::time::{Duration, Instant};
+
+/// The phase of a command reported by an OSC 133 sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SemanticMarkKind {
+  PromptStart,
+  InputStart,
+  OutputStart,
+  CommandFinished { exit_code: Option<i32> },
+}
+
+/// An OSC 133 mark together with where and when it was seen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SemanticMark {
+  pub kind: SemanticMarkKind,
+  /// Absolute line of the cursor when the mark was emitted.
+  pub line: i32,
+  pub at: Instant,
+}
+
+/// Parse the parameters of an OSC 133 sequence, without the leading `133`.
+///
+/// Unknown kinds return `None`; `key=value` options after the kind are ignored.
+pub fn parse_osc133_params(params: &[&[u8]]) -> Option<SemanticMarkKind> {
+  match *params.first()? {
+    b"A" => Some(SemanticMarkKind::PromptStart),
+    b"B" => Some(SemanticMarkKind::InputStart),
+    b"C" => Some(SemanticMarkKind::OutputStart),
+    b"D" => Some(SemanticMarkKind::CommandFinished {
+      exit_code: params
+        .get(1)
+        .and_then(|code| std::str::from_utf8(code).ok())
+        .and_then(|code| code.parse().ok()),
+    }),
+    _ => None,
+  }
+}
+
+/// Find every complete OSC 133 sequence in a chunk of raw PTY output bytes.
+///
+/// Returns the byte range of each sequence, from its `ESC` through its
+/// terminator, with the parsed mark. Sequences split across chunks are
+/// skipped, as for OSC 7.
+pub fn extract_osc133_marks(data: &[u8]) -> Vec<(Range<usize>, SemanticMarkKind)> {
+  const PREFIX: &[u8] = b"\x1b]133;";
+
+  let mut marks = Vec::new();
+  let mut i = 0;
+  while let Some(start) = find(&data[i..], PREFIX).map(|pos| i + pos) {
+    let body_start = start + PREFIX.len();
+    let Some((body_end, terminator_len)) =
+      data[body_start..]
+        .iter()
+        .enumerate()
+        .find_map(|(ix, &byte)| match byte {
+          0x07 => Some((body_start + ix, 1)),
+          0x1B if data.get(body_start + ix + 1) == Some(&b'\\') => Some((body_start + ix, 2)),
+          _ => None,
+        })
+    else {
+      break;
+    };
+
+    let params: Vec<&[u8]> = data[body_start..body_end].split(|&b| b == b';').collect();
+    if let Some(kind) = parse_osc133_params(&params) {
+      marks.push((start..body_end + terminator_len, kind));
+    }
+    i = body_end + terminator_len;
+  }
+
+  marks
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+  haystack
+    .windows(needle.len())
+    .position(|window| window == needle)
+}
+
+/// One prompt/command cycle assembled from its marks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandMarks {
+  pub prompt_line: i32,
+  pub input_line: Option<i32>,
+  pub output_line: Option<i32>,
+  pub finished_line: Option<i32>,
+  pub exit_code: Option<i32>,
+  /// Time between the `C` and `D` marks.
+  pub duration: Option<Duration>,
+  started_at: Option<Instant>,
+}
+
+impl CommandMarks {
+  fn new(prompt_line: i32) -> Self {
+    Self {
+      prompt_line,
+      input_line: None,
+      output_line: None,
+      finished_line: None,
+      exit_code: None,
+      duration: None,
+      started_at: None,
+    }
+  }
+
+  /// Whether the command ran and reported a non-zero exit code.
+  pub fn failed(&self) -> bool {
+    self.exit_code.is_some_and(|code| code != 0)
+  }
+
+  /// Lines the command printed: from its `C` mark to the line before its `D`
+  /// mark, or to `cursor_line` while it is still running. `None` when the
+  /// command never ran or printed nothing.
+  pub fn output_lines(&self, cursor_line: i32) -> Option<RangeInclusive<i32>> {
+    let start = self.output_line?;
+    let end = self.finished_line.map_or(cursor_line, |line| line - 1);
+    (start <= end).then_some(start..=end)
+  }
+}
+
+/// A finished command whose prompt is in the viewport, for the exit-status gutter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VisibleCommand {
+  /// Row of the prompt, counted from the top of the viewport.
+  pub viewport_line: usize,
+  pub exit_code: i32,
+  pub duration: Option<Duration>,
+}
+
+impl VisibleCommand {
+  pub fn failed(&self) -> bool {
+    self.exit_code != 0
+  }
+
+  /// Text shown when hovering the command's gutter mark.
+  pub fn hover_label(&self) -> String {
+    let duration = self
+      .duration
+      .map(format_duration)
+      .unwrap_or_else(|| "unknown duration".to_string());
+    if self.failed() {
+      format!("{duration} (exit {})", self.exit_code)
+    } else {
+      duration
+    }
+  }
+}
+
+/// Format a command duration compactly, e.g. `850ms`, `12.4s`, `3m 05s`, `1h 02m`.
+pub fn format_duration(duration: Duration) -> String {
+  let secs = duration.as_secs();
+  if secs >= 3600 {
+    format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+  } else if secs >= 60 {
+    format!("{}m {:02}s", secs / 60, secs % 60)
+  } else if duration.as_millis() >= 1000 {
+    format!("{:.1}s", duration.as_secs_f64())
+  } else {
+    format!("{}ms", duration.as_millis())
+  }
+}
+
+/// Per-line record of the commands a shell reported via OSC 133.
+#[derive(Debug, Default)]
+pub struct SemanticMarks {
+  /// Commands keyed by the absolute line of their prompt.
+  commands: BTreeMap<i32, CommandMarks>,
+}
+
+impl SemanticMarks {
+  pub fn apply(&mut self, mark: SemanticMark) {
+    if mark.kind == SemanticMarkKind::PromptStart {
+      // A prompt at or above a known one means the screen was redrawn.
+      self.commands.split_off(&mark.line);
+      self
+        .commands
+        .insert(mark.line, CommandMarks::new(mark.line));
+      return;
+    }
+
+    // Marks before the first prompt (e.g. a `D` while the snippet loads)
+    // have nothing to attach to.
+    let Some(command) = self.commands.values_mut().next_back() else {
+      return;
+    };
+    match mark.kind {
+      SemanticMarkKind::PromptStart => {}
+      SemanticMarkKind::InputStart => command.input_line = Some(mark.line),
+      SemanticMarkKind::OutputStart => {
+        command.output_line = Some(mark.line);
+        command.started_at = Some(mark.at);
+      }
+      SemanticMarkKind::CommandFinished { exit_code } => {
+        if command.finished_line.is_some() {
+          return;
+        }
+        command.finished_line = Some(mark.line);
+        // `D` without `C` is sent for empty command lines; it has no status.
+        if let Some(started_at) = command.started_at {
+          command.exit_code = exit_code;
+          command.duration = Some(mark.at.saturating_duration_since(started_at));
+        }
+      }
+    }
+  }
+
+  /// All commands, oldest first.
+  pub fn commands(&self) -> impl DoubleEndedIterator<Item = &CommandMarks> {
+    self.commands.values()
+  }
+
+  /// The command whose prompt, input or output covers `line`.
+  pub fn command_at(&self, line: i32) -> Option<&CommandMarks> {
+    let (_, command) = self.commands.range(..=line).next_back()?;
+    match command.finished_line {
+      Some(finished_line) if finished_line < line => None,
+      _ => Some(command),
+    }
+  }
+
+  /// The mark that starts on `line`, if any. When several marks share a line
+  /// (e.g. a one-line prompt) the earliest phase wins.
+  pub fn mark_at(&self, line: i32) -> Option<SemanticMarkKind> {
+    let command = self.command_at(line)?;
+    if command.prompt_line == line {
+      Some(SemanticMarkKind::PromptStart)
+    } else if command.input_line == Some(line) {
+      Some(SemanticMarkKind::InputStart)
+    } else if command.output_line == Some(line) {
+      Some(SemanticMarkKind::OutputStart)
+    } else if command.finished_line == Some(line) {
+      Some(SemanticMarkKind::CommandFinished {
+        exit_code: command.exit_code,
+      })
+    } else {
+      None
+    }
+  }
+
+  /// The closest prompt line above `line`.
+  pub fn previous_prompt(&self, line: i32) -> Option<i32> {
+    self
+      .commands
+      .range(..line)
+      .next_back()
+      .map(|(&line, _)| line)
+  }
+
+  /// The closest prompt line below `line`.
+  pub fn next_prompt(&self, line: i32) -> Option<i32> {
+    self
+      .commands
+      .range(line.saturating_add(1)..)
+      .next()
+      .map(|(&line, _)| line)
+  }
+
+  /// Commands with an exit code whose prompt is among the `viewport_lines`
+  /// rows starting at absolute line `viewport_top`.
+  pub fn visible_commands(&self, viewport_top: i32, viewport_lines: usize) -> Vec<VisibleCommand> {
+    self
+      .commands
+      .range(viewport_top..viewport_top.saturating_add(viewport_lines as i32))
+      .filter_map(|(&line, command)| {
+        Some(VisibleCommand {
+          viewport_line: (line - viewport_top) as usize,
+          exit_code: command.exit_code?,
+          duration: command.duration,
+        })
+      })
+      .collect()
+  }
+
+  /// Absolute prompt lines of the commands that failed, oldest first.
+  /// A failed command whose prompt was rebased above line 0 by
+  /// [`Self::rebase`] is marked on line 0 while the rest of it is still there.
+  pub fn failed_command_lines(&self) -> Vec<usize> {
+    self
+      .commands
+      .values()
+      .filter(|command| command.failed())
+      .map(|command| command.prompt_line.max(0) as usize)
+      .collect()
+  }
+
+  /// Move every mark up after `lines` lines were dropped from the top of the
+  /// scrollback, forgetting commands that scrolled out entirely.
+  pub fn rebase(&mut self, lines: usize) {
+    let lines = i32::try_from(lines).unwrap_or(i32::MAX);
+    let mut commands = std::mem::take(&mut self.commands).into_values().peekable();
+    while let Some(mut command) = commands.next() {
+      command.prompt_line = command.prompt_line.saturating_sub(lines);
+      for line in [
+        &mut command.input_line,
+        &mut command.output_line,
+        &mut command.finished_line,
+      ]
+      .into_iter()
+      .flatten()
+      {
+        *line = line.saturating_sub(lines);
+      }
+
+      // A command ends at its `D` mark, or at the latest before the next prompt.
+      let end = command.finished_line.or_else(|| {
+        commands
+          .peek()
+          .map(|next| next.prompt_line.saturating_sub(lines).saturating_sub(1))
+      });
+      if end.is_none_or(|end| end >= 0) {
+        self.commands.insert(command.prompt_line, command);
+      }
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.commands.is_empty()
+  }
+
+  pub fn clear(&mut self) {
+    self.commands.clear();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn mark(kind: SemanticMarkKind, line: i32, at: Instant) -> SemanticMark {
+    SemanticMark { kind, line, at }
+  }
+
+  fn parse(params: &str) -> Option<SemanticMarkKind> {
+    let params: Vec<&[u8]> = params.split(';').map(str::as_bytes).collect();
+    parse_osc133_params(&params)
+  }
+
+  #[test]
+  fn parses_all_mark_kinds() {
+    assert_eq!(parse("A;cl=m"), Some(SemanticMarkKind::PromptStart));
+    assert_eq!(parse("B"), Some(SemanticMarkKind::InputStart));
+    assert_eq!(parse("C"), Some(SemanticMarkKind::OutputStart));
+    assert_eq!(
+      parse("D;127"),
+      Some(SemanticMarkKind::CommandFinished {
+        exit_code: Some(127)
+      })
+    );
+    assert_eq!(
+      parse("D"),
+      Some(SemanticMarkKind::CommandFinished { exit_code: None })
+    );
+    assert_eq!(parse("P;k=i"), None);
+  }
+
+  #[test]
+  fn extracts_marks_with_offsets() {
+    let data = b"out\n\x1b]133;D;1\x07\x1b]133;A\x1b\\$ \x1b]133;B\x07";
+    assert_eq!(
+      extract_osc133_marks(data),
+      vec![
+        (
+          4..14,
+          SemanticMarkKind::CommandFinished { exit_code: Some(1) }
+        ),
+        (14..23, SemanticMarkKind::PromptStart),
+        (25..33, SemanticMarkKind::InputStart),
+      ]
+    );
+  }
+
+  #[test]
+  fn incomplete_sequence_is_skipped() {
+    assert!(extract_osc133_marks(b"\x1b]133;A").is_empty());
+    assert!(extract_osc133_marks(b"\x1b]7;file:///tmp\x07").is_empty());
+  }
+
+  #[test]
+  fn records_exit_code_and_duration() {
+    let start = Instant::now();
+    let mut marks = SemanticMarks::default();
+    marks.apply(mark(SemanticMarkKind::PromptStart, 0, start));
+    marks.apply(mark(SemanticMarkKind::InputStart, 0, start));
+    marks.apply(mark(SemanticMarkKind::OutputStart, 1, start));
+    marks.apply(mark(
+      SemanticMarkKind::CommandFinished { exit_code: Some(2) },
+      3,
+      start + Duration::from_secs(5),
+    ));
+    marks.apply(mark(SemanticMarkKind::PromptStart, 3, start));
+
+    let command = marks.command_at(2).unwrap();
+    assert_eq!(command.prompt_line, 0);
+    assert_eq!(command.exit_code, Some(2));
+    assert_eq!(command.duration, Some(Duration::from_secs(5)));
+    assert!(command.failed());
+    assert_eq!(marks.mark_at(1), Some(SemanticMarkKind::OutputStart));
+    assert_eq!(marks.mark_at(3), Some(SemanticMarkKind::PromptStart));
+    assert_eq!(marks.commands().count(), 2);
+  }
+
+  #[test]
+  fn empty_command_has_no_exit_code() {
+    let now = Instant::now();
+    let mut marks = SemanticMarks::default();
+    marks.apply(mark(SemanticMarkKind::PromptStart, 0, now));
+    marks.apply(mark(
+      SemanticMarkKind::CommandFinished { exit_code: Some(1) },
+      1,
+      now,
+    ));
+    let command = marks.command_at(0).unwrap();
+    assert_eq!(command.finished_line, Some(1));
+    assert_eq!(command.exit_code, None);
+    assert!(!command.failed());
+  }
+
+  #[test]
+  fn prompt_above_known_prompts_drops_them() {
+    let now = Instant::now();
+    let mut marks = SemanticMarks::default();
+    marks.apply(mark(SemanticMarkKind::PromptStart, 0, now));
+    marks.apply(mark(SemanticMarkKind::PromptStart, 5, now));
+    marks.apply(mark(SemanticMarkKind::PromptStart, 2, now));
+    assert_eq!(
+      marks
+        .commands()
+        .map(|command| command.prompt_line)
+        .collect::<Vec<_>>(),
+      vec![0, 2]
+    );
+  }
+
+  #[test]
+  fn rebase_moves_marks_and_forgets_evicted_commands() {
+    let now = Instant::now();
+    let mut marks = SemanticMarks::default();
+    marks.apply(mark(SemanticMarkKind::PromptStart, 0, now));
+    marks.apply(mark(SemanticMarkKind::OutputStart, 1, now));
+    marks.apply(mark(
+      SemanticMarkKind::CommandFinished { exit_code: Some(0) },
+      2,
+      now,
+    ));
+    marks.apply(mark(SemanticMarkKind::PromptStart, 2, now));
+    marks.apply(mark(SemanticMarkKind::OutputStart, 3, now));
+    marks.apply(mark(
+      SemanticMarkKind::CommandFinished { exit_code: Some(1) },
+      8,
+      now,
+    ));
+    marks.apply(mark(SemanticMarkKind::PromptStart, 8, now));
+
+    marks.rebase(3);
+
+    let commands = marks.commands().cloned().collect::<Vec<_>>();
+    assert_eq!(commands.len(), 2);
+    assert_eq!(commands[0].prompt_line, -1);
+    assert_eq!(commands[0].output_line, Some(0));
+    assert_eq!(commands[0].finished_line, Some(5));
+    assert_eq!(commands[1].prompt_line, 5);
+    assert_eq!(marks.failed_command_lines(), vec![0]);
+    assert_eq!(marks.previous_prompt(5), Some(-1));
+
+    marks.rebase(6);
+    assert_eq!(marks.commands().count(), 1);
+    assert_eq!(marks.commands().next().unwrap().prompt_line, -1);
+  }
+
+  #[test]
+  fn finds_neighbouring_prompts() {
+    let now = Instant::now();
+    let mut marks = SemanticMarks::default();
+    for line in [2, 10, 30] {
+      marks.apply(mark(SemanticMarkKind::PromptStart, line, now));
+    }
+    assert_eq!(marks.previous_prompt(10), Some(2));
+    assert_eq!(marks.previous_prompt(2), None);
+    assert_eq!(marks.next_prompt(10), Some(30));
+    assert_eq!(marks.next_prompt(11), Some(30));
+    assert_eq!(marks.next_prompt(30), None);
+  }
+
+  #[test]
+  fn output_lines_end_before_finish_or_at_cursor() {
+    let now = Instant::now();
+    let mut marks = SemanticMarks::default();
+    marks.apply(mark(SemanticMarkKind::PromptStart, 0, now));
+    assert_eq!(marks.command_at(0).unwrap().output_lines(5), None);
+
+    marks.apply(mark(SemanticMarkKind::OutputStart, 1, now));
+    assert_eq!(marks.command_at(0).unwrap().output_lines(5), Some(1..=5));
+
+    marks.apply(mark(
+      SemanticMarkKind::CommandFinished { exit_code: Some(0) },
+      4,
+      now,
+    ));
+    assert_eq!(marks.command_at(0).unwrap().output_lines(5), Some(1..=3));
+
+    marks.apply(mark(SemanticMarkKind::PromptStart, 4, now));
+    marks.apply(mark(SemanticMarkKind::OutputStart, 5, now));
+    marks.apply(mark(
+      SemanticMarkKind::CommandFinished { exit_code: Some(0) },
+      5,
+      now,
+    ));
+    assert_eq!(marks.command_at(4).unwrap().output_lines(9), None);
+  }
+
+  #[test]
+  fn formats_durations() {
+    assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+    assert_eq!(format_duration(Duration::from_millis(12_400)), "12.4s");
+    assert_eq!(format_duration(Duration::from_secs(185)), "3m 05s");
+    assert_eq!(format_duration(Duration::from_secs(3720)), "1h 02m");
+  }
+
+  #[test]
+  fn visible_commands_carry_status_and_viewport_row() {
+    let start = Instant::now();
+    let mut marks = SemanticMarks::default();
+    for (prompt, exit_code) in [(0, 0), (4, 1)] {
+      marks.apply(mark(SemanticMarkKind::PromptStart, prompt, start));
+      marks.apply(mark(SemanticMarkKind::OutputStart, prompt + 1, start));
+      marks.apply(mark(
+        SemanticMarkKind::CommandFinished {
+          exit_code: Some(exit_code),
+        },
+        prompt + 3,
+        start + Duration::from_secs(2),
+      ));
+    }
+    // The prompt still waiting for a command has no status yet.
+    marks.apply(mark(SemanticMarkKind::PromptStart, 8, start));
+
+    let visible = marks.visible_commands(2, 10);
+    assert_eq!(
+      visible,
+      vec![VisibleCommand {
+        viewport_line: 2,
+        exit_code: 1,
+        duration: Some(Duration::from_secs(2)),
+      }]
+    );
+    assert_eq!(visible[0].hover_label(), "2.0s (exit 1)");
+    assert_eq!(marks.visible_commands(0, 10).len(), 2);
+    assert_eq!(marks.failed_command_lines(), vec![4]);
+
+    marks.rebase(3);
+    assert_eq!(marks.failed_command_lines(), vec![1]);
+    // The prompt is gone but the failed command's output is still in the grid.
+    marks.rebase(2);
+    assert_eq!(marks.failed_command_lines(), vec![0]);
+    marks.rebase(3);
+    assert_eq!(marks.failed_command_lines(), Vec::<usize>::new());
+  }
+}
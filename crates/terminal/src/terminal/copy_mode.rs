@@ -0,0 +1,717 @@
+use std::ops::RangeInclusive;
+
+use gpui::{ClipboardItem, Context};
+use terminal_kernel::{
+  TerminalBackend,
+  index::{Column, Line, Point as AlacPoint, Side},
+  selection::SelectionType,
+  term::cell::Flags,
+};
+
+use super::{SearchState, Terminal};
+
+/// Keys other than plain characters that copy mode reacts to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CopyModeKey {
+  Escape,
+  Enter,
+  Backspace,
+  Left,
+  Right,
+  Up,
+  Down,
+  Home,
+  End,
+  /// `ctrl-v`: start or switch to a block selection.
+  BlockSelection,
+}
+
+/// Copy mode cursor and status text for rendering.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CopyModeDisplay {
+  pub cursor: AlacPoint,
+  pub cursor_char: char,
+  /// Mode indicator, or the search being typed after `/` or `?`.
+  pub indicator: String,
+  /// Matches of the last `/` or `?` search, in grid order.
+  pub search_matches: Vec<RangeInclusive<AlacPoint>>,
+}
+
+/// A cursor movement in copy mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Motion {
+  Left,
+  Right,
+  Up,
+  Down,
+  LineStart,
+  LineEnd,
+  WordForward,
+  WordBackward,
+  WordEnd,
+  Top,
+  Bottom,
+}
+
+/// A search query being typed after `/` (forward) or `?` (backward).
+struct SearchPrompt {
+  backward: bool,
+  query: String,
+}
+
+/// Cursor, selection and pending input of an active copy mode session.
+pub(super) struct CopyModeState {
+  cursor: AlacPoint,
+  /// Selection type and the point it started from, while selecting.
+  selection: Option<(SelectionType, AlacPoint)>,
+  /// `g` typed, waiting for the second `g`.
+  pending_g: bool,
+  search_prompt: Option<SearchPrompt>,
+  /// Direction of the last `/` or `?`, followed by `n` and reversed by `N`.
+  search_backward: bool,
+  /// The last `/` or `?` search, kept apart from the search bar's query.
+  search: Option<SearchState>,
+  search_matches: Vec<RangeInclusive<AlacPoint>>,
+  /// History size and terminal cursor when `search_matches` were found.
+  search_fingerprint: (usize, AlacPoint),
+  /// History size at the last sync, to follow content pushed into scrollback.
+  history_size: usize,
+}
+
+impl Terminal {
+  /// Enter copy mode with its cursor on the terminal cursor, or on the
+  /// closest visible line when the cursor is scrolled out of view.
+  pub fn start_copy_mode(&mut self, cx: &mut Context<Self>) {
+    self.cancel_hint_mode(cx);
+    let top = Line(-(self.term.display_offset() as i32));
+    let bottom = top + (self.term.screen_lines() as i32 - 1);
+    let cursor = self.term.cursor_point();
+    self.copy_mode = Some(CopyModeState {
+      cursor: AlacPoint::new(cursor.line.clamp(top, bottom), cursor.column),
+      selection: None,
+      pending_g: false,
+      search_prompt: None,
+      search_backward: false,
+      search: None,
+      search_matches: Vec::new(),
+      search_fingerprint: (0, AlacPoint::default()),
+      history_size: self.term.history_size(),
+    });
+    cx.notify();
+  }
+
+  pub fn copy_mode_active(&self) -> bool {
+    self.copy_mode.is_some()
+  }
+
+  /// Leave copy mode, dropping its selection and search highlights.
+  pub fn exit_copy_mode(&mut self, cx: &mut Context<Self>) {
+    let Some(state) = self.copy_mode.take() else {
+      return;
+    };
+    if state.selection.is_some() {
+      self.clear_selection(cx);
+    }
+    self.last_content.copy_mode = None;
+    cx.notify();
+  }
+
+  /// Feed one typed character to copy mode.
+  pub fn copy_mode_input(&mut self, c: char, cx: &mut Context<Self>) {
+    let Some(state) = self.copy_mode.as_mut() else {
+      return;
+    };
+    if let Some(prompt) = state.search_prompt.as_mut() {
+      prompt.query.push(c);
+      cx.notify();
+      return;
+    }
+    if std::mem::take(&mut state.pending_g) {
+      if c == 'g' {
+        self.move_copy_cursor(Motion::Top, cx);
+      }
+      return;
+    }
+
+    match c {
+      'h' => self.move_copy_cursor(Motion::Left, cx),
+      'j' => self.move_copy_cursor(Motion::Down, cx),
+      'k' => self.move_copy_cursor(Motion::Up, cx),
+      'l' => self.move_copy_cursor(Motion::Right, cx),
+      '0' => self.move_copy_cursor(Motion::LineStart, cx),
+      '$' => self.move_copy_cursor(Motion::LineEnd, cx),
+      'w' => self.move_copy_cursor(Motion::WordForward, cx),
+      'b' => self.move_copy_cursor(Motion::WordBackward, cx),
+      'e' => self.move_copy_cursor(Motion::WordEnd, cx),
+      'G' => self.move_copy_cursor(Motion::Bottom, cx),
+      'g' => state.pending_g = true,
+      'v' => self.toggle_copy_selection(SelectionType::Simple, cx),
+      'V' => self.toggle_copy_selection(SelectionType::Lines, cx),
+      'y' => self.yank(cx),
+      '/' | '?' => {
+        state.search_prompt = Some(SearchPrompt {
+          backward: c == '?',
+          query: String::new(),
+        });
+        cx.notify();
+      }
+      'n' => {
+        let backward = state.search_backward;
+        self.jump_to_search_match(backward, cx);
+      }
+      'N' => {
+        let backward = !state.search_backward;
+        self.jump_to_search_match(backward, cx);
+      }
+      'q' => self.exit_copy_mode(cx),
+      _ => {}
+    }
+  }
+
+  /// Handle a non-character key in copy mode.
+  pub fn copy_mode_key(&mut self, key: CopyModeKey, cx: &mut Context<Self>) {
+    let Some(state) = self.copy_mode.as_mut() else {
+      return;
+    };
+    state.pending_g = false;
+    if let Some(prompt) = state.search_prompt.as_mut() {
+      match key {
+        CopyModeKey::Escape => state.search_prompt = None,
+        CopyModeKey::Backspace => {
+          prompt.query.pop();
+        }
+        CopyModeKey::Enter => {
+          let prompt = state.search_prompt.take();
+          if let Some(SearchPrompt { backward, query }) = prompt {
+            self.search_from_copy_mode(query, backward, cx);
+          }
+        }
+        _ => {}
+      }
+      cx.notify();
+      return;
+    }
+
+    match key {
+      CopyModeKey::Escape if state.selection.is_some() => {
+        state.selection = None;
+        self.clear_selection(cx);
+      }
+      CopyModeKey::Escape => self.exit_copy_mode(cx),
+      CopyModeKey::Enter => self.yank(cx),
+      CopyModeKey::Backspace | CopyModeKey::Left => self.move_copy_cursor(Motion::Left, cx),
+      CopyModeKey::Right => self.move_copy_cursor(Motion::Right, cx),
+      CopyModeKey::Up => self.move_copy_cursor(Motion::Up, cx),
+      CopyModeKey::Down => self.move_copy_cursor(Motion::Down, cx),
+      CopyModeKey::Home => self.move_copy_cursor(Motion::LineStart, cx),
+      CopyModeKey::End => self.move_copy_cursor(Motion::LineEnd, cx),
+      CopyModeKey::BlockSelection => self.toggle_copy_selection(SelectionType::Block, cx),
+    }
+  }
+
+  /// Account for `lines` lines dropped from the top of the scrollback, so
+  /// the next sync doesn't take the smaller history for content scrolling
+  /// back down. Cursor and anchor lines count from the top of the screen
+  /// and keep pointing at the same text.
+  pub(super) fn rebase_copy_mode(&mut self, lines: usize) {
+    if let Some(state) = self.copy_mode.as_mut() {
+      state.history_size = state.history_size.saturating_sub(lines);
+    }
+  }
+
+  /// Keep the copy mode cursor on the same content as output scrolls it
+  /// into history, and inside the grid after a resize.
+  pub(super) fn sync_copy_mode(&mut self) {
+    let history_size = self.term.history_size();
+    let (topmost, bottommost) = (self.term.topmost_line(), self.term.bottommost_line());
+    let last_column = self.term.last_column();
+    let Some(state) = self.copy_mode.as_mut() else {
+      self.last_content.copy_mode = None;
+      return;
+    };
+
+    let shift = history_size as i32 - state.history_size as i32;
+    state.history_size = history_size;
+    let follow = |point: AlacPoint| {
+      AlacPoint::new(
+        (point.line - shift).clamp(topmost, bottommost),
+        point.column.min(last_column),
+      )
+    };
+    state.cursor = follow(state.cursor);
+    if let Some((_, anchor)) = state.selection.as_mut() {
+      *anchor = follow(*anchor);
+    }
+    if let Some(search) = &state.search {
+      let fingerprint = (history_size, self.term.cursor_point());
+      if fingerprint != state.search_fingerprint {
+        state.search_fingerprint = fingerprint;
+        state.search_matches = Self::execute_search(&*self.term, search);
+      }
+    }
+
+    let indicator = match (&state.search_prompt, state.selection) {
+      (Some(prompt), _) => {
+        format!(
+          "{}{}",
+          if prompt.backward { '?' } else { '/' },
+          prompt.query
+        )
+      }
+      (None, Some((SelectionType::Lines, _))) => "VISUAL LINE".to_string(),
+      (None, Some((SelectionType::Block, _))) => "VISUAL BLOCK".to_string(),
+      (None, Some(_)) => "VISUAL".to_string(),
+      (None, None) => "COPY".to_string(),
+    };
+    self.last_content.copy_mode = Some(CopyModeDisplay {
+      cursor: state.cursor,
+      cursor_char: self.term.cell_at(state.cursor).c,
+      indicator,
+      search_matches: state.search_matches.clone(),
+    });
+  }
+
+  fn move_copy_cursor(&mut self, motion: Motion, cx: &mut Context<Self>) {
+    let Some(state) = self.copy_mode.as_mut() else {
+      return;
+    };
+    state.cursor = copy_mode_motion(&*self.term, state.cursor, motion);
+    self.term.scroll_to_point(state.cursor);
+    self.sync_copy_selection(cx);
+    cx.notify();
+  }
+
+  /// Start a selection of type `ty`, switch the current one to `ty`, or stop
+  /// selecting when it already is `ty`.
+  fn toggle_copy_selection(&mut self, ty: SelectionType, cx: &mut Context<Self>) {
+    let Some(state) = self.copy_mode.as_mut() else {
+      return;
+    };
+    state.selection = match state.selection {
+      Some((current, _)) if current == ty => None,
+      Some((_, anchor)) => Some((ty, anchor)),
+      None => Some((ty, state.cursor)),
+    };
+    if state.selection.is_some() {
+      self.sync_copy_selection(cx);
+    } else {
+      self.clear_selection(cx);
+    }
+  }
+
+  /// Stretch the selection from its anchor to the copy mode cursor.
+  fn sync_copy_selection(&mut self, cx: &mut Context<Self>) {
+    let Some(state) = &self.copy_mode else {
+      return;
+    };
+    let cursor = state.cursor;
+    if let Some((ty, anchor)) = state.selection {
+      let (anchor_side, cursor_side) = selection_sides(ty, anchor, cursor);
+      self.select_range(ty, (anchor, anchor_side), (cursor, cursor_side), cx);
+    }
+  }
+
+  /// Copy the selection, or the cursor line without one, and leave copy mode.
+  fn yank(&mut self, cx: &mut Context<Self>) {
+    let Some(state) = &self.copy_mode else {
+      return;
+    };
+    let text = if state.selection.is_some() {
+      self.term.selection_to_string()
+    } else {
+      let line = state.cursor.line;
+      Some(self.term.bounds_to_string(
+        AlacPoint::new(line, Column(0)),
+        AlacPoint::new(line, self.term.last_column()),
+      ))
+    };
+    if let Some(text) = text {
+      cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+    self.exit_copy_mode(cx);
+  }
+
+  /// Run a `/` or `?` search, matching case only when the query has
+  /// uppercase letters. The search bar's query is left alone.
+  fn search_from_copy_mode(&mut self, query: String, backward: bool, cx: &mut Context<Self>) {
+    let match_case = query.chars().any(char::is_uppercase);
+    let Some(search) = SearchState::new(query, match_case, false, false) else {
+      return;
+    };
+    let fingerprint = (self.term.history_size(), self.term.cursor_point());
+    let search_matches = Self::execute_search(&*self.term, &search);
+    if let Some(state) = self.copy_mode.as_mut() {
+      state.search_backward = backward;
+      state.search = Some(search);
+      state.search_matches = search_matches;
+      state.search_fingerprint = fingerprint;
+    }
+    self.jump_to_search_match(backward, cx);
+  }
+
+  /// Move the cursor to the next search match after (or before) it, wrapping
+  /// around the scrollback.
+  fn jump_to_search_match(&mut self, backward: bool, cx: &mut Context<Self>) {
+    let Some(state) = self.copy_mode.as_mut() else {
+      return;
+    };
+    let starts = state
+      .search_matches
+      .iter()
+      .map(|range| *range.start())
+      .collect::<Vec<_>>();
+    let Some(index) = next_match_index(&starts, state.cursor, backward) else {
+      return;
+    };
+
+    state.cursor = starts[index];
+    self.term.scroll_to_point(starts[index]);
+    self.sync_copy_selection(cx);
+    cx.notify();
+  }
+}
+
+/// Index of the first match start after `cursor` (or the last one before it
+/// when `backward`), wrapping around. `starts` is in grid order.
+fn next_match_index(starts: &[AlacPoint], cursor: AlacPoint, backward: bool) -> Option<usize> {
+  if starts.is_empty() {
+    return None;
+  }
+  let after = starts.partition_point(|start| *start <= cursor);
+  Some(if backward {
+    let before = starts.partition_point(|start| *start < cursor);
+    before.checked_sub(1).unwrap_or(starts.len() - 1)
+  } else if after == starts.len() {
+    0
+  } else {
+    after
+  })
+}
+
+/// Sides for the selection anchor and the cursor so that both cells are
+/// included whichever way the cursor moved.
+fn selection_sides(ty: SelectionType, anchor: AlacPoint, cursor: AlacPoint) -> (Side, Side) {
+  let forward = match ty {
+    SelectionType::Block => cursor.column >= anchor.column,
+    _ => cursor >= anchor,
+  };
+  if forward {
+    (Side::Left, Side::Right)
+  } else {
+    (Side::Right, Side::Left)
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CharClass {
+  Blank,
+  Word,
+  Punctuation,
+}
+
+fn char_class(backend: &dyn TerminalBackend, point: AlacPoint) -> CharClass {
+  let cell = backend.cell_at(point);
+  if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+    return CharClass::Word;
+  }
+  match cell.c {
+    ' ' | '\t' | '\0' => CharClass::Blank,
+    c if c.is_alphanumeric() || c == '_' => CharClass::Word,
+    _ => CharClass::Punctuation,
+  }
+}
+
+/// The cell after `point` in reading order, and whether a hard line break
+/// lies between them.
+fn next_cell(backend: &dyn TerminalBackend, point: AlacPoint) -> Option<(AlacPoint, bool)> {
+  if point.column < backend.last_column() {
+    return Some((AlacPoint::new(point.line, point.column + 1), false));
+  }
+  if point.line >= backend.bottommost_line() {
+    return None;
+  }
+  Some((
+    AlacPoint::new(point.line + 1, Column(0)),
+    !is_wrapped(backend, point.line),
+  ))
+}
+
+/// The cell before `point` in reading order, and whether a hard line break
+/// lies between them.
+fn previous_cell(backend: &dyn TerminalBackend, point: AlacPoint) -> Option<(AlacPoint, bool)> {
+  if point.column > Column(0) {
+    return Some((AlacPoint::new(point.line, point.column - 1), false));
+  }
+  if point.line <= backend.topmost_line() {
+    return None;
+  }
+  let line = point.line - 1;
+  Some((
+    AlacPoint::new(line, backend.last_column()),
+    !is_wrapped(backend, line),
+  ))
+}
+
+fn is_wrapped(backend: &dyn TerminalBackend, line: Line) -> bool {
+  backend
+    .cell_at(AlacPoint::new(line, backend.last_column()))
+    .flags
+    .contains(Flags::WRAPLINE)
+}
+
+fn copy_mode_motion(backend: &dyn TerminalBackend, point: AlacPoint, motion: Motion) -> AlacPoint {
+  match motion {
+    Motion::Left => AlacPoint::new(point.line, Column(point.column.0.saturating_sub(1))),
+    Motion::Right => AlacPoint::new(point.line, (point.column + 1).min(backend.last_column())),
+    Motion::Up => AlacPoint::new(
+      (point.line - Line(1)).max(backend.topmost_line()),
+      point.column,
+    ),
+    Motion::Down => AlacPoint::new(
+      (point.line + Line(1)).min(backend.bottommost_line()),
+      point.column,
+    ),
+    Motion::LineStart => AlacPoint::new(point.line, Column(0)),
+    Motion::LineEnd => {
+      let last_column = backend.last_column().0;
+      let column = (0..=last_column)
+        .rev()
+        .find(|&column| {
+          char_class(backend, AlacPoint::new(point.line, Column(column))) != CharClass::Blank
+        })
+        .unwrap_or(0);
+      AlacPoint::new(point.line, Column(column))
+    }
+    Motion::WordForward => word_forward(backend, point),
+    Motion::WordBackward => word_backward(backend, point),
+    Motion::WordEnd => word_end(backend, point),
+    Motion::Top => AlacPoint::new(backend.topmost_line(), Column(0)),
+    Motion::Bottom => AlacPoint::new(backend.bottommost_line(), Column(0)),
+  }
+}
+
+/// `w`: the start of the next word, where a hard line break also ends a word.
+fn word_forward(backend: &dyn TerminalBackend, mut point: AlacPoint) -> AlacPoint {
+  let class = char_class(backend, point);
+  loop {
+    let Some((next, line_break)) = next_cell(backend, point) else {
+      return point;
+    };
+    point = next;
+    if line_break || char_class(backend, point) != class {
+      break;
+    }
+  }
+  while char_class(backend, point) == CharClass::Blank {
+    let Some((next, _)) = next_cell(backend, point) else {
+      return point;
+    };
+    point = next;
+  }
+  point
+}
+
+/// `b`: the start of the current or previous word.
+fn word_backward(backend: &dyn TerminalBackend, mut point: AlacPoint) -> AlacPoint {
+  loop {
+    let Some((previous, _)) = previous_cell(backend, point) else {
+      return point;
+    };
+    point = previous;
+    if char_class(backend, point) != CharClass::Blank {
+      break;
+    }
+  }
+  let class = char_class(backend, point);
+  while let Some((previous, line_break)) = previous_cell(backend, point) {
+    if line_break || char_class(backend, previous) != class {
+      break;
+    }
+    point = previous;
+  }
+  point
+}
+
+/// `e`: the end of the current or next word.
+fn word_end(backend: &dyn TerminalBackend, mut point: AlacPoint) -> AlacPoint {
+  loop {
+    let Some((next, _)) = next_cell(backend, point) else {
+      return point;
+    };
+    point = next;
+    if char_class(backend, point) != CharClass::Blank {
+      break;
+    }
+  }
+  let class = char_class(backend, point);
+  while let Some((next, line_break)) = next_cell(backend, point) {
+    if line_break || char_class(backend, next) != class {
+      break;
+    }
+    point = next;
+  }
+  point
+}
+
+#[cfg(test)]
+mod tests {
+  use gpui::{AppContext as _, Entity, TestAppContext};
+  use terminal_kernel::{
+    index::{Column, Line, Point as AlacPoint, Side},
+    selection::SelectionType,
+    test_support::backend_with,
+  };
+
+  use super::{CopyModeKey, Motion, copy_mode_motion, next_match_index, selection_sides};
+  use crate::Terminal;
+  use crate::test_support::fake_terminal_with;
+
+  fn point(line: i32, column: usize) -> AlacPoint {
+    AlacPoint::new(Line(line), Column(column))
+  }
+
+  #[test]
+  fn word_motions_stop_at_word_and_punctuation_boundaries() {
+    let backend = backend_with(3, 20, b"let foo.bar = 1;\r\nnext");
+    let w = |p| copy_mode_motion(&backend, p, Motion::WordForward);
+    let b = |p| copy_mode_motion(&backend, p, Motion::WordBackward);
+    let e = |p| copy_mode_motion(&backend, p, Motion::WordEnd);
+
+    assert_eq!(w(point(0, 0)), point(0, 4));
+    assert_eq!(w(point(0, 4)), point(0, 7));
+    assert_eq!(w(point(0, 7)), point(0, 8));
+    assert_eq!(w(point(0, 15)), point(1, 0));
+    assert_eq!(b(point(0, 8)), point(0, 7));
+    assert_eq!(b(point(1, 0)), point(0, 15));
+    assert_eq!(b(point(0, 6)), point(0, 4));
+    assert_eq!(e(point(0, 0)), point(0, 2));
+    assert_eq!(e(point(0, 2)), point(0, 6));
+  }
+
+  #[test]
+  fn line_and_grid_motions_stay_in_bounds() {
+    let backend = backend_with(3, 10, b"abc  \r\n\r\nxyz");
+    let motion = |p, m| copy_mode_motion(&backend, p, m);
+
+    assert_eq!(motion(point(0, 1), Motion::LineEnd), point(0, 2));
+    assert_eq!(motion(point(1, 5), Motion::LineEnd), point(1, 0));
+    assert_eq!(motion(point(0, 2), Motion::LineStart), point(0, 0));
+    assert_eq!(motion(point(0, 0), Motion::Left), point(0, 0));
+    assert_eq!(motion(point(0, 9), Motion::Right), point(0, 9));
+    assert_eq!(motion(point(0, 3), Motion::Up), point(0, 3));
+    assert_eq!(motion(point(2, 3), Motion::Down), point(2, 3));
+    assert_eq!(motion(point(1, 3), Motion::Top), point(0, 0));
+    assert_eq!(motion(point(0, 3), Motion::Bottom), point(2, 0));
+  }
+
+  #[test]
+  fn selection_includes_anchor_and_cursor_cells() {
+    assert_eq!(
+      selection_sides(SelectionType::Simple, point(1, 4), point(2, 0)),
+      (Side::Left, Side::Right)
+    );
+    assert_eq!(
+      selection_sides(SelectionType::Simple, point(1, 4), point(1, 2)),
+      (Side::Right, Side::Left)
+    );
+    assert_eq!(
+      selection_sides(SelectionType::Block, point(1, 4), point(3, 2)),
+      (Side::Right, Side::Left)
+    );
+  }
+
+  #[test]
+  fn search_jumps_wrap_around() {
+    let starts = [point(-3, 0), point(0, 5), point(2, 1)];
+    assert_eq!(next_match_index(&starts, point(0, 0), false), Some(1));
+    assert_eq!(next_match_index(&starts, point(0, 5), false), Some(2));
+    assert_eq!(next_match_index(&starts, point(2, 1), false), Some(0));
+    assert_eq!(next_match_index(&starts, point(0, 5), true), Some(0));
+    assert_eq!(next_match_index(&starts, point(-3, 0), true), Some(2));
+    assert_eq!(next_match_index(&[], point(0, 0), true), None);
+  }
+
+  /// A copy mode session over two lines of output, with the cursor on the
+  /// prompt below them.
+  fn copy_mode_terminal(cx: &mut TestAppContext) -> Entity<Terminal> {
+    let terminal = cx.new(|_| fake_terminal_with(4, 20, b"one two\r\nthree four\r\n$ "));
+    terminal.update(cx, |terminal, cx| terminal.start_copy_mode(cx));
+    terminal
+  }
+
+  fn type_keys(terminal: &Entity<Terminal>, keys: &str, cx: &mut TestAppContext) {
+    terminal.update(cx, |terminal, cx| {
+      for c in keys.chars() {
+        terminal.copy_mode_input(c, cx);
+      }
+    });
+  }
+
+  fn press(terminal: &Entity<Terminal>, key: CopyModeKey, cx: &mut TestAppContext) {
+    terminal.update(cx, |terminal, cx| terminal.copy_mode_key(key, cx));
+  }
+
+  fn clipboard_text(cx: &mut TestAppContext) -> Option<String> {
+    cx.read_from_clipboard().and_then(|item| item.text())
+  }
+
+  #[gpui::test]
+  fn visual_selections_are_yanked_and_leave_copy_mode(cx: &mut TestAppContext) {
+    let terminal = copy_mode_terminal(cx);
+    type_keys(&terminal, "kk0vey", cx);
+    assert_eq!(clipboard_text(cx).as_deref(), Some("one"));
+    assert!(!terminal.read_with(cx, |terminal, _| terminal.copy_mode_active()));
+
+    terminal.update(cx, |terminal, cx| terminal.start_copy_mode(cx));
+    type_keys(&terminal, "kVy", cx);
+    assert_eq!(clipboard_text(cx).unwrap().trim_end(), "three four");
+    assert!(!terminal.read_with(cx, |terminal, _| terminal.copy_mode_active()));
+
+    terminal.update(cx, |terminal, cx| terminal.start_copy_mode(cx));
+    type_keys(&terminal, "kk0", cx);
+    press(&terminal, CopyModeKey::BlockSelection, cx);
+    type_keys(&terminal, "jlly", cx);
+    assert_eq!(
+      clipboard_text(cx).unwrap().lines().collect::<Vec<_>>(),
+      ["one", "thr"]
+    );
+    assert!(!terminal.read_with(cx, |terminal, _| terminal.copy_mode_active()));
+  }
+
+  #[gpui::test]
+  fn escape_clears_the_selection_before_leaving_copy_mode(cx: &mut TestAppContext) {
+    let terminal = copy_mode_terminal(cx);
+    type_keys(&terminal, "kvl", cx);
+    assert!(terminal.read_with(cx, |terminal, _| {
+      terminal.term.selection_to_string().is_some()
+    }));
+
+    press(&terminal, CopyModeKey::Escape, cx);
+    assert!(terminal.read_with(cx, |terminal, _| terminal.copy_mode_active()));
+    assert!(terminal.read_with(cx, |terminal, _| {
+      terminal.term.selection_to_string().is_none()
+    }));
+
+    press(&terminal, CopyModeKey::Escape, cx);
+    assert!(!terminal.read_with(cx, |terminal, _| terminal.copy_mode_active()));
+  }
+
+  #[gpui::test]
+  fn search_prompt_edits_its_query_and_jumps_on_enter(cx: &mut TestAppContext) {
+    let terminal = copy_mode_terminal(cx);
+    type_keys(&terminal, "/tx", cx);
+    press(&terminal, CopyModeKey::Backspace, cx);
+    type_keys(&terminal, "h", cx);
+    let indicator = terminal.update(cx, |terminal, _cx| {
+      terminal.sync_copy_mode();
+      terminal.last_content.copy_mode.clone().unwrap().indicator
+    });
+    assert_eq!(indicator, "/th");
+
+    press(&terminal, CopyModeKey::Enter, cx);
+    assert!(terminal.read_with(cx, |terminal, _| terminal.copy_mode_active()));
+    type_keys(&terminal, "vey", cx);
+    assert_eq!(clipboard_text(cx).as_deref(), Some("three"));
+    assert!(!terminal.read_with(cx, |terminal, _| terminal.copy_mode_active()));
+  }
+}
@@ -0,0 +1,381 @@
+use std::ops::RangeInclusive;
+
+use config::{HintAction, HintsConfig};
+use gpui::{ClipboardItem, Context};
+use regex::Regex;
+use terminal_kernel::{
+  TerminalBackend,
+  index::{Column, Line, Point as AlacPoint},
+  term::cell::Flags,
+};
+
+use super::{Event, Terminal};
+
+const WIDE_CHAR_SPACERS: Flags =
+  Flags::from_bits(Flags::LEADING_WIDE_CHAR_SPACER.bits() | Flags::WIDE_CHAR_SPACER.bits())
+    .unwrap();
+
+/// A labelled match rendered while hint mode is active.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HintLabel {
+  pub range: RangeInclusive<AlacPoint>,
+  pub label: String,
+  /// Number of leading label characters the user has already typed.
+  pub typed: usize,
+}
+
+/// A configured hint rule with its regex compiled.
+struct HintPattern {
+  regex: Regex,
+  action: HintAction,
+}
+
+#[derive(Debug, PartialEq)]
+struct HintMatch {
+  range: RangeInclusive<AlacPoint>,
+  text: String,
+  action: HintAction,
+}
+
+/// Matches and typed label prefix for an active hint mode session.
+pub(super) struct HintState {
+  matches: Vec<(HintMatch, String)>,
+  typed: String,
+  /// `(history_size, display_offset, cursor)` when the hints were collected.
+  /// Any change means the labelled cells moved, so hint mode is cancelled.
+  fingerprint: (usize, usize, AlacPoint),
+}
+
+impl Terminal {
+  /// Label every visible match of the configured hint rules.
+  ///
+  /// Returns `false` (and stays out of hint mode) when nothing matched.
+  pub fn start_hint_mode(&mut self, config: &HintsConfig, cx: &mut Context<Self>) -> bool {
+    let patterns = compile_hint_patterns(config);
+    let matches = visible_hint_matches(&*self.term, &patterns);
+    let labels = hint_labels(&config.label_alphabet(), matches.len());
+    if labels.is_empty() {
+      self.cancel_hint_mode(cx);
+      return false;
+    }
+
+    // The first labels go to the bottom-most matches, closest to the prompt.
+    let matches = matches.into_iter().rev().zip(labels).collect();
+    self.hint_state = Some(HintState {
+      matches,
+      typed: String::new(),
+      fingerprint: self.hint_fingerprint(),
+    });
+    self.refresh_hint_labels();
+    cx.notify();
+    true
+  }
+
+  pub fn hint_mode_active(&self) -> bool {
+    self.hint_state.is_some()
+  }
+
+  pub fn cancel_hint_mode(&mut self, cx: &mut Context<Self>) {
+    if self.hint_state.take().is_some() {
+      self.refresh_hint_labels();
+      cx.notify();
+    }
+  }
+
+  /// Remove the last typed label character.
+  pub fn hint_backspace(&mut self, cx: &mut Context<Self>) {
+    if let Some(state) = self.hint_state.as_mut() {
+      state.typed.pop();
+      self.refresh_hint_labels();
+      cx.notify();
+    }
+  }
+
+  /// Feed one typed character to hint mode. Characters that do not continue
+  /// any label are ignored; completing a label runs its rule's action.
+  pub fn hint_input(&mut self, c: char, cx: &mut Context<Self>) {
+    let Some(state) = self.hint_state.as_mut() else {
+      return;
+    };
+
+    let mut typed = state.typed.clone();
+    typed.push(c);
+    if let Some(index) = state.matches.iter().position(|(_, label)| *label == typed) {
+      let (hint, _) = state.matches.swap_remove(index);
+      self.hint_state = None;
+      self.refresh_hint_labels();
+      self.run_hint_action(hint, cx);
+    } else if state
+      .matches
+      .iter()
+      .any(|(_, label)| label.starts_with(&typed))
+    {
+      state.typed = typed;
+      self.refresh_hint_labels();
+    }
+    cx.notify();
+  }
+
+  /// Cancel hint mode when the labelled content has scrolled or changed.
+  pub(super) fn sync_hint_mode(&mut self) {
+    if let Some(state) = &self.hint_state
+      && state.fingerprint != self.hint_fingerprint()
+    {
+      self.hint_state = None;
+      self.refresh_hint_labels();
+    }
+  }
+
+  fn hint_fingerprint(&self) -> (usize, usize, AlacPoint) {
+    (
+      self.term.history_size(),
+      self.term.display_offset(),
+      self.term.cursor_point(),
+    )
+  }
+
+  fn refresh_hint_labels(&mut self) {
+    self.last_content.hint_labels = self
+      .hint_state
+      .as_ref()
+      .map(|state| {
+        state
+          .matches
+          .iter()
+          .filter(|(_, label)| label.starts_with(&state.typed))
+          .map(|(hint, label)| HintLabel {
+            range: hint.range.clone(),
+            label: label.clone(),
+            typed: state.typed.chars().count(),
+          })
+          .collect()
+      })
+      .unwrap_or_default();
+  }
+
+  fn run_hint_action(&mut self, hint: HintMatch, cx: &mut Context<Self>) {
+    match hint.action {
+      HintAction::Copy => cx.write_to_clipboard(ClipboardItem::new_string(hint.text)),
+      HintAction::Paste => self.paste_text(&hint.text),
+      HintAction::Open => cx.emit(Event::Open(hint.text)),
+    }
+  }
+}
+
+fn compile_hint_patterns(config: &HintsConfig) -> Vec<HintPattern> {
+  config
+    .rules
+    .iter()
+    .filter_map(|rule| match Regex::new(&rule.regex) {
+      Ok(regex) => Some(HintPattern {
+        regex,
+        action: rule.action,
+      }),
+      Err(error) => {
+        tracing::warn!("Ignoring hint rule '{}': {}", rule.name, error);
+        None
+      }
+    })
+    .collect()
+}
+
+/// A soft-wrapped run of visible rows, with the grid point of every char.
+struct LogicalLine {
+  text: String,
+  /// `(byte offset in text, grid point)` for each char pushed to `text`.
+  points: Vec<(usize, AlacPoint)>,
+}
+
+impl LogicalLine {
+  fn point_at(&self, byte_offset: usize) -> AlacPoint {
+    let index = self
+      .points
+      .partition_point(|(offset, _)| *offset <= byte_offset);
+    self.points[index.saturating_sub(1)].1
+  }
+}
+
+fn visible_logical_lines(backend: &dyn TerminalBackend) -> Vec<LogicalLine> {
+  let top = Line(-(backend.display_offset() as i32));
+  let bottom = top + (backend.screen_lines() as i32 - 1);
+  let last_column = backend.last_column();
+  let start = AlacPoint::new(top, Column(0));
+
+  let mut lines = Vec::new();
+  let mut current = LogicalLine {
+    text: String::new(),
+    points: Vec::new(),
+  };
+  let mut previous: Option<AlacPoint> = None;
+  let mut wraps = false;
+
+  let mut visit = |point: AlacPoint, c: char, flags: Flags| {
+    if previous.is_some_and(|previous| point <= previous) {
+      return true;
+    }
+    if point.line > bottom {
+      return false;
+    }
+    if previous.is_some_and(|previous| previous.line != point.line) && !wraps {
+      lines.push(std::mem::replace(
+        &mut current,
+        LogicalLine {
+          text: String::new(),
+          points: Vec::new(),
+        },
+      ));
+    }
+    previous = Some(point);
+    if point.column == last_column {
+      wraps = flags.contains(Flags::WRAPLINE);
+    }
+    if !flags.intersects(WIDE_CHAR_SPACERS) {
+      current.points.push((current.text.len(), point));
+      current.text.push(match c {
+        '\t' | '\0' => ' ',
+        c => c,
+      });
+    }
+    true
+  };
+
+  // Backends differ on whether `iter_from` yields its start cell, so visit it
+  // explicitly and let `visit` skip anything already seen.
+  let first = backend.cell_at(start);
+  visit(start, first.c, first.flags);
+  backend.iter_from(start, &mut |point, cell| visit(point, cell.c, cell.flags));
+  lines.push(current);
+  lines
+}
+
+/// Find matches of `patterns` in the visible viewport, ordered top to bottom.
+/// When matches overlap, the earlier pattern wins.
+fn visible_hint_matches(backend: &dyn TerminalBackend, patterns: &[HintPattern]) -> Vec<HintMatch> {
+  let lines = visible_logical_lines(backend);
+  let mut matches: Vec<HintMatch> = Vec::new();
+
+  for pattern in patterns {
+    for line in &lines {
+      for found in pattern.regex.find_iter(&line.text) {
+        if found.is_empty() {
+          continue;
+        }
+        let last_char_start =
+          found.end() - found.as_str().chars().next_back().map_or(0, char::len_utf8);
+        let range = line.point_at(found.start())..=line.point_at(last_char_start);
+        let overlaps = matches
+          .iter()
+          .any(|other| range.start() <= other.range.end() && other.range.start() <= range.end());
+        if !overlaps {
+          matches.push(HintMatch {
+            range,
+            text: found.as_str().to_string(),
+            action: pattern.action,
+          });
+        }
+      }
+    }
+  }
+
+  matches.sort_by_key(|hint| *hint.range.start());
+  matches
+}
+
+/// Build `count` prefix-free labels of equal length from `alphabet`.
+fn hint_labels(alphabet: &[char], count: usize) -> Vec<String> {
+  if count == 0 || alphabet.len() < 2 {
+    return Vec::new();
+  }
+
+  let mut length = 1;
+  let mut capacity = alphabet.len();
+  while capacity < count {
+    length += 1;
+    capacity = capacity.saturating_mul(alphabet.len());
+  }
+
+  (0..count)
+    .map(|mut index| {
+      let mut label = vec![alphabet[0]; length];
+      for slot in label.iter_mut().rev() {
+        *slot = alphabet[index % alphabet.len()];
+        index /= alphabet.len();
+      }
+      label.into_iter().collect()
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use config::{HintAction, HintsConfig};
+  use terminal_kernel::{
+    index::{Column, Line, Point as AlacPoint},
+    test_support::backend_with,
+  };
+
+  use super::{compile_hint_patterns, hint_labels, visible_hint_matches};
+
+  fn point(line: i32, column: usize) -> AlacPoint {
+    AlacPoint::new(Line(line), Column(column))
+  }
+
+  #[test]
+  fn labels_are_single_characters_when_alphabet_suffices() {
+    assert_eq!(hint_labels(&['a', 'b', 'c'], 2), vec!["a", "b"]);
+  }
+
+  #[test]
+  fn labels_grow_to_stay_prefix_free() {
+    let labels = hint_labels(&['a', 'b'], 3);
+    assert_eq!(labels, vec!["aa", "ab", "ba"]);
+    for label in &labels {
+      assert_eq!(
+        labels
+          .iter()
+          .filter(|other| other.starts_with(label.as_str()))
+          .count(),
+        1
+      );
+    }
+  }
+
+  #[test]
+  fn labels_need_two_alphabet_characters() {
+    assert!(hint_labels(&['a'], 3).is_empty());
+  }
+
+  #[test]
+  fn default_rules_match_visible_text() {
+    let backend = backend_with(
+      3,
+      40,
+      b"commit 1a2b3c4d at 10.0.0.1\r\nsrc/main.rs:12:5 failed\r\npid 1700000000 ok",
+    );
+    let matches = visible_hint_matches(&backend, &compile_hint_patterns(&HintsConfig::default()));
+    let found: Vec<_> = matches
+      .iter()
+      .map(|hint| (hint.text.as_str(), hint.action))
+      .collect();
+    assert_eq!(
+      found,
+      vec![
+        ("1a2b3c4d", HintAction::Copy),
+        ("10.0.0.1", HintAction::Copy),
+        ("src/main.rs:12:5", HintAction::Copy),
+      ]
+    );
+    assert_eq!(matches[0].range, point(0, 7)..=point(0, 14));
+    assert_eq!(matches[2].range, point(1, 0)..=point(1, 15));
+  }
+
+  #[test]
+  fn matches_span_soft_wrapped_rows() {
+    let backend = backend_with(3, 10, b"see https://example.com/x");
+    let matches = visible_hint_matches(&backend, &compile_hint_patterns(&HintsConfig::default()));
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].text, "https://example.com/x");
+    assert_eq!(matches[0].action, HintAction::Open);
+    assert_eq!(matches[0].range, point(0, 4)..=point(2, 4));
+  }
+}
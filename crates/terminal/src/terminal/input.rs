@@ -4,11 +4,14 @@ use std::sync::atomic::Ordering;
 
 use gpui::{Context, Keystroke};
 use terminal_kernel::{
+  SelectionDisplay,
   grid::Scroll,
-  index::{Column, Point as AlacPoint},
+  index::{Column, Line, Point as AlacPoint, Side},
+  selection::{Selection, SelectionType},
+  term::TermMode,
 };
 
-use super::{Event, InternalEvent, Terminal};
+use super::{Event, InternalEvent, Terminal, TextRegion};
 
 impl Terminal {
   pub fn get_content(&self) -> String {
@@ -17,6 +20,27 @@ impl Terminal {
     self.term.bounds_to_string(start, end)
   }
 
+  /// Text of `region`, optionally keeping colors and attributes as SGR sequences.
+  pub fn region_text(&self, region: TextRegion, ansi: bool) -> String {
+    let (top, bottom) = match region {
+      TextRegion::Screen => (Line(0), self.term.bottommost_line()),
+      TextRegion::Scrollback => {
+        if self.term.history_size() == 0 {
+          return String::new();
+        }
+        (self.term.topmost_line(), Line(-1))
+      }
+      TextRegion::All => (self.term.topmost_line(), self.term.bottommost_line()),
+    };
+    let start = AlacPoint::new(top, Column(0));
+    let end = AlacPoint::new(bottom, self.term.last_column());
+    if ansi {
+      self.term.bounds_to_ansi_string(start, end)
+    } else {
+      self.term.bounds_to_string(start, end)
+    }
+  }
+
   pub fn input(&mut self, input: impl Into<Cow<'static, [u8]>>) {
     self.last_input_time = std::time::Instant::now();
     self.events.push_back(InternalEvent::Scroll(Scroll::Bottom));
@@ -33,6 +57,11 @@ impl Terminal {
     if let Some(txt) = self.term.selection_to_string() {
       cx.write_to_clipboard(gpui::ClipboardItem::new_string(txt));
     }
+    self.clear_selection(cx);
+  }
+
+  /// Drop the selection without copying it.
+  pub(super) fn clear_selection(&mut self, cx: &mut Context<Self>) {
     self.term.set_selection(None);
     self.term.sync_selection_display(None);
     self.selection_display = None;
@@ -43,6 +72,54 @@ impl Terminal {
     cx.notify();
   }
 
+  /// Replace the selection with one of type `ty` from `start` to `end`.
+  pub(super) fn select_range(
+    &mut self,
+    ty: SelectionType,
+    (start, start_side): (AlacPoint, Side),
+    (end, end_side): (AlacPoint, Side),
+    cx: &mut Context<Self>,
+  ) {
+    let mut selection = Selection::new(ty, start, start_side);
+    selection.update(end, end_side);
+    self.term.set_selection(Some(selection));
+    self.selection_display = Some(SelectionDisplay {
+      ty,
+      start,
+      start_side,
+      end,
+      end_side,
+    });
+    self.term.sync_selection_display(self.selection_display);
+    self.selection_head = Some(end);
+
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    if let Some(selection_text) = self.term.selection_to_string() {
+      cx.write_to_primary(gpui::ClipboardItem::new_string(selection_text));
+    }
+
+    cx.emit(Event::SelectionsChanged);
+    cx.notify();
+  }
+
+  /// Write pasted text to the PTY, honoring bracketed paste mode.
+  pub fn paste_text(&mut self, text: &str) {
+    // Normalize \r\n to \r for proper terminal line endings
+    let text = text.replace("\r\n", "\r").replace('\n', "\r");
+
+    if self.last_content.mode.contains(TermMode::BRACKETED_PASTE) {
+      // Wrap with bracketed paste escape sequences so applications like
+      // neovim know this is pasted text and won't auto-indent each line.
+      let mut payload = Vec::with_capacity(text.len() + 12);
+      payload.extend_from_slice(b"\x1b[200~");
+      payload.extend_from_slice(text.as_bytes());
+      payload.extend_from_slice(b"\x1b[201~");
+      self.input(payload);
+    } else {
+      self.input(text.into_bytes());
+    }
+  }
+
   pub fn try_keystroke(&mut self, keystroke: &Keystroke, alt_is_meta: bool) -> bool {
     let keyboard_protocol_flags = self.keyboard_protocol_flags.load(Ordering::Relaxed);
     let input = crate::mappings::keys::to_input_bytes(
@@ -5,15 +5,17 @@ use crate::{
   TerminalBounds,
   indexed_cell::IndexedCell,
   kitty_graphics::{
-    ImagePlacement, KittyAction, KittyCommand, KittyDelete, KittyImageStorage, KittyParser,
-    PlacementManager, RawGraphicsCommand,
+    ImagePlacement, KittyAction, KittyCommand, KittyDelete, KittyError, KittyFormat,
+    KittyImageStorage, KittyParser, KittyResponse, KittyTransmission, PlacementManager,
+    RawGraphicsCommand, VirtualPlacement, sixel, transmission,
   },
   mouse::grid_point_and_side,
   pty_info::PtyProcessInfo,
+  shell_integration::{CommandMarks, SemanticMark, SemanticMarkKind, SemanticMarks},
   terminal_content::TerminalContent,
   terminal_hyperlinks::RegexSearches,
 };
-use gpui::{Context, EventEmitter, Pixels, Window, px};
+use gpui::{Context, EventEmitter, Pixels, Task, Window, px};
 use terminal_kernel::{
   SelectionDisplay, TerminalBackend,
   event::Event as AlacTermEvent,
@@ -23,14 +25,23 @@ use terminal_kernel::{
 };
 use themeing::ActiveTheme;
 
+mod copy_mode;
 mod events;
+mod hints;
 mod input;
 mod mouse_scroll;
+mod prompts;
+mod scrollback;
 mod search;
 mod touch;
 
+use copy_mode::CopyModeState;
+pub use copy_mode::{CopyModeDisplay, CopyModeKey};
 pub use events::TerminalEventListener;
-pub use search::SearchState;
+pub use hints::HintLabel;
+use hints::HintState;
+pub use scrollback::scrollback_replay_bytes;
+pub use search::{SearchResultLine, SearchState};
 #[allow(unused_imports)]
 pub use touch::{TouchMode, TouchState};
 
@@ -59,9 +70,15 @@ pub enum Event {
   SelectionsChanged,
   NewNavigationTarget(Option<String>),
   Open(String),
-  /// Emitted when the shell prompt returns (detected via OSC 7 or cwd_file change).
+  /// Emitted when the shell prompt returns (detected via OSC 133, or OSC 7 or
+  /// cwd_file change for shells without integration).
   /// Used to trigger notifications for long-running command completion.
   PromptReturned,
+  /// The shell's working directory changed (reported via OSC 7 or the cwd file).
+  CwdChanged(std::path::PathBuf),
+  /// A kitty image animation started playing; drive it with
+  /// `advance_image_animations`.
+  ImagesAnimating,
 }
 
 /// Abstraction for sending data to the PTY process.
@@ -103,11 +120,21 @@ pub struct Terminal {
   /// Tracks the last time the user sent input (keystrokes/paste) to the terminal.
   /// Used to determine if a command ran long enough to warrant a notification.
   pub last_input_time: std::time::Instant,
+  /// Bumped on every wakeup from the PTY, so callers can tell whether any
+  /// output arrived since they last looked.
+  pub output_generation: u64,
   /// Kitty graphics protocol state.
   graphics_rx: Option<std::sync::mpsc::Receiver<RawGraphicsCommand>>,
+  /// Graphics commands received but not run yet, in arrival order.
+  queued_graphics: VecDeque<RawGraphicsCommand>,
+  /// Reads the file or shared memory payload of a Kitty command on the
+  /// background executor. Queued commands wait until it finishes.
+  graphics_load: Option<Task<()>>,
   graphics_parser: KittyParser,
   pub image_storage: KittyImageStorage,
   pub placement_manager: PlacementManager,
+  /// Whether an image animation is playing and being driven by the view.
+  images_animating: bool,
   /// Shared atomic for signaling cursor advancement to the PTY filter.
   pending_cnl: Option<Arc<std::sync::atomic::AtomicU32>>,
   /// Shared atomic exposing active kitty keyboard protocol flags.
@@ -118,6 +145,12 @@ pub struct Terminal {
   pub osc7_cwd: Option<std::path::PathBuf>,
   /// Path to a temp file where the shell writes its CWD on each prompt.
   cwd_file: Option<std::path::PathBuf>,
+  /// Receives OSC 133 prompt and command marks from the PTY filter or kernel.
+  semantic_rx: Option<std::sync::mpsc::Receiver<SemanticMark>>,
+  /// Commands reported by the shell via OSC 133, keyed by prompt line.
+  pub semantic_marks: SemanticMarks,
+  /// Set once the shell emits OSC 133; prompt returns then come only from its marks.
+  shell_integration_active: bool,
   /// Last known modification time of `cwd_file`, used to detect prompt returns on Windows.
   cwd_file_mtime: Option<std::time::SystemTime>,
   /// Throttle for cwd_file polling (only check every ~500ms).
@@ -127,6 +160,13 @@ pub struct Terminal {
   /// Fingerprint of terminal content at last search execution.
   /// Used to skip re-running the search when nothing changed.
   search_fingerprint: (usize, AlacPoint),
+  /// Absolute lines (0 = oldest history line) matched by the search panel,
+  /// marked on the scrollbar and minimap next to the search bar's matches.
+  pub search_panel_match_lines: Vec<usize>,
+  /// Active hint mode labels. Cleared once a label is chosen or the content moves.
+  hint_state: Option<HintState>,
+  /// Active copy mode cursor and selection, driven from the keyboard.
+  copy_mode: Option<CopyModeState>,
 }
 
 impl Terminal {
@@ -138,6 +178,7 @@ impl Terminal {
     pending_cnl: Option<Arc<std::sync::atomic::AtomicU32>>,
     keyboard_protocol_flags: Arc<AtomicU32>,
     osc7_rx: Option<std::sync::mpsc::Receiver<std::path::PathBuf>>,
+    semantic_rx: Option<std::sync::mpsc::Receiver<SemanticMark>>,
     cwd_file: Option<std::path::PathBuf>,
   ) -> Self {
     Self {
@@ -164,19 +205,29 @@ impl Terminal {
       last_scroll_time: None,
       touch_state: None,
       last_input_time: std::time::Instant::now(),
+      output_generation: 0,
       graphics_rx,
+      queued_graphics: VecDeque::new(),
+      graphics_load: None,
       graphics_parser: KittyParser::new(),
       image_storage: KittyImageStorage::new(),
       placement_manager: PlacementManager::new(),
+      images_animating: false,
       pending_cnl,
       keyboard_protocol_flags,
       osc7_rx,
       osc7_cwd: None,
       cwd_file,
+      semantic_rx,
+      semantic_marks: SemanticMarks::default(),
+      shell_integration_active: false,
       cwd_file_mtime: None,
       last_cwd_file_check: None,
       search_state: None,
       search_fingerprint: (0, AlacPoint::new(AlacLine(0), AlacColumn(0))),
+      search_panel_match_lines: Vec::new(),
+      hint_state: None,
+      copy_mode: None,
     }
   }
 
@@ -319,11 +370,18 @@ impl Terminal {
       }
     }
 
+    self.sync_hint_mode();
+    self.sync_copy_mode();
+
     let history_size = self.term.history_size() as i32;
     let display_offset = self.last_content.display_offset as i32;
 
     // Process graphics commands AFTER terminal events so terminal_bounds is up to date.
-    self.process_graphics_commands();
+    self.process_graphics_commands(cx);
+    if !self.images_animating && self.image_storage.next_animation_deadline().is_some() {
+      self.images_animating = true;
+      cx.emit(Event::ImagesAnimating);
+    }
 
     // Detect shell prompt returns and update CWD (non-blocking).
     self.process_prompt_detection(cx);
@@ -336,33 +394,138 @@ impl Terminal {
       self
         .placement_manager
         .visible_placements(&self.image_storage, viewport_top, viewport_lines);
+    self.last_content.placeholder_images = self
+      .placement_manager
+      .placeholder_images(&self.image_storage);
+
+    self.last_content.visible_commands = self
+      .semantic_marks
+      .visible_commands(viewport_top, viewport_lines as usize);
+    self.last_content.failed_command_lines = self.semantic_marks.failed_command_lines();
+
+    // Scrollback past the limit is only dropped here, so the marks read above
+    // use the same absolute lines as this frame's content.
+    match self.term.trim_scrollback() {
+      Some(0) => {}
+      Some(lines) => {
+        self.semantic_marks.rebase(lines);
+        self.placement_manager.rebase(lines);
+        self.rebase_copy_mode(lines);
+        self.rebase_search_panel_matches(lines);
+      }
+      None => {
+        self.semantic_marks.clear();
+        self.placement_manager.clear_grid_placements();
+        self.search_panel_match_lines.clear();
+        self.exit_copy_mode(cx);
+      }
+    }
+  }
+
+  /// Move animated images on to the frames due now, repainting if one
+  /// changed. Returns when the next frame is due, or `None` once nothing is
+  /// playing.
+  pub fn advance_image_animations(&mut self, cx: &mut Context<Self>) -> Option<std::time::Instant> {
+    if self
+      .image_storage
+      .advance_animations(std::time::Instant::now())
+    {
+      cx.notify();
+    }
+    let deadline = self.image_storage.next_animation_deadline();
+    self.images_animating = deadline.is_some();
+    deadline
   }
 
-  fn process_graphics_commands(&mut self) {
+  fn process_graphics_commands(&mut self, cx: &mut Context<Self>) {
     // Drain all available graphics commands (non-blocking).
-    let mut raw_commands: Vec<RawGraphicsCommand> = Vec::new();
     if let Some(rx) = &self.graphics_rx {
       while let Ok(raw_cmd) = rx.try_recv() {
-        raw_commands.push(raw_cmd);
+        self.queued_graphics.push_back(raw_cmd);
       }
     }
 
-    for raw_cmd in raw_commands {
+    while self.graphics_load.is_none()
+      && let Some(raw_cmd) = self.queued_graphics.pop_front()
+    {
       if raw_cmd.clear_all {
         self.placement_manager.clear();
         self.image_storage.clear();
+        if raw_cmd.clear_scrollback {
+          self.semantic_marks.clear();
+        }
+        continue;
+      }
+      if raw_cmd.sixel {
+        if let Err(e) = self.place_sixel(&raw_cmd) {
+          tracing::warn!("Sixel image dropped: {e}");
+        }
         continue;
       }
       let cursor_line = raw_cmd.cursor_line;
       let cursor_column = raw_cmd.cursor_column;
       if let Some(cmd) = self.graphics_parser.parse(&raw_cmd.data) {
-        self.execute_graphics_command(&cmd, cursor_line, cursor_column);
+        if transmission::loads_by_reference(&cmd) {
+          self.load_graphics_payload(cmd, cursor_line, cursor_column, cx);
+        } else {
+          let result = self.execute_graphics_command(&cmd, cursor_line, cursor_column);
+          self.finish_graphics_command(&cmd, result);
+        }
       }
     }
-    // Note: Kitty protocol responses are intentionally NOT sent back.
-    // Our architecture intercepts APC on the read side, so write_to_pty
-    // would send responses to the shell's stdin (appearing as typed text).
-    // Tools like kitten icat use q=2 (suppress all) and handle timeouts.
+  }
+
+  /// Read the file or shared memory payload of `cmd` on the background
+  /// executor, then run it with the data inline and carry on with the
+  /// commands queued behind it.
+  fn load_graphics_payload(
+    &mut self,
+    cmd: KittyCommand,
+    cursor_line: i32,
+    cursor_column: i32,
+    cx: &mut Context<Self>,
+  ) {
+    self.graphics_load = Some(cx.spawn(async move |this, cx| {
+      let (cmd, payload) = cx
+        .background_executor()
+        .spawn(async move {
+          let payload = transmission::load_payload(&cmd).map(|payload| payload.into_owned());
+          (cmd, payload)
+        })
+        .await;
+      this
+        .update(cx, |terminal, cx| {
+          terminal.graphics_load = None;
+          let result = payload.and_then(|payload| {
+            let mut cmd = cmd.clone();
+            cmd.payload = payload;
+            cmd.transmission = KittyTransmission::Direct;
+            terminal.execute_graphics_command(&cmd, cursor_line, cursor_column)
+          });
+          terminal.finish_graphics_command(&cmd, result);
+          terminal.process_graphics_commands(cx);
+          cx.notify();
+        })
+        .ok();
+    }));
+  }
+
+  /// Log a failed Kitty command and reply to it.
+  fn finish_graphics_command(&mut self, cmd: &KittyCommand, result: Result<u32, KittyError>) {
+    if let Err(e) = &result {
+      tracing::warn!(
+        "Kitty graphics command failed for image {}: {e}",
+        cmd.image_id
+      );
+    }
+    // Replies go to the application's stdin like any other terminal
+    // report. Only commands with an image ID get one, and q= silences them.
+    // Deletes only reply when they fail.
+    if (cmd.action != KittyAction::Delete || result.is_err())
+      && let Some(response) = KittyResponse::for_command(cmd, result)
+    {
+      self.write_to_pty(response.encode());
+    }
   }
 
   /// Detect shell prompt returns and update CWD.
@@ -371,17 +534,29 @@ impl Terminal {
   /// On Windows (where the PTY filter is not used), we poll the `cwd_file` modification
   /// time to detect when the shell writes a new CWD on prompt display.
   ///
+  /// Shells with OSC 133 integration report prompts directly; once a mark has
+  /// been seen, OSC 7 and `cwd_file` only update the CWD.
+  ///
   /// Emits `Event::PromptReturned` whenever a prompt is detected, which is used
   /// to trigger notifications for long-running command completion.
   fn process_prompt_detection(&mut self, cx: &mut Context<Self>) {
     let mut prompt_returned = false;
 
+    if let Some(rx) = &self.semantic_rx {
+      while let Ok(mark) = rx.try_recv() {
+        self.shell_integration_active = true;
+        prompt_returned |= mark.kind == SemanticMarkKind::PromptStart;
+        self.semantic_marks.apply(mark);
+      }
+    }
+    let infer_prompts = !self.shell_integration_active;
+
     // OSC 7 channel (Unix: extracted by PTY filter)
     if let Some(rx) = &self.osc7_rx {
       let mut new_cwd = None;
       while let Ok(path) = rx.try_recv() {
         new_cwd = Some(path);
-        prompt_returned = true;
+        prompt_returned |= infer_prompts;
       }
       if let Some(cwd) = new_cwd {
         self.update_cwd(cwd, cx);
@@ -397,7 +572,7 @@ impl Terminal {
         if let Ok(mtime) = std::fs::metadata(&cwd_file).and_then(|m| m.modified()) {
           // Only treat as prompt return if mtime changed (not the initial read).
           if self.cwd_file_mtime.is_some_and(|prev| prev < mtime) {
-            prompt_returned = true;
+            prompt_returned |= infer_prompts;
             if let Ok(contents) = std::fs::read_to_string(&cwd_file) {
               let cwd_str = contents.trim().to_string();
               if !cwd_str.is_empty() {
@@ -415,6 +590,19 @@ impl Terminal {
     }
   }
 
+  /// Whether the shell has reported any OSC 133 marks, which reserves the
+  /// exit-status gutter left of the grid.
+  pub fn shell_integration_active(&self) -> bool {
+    self.shell_integration_active
+  }
+
+  /// The OSC 133 command whose prompt, input or output covers a grid line.
+  pub fn command_at_line(&self, line: AlacLine) -> Option<&CommandMarks> {
+    self
+      .semantic_marks
+      .command_at(self.term.history_size() as i32 + line.0)
+  }
+
   /// Update the tracked CWD if it changed.
   fn update_cwd(&mut self, cwd: std::path::PathBuf, cx: &mut Context<Self>) {
     let changed = self.osc7_cwd.as_ref() != Some(&cwd);
@@ -422,37 +610,75 @@ impl Terminal {
       if let Some(info) = &mut self.pty_info.current {
         info.cwd = cwd.clone();
       }
-      self.osc7_cwd = Some(cwd);
+      self.osc7_cwd = Some(cwd.clone());
+      cx.emit(Event::CwdChanged(cwd));
       cx.emit(Event::TitleChanged);
     }
   }
 
-  fn execute_graphics_command(&mut self, cmd: &KittyCommand, cursor_line: i32, cursor_column: i32) {
+  /// Run a Kitty command, returning the ID of the image it acted on.
+  fn execute_graphics_command(
+    &mut self,
+    cmd: &KittyCommand,
+    cursor_line: i32,
+    cursor_column: i32,
+  ) -> Result<u32, KittyError> {
     match cmd.action {
-      KittyAction::Transmit => {
-        let _ = self.image_storage.store(cmd);
-      }
+      KittyAction::Transmit => self.image_storage.store(cmd),
       KittyAction::TransmitAndDisplay => {
-        if let Ok(id) = self.image_storage.store(cmd) {
-          self.place_image(id, cmd, cursor_line, cursor_column);
-        }
+        let id = self.image_storage.store(cmd)?;
+        self.place_image(id, cmd, cursor_line, cursor_column);
+        Ok(id)
       }
       KittyAction::Display => {
-        let image_id = cmd.image_id;
-        if self.image_storage.get(image_id).is_some() {
-          self.place_image(image_id, cmd, cursor_line, cursor_column);
+        let image_id = if cmd.image_id == 0 && cmd.image_number != 0 {
+          self
+            .image_storage
+            .id_for_number(cmd.image_number)
+            .unwrap_or(0)
+        } else {
+          cmd.image_id
+        };
+        if self.image_storage.get(image_id).is_none() {
+          return Err(KittyError::new(
+            "ENOENT",
+            format!("No image with id {image_id}"),
+          ));
         }
+        self.place_image(image_id, cmd, cursor_line, cursor_column);
+        Ok(image_id)
       }
-      KittyAction::Delete => {
-        self.handle_delete(cmd);
-      }
-      KittyAction::Query => {
-        // We support the protocol but can't send responses back
-        // without them leaking to the shell's stdin.
-      }
+      KittyAction::Delete => self.handle_delete(cmd).map(|()| cmd.image_id),
+      KittyAction::Query => KittyImageStorage::check(cmd).map(|()| cmd.image_id),
+      KittyAction::Frame => self
+        .image_storage
+        .add_frame(cmd, std::time::Instant::now())
+        .map(|()| cmd.image_id),
+      KittyAction::Animate => self
+        .image_storage
+        .control_animation(cmd, std::time::Instant::now())
+        .map(|()| cmd.image_id),
     }
   }
 
+  /// Decode a sixel image and store and place it like a Kitty image at the
+  /// cursor. Sixel has no replies, so failures are only logged.
+  fn place_sixel(&mut self, raw_cmd: &RawGraphicsCommand) -> Result<(), KittyError> {
+    let image = sixel::decode(&raw_cmd.data)?;
+    let cmd = KittyCommand {
+      action: KittyAction::TransmitAndDisplay,
+      format: KittyFormat::Rgba,
+      source_width: image.width,
+      source_height: image.height,
+      cursor_movement: u8::from(raw_cmd.cursor_advanced),
+      payload: image.rgba,
+      ..Default::default()
+    };
+    self
+      .execute_graphics_command(&cmd, raw_cmd.cursor_line, raw_cmd.cursor_column)
+      .map(|_| ())
+  }
+
   fn place_image(
     &mut self,
     image_id: u32,
@@ -491,6 +717,19 @@ impl Terminal {
       (1, 1)
     };
 
+    if cmd.unicode_placeholder {
+      // Virtual placements are drawn by placeholder cells the client
+      // prints, so they don't move the cursor either.
+      self.placement_manager.add_virtual(VirtualPlacement {
+        image_id,
+        placement_id: cmd.placement_id,
+        columns: width_cells,
+        rows: height_cells,
+        z_index: cmd.z_index,
+      });
+      return;
+    }
+
     self.placement_manager.add(ImagePlacement {
       image_id,
       placement_id: cmd.placement_id,
@@ -506,44 +745,92 @@ impl Terminal {
 
     // Signal the PTY filter to inject cursor advancement on next read.
     // This is the fallback mechanism for when the filter couldn't compute
-    // the height from APC params alone (e.g., PNG without r=/v=).
-    if let Some(cnl) = &self.pending_cnl {
+    // the height from APC params alone (e.g., PNG without r=/v=). C=1 asks
+    // for the cursor to stay put.
+    if cmd.cursor_movement == 0
+      && let Some(cnl) = &self.pending_cnl
+    {
       cnl.store(height_cells, std::sync::atomic::Ordering::Release);
     }
   }
 
-  fn handle_delete(&mut self, cmd: &KittyCommand) {
+  fn handle_delete(&mut self, cmd: &KittyCommand) -> Result<(), KittyError> {
     let delete = cmd.delete.as_ref().cloned().unwrap_or(KittyDelete::All);
-    match delete {
+    // Placement lines are absolute; delete coordinates are on the screen.
+    let screen_top = self.term.history_size() as i32;
+    let affected = match delete {
       KittyDelete::All => {
-        self.placement_manager.clear();
-        self.image_storage.clear();
+        let screen_lines = self.term.screen_lines() as u32;
+        self
+          .placement_manager
+          .remove_in_lines(screen_top, screen_lines)
       }
-      KittyDelete::ById {
-        image_id: _,
+      KittyDelete::ById { image_id } => {
+        self.placement_manager.remove_by_id(image_id, None);
+        vec![image_id]
+      }
+      KittyDelete::ByPlacement {
+        image_id,
         placement_id,
       } => {
-        let id = cmd.image_id;
-        self.placement_manager.remove_by_id(id, placement_id);
-        if placement_id.is_none() {
-          self.image_storage.remove(id);
+        self
+          .placement_manager
+          .remove_by_id(image_id, Some(placement_id));
+        vec![image_id]
+      }
+      KittyDelete::ByNumber {
+        image_number,
+        placement_id,
+      } => match self.image_storage.id_for_number(image_number) {
+        Some(image_id) => {
+          self.placement_manager.remove_by_id(image_id, placement_id);
+          vec![image_id]
         }
+        None => Vec::new(),
+      },
+      KittyDelete::IdRange { first, last } => {
+        self.placement_manager.remove_by_image_range(first, last);
+        self.image_storage.ids_in_range(first, last)
       }
       KittyDelete::AtCursor => {
         let cursor = self.term.cursor_point();
-        let history_size = self.term.history_size() as i32;
-        let line = history_size + cursor.line.0;
+        let line = screen_top + cursor.line.0;
         let col = cursor.column.0 as i32;
-        self.placement_manager.remove_at_cursor(line, col);
-      }
-      KittyDelete::ByZIndex(_) | KittyDelete::AtColumn(_) | KittyDelete::AtRow(_) => {
-        // Simplified: just remove all for these advanced cases.
-        self.placement_manager.clear();
+        self.placement_manager.remove_at_cursor(line, col)
       }
+      KittyDelete::AtCell {
+        column,
+        row,
+        z_index,
+      } => self
+        .placement_manager
+        .remove_at_cell(screen_top + row as i32, column as i32, z_index),
+      KittyDelete::AtColumn(column) => self.placement_manager.remove_in_column(column as i32),
+      KittyDelete::AtRow(row) => self
+        .placement_manager
+        .remove_in_lines(screen_top + row as i32, 1),
+      KittyDelete::ByZIndex(z_index) => self.placement_manager.remove_by_z_index(z_index),
       KittyDelete::AnimationFrames => {
-        // Not supported in MVP.
+        self.image_storage.remove_animation_frames(cmd.image_id);
+        Vec::new()
+      }
+      KittyDelete::Unsupported(specifier) => {
+        return Err(KittyError::new(
+          "EINVAL",
+          format!("Unknown delete specifier '{specifier}'"),
+        ));
+      }
+    };
+
+    // Uppercase variants also free images that no placement shows any more.
+    if cmd.delete_image_data {
+      for image_id in affected {
+        if !self.placement_manager.has_image(image_id) {
+          self.image_storage.remove(image_id);
+        }
       }
     }
+    Ok(())
   }
 
   fn make_content(term: &dyn TerminalBackend, last_content: &TerminalContent) -> TerminalContent {
@@ -583,7 +870,12 @@ impl Terminal {
       // re-computed in sync() if there is an active search query.
       search_matches: last_content.search_matches.clone(),
       current_search_match_index: last_content.current_search_match_index,
+      hint_labels: last_content.hint_labels.clone(),
       image_placements: Vec::new(),
+      placeholder_images: Vec::new(),
+      visible_commands: Vec::new(),
+      failed_command_lines: Vec::new(),
+      copy_mode: None,
     }
   }
 
@@ -640,6 +932,7 @@ impl Terminal {
       }
       AlacTermEvent::MouseCursorDirty => {}
       AlacTermEvent::Wakeup => {
+        self.output_generation += 1;
         cx.emit(Event::Wakeup);
 
         // Run prompt detection on every wakeup so background terminals
@@ -799,6 +1092,17 @@ impl Terminal {
 
 impl EventEmitter<Event> for Terminal {}
 
+/// Which part of the grid `Terminal::region_text` reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextRegion {
+  /// The live screen, regardless of the current scroll position.
+  Screen,
+  /// History lines above the screen.
+  Scrollback,
+  /// Scrollback followed by the screen.
+  All,
+}
+
 #[derive(PartialEq, Eq)]
 pub enum SelectionPhase {
   Selecting,
@@ -823,9 +1127,286 @@ fn should_hide_mouse_cursor(
 
 #[cfg(test)]
 mod tests {
+  use std::sync::atomic::{AtomicU32, Ordering};
+  use std::sync::{Arc, mpsc};
   use std::time::{Duration, Instant};
 
-  use super::should_hide_mouse_cursor;
+  use terminal_kernel::test_support::backend_with;
+
+  use gpui::{AppContext as _, TestAppContext};
+  use terminal_kernel::event::VoidListener;
+  use terminal_kernel::index::{Column, Line, Point};
+  use terminal_kernel::sync::FairMutex;
+  use terminal_kernel::term::{Config, Term};
+  use terminal_kernel::test_support::StubDims;
+  use terminal_kernel::vte::ansi::Processor;
+  use terminal_kernel::{AlacrittyBackend, SCROLLBACK_SLACK};
+
+  use super::{PtySender, should_hide_mouse_cursor};
+  use crate::kitty_graphics::command::RawGraphicsCommand;
+  use crate::test_support::FakePtySender;
+  use crate::{PtyProcessInfo, Terminal};
+
+  fn graphics_terminal(
+    pty_tx: Box<dyn PtySender>,
+    graphics_rx: mpsc::Receiver<RawGraphicsCommand>,
+    pending_cnl: Arc<AtomicU32>,
+  ) -> Terminal {
+    Terminal::new(
+      pty_tx,
+      Box::new(backend_with(4, 20, b"")),
+      PtyProcessInfo::test_stub(),
+      Some(graphics_rx),
+      Some(pending_cnl),
+      Arc::new(AtomicU32::new(0)),
+      None,
+      None,
+      None,
+    )
+  }
+
+  fn raw(data: &[u8], sixel: bool, cursor_advanced: bool) -> RawGraphicsCommand {
+    RawGraphicsCommand {
+      data: data.to_vec(),
+      cursor_line: 0,
+      cursor_column: 0,
+      clear_all: false,
+      clear_scrollback: false,
+      sixel,
+      cursor_advanced,
+    }
+  }
+
+  #[gpui::test]
+  fn only_images_the_filter_did_not_advance_past_ask_for_rows(cx: &mut TestAppContext) {
+    let (graphics_tx, graphics_rx) = mpsc::channel();
+    let pending_cnl = Arc::new(AtomicU32::new(0));
+    let terminal =
+      cx.new(|_| graphics_terminal(FakePtySender::new().0, graphics_rx, pending_cnl.clone()));
+
+    // One sixel draws, one is too large to decode.
+    graphics_tx.send(raw(b"q#0~~", true, true)).unwrap();
+    graphics_tx
+      .send(raw(b"q\"1;1;4096;4097#0~", true, true))
+      .unwrap();
+    terminal.update(cx, |terminal, cx| {
+      terminal.process_graphics_commands(cx);
+      assert_eq!(terminal.placement_manager.placement_count(), 1);
+    });
+    assert_eq!(pending_cnl.load(Ordering::Acquire), 0);
+
+    graphics_tx
+      .send(raw(b"a=T,f=24,s=1,v=1;AAAA", false, false))
+      .unwrap();
+    terminal.update(cx, |terminal, cx| terminal.process_graphics_commands(cx));
+    assert_eq!(pending_cnl.load(Ordering::Acquire), 1);
+  }
+
+  #[gpui::test]
+  fn numbered_transmissions_are_answered_with_the_assigned_id(cx: &mut TestAppContext) {
+    let (pty_tx, writes, _) = FakePtySender::new();
+    let (graphics_tx, graphics_rx) = mpsc::channel();
+    let terminal = cx.new(|_| graphics_terminal(pty_tx, graphics_rx, Arc::new(AtomicU32::new(0))));
+
+    graphics_tx
+      .send(raw(b"a=t,f=24,s=1,v=1,I=5;AAAA", false, false))
+      .unwrap();
+    terminal.update(cx, |terminal, cx| terminal.process_graphics_commands(cx));
+    assert_eq!(
+      writes.lock().unwrap().as_slice(),
+      [b"\x1b_Gi=1,I=5;OK\x1b\\".to_vec()]
+    );
+  }
+
+  #[gpui::test]
+  fn file_payloads_load_in_the_background_ahead_of_later_commands(cx: &mut TestAppContext) {
+    use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+
+    let path = std::env::temp_dir().join(format!("kazeterm-kitty-load-{}", std::process::id()));
+    std::fs::write(&path, [255, 0, 0, 255]).unwrap();
+    let (graphics_tx, graphics_rx) = mpsc::channel();
+    let terminal = cx.new(|_| {
+      graphics_terminal(
+        FakePtySender::new().0,
+        graphics_rx,
+        Arc::new(AtomicU32::new(0)),
+      )
+    });
+
+    let transmit = format!(
+      "a=t,t=f,f=32,s=1,v=1,i=7,q=2;{}",
+      BASE64.encode(path.to_str().unwrap())
+    );
+    graphics_tx
+      .send(raw(transmit.as_bytes(), false, false))
+      .unwrap();
+    graphics_tx.send(raw(b"a=p,i=7,q=2", false, false)).unwrap();
+    terminal.update(cx, |terminal, cx| {
+      terminal.process_graphics_commands(cx);
+      assert!(terminal.image_storage.get(7).is_none());
+      assert_eq!(terminal.placement_manager.placement_count(), 0);
+    });
+
+    cx.run_until_parked();
+    terminal.update(cx, |terminal, _| {
+      assert!(terminal.image_storage.get(7).is_some());
+      assert_eq!(terminal.placement_manager.placement_count(), 1);
+    });
+    std::fs::remove_file(&path).unwrap();
+  }
+
+  /// A terminal over an alacritty `Term` holding `lines` lines `l0`, `l1`,
+  /// …, whose history is trimmed to `limit` on sync. Returns the `Term` so
+  /// tests can feed it more output.
+  fn trimming_terminal(
+    cx: &mut TestAppContext,
+    limit: usize,
+    lines: usize,
+  ) -> (gpui::Entity<Terminal>, Arc<FairMutex<Term<VoidListener>>>) {
+    let config = Config {
+      scrolling_history: limit + SCROLLBACK_SLACK,
+      ..Config::default()
+    };
+    let term = Term::new(config, &StubDims { cols: 20, lines: 4 }, VoidListener);
+    let term = Arc::new(FairMutex::new(term));
+    feed(
+      &term,
+      (0..lines)
+        .map(|i| format!("l{i}"))
+        .collect::<Vec<_>>()
+        .join("\r\n"),
+    );
+
+    let backend = AlacrittyBackend::new(term.clone()).with_history_limit(limit);
+    let terminal = cx.new(|_| {
+      Terminal::new(
+        FakePtySender::new().0,
+        Box::new(backend),
+        PtyProcessInfo::test_stub(),
+        None,
+        None,
+        Arc::new(AtomicU32::new(0)),
+        None,
+        None,
+        None,
+      )
+    });
+    (terminal, term)
+  }
+
+  fn feed(term: &FairMutex<Term<VoidListener>>, bytes: String) {
+    let mut parser: Processor = Processor::new();
+    parser.advance(&mut *term.lock(), bytes.as_bytes());
+  }
+
+  #[gpui::test]
+  fn copy_mode_cursor_stays_on_its_text_when_scrollback_is_trimmed(cx: &mut TestAppContext) {
+    const LIMIT: usize = 10;
+    // Fill the scrollback to just short of the slack, so one trim drops
+    // almost `SCROLLBACK_SLACK` lines.
+    let lines = LIMIT + SCROLLBACK_SLACK - 1 + 4;
+    let (terminal, term) = trimming_terminal(cx, LIMIT, lines);
+    let cx = cx.add_empty_window();
+    let cursor_text = |cx: &mut gpui::VisualTestContext| {
+      cx.update(|window, cx| {
+        terminal.update(cx, |terminal, cx| {
+          terminal.sync(window, cx);
+          let cursor = terminal.last_content.copy_mode.as_ref()?.cursor;
+          Some(terminal.term.bounds_to_string(
+            Point::new(cursor.line, Column(0)),
+            Point::new(cursor.line, terminal.term.last_column()),
+          ))
+        })
+      })
+    };
+
+    cx.update(|_, cx| {
+      terminal.update(cx, |terminal, cx| {
+        terminal.start_copy_mode(cx);
+        terminal.copy_mode_input('k', cx);
+        terminal.copy_mode_input('k', cx);
+      })
+    });
+    let expected = format!("l{}", lines - 3);
+    assert_eq!(
+      cursor_text(cx).as_deref().map(str::trim_end),
+      Some(&*expected)
+    );
+    assert_eq!(
+      terminal.read_with(cx, |terminal, _| terminal.term.history_size()),
+      LIMIT
+    );
+    assert_eq!(
+      cursor_text(cx).as_deref().map(str::trim_end),
+      Some(&*expected)
+    );
+
+    // Output after the trim still scrolls the cursor along with its text.
+    feed(&term, "\r\nnext\r\nnext".to_string());
+    assert_eq!(
+      cursor_text(cx).as_deref().map(str::trim_end),
+      Some(&*expected)
+    );
+    assert_eq!(
+      terminal.read_with(cx, |terminal, _| terminal
+        .last_content
+        .copy_mode
+        .as_ref()
+        .unwrap()
+        .cursor
+        .line),
+      Line(-1)
+    );
+
+    // Past the slack the dropped lines weren't counted, so copy mode ends.
+    feed(&term, "\r\nnext".repeat(LIMIT + SCROLLBACK_SLACK));
+    assert_eq!(cursor_text(cx), None);
+    assert!(!terminal.read_with(cx, |terminal, _| terminal.copy_mode_active()));
+  }
+
+  #[gpui::test]
+  fn search_panel_matches_stay_on_their_text_when_scrollback_is_trimmed(cx: &mut TestAppContext) {
+    const LIMIT: usize = 10;
+    let lines = LIMIT + SCROLLBACK_SLACK - 1 + 4;
+    let (terminal, term) = trimming_terminal(cx, LIMIT, lines);
+    let cx = cx.add_empty_window();
+    let sync = |cx: &mut gpui::VisualTestContext| {
+      cx.update(|window, cx| terminal.update(cx, |terminal, cx| terminal.sync(window, cx)))
+    };
+    let match_text = |cx: &mut gpui::VisualTestContext| {
+      terminal.read_with(cx, |terminal, _| {
+        let history = terminal.term.history_size() as i32;
+        terminal
+          .search_panel_match_lines
+          .iter()
+          .map(|&line| {
+            let line = Line(line as i32 - history);
+            let text = terminal.term.bounds_to_string(
+              Point::new(line, Column(0)),
+              Point::new(line, terminal.term.last_column()),
+            );
+            text.trim_end().to_string()
+          })
+          .collect::<Vec<_>>()
+      })
+    };
+
+    // One match is in the lines the trim drops, one is kept.
+    terminal.update(cx, |terminal, _| {
+      terminal.search_panel_match_lines = vec![5, lines - 3];
+    });
+    sync(cx);
+    assert_eq!(
+      terminal.read_with(cx, |terminal, _| terminal.term.history_size()),
+      LIMIT
+    );
+    assert_eq!(match_text(cx), vec![format!("l{}", lines - 3)]);
+
+    // Past the slack the dropped lines weren't counted, so the matches go.
+    feed(&term, "\r\nnext".repeat(LIMIT + SCROLLBACK_SLACK));
+    sync(cx);
+    assert!(match_text(cx).is_empty());
+  }
 
   #[test]
   fn hide_mouse_cursor_when_input_is_newer_than_mouse_activity() {
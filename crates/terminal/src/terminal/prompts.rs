@@ -0,0 +1,173 @@
+use std::ops::RangeInclusive;
+
+use gpui::{ClipboardItem, Context};
+use terminal_kernel::{
+  index::{Column, Line, Point as AlacPoint, Side},
+  selection::SelectionType,
+};
+
+use super::Terminal;
+use crate::shell_integration::CommandMarks;
+
+impl Terminal {
+  /// Scroll the closest OSC 133 prompt above the viewport to its top line.
+  pub fn scroll_to_previous_prompt(&mut self, cx: &mut Context<Self>) {
+    let top = self.viewport_top();
+    if let Some(line) = self.semantic_marks.previous_prompt(self.absolute_line(top)) {
+      self.scroll_line_to_top(self.grid_line(line));
+      cx.notify();
+    }
+  }
+
+  /// Scroll the closest OSC 133 prompt below the top of the viewport to its top line.
+  pub fn scroll_to_next_prompt(&mut self, cx: &mut Context<Self>) {
+    let top = self.viewport_top();
+    if let Some(line) = self.semantic_marks.next_prompt(self.absolute_line(top)) {
+      self.scroll_line_to_top(self.grid_line(line));
+      cx.notify();
+    }
+  }
+
+  /// Select the output of the command at the top of the viewport, or of the
+  /// last command when the viewport is at the bottom.
+  ///
+  /// Returns `false` when no command output is available.
+  pub fn select_command_output(&mut self, cx: &mut Context<Self>) -> bool {
+    let top = self.absolute_line(self.viewport_top());
+    let at_bottom = self.term.display_offset() == 0;
+    let lines = {
+      let mut commands = self
+        .semantic_marks
+        .commands()
+        .filter(|command| command.output_line.is_some());
+      let command = if at_bottom {
+        commands.next_back()
+      } else {
+        commands.find(|command| command.finished_line.is_none_or(|line| line > top))
+      };
+      command.and_then(|command| self.output_points(command))
+    };
+    let Some(lines) = lines else {
+      return false;
+    };
+
+    let (start, end) = (*lines.start(), *lines.end());
+    if !at_bottom {
+      self.term.scroll_to_point(start);
+    }
+    self.select_range(
+      SelectionType::Lines,
+      (start, Side::Left),
+      (end, Side::Right),
+      cx,
+    );
+    true
+  }
+
+  /// Copy the output of the last finished command to the clipboard.
+  ///
+  /// Returns `false` when no finished command printed anything.
+  pub fn copy_last_command_output(&mut self, cx: &mut Context<Self>) -> bool {
+    let Some(lines) = self
+      .semantic_marks
+      .commands()
+      .rev()
+      .filter(|command| command.finished_line.is_some())
+      .find_map(|command| self.output_points(command))
+    else {
+      return false;
+    };
+
+    let text = self.term.bounds_to_string(*lines.start(), *lines.end());
+    cx.write_to_clipboard(ClipboardItem::new_string(text));
+    true
+  }
+
+  /// The grid points spanning a command's output, clamped to the scrollback
+  /// that is still available.
+  fn output_points(&self, command: &CommandMarks) -> Option<RangeInclusive<AlacPoint>> {
+    let cursor_line = self.absolute_line(self.term.cursor_point().line);
+    let lines = command.output_lines(cursor_line)?;
+    let start = self.grid_line(*lines.start()).max(self.term.topmost_line());
+    let end = self.grid_line(*lines.end());
+    (start <= end)
+      .then(|| AlacPoint::new(start, Column(0))..=AlacPoint::new(end, self.term.last_column()))
+  }
+
+  /// Put `line` on the first row of the viewport, as far as the scrollback allows.
+  fn scroll_line_to_top(&self, line: Line) {
+    let line = line.max(self.term.topmost_line());
+    let target = if line < self.viewport_top() {
+      line
+    } else {
+      // Reveal the row a screen below, which scrolls `line` to the top.
+      (line + (self.term.screen_lines() as i32 - 1)).min(self.term.bottommost_line())
+    };
+    self.term.scroll_to_point(AlacPoint::new(target, Column(0)));
+  }
+
+  fn viewport_top(&self) -> Line {
+    Line(-(self.term.display_offset() as i32))
+  }
+
+  /// Absolute line (0 = oldest history line) of a grid line.
+  fn absolute_line(&self, line: Line) -> i32 {
+    self.term.history_size() as i32 + line.0
+  }
+
+  pub(super) fn grid_line(&self, absolute_line: i32) -> Line {
+    Line(absolute_line - self.term.history_size() as i32)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Instant;
+
+  use gpui::{AppContext as _, TestAppContext};
+
+  use crate::Terminal;
+  use crate::shell_integration::{SemanticMark, SemanticMarkKind};
+  use crate::test_support::fake_terminal_with;
+
+  fn terminal_with(bytes: &[u8], marks: &[(SemanticMarkKind, i32)]) -> Terminal {
+    let mut terminal = fake_terminal_with(6, 20, bytes);
+    let at = Instant::now();
+    for &(kind, line) in marks {
+      terminal
+        .semantic_marks
+        .apply(SemanticMark { kind, line, at });
+    }
+    terminal
+  }
+
+  #[gpui::test]
+  fn select_command_output_selects_the_last_command(cx: &mut TestAppContext) {
+    let terminal = cx.new(|_| {
+      terminal_with(
+        b"$ ls\r\na.txt\r\nb.txt\r\n$ ",
+        &[
+          (SemanticMarkKind::PromptStart, 0),
+          (SemanticMarkKind::OutputStart, 1),
+          (SemanticMarkKind::CommandFinished { exit_code: Some(0) }, 3),
+          (SemanticMarkKind::PromptStart, 3),
+        ],
+      )
+    });
+
+    let selected = terminal.update(cx, |terminal, cx| {
+      assert!(terminal.select_command_output(cx));
+      terminal.term.selection_to_string()
+    });
+    assert_eq!(selected.unwrap().trim_end(), "a.txt\nb.txt");
+  }
+
+  #[gpui::test]
+  fn select_command_output_without_marks_selects_nothing(cx: &mut TestAppContext) {
+    let terminal = cx.new(|_| terminal_with(b"$ ls", &[]));
+    terminal.update(cx, |terminal, cx| {
+      assert!(!terminal.select_command_output(cx));
+      assert!(terminal.term.selection_to_string().is_none());
+    });
+  }
+}
@@ -0,0 +1,100 @@
+use terminal_kernel::{
+  TerminalBackend,
+  index::{Column, Line, Point as AlacPoint},
+  term::TermMode,
+};
+
+use super::Terminal;
+
+impl Terminal {
+  /// Styled text of up to `max_lines` lines above the cursor, for saving
+  /// across restarts. `None` when there is nothing worth keeping.
+  pub fn scrollback_snapshot(&self, max_lines: usize, max_bytes: usize) -> Option<String> {
+    if self.last_content.mode.contains(TermMode::ALT_SCREEN) {
+      return None;
+    }
+    scrollback_snapshot(self.term.as_ref(), max_lines, max_bytes)
+  }
+}
+
+fn scrollback_snapshot(
+  backend: &dyn TerminalBackend,
+  max_lines: usize,
+  max_bytes: usize,
+) -> Option<String> {
+  // The cursor line holds the prompt the new shell will print again.
+  let cursor_line = backend.cursor_point().line;
+  let max_lines = Line(max_lines.min(i32::MAX as usize) as i32);
+  let top = backend.topmost_line().max(cursor_line - max_lines);
+  if top >= cursor_line {
+    return None;
+  }
+  let start = AlacPoint::new(top, Column(0));
+  let end = AlacPoint::new(cursor_line - Line(1), backend.last_column());
+  let text = backend.bounds_to_ansi_string(start, end);
+  let text = tail_lines_within(&text, max_bytes).trim_start_matches('\n');
+  (!text.trim().is_empty()).then(|| text.to_string())
+}
+
+/// The longest run of whole lines at the end of `text` that fits in `max_bytes`.
+fn tail_lines_within(text: &str, max_bytes: usize) -> &str {
+  if text.len() <= max_bytes {
+    return text;
+  }
+  let cut = text.len() - max_bytes;
+  match text.as_bytes()[cut..].iter().position(|&b| b == b'\n') {
+    Some(ix) => &text[cut + ix + 1..],
+    None => "",
+  }
+}
+
+/// Bytes that print a saved snapshot back into a fresh terminal, leaving the
+/// cursor at the start of the line below it.
+pub fn scrollback_replay_bytes(snapshot: &str) -> Vec<u8> {
+  let mut bytes = snapshot.replace('\n', "\r\n").into_bytes();
+  bytes.extend_from_slice(b"\x1b[0m\r\n");
+  bytes
+}
+
+#[cfg(test)]
+mod tests {
+  use terminal_kernel::test_support::backend_with;
+
+  use super::{scrollback_replay_bytes, scrollback_snapshot, tail_lines_within};
+
+  #[test]
+  fn snapshot_keeps_styled_lines_above_the_prompt() {
+    let backend = backend_with(4, 20, b"one\r\n\x1b[31mtwo\x1b[0m\r\nthree\r\n$ ");
+    assert_eq!(
+      scrollback_snapshot(&backend, 100, 1024).as_deref(),
+      Some("one\n\x1b[0;31mtwo\x1b[0m\nthree")
+    );
+    assert_eq!(
+      scrollback_snapshot(&backend, 1, 1024).as_deref(),
+      Some("three")
+    );
+  }
+
+  #[test]
+  fn snapshot_is_empty_at_the_first_prompt() {
+    let backend = backend_with(4, 20, b"$ ");
+    assert_eq!(scrollback_snapshot(&backend, 100, 1024), None);
+  }
+
+  #[test]
+  fn byte_limit_drops_oldest_whole_lines() {
+    assert_eq!(tail_lines_within("aaa\nbbb\nccc", 11), "aaa\nbbb\nccc");
+    assert_eq!(tail_lines_within("aaa\nbbb\nccc", 8), "bbb\nccc");
+    assert_eq!(tail_lines_within("aaa\nbbb\nccc", 6), "ccc");
+    assert_eq!(tail_lines_within("ééé\nb", 4), "b");
+    assert_eq!(tail_lines_within("aaa\nbbb\nccc", 2), "");
+  }
+
+  #[test]
+  fn replay_uses_carriage_returns() {
+    assert_eq!(
+      scrollback_replay_bytes("a\nb"),
+      b"a\r\nb\x1b[0m\r\n".to_vec()
+    );
+  }
+}
@@ -1,3 +1,13 @@
+use std::ops::{Range, RangeInclusive};
+
+use gpui::Context;
+use terminal_kernel::{
+  TerminalBackend,
+  index::{Column, Line, Point as AlacPoint},
+};
+
+use super::Terminal;
+
 /// Active search parameters stored in Terminal so the search can be
 /// automatically re-executed whenever terminal content changes.
 pub struct SearchState {
@@ -43,3 +53,145 @@ impl SearchState {
     })
   }
 }
+
+/// A terminal line holding search matches, listed outside the terminal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResultLine {
+  /// Absolute line (0 = oldest history line) holding the matches, as taken
+  /// by `Terminal::scroll_to_search_result`.
+  pub line: i32,
+  pub text: String,
+  /// Byte ranges of the matches within `text`.
+  pub highlights: Vec<Range<usize>>,
+  /// Up to the requested number of lines before and after the match line.
+  pub before: Vec<String>,
+  pub after: Vec<String>,
+}
+
+impl Terminal {
+  /// Lines matching `search`, each with `context` lines around it. Unlike
+  /// the search bar, this leaves the terminal's own query and highlights alone.
+  pub fn search_result_lines(&self, search: &SearchState, context: usize) -> Vec<SearchResultLine> {
+    let matches = Self::execute_search(&*self.term, search);
+    search_result_lines(&*self.term, &matches, context)
+  }
+
+  /// Scroll a line from `search_result_lines` into view, unless it has since
+  /// been dropped from the scrollback.
+  pub fn scroll_to_search_result(&mut self, line: i32, cx: &mut Context<Self>) {
+    let line = self.grid_line(line);
+    if line >= self.term.topmost_line() {
+      self.term.scroll_to_point(AlacPoint::new(line, Column(0)));
+      cx.notify();
+    }
+  }
+
+  /// Move the search panel's match lines up after `lines` lines were dropped
+  /// from the top of the scrollback, dropping those that went with them.
+  pub(super) fn rebase_search_panel_matches(&mut self, lines: usize) {
+    self.search_panel_match_lines.retain_mut(|line| {
+      let kept = *line >= lines;
+      *line = line.saturating_sub(lines);
+      kept
+    });
+  }
+}
+
+fn search_result_lines(
+  term: &dyn TerminalBackend,
+  matches: &[RangeInclusive<AlacPoint>],
+  context: usize,
+) -> Vec<SearchResultLine> {
+  let context = context as i32;
+  let history_size = term.history_size() as i32;
+  let mut results: Vec<(Line, Vec<Range<usize>>, SearchResultLine)> = Vec::new();
+  for range in matches {
+    let line = range.start().line;
+    let columns = range.start().column.0..range.end().column.0 + 1;
+    match results.last_mut() {
+      Some((last_line, columns_on_line, _)) if *last_line == line => {
+        columns_on_line.push(columns);
+      }
+      _ => {
+        let lines = |lines: RangeInclusive<i32>| {
+          lines
+            .filter(|&line| line >= term.topmost_line().0 && line <= term.bottommost_line().0)
+            .map(|line| line_text(term, Line(line)))
+            .collect()
+        };
+        results.push((
+          line,
+          vec![columns],
+          SearchResultLine {
+            line: history_size + line.0,
+            text: line_text(term, line),
+            highlights: Vec::new(),
+            before: lines(line.0 - context..=line.0 - 1),
+            after: lines(line.0 + 1..=line.0 + context),
+          },
+        ));
+      }
+    }
+  }
+
+  results
+    .into_iter()
+    .map(|(_, columns, mut result)| {
+      result.highlights = columns
+        .into_iter()
+        .map(|columns| {
+          byte_offset(&result.text, columns.start)..byte_offset(&result.text, columns.end)
+        })
+        .filter(|range| !range.is_empty())
+        .collect();
+      result
+    })
+    .collect()
+}
+
+/// The text of a grid line with one character per cell, as searched.
+fn line_text(term: &dyn TerminalBackend, line: Line) -> String {
+  let text: String = (0..term.columns())
+    .map(|column| term.cell_at(AlacPoint::new(line, Column(column))).c)
+    .collect();
+  text.trim_end().to_string()
+}
+
+/// Byte offset of the `column`th character, clamped to the end of `text`.
+fn byte_offset(text: &str, column: usize) -> usize {
+  text
+    .char_indices()
+    .nth(column)
+    .map_or(text.len(), |(offset, _)| offset)
+}
+
+#[cfg(test)]
+mod tests {
+  use terminal_kernel::{
+    index::{Column, Line, Point as AlacPoint},
+    test_support::backend_with,
+  };
+
+  use super::search_result_lines;
+
+  fn range(line: i32, start: usize, end: usize) -> std::ops::RangeInclusive<AlacPoint> {
+    AlacPoint::new(Line(line), Column(start))..=AlacPoint::new(Line(line), Column(end))
+  }
+
+  #[test]
+  fn groups_matches_by_line_with_context() {
+    let backend = backend_with(4, 20, "one\r\nerr → err\r\nthree\r\nfour".as_bytes());
+    let matches = [range(1, 0, 2), range(1, 6, 8), range(3, 0, 1)];
+    let results = search_result_lines(&backend, &matches, 1);
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].line, 1);
+    assert_eq!(results[0].text, "err → err");
+    assert_eq!(results[0].highlights, vec![0..3, 8..11]);
+    assert_eq!(results[0].before, vec!["one"]);
+    assert_eq!(results[0].after, vec!["three"]);
+    assert_eq!(results[1].line, 3);
+    assert_eq!(results[1].before, vec!["three"]);
+    assert!(results[1].after.is_empty());
+  }
+}
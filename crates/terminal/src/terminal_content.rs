@@ -7,7 +7,11 @@ use terminal_kernel::{
 };
 
 use crate::{
-  indexed_cell::IndexedCell, kitty_graphics::VisiblePlacement, terminal_bounds::TerminalBounds,
+  indexed_cell::IndexedCell,
+  kitty_graphics::{PlaceholderImage, VisiblePlacement},
+  shell_integration::VisibleCommand,
+  terminal::{CopyModeDisplay, HintLabel},
+  terminal_bounds::TerminalBounds,
 };
 
 #[derive(Clone)]
@@ -26,7 +30,16 @@ pub struct TerminalContent {
   pub scrolled_to_bottom: bool,
   pub search_matches: Vec<RangeInclusive<AlacPoint>>,
   pub current_search_match_index: usize,
+  pub hint_labels: Vec<HintLabel>,
   pub image_placements: Vec<VisiblePlacement>,
+  /// Virtual (`U=1`) placements, drawn where placeholder cells appear.
+  pub placeholder_images: Vec<PlaceholderImage>,
+  /// Finished OSC 133 commands whose prompt is in the viewport.
+  pub visible_commands: Vec<VisibleCommand>,
+  /// Absolute prompt lines (0 = oldest history line) of failed commands.
+  pub failed_command_lines: Vec<usize>,
+  /// Copy mode cursor and indicator while copy mode is active.
+  pub copy_mode: Option<CopyModeDisplay>,
 }
 
 impl Default for TerminalContent {
@@ -49,7 +62,12 @@ impl Default for TerminalContent {
       scrolled_to_bottom: false,
       search_matches: Vec::new(),
       current_search_match_index: 0,
+      hint_labels: Vec::new(),
       image_placements: Vec::new(),
+      placeholder_images: Vec::new(),
+      visible_commands: Vec::new(),
+      failed_command_lines: Vec::new(),
+      copy_mode: None,
     }
   }
 }
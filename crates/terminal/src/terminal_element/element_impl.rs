@@ -69,6 +69,7 @@ impl Element for TerminalElement {
     cx: &mut gpui::App,
   ) -> Self::PrepaintState {
     let inactive = self.inactive;
+    let search_panel_match_lines = self.terminal.read(cx).search_panel_match_lines.clone();
     let inactive_opacity = cx
       .try_global::<::config::Config>()
       .map(|c| c.pane.get_inactive_opacity())
@@ -130,7 +131,13 @@ impl Element for TerminalElement {
             .advance(font_id, font_pixels, 'm')
             .unwrap()
             .width;
-          gutter = cell_width;
+          // Only shells with OSC 133 integration get the exit-status gutter;
+          // everyone else keeps the full width for the grid.
+          gutter = if self.terminal.read(cx).shell_integration_active() {
+            cell_width
+          } else {
+            px(0.0)
+          };
 
           let mut size = bounds.size;
           size.width -= gutter + scrollbar_width + minimap_width;
@@ -181,12 +188,17 @@ impl Element for TerminalElement {
           cursor,
           search_matches,
           current_search_match_index,
+          hint_labels,
           last_hovered_word,
           history_size,
           image_placements,
+          placeholder_images,
+          visible_commands,
+          failed_command_lines,
+          copy_mode,
           ..
         } = &self.terminal.read(cx).last_content;
-        let image_placements = image_placements.clone();
+        let mut image_placements = image_placements.clone();
 
         let mode = *mode;
         let display_offset = *display_offset;
@@ -216,13 +228,30 @@ impl Element for TerminalElement {
           };
           relative_highlighted_ranges.push((search_match.clone(), color));
         }
+        if let Some(copy_mode) = copy_mode {
+          for search_match in &copy_mode.search_matches {
+            let color = if *search_match.start() == copy_mode.cursor {
+              theme.colors().search_match_background
+            } else {
+              theme.colors().search_highlight_background
+            };
+            relative_highlighted_ranges.push((search_match.clone(), color));
+          }
+        }
+
+        for hint in hint_labels {
+          relative_highlighted_ranges.push((
+            hint.range.clone(),
+            theme.colors().search_highlight_background,
+          ));
+        }
 
         if let Some(selection) = selection {
           let selection_color = cx.theme().colors().element_selection_background;
           relative_highlighted_ranges.push((selection.start..=selection.end, selection_color));
         }
 
-        let (rects, batched_text_runs) = TerminalElement::layout_grid(
+        let (rects, batched_text_runs, placeholder_slices) = TerminalElement::layout_grid(
           cells.iter().cloned(),
           0,
           &text_style,
@@ -232,13 +261,24 @@ impl Element for TerminalElement {
           minimum_contrast,
           bold_as_bright,
           &color_table,
+          placeholder_images,
           cx,
         );
-
-        let cursor = if let AlacCursorShape::Hidden = cursor.shape {
+        image_placements.extend(placeholder_slices);
+
+        // Copy mode draws its own block cursor in place of the terminal's.
+        let (cursor_point, cursor_char, cursor_shape) = match copy_mode {
+          Some(copy_mode) => (
+            copy_mode.cursor,
+            &copy_mode.cursor_char,
+            AlacCursorShape::Block,
+          ),
+          None => (cursor.point, cursor_char, cursor.shape),
+        };
+        let cursor = if let AlacCursorShape::Hidden = cursor_shape {
           None
         } else {
-          let cursor_point = DisplayCursor::from(cursor.point, display_offset);
+          let cursor_point = DisplayCursor::from(cursor_point, display_offset);
           let cursor_text: gpui::ShapedLine = {
             let str_trxt = cursor_char.to_string();
             let len = str_trxt.len();
@@ -260,7 +300,7 @@ impl Element for TerminalElement {
           let focused = self.focused;
           TerminalElement::shape_cursor(cursor_point, dimensions, &cursor_text).map(
             move |(cursor_position, block_width)| {
-              let (shape, text) = match cursor.shape {
+              let (shape, text) = match cursor_shape {
                 AlacCursorShape::Block if !focused => (AlacCursorShape::HollowBlock, None),
                 AlacCursorShape::Block => (AlacCursorShape::Block, Some(cursor_text)),
                 AlacCursorShape::Underline => (AlacCursorShape::Underline, None),
@@ -281,10 +321,83 @@ impl Element for TerminalElement {
           )
         };
 
+        let copy_mode_indicator = copy_mode.as_ref().map(|copy_mode| {
+          let indicator = copy_mode.indicator.clone();
+          let len = indicator.len();
+          let mut font = text_style.font();
+          font.weight = FontWeight::BOLD;
+          window.text_system().shape_line(
+            indicator.into(),
+            text_style.font_size.to_pixels(window.rem_size()),
+            &[TextRun {
+              len,
+              font,
+              color: theme.colors().text,
+              background_color: None,
+              underline: None,
+              strikethrough: None,
+            }],
+            None,
+          )
+        });
+
+        let hint_label_background = theme.colors().terminal_ansi_yellow;
+        let hint_labels = hint_labels
+          .iter()
+          .map(|hint| {
+            let label: String = hint.label.chars().skip(hint.typed).collect();
+            let len = label.len();
+            let position = DisplayCursor::from(*hint.range.start(), display_offset);
+            let mut font = text_style.font();
+            font.weight = FontWeight::BOLD;
+            let shaped_label = window.text_system().shape_line(
+              label.into(),
+              text_style.font_size.to_pixels(window.rem_size()),
+              &[TextRun {
+                len,
+                font,
+                color: theme.colors().terminal_ansi_black,
+                background_color: None,
+                underline: None,
+                strikethrough: None,
+              }],
+              None,
+            );
+            (
+              Point::new(
+                position.col() as f32 * dimensions.cell_width(),
+                position.line() as f32 * dimensions.line_height(),
+              ),
+              shaped_label,
+            )
+          })
+          .collect();
+
+        let command_marks = visible_commands
+          .iter()
+          .map(|command| {
+            let color = if command.failed() {
+              theme.colors().terminal_ansi_red
+            } else {
+              theme.colors().terminal_ansi_green
+            };
+            (command.viewport_line, color, command.hover_label())
+          })
+          .collect();
+
         let visible_lines = dimensions.screen_lines();
         let total_lines = visible_lines + history_size;
+        let mut search_match_lines: Vec<usize> = search_matches
+          .iter()
+          .map(|search_match| (history_size as i32 + search_match.start().line.0).max(0) as usize)
+          .collect();
+        search_match_lines.extend(search_panel_match_lines);
+        search_match_lines.sort_unstable();
+        search_match_lines.dedup();
         let scrollbar_state =
-          ScrollbarState::new(total_lines, visible_lines, display_offset, history_size);
+          ScrollbarState::new(total_lines, visible_lines, display_offset, history_size)
+            .with_failed_command_lines(failed_command_lines.clone())
+            .with_search_match_lines(search_match_lines.clone());
 
         let scrollbar_bounds = if scrollbar_state.should_show() {
           Some(Bounds {
@@ -303,7 +416,9 @@ impl Element for TerminalElement {
 
         let (minimap_state, minimap_bounds) = if minimap_enabled {
           let minimap_state =
-            MinimapState::new(total_lines, visible_lines, display_offset, history_size);
+            MinimapState::new(total_lines, visible_lines, display_offset, history_size)
+              .with_failed_command_lines(failed_command_lines.clone())
+              .with_search_match_lines(search_match_lines);
           let minimap_bounds = Bounds {
             origin: Point {
               x: bounds.origin.x + bounds.size.width - scrollbar_width - minimap_width,
@@ -327,6 +442,11 @@ impl Element for TerminalElement {
           dimensions,
           rects,
           relative_highlighted_ranges,
+          hint_labels,
+          hint_label_background,
+          command_marks,
+          copy_mode_indicator,
+          copy_mode_indicator_background: theme.colors().elevated_surface_background,
           mode,
           display_offset,
           gutter,
@@ -419,6 +539,20 @@ impl Element for TerminalElement {
         window.set_cursor_style(gpui::CursorStyle::IBeam, &layout.hitbox);
       }
 
+      let command_mark_lines = layout
+        .command_marks
+        .iter()
+        .map(|(line, _, _)| *line)
+        .collect::<Vec<_>>();
+      let (gutter, line_height) = (layout.gutter, layout.dimensions.line_height);
+      let hovered_command_mark = command_mark_at(
+        &command_mark_lines,
+        gutter,
+        line_height,
+        origin,
+        bounds,
+        window.mouse_position(),
+      );
       let original_cursor = layout.cursor.take();
       self.interactivity.paint(
         global_id,
@@ -434,6 +568,23 @@ impl Element for TerminalElement {
             rect.paint(origin, &layout.dimensions, window);
           }
 
+          let mark_width = px(3.0).min(layout.gutter);
+          for (line, color, _) in &layout.command_marks {
+            window.paint_quad(fill(
+              Bounds {
+                origin: Point::new(
+                  origin.x - (layout.gutter + mark_width) / 2.0,
+                  origin.y + *line as f32 * layout.dimensions.line_height,
+                ),
+                size: gpui::Size {
+                  width: mark_width,
+                  height: layout.dimensions.line_height,
+                },
+              },
+              *color,
+            ));
+          }
+
           // Paint Kitty graphics images with negative z-index (behind text).
           for placement in &layout.image_placements {
             if placement.z_index >= 0 {
@@ -462,6 +613,23 @@ impl Element for TerminalElement {
             batch.paint(origin, &layout.dimensions, window, cx);
           }
 
+          for (position, label) in &layout.hint_labels {
+            let label_origin = origin + *position;
+            window.paint_quad(fill(
+              Bounds {
+                origin: label_origin,
+                size: gpui::Size {
+                  width: label.width,
+                  height: layout.dimensions.line_height,
+                },
+              },
+              layout.hint_label_background,
+            ));
+            label
+              .paint(label_origin, layout.dimensions.line_height, window, cx)
+              .unwrap_or_default();
+          }
+
           if let Some(text_to_mark) = &marked_text_cloned
             && !text_to_mark.is_empty()
             && let Some(cursor_layout) = &original_cursor
@@ -493,12 +661,39 @@ impl Element for TerminalElement {
               .unwrap_or_default();
           }
 
-          if self.cursor_visible
+          // The copy mode cursor does not blink.
+          if (self.cursor_visible || layout.copy_mode_indicator.is_some())
             && let Some(mut cursor) = original_cursor
           {
             cursor.paint(origin, window, cx);
           }
 
+          if let Some(indicator) = &layout.copy_mode_indicator {
+            let cell_width = layout.dimensions.cell_width;
+            let width = indicator.width + cell_width;
+            let grid_width = layout.dimensions.num_columns() as f32 * cell_width;
+            let indicator_origin =
+              Point::new(origin.x + (grid_width - width).max(px(0.)), origin.y);
+            window.paint_quad(fill(
+              Bounds {
+                origin: indicator_origin,
+                size: gpui::Size {
+                  width,
+                  height: layout.dimensions.line_height,
+                },
+              },
+              layout.copy_mode_indicator_background,
+            ));
+            indicator
+              .paint(
+                indicator_origin + Point::new(cell_width / 2.0, px(0.)),
+                layout.dimensions.line_height,
+                window,
+                cx,
+              )
+              .unwrap_or_default();
+          }
+
           // Paint Kitty graphics images with non-negative z-index (on top of text).
           for placement in &layout.image_placements {
             if placement.z_index < 0 {
@@ -506,9 +701,72 @@ impl Element for TerminalElement {
             }
             paint_image_placement(placement, origin, &layout.dimensions, window);
           }
+
+          // Show the hovered command's duration next to its gutter mark.
+          if let Some((line, _, label)) =
+            hovered_command_mark.and_then(|index| layout.command_marks.get(index))
+          {
+            let (label_color, label_background) = {
+              let colors = cx.theme().colors();
+              (colors.text, colors.elevated_surface_background)
+            };
+            let label_origin = Point::new(
+              origin.x,
+              origin.y + *line as f32 * layout.dimensions.line_height,
+            );
+            let shaped_label = window.text_system().shape_line(
+              label.clone().into(),
+              layout
+                .base_text_style
+                .font_size
+                .to_pixels(window.rem_size()),
+              &[TextRun {
+                len: label.len(),
+                font: layout.base_text_style.font(),
+                color: label_color,
+                background_color: None,
+                underline: None,
+                strikethrough: None,
+              }],
+              None,
+            );
+            window.paint_quad(fill(
+              Bounds {
+                origin: label_origin,
+                size: gpui::Size {
+                  width: shaped_label.width + layout.dimensions.cell_width,
+                  height: layout.dimensions.line_height,
+                },
+              },
+              label_background,
+            ));
+            shaped_label
+              .paint(
+                label_origin + Point::new(layout.dimensions.cell_width / 2.0, px(0.)),
+                layout.dimensions.line_height,
+                window,
+                cx,
+              )
+              .unwrap_or_default();
+          }
         },
       );
 
+      // Repaint when the pointer moves onto or off a command's gutter mark.
+      window.on_mouse_event(move |e: &gpui::MouseMoveEvent, _phase, window, _cx| {
+        if command_mark_at(
+          &command_mark_lines,
+          gutter,
+          line_height,
+          origin,
+          bounds,
+          e.position,
+        ) != hovered_command_mark
+        {
+          window.refresh();
+        }
+      });
+
       // Paint scrollbar outside the main terminal content area
       if let (Some(scrollbar_state), Some(scrollbar_bounds)) =
         (&layout.scrollbar_state, &layout.scrollbar_bounds)
@@ -516,12 +774,16 @@ impl Element for TerminalElement {
         let theme = cx.theme();
         let track_color = theme.colors().scrollbar_track_background;
         let thumb_color = theme.colors().scrollbar_thumb_background;
+        let tick_color = theme.colors().terminal_ansi_red;
+        let search_tick_color = theme.colors().terminal_ansi_yellow;
         let hovered = scrollbar_bounds.contains(&window.mouse_position());
         paint_scrollbar(
           *scrollbar_bounds,
           scrollbar_state,
           track_color,
           thumb_color,
+          tick_color,
+          search_tick_color,
           hovered,
           window,
         );
@@ -645,6 +907,8 @@ impl Element for TerminalElement {
           &layout.color_table,
           background_color,
           viewport_color,
+          theme.colors().terminal_ansi_red,
+          theme.colors().terminal_ansi_yellow,
           window,
         );
 
@@ -670,6 +934,26 @@ impl Element for TerminalElement {
   }
 }
 
+/// Index into `lines` of the gutter row under `position`, if it has a mark.
+fn command_mark_at(
+  lines: &[usize],
+  gutter: Pixels,
+  line_height: Pixels,
+  origin: Point<Pixels>,
+  bounds: Bounds<Pixels>,
+  position: Point<Pixels>,
+) -> Option<usize> {
+  if !bounds.contains(&position)
+    || position.x < origin.x - gutter
+    || position.x >= origin.x
+    || position.y < origin.y
+  {
+    return None;
+  }
+  let row = ((position.y - origin.y) / line_height).floor() as usize;
+  lines.iter().position(|line| *line == row)
+}
+
 /// Paint a single Kitty graphics image placement at its grid position.
 fn paint_image_placement(
   placement: &crate::kitty_graphics::VisiblePlacement,
@@ -694,13 +978,37 @@ fn paint_image_placement(
     },
   );
 
-  let _ = window.paint_image(
-    img_bounds,
-    gpui::Corners::default(),
-    placement.render_image.clone(),
-    0,
-    false,
+  let Some((line, column, cells)) = placement.clip else {
+    let _ = window.paint_image(
+      img_bounds,
+      gpui::Corners::default(),
+      placement.render_image.clone(),
+      0,
+      false,
+    );
+    return;
+  };
+
+  // A placeholder slice: paint the whole image, masked to the slice's cells.
+  let bounds = Bounds::new(
+    Point::new(
+      origin.x + column as f32 * dimensions.cell_width(),
+      origin.y + line as f32 * dimensions.line_height(),
+    ),
+    gpui::Size {
+      width: cells as f32 * dimensions.cell_width(),
+      height: dimensions.line_height(),
+    },
   );
+  window.with_content_mask(Some(gpui::ContentMask { bounds }), |window| {
+    let _ = window.paint_image(
+      img_bounds,
+      gpui::Corners::default(),
+      placement.render_image.clone(),
+      0,
+      false,
+    );
+  });
 }
 
 /// Blend a color toward grey based on an opacity factor.
@@ -733,6 +1041,10 @@ fn desaturate_layout(layout: &mut LayoutState, factor: f32) {
     *color = desaturate_color(*color, factor);
   }
 
+  for (_, color, _) in &mut layout.command_marks {
+    *color = desaturate_color(*color, factor);
+  }
+
   if let Some(cursor) = &mut layout.cursor {
     cursor.color = desaturate_color(cursor.color, factor);
   }
@@ -13,7 +13,12 @@ use terminal_kernel::{
 use themeing::ActiveTheme as _;
 
 use crate::{
-  background_region::BackgroundRegion, indexed_cell::IndexedCell,
+  background_region::BackgroundRegion,
+  indexed_cell::IndexedCell,
+  kitty_graphics::{
+    PLACEHOLDER, PlaceholderCell, PlaceholderImage, VisiblePlacement,
+    placeholder::{placeholder_placements, push_placeholder},
+  },
   mappings::colors::resolve_terminal_color,
 };
 
@@ -31,8 +36,9 @@ impl TerminalElement {
     minimum_contrast: f32,
     bold_as_bright: bool,
     color_table: &[Option<Rgb>; ANSI_COLOR_COUNT],
+    placeholder_images: &[PlaceholderImage],
     cx: &App,
-  ) -> (Vec<LayoutRect>, Vec<BatchedTextRun>) {
+  ) -> (Vec<LayoutRect>, Vec<BatchedTextRun>, Vec<VisiblePlacement>) {
     let theme = cx.theme();
 
     let estimated_cells = grid.size_hint().0;
@@ -42,6 +48,7 @@ impl TerminalElement {
     let mut batched_runs = Vec::with_capacity(estimated_runs);
     let mut background_regions: Vec<BackgroundRegion> = Vec::with_capacity(estimated_regions);
     let mut current_batch: Option<BatchedTextRun> = None;
+    let mut placeholder_runs = Vec::new();
 
     let linegroups = grid.into_iter().chunk_by(|i| i.point.line);
     for (line_index, (_, line)) in linegroups.into_iter().enumerate() {
@@ -98,6 +105,27 @@ impl TerminalElement {
           continue;
         }
 
+        // Kitty Unicode placeholders draw a slice of an image instead of a
+        // glyph.
+        if cell.c == PLACEHOLDER && !placeholder_images.is_empty() {
+          let marks = cell.zerowidth().unwrap_or_default();
+          let placeholder = PlaceholderCell::decode(
+            placeholder_number(&cell.fg),
+            cell
+              .underline_color()
+              .map_or(0, |color| placeholder_number(&color)),
+            marks,
+          );
+          push_placeholder(
+            &mut placeholder_runs,
+            alac_line,
+            cell.point.column.0 as i32,
+            placeholder,
+          );
+          previous_cell_had_extras = false;
+          continue;
+        }
+
         if cell.c == ' ' && previous_cell_had_extras {
           previous_cell_had_extras = false;
           continue;
@@ -165,7 +193,8 @@ impl TerminalElement {
       }
     }
 
-    (rects, batched_runs)
+    let image_slices = placeholder_placements(&placeholder_runs, placeholder_images);
+    (rects, batched_runs, image_slices)
   }
 
   /// Converts the Alacritty cell styles to GPUI text styles and background color.
@@ -247,6 +276,16 @@ impl TerminalElement {
   }
 }
 
+/// The number a placeholder cell encodes in a color: the 24-bit value of a
+/// true color, or the index of a 256-color palette entry.
+fn placeholder_number(color: &Color) -> u32 {
+  match color {
+    Color::Spec(rgb) => ((rgb.r as u32) << 16) | ((rgb.g as u32) << 8) | rgb.b as u32,
+    Color::Indexed(index) => *index as u32,
+    Color::Named(_) => 0,
+  }
+}
+
 /// Promote a standard named ANSI color (0–7) to its bright variant (8–15).
 /// Non-standard named colors, indexed colors, and true colors are returned unchanged.
 fn to_bright_named(color: Color) -> Color {
@@ -75,7 +75,7 @@ impl TerminalElement {
     size: TerminalBounds,
     text_fragment: &ShapedLine,
   ) -> Option<(Point<Pixels>, Pixels)> {
-    if cursor_point.line() < size.total_lines() as i32 {
+    if (0..size.total_lines() as i32).contains(&cursor_point.line()) {
       let cursor_width = if text_fragment.width == Pixels::ZERO {
         size.cell_width()
       } else {
@@ -117,6 +117,15 @@ pub struct LayoutState {
   batched_text_runs: Vec<BatchedTextRun>,
   rects: Vec<LayoutRect>,
   relative_highlighted_ranges: Vec<(RangeInclusive<AlacPoint>, Hsla)>,
+  /// Hint mode labels, positioned relative to the grid origin.
+  hint_labels: Vec<(Point<Pixels>, ShapedLine)>,
+  hint_label_background: Hsla,
+  /// `(viewport line, color, hover label)` of each finished command's prompt,
+  /// painted in the gutter left of the grid.
+  command_marks: Vec<(usize, Hsla, String)>,
+  /// Copy mode status shown in the top-right corner while copy mode is active.
+  copy_mode_indicator: Option<ShapedLine>,
+  copy_mode_indicator_background: Hsla,
   cursor: Option<CursorLayout>,
   background_color: Hsla,
   dimensions: TerminalBounds,
@@ -25,11 +25,17 @@ actions!(
     SendPageDown,
     ZoomIn,
     ZoomOut,
-    ZoomReset
+    ZoomReset,
+    HintMode,
+    ScrollToPreviousPrompt,
+    ScrollToNextPrompt,
+    SelectCommandOutput,
+    CopyLastCommandOutput,
+    CopyMode
   ]
 );
 
-use super::terminal::Terminal;
+use super::terminal::{CopyModeKey, Terminal};
 use super::terminal_element::TerminalElement;
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(750);
@@ -141,6 +147,8 @@ pub enum TerminalEvent {
   CloseTerminal(usize),
   /// A long-running command may have finished (shell prompt returned).
   CommandFinished,
+  /// The shell's working directory changed.
+  CwdChanged(String),
 }
 
 pub struct TerminalView {
@@ -179,6 +187,8 @@ pub struct TerminalView {
   momentum_scroll_task: Task<()>,
   /// Task for touch long-press detection
   long_press_task: Task<()>,
+  /// Task waking up for the next frame of playing kitty image animations
+  image_animation_task: Task<()>,
   suppress_enter_text_commit_until: Option<Instant>,
 }
 
@@ -215,6 +225,12 @@ impl Render for TerminalView {
       .on_action(cx.listener(Self::zoom_in))
       .on_action(cx.listener(Self::zoom_out))
       .on_action(cx.listener(Self::zoom_reset))
+      .on_action(cx.listener(Self::hint_mode))
+      .on_action(cx.listener(Self::scroll_to_previous_prompt))
+      .on_action(cx.listener(Self::scroll_to_next_prompt))
+      .on_action(cx.listener(Self::select_command_output))
+      .on_action(cx.listener(Self::copy_last_command_output))
+      .on_action(cx.listener(Self::copy_mode))
       .child(
         div()
           .id("terminal-view-container")
@@ -273,6 +289,7 @@ impl TerminalView {
       pending_tab_title_update: Task::ready(()),
       momentum_scroll_task: Task::ready(()),
       long_press_task: Task::ready(()),
+      image_animation_task: Task::ready(()),
       suppress_enter_text_commit_until: None,
     }
   }
@@ -342,6 +359,24 @@ impl TerminalView {
       return;
     }
 
+    if self.terminal.read(cx).hint_mode_active() {
+      self.terminal.update(cx, |term, cx| {
+        for c in text.chars() {
+          term.hint_input(c, cx);
+        }
+      });
+      return;
+    }
+
+    if self.terminal.read(cx).copy_mode_active() {
+      self.terminal.update(cx, |term, cx| {
+        for c in text.chars() {
+          term.copy_mode_input(c, cx);
+        }
+      });
+      return;
+    }
+
     if let Some(bytes) = committed_text_bytes(text) {
       self.terminal.update(cx, |term, _| {
         term.input(bytes);
@@ -420,6 +455,18 @@ impl TerminalView {
       return;
     }
 
+    if self.terminal.read(cx).hint_mode_active() {
+      self.handle_hint_keystroke(&keystroke, cx);
+      cx.stop_propagation();
+      return;
+    }
+
+    if self.terminal.read(cx).copy_mode_active() {
+      self.handle_copy_mode_keystroke(&keystroke, cx);
+      cx.stop_propagation();
+      return;
+    }
+
     if should_defer_keydown_text_input(&keystroke) {
       return;
     }
@@ -430,6 +477,90 @@ impl TerminalView {
     }
   }
 
+  /// Route a keystroke to the active hint mode instead of the PTY.
+  fn handle_hint_keystroke(&mut self, keystroke: &gpui::Keystroke, cx: &mut Context<Self>) {
+    match keystroke.key.as_str() {
+      "escape" => self
+        .terminal
+        .update(cx, |term, cx| term.cancel_hint_mode(cx)),
+      "backspace" => self.terminal.update(cx, |term, cx| term.hint_backspace(cx)),
+      _ if should_defer_keydown_text_input(keystroke) => {}
+      _ => {
+        let modifiers = keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.platform {
+          let toggles_hint_mode = cx.global::<config::Config>().keybindings.hint_mode.matches(
+            modifiers.control,
+            modifiers.shift,
+            modifiers.alt,
+            modifiers.platform,
+            &keystroke.key,
+          );
+          if toggles_hint_mode {
+            self
+              .terminal
+              .update(cx, |term, cx| term.cancel_hint_mode(cx));
+          }
+          return;
+        }
+        let text = keystroke.key_char.as_deref().unwrap_or(&keystroke.key);
+        let mut chars = text.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+          self.terminal.update(cx, |term, cx| term.hint_input(c, cx));
+        }
+      }
+    }
+  }
+
+  /// Route a keystroke to the active copy mode instead of the PTY.
+  fn handle_copy_mode_keystroke(&mut self, keystroke: &gpui::Keystroke, cx: &mut Context<Self>) {
+    let modifiers = keystroke.modifiers;
+    let key = match keystroke.key.as_str() {
+      "escape" => Some(CopyModeKey::Escape),
+      "enter" => Some(CopyModeKey::Enter),
+      "backspace" => Some(CopyModeKey::Backspace),
+      "left" => Some(CopyModeKey::Left),
+      "right" => Some(CopyModeKey::Right),
+      "up" => Some(CopyModeKey::Up),
+      "down" => Some(CopyModeKey::Down),
+      "home" => Some(CopyModeKey::Home),
+      "end" => Some(CopyModeKey::End),
+      "v" if modifiers.control && !modifiers.alt && !modifiers.platform => {
+        Some(CopyModeKey::BlockSelection)
+      }
+      _ => None,
+    };
+    if let Some(key) = key {
+      self
+        .terminal
+        .update(cx, |term, cx| term.copy_mode_key(key, cx));
+      return;
+    }
+    if should_defer_keydown_text_input(keystroke) {
+      return;
+    }
+
+    if modifiers.control || modifiers.alt || modifiers.platform {
+      let toggles_copy_mode = cx.global::<config::Config>().keybindings.copy_mode.matches(
+        modifiers.control,
+        modifiers.shift,
+        modifiers.alt,
+        modifiers.platform,
+        &keystroke.key,
+      );
+      if toggles_copy_mode {
+        self.terminal.update(cx, |term, cx| term.exit_copy_mode(cx));
+      }
+      return;
+    }
+    let text = keystroke.key_char.as_deref().unwrap_or(&keystroke.key);
+    let mut chars = text.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+      self
+        .terminal
+        .update(cx, |term, cx| term.copy_mode_input(c, cx));
+    }
+  }
+
   fn focus_in(&mut self, window: &mut Window, cx: &mut Context<Self>) {
     self.terminal.update(cx, |terminal, _| {
       terminal.focus_in();
@@ -516,6 +647,27 @@ impl TerminalView {
     });
   }
 
+  /// Keep repainting while kitty image animations play, sleeping until the
+  /// next frame is due.
+  fn start_image_animation(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+    self.image_animation_task = cx.spawn_in(window, async move |this, cx| {
+      loop {
+        let deadline = this
+          .update(cx, |this, cx| {
+            this
+              .terminal
+              .update(cx, |term, cx| term.advance_image_animations(cx))
+          })
+          .ok()
+          .flatten();
+        let Some(deadline) = deadline else {
+          break;
+        };
+        Timer::at(deadline).await;
+      }
+    });
+  }
+
   /// Start a timer that promotes a pending touch to selection after a long press
   pub fn start_long_press_timer(&mut self, window: &mut Window, cx: &mut Context<Self>) {
     const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
@@ -738,21 +890,7 @@ impl TerminalView {
   fn paste(&mut self, _: &Paste, _window: &mut Window, cx: &mut Context<Self>) {
     if let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) {
       self.terminal.update(cx, |term, _cx| {
-        // Normalize \r\n to \r for proper terminal line endings
-        let text = text.replace("\r\n", "\r").replace('\n', "\r");
-
-        let bracketed = term.last_content.mode.contains(TermMode::BRACKETED_PASTE);
-        if bracketed {
-          // Wrap with bracketed paste escape sequences so applications like
-          // neovim know this is pasted text and won't auto-indent each line.
-          let mut payload = Vec::with_capacity(text.len() + 12);
-          payload.extend_from_slice(b"\x1b[200~");
-          payload.extend_from_slice(text.as_bytes());
-          payload.extend_from_slice(b"\x1b[201~");
-          term.input(payload);
-        } else {
-          term.input(text.into_bytes());
-        }
+        term.paste_text(&text);
       });
     }
   }
@@ -799,6 +937,71 @@ impl TerminalView {
     cx.notify();
   }
 
+  fn hint_mode(&mut self, _: &HintMode, _window: &mut Window, cx: &mut Context<Self>) {
+    let hints = cx.global::<config::Config>().hints.clone();
+    self.terminal.update(cx, |term, cx| {
+      if term.hint_mode_active() {
+        term.cancel_hint_mode(cx);
+      } else {
+        term.start_hint_mode(&hints, cx);
+      }
+    });
+  }
+
+  fn copy_mode(&mut self, _: &CopyMode, _window: &mut Window, cx: &mut Context<Self>) {
+    self.terminal.update(cx, |term, cx| {
+      if term.copy_mode_active() {
+        term.exit_copy_mode(cx);
+      } else {
+        term.start_copy_mode(cx);
+      }
+    });
+  }
+
+  fn scroll_to_previous_prompt(
+    &mut self,
+    _: &ScrollToPreviousPrompt,
+    _window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    self
+      .terminal
+      .update(cx, |term, cx| term.scroll_to_previous_prompt(cx));
+  }
+
+  fn scroll_to_next_prompt(
+    &mut self,
+    _: &ScrollToNextPrompt,
+    _window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    self
+      .terminal
+      .update(cx, |term, cx| term.scroll_to_next_prompt(cx));
+  }
+
+  fn select_command_output(
+    &mut self,
+    _: &SelectCommandOutput,
+    _window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    self.terminal.update(cx, |term, cx| {
+      term.select_command_output(cx);
+    });
+  }
+
+  fn copy_last_command_output(
+    &mut self,
+    _: &CopyLastCommandOutput,
+    _window: &mut Window,
+    cx: &mut Context<Self>,
+  ) {
+    self.terminal.update(cx, |term, cx| {
+      term.copy_last_command_output(cx);
+    });
+  }
+
   fn zoom_reset(&mut self, _: &ZoomReset, _window: &mut Window, cx: &mut Context<Self>) {
     themeing::ZoomState::update_global(cx, |zoom: &mut themeing::ZoomState, _| {
       zoom.reset();
@@ -846,6 +1049,14 @@ fn subscribe_for_terminal_events(
       crate::terminal::Event::PromptReturned => {
         cx.emit(TerminalEvent::CommandFinished);
       }
+      crate::terminal::Event::ImagesAnimating => {
+        terminal_view.start_image_animation(window, cx);
+      }
+      crate::terminal::Event::CwdChanged(cwd) => {
+        cx.emit(TerminalEvent::CwdChanged(
+          cwd.to_string_lossy().into_owned(),
+        ));
+      }
       crate::terminal::Event::Open(url) => {
         cx.open_url(url);
         cx.notify();
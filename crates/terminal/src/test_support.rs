@@ -11,9 +11,9 @@ use std::sync::{
 };
 
 use terminal_kernel::event::{VoidListener, WindowSize};
-use terminal_kernel::grid::Dimensions;
 use terminal_kernel::sync::FairMutex;
 use terminal_kernel::term::{Config as AlacConfig, Term};
+use terminal_kernel::test_support::{StubDims, backend_with};
 use terminal_kernel::{AlacrittyBackend, SessionEvents};
 
 use crate::{PtyProcessInfo, PtySender, Terminal};
@@ -55,22 +55,6 @@ impl PtySender for FakePtySender {
   }
 }
 
-struct StubDims {
-  cols: usize,
-  lines: usize,
-}
-impl Dimensions for StubDims {
-  fn total_lines(&self) -> usize {
-    self.lines
-  }
-  fn screen_lines(&self) -> usize {
-    self.lines
-  }
-  fn columns(&self) -> usize {
-    self.cols
-  }
-}
-
 /// Build a fully in-memory `Terminal` + `SessionEvents` pair for tests.
 ///
 /// - No child process is spawned.
@@ -102,11 +86,26 @@ pub fn fake_terminal_session(
 
   let (_tx, rx) = futures::channel::mpsc::unbounded();
 
+  (terminal_on(sender, backend), rx, writes, resizes)
+}
+
+/// Build an in-memory `lines` x `cols` `Terminal` that has already parsed
+/// `bytes`, for tests that drive the `Terminal` directly rather than a session.
+#[doc(hidden)]
+pub fn fake_terminal_with(lines: usize, cols: usize, bytes: &[u8]) -> Terminal {
+  let (sender, _writes, _resizes) = FakePtySender::new();
+  terminal_on(sender, Box::new(backend_with(lines, cols, bytes)))
+}
+
+fn terminal_on(
+  sender: Box<dyn PtySender>,
+  backend: Box<AlacrittyBackend<VoidListener>>,
+) -> Terminal {
   let keyboard_flags = Arc::new(AtomicU32::new(0));
   // Silence the "unused" warning by touching the atomic.
   let _ = keyboard_flags.load(Ordering::Relaxed);
 
-  let terminal = Terminal::new(
+  Terminal::new(
     sender,
     backend,
     PtyProcessInfo::test_stub(),
@@ -115,7 +114,6 @@ pub fn fake_terminal_session(
     keyboard_flags,
     None,
     None,
-  );
-
-  (terminal, rx, writes, resizes)
+    None,
+  )
 }